@@ -0,0 +1,181 @@
+//! Administrative operations requested from outside the radio link (the web admin UI and
+//! the `meshbbs user` / `meshbbs topic` subcommands).
+//!
+//! An [`AdminHandle`] sends [`AdminRequest`]s to the running [`BbsServer`](super::BbsServer),
+//! which executes them in its event loop next to radio traffic, so they act on the same
+//! sessions and storage as the `KICK`, `BROADCAST` and moderation commands. Results come
+//! back as JSON values shaped for the admin API. The CLI runs the same requests through
+//! [`BbsServer::handle_admin`](super::BbsServer::handle_admin) with the actor [`CLI_ACTOR`]
+//! while the server is stopped.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tokio::sync::{mpsc, oneshot};
+
+use super::roles::role_name;
+use crate::storage::{TopicRetention, TrustedNode, User};
+
+/// Entries per page for the audit log listings.
+pub const AUDIT_PAGE_SIZE: usize = 20;
+
+/// Actor recorded in the admin audit log for `meshbbs user` / `meshbbs topic` commands.
+pub const CLI_ACTOR: &str = "cli";
+
+#[derive(Debug, Clone)]
+pub enum AdminRequest {
+    Overview,
+    ListUsers,
+    /// Promote or demote; `level` is 1, 5 or 10
+    SetUserLevel { username: String, level: u8 },
+    ResetPassword { username: String, password: String },
+    ShowUser { username: String },
+    /// Register with a password, optionally bound to a node; `level` is 1, 5 or 10
+    CreateUser { username: String, password: String, level: u8, node_id: Option<String> },
+    /// Clear the node binding so the next login binds again
+    UnbindNode { username: String },
+    /// Remove the account and its mailbox (posts keep the author name)
+    DeleteUser { username: String },
+    ListTopics,
+    CreateTopic(NewTopic),
+    ModifyTopic { id: String, changes: TopicChanges },
+    DeleteTopic { id: String },
+    SetTopicLocked { id: String, locked: bool },
+    /// Newest first
+    ListMessages { topic: String, limit: usize },
+    DeleteMessage { topic: String, id: String },
+    SetMessagePinned { topic: String, id: String, pinned: bool },
+    DeletionAudit { page: usize },
+    AdminAudit { page: usize },
+    ListSessions,
+    Kick { username: String },
+    Broadcast { message: String },
+    /// Web admin login gate: when `source` (`web:<ip>`) is locked out after failed logins,
+    /// `{"locked_until": time}`, else `null`
+    LoginLockout { source: String },
+    /// Outcome of a web admin login from `source`; failures count toward its lockout
+    LoginAttempt { source: String, ok: bool },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewTopic {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub read_level: u8,
+    #[serde(default)]
+    pub post_level: u8,
+    /// Create as a subtopic of this topic
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TopicChanges {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub read_level: Option<u8>,
+    pub post_level: Option<u8>,
+    /// Replaces the retention limits; `{}` removes them
+    pub retention: Option<TopicRetention>,
+}
+
+/// A user as shown to administrators (no password hash).
+#[derive(Debug, Clone, Serialize)]
+pub struct UserView {
+    pub username: String,
+    pub node_id: Option<String>,
+    pub trusted_nodes: Vec<TrustedNode>,
+    pub level: u8,
+    pub role: &'static str,
+    pub first_login: DateTime<Utc>,
+    pub last_login: DateTime<Utc>,
+    pub total_messages: u32,
+    pub has_password: bool,
+}
+
+impl From<User> for UserView {
+    fn from(u: User) -> Self {
+        UserView {
+            role: role_name(u.user_level),
+            level: u.user_level,
+            has_password: u.password_hash.is_some(),
+            trusted_nodes: u.known_nodes(),
+            username: u.username,
+            node_id: u.node_id,
+            first_login: u.first_login,
+            last_login: u.last_login,
+            total_messages: u.total_messages,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicView {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub read_level: u8,
+    pub post_level: u8,
+    pub parent: Option<String>,
+    pub locked: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub retention: Option<TopicRetention>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionView {
+    pub node_id: String,
+    pub username: Option<String>,
+    pub level: u8,
+    pub role: &'static str,
+    pub state: String,
+    pub login_time: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// The target of a request does not exist (HTTP 404 in the admin API).
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// A request in flight to the server loop.
+#[derive(Debug)]
+pub struct AdminCall {
+    /// Recorded in the admin audit log
+    pub actor: String,
+    pub request: AdminRequest,
+    pub reply: oneshot::Sender<Result<serde_json::Value>>,
+}
+
+/// Cloneable sender side; obtained from [`BbsServer::admin_handle`](super::BbsServer::admin_handle).
+#[derive(Debug, Clone)]
+pub struct AdminHandle {
+    tx: mpsc::UnboundedSender<AdminCall>,
+}
+
+impl AdminHandle {
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<AdminCall>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (AdminHandle { tx }, rx)
+    }
+
+    /// Run `request` on the server and wait for its result.
+    pub async fn call(&self, actor: &str, request: AdminRequest) -> Result<serde_json::Value> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(AdminCall { actor: actor.to_string(), request, reply })
+            .map_err(|_| anyhow!("BBS server is not running"))?;
+        rx.await.map_err(|_| anyhow!("BBS server dropped the request"))?
+    }
+}
@@ -1,13 +1,15 @@
 use anyhow::Result;
+use chrono::Utc;
 // use log::{debug}; // retained for future detailed command tracing
 use log::{info, warn, error};
 use crate::logutil::escape_log;
 
 use crate::config::Config;
-use crate::storage::{Storage, ReplyEntry};
+use crate::storage::{MailMessage, SanctionKind, SearchHit, Storage, ReplyEntry};
 use super::roles::{LEVEL_MODERATOR};
 use crate::validation::{validate_user_name, validate_topic_name, sanitize_message_content};
 use super::session::{Session, SessionState};
+use super::server::{sanction_notice, sec_log};
 
 /// UI rendering helpers for compact, 230-byte-safe outputs
 mod ui {
@@ -47,6 +49,13 @@ fn self_topic_can_read(user_level: u8, topic: &str, storage: &Storage) -> bool {
     }
 }
 
+/// What a muted user is told instead of posting or replying; `None` if they may post.
+async fn mute_notice(session: &Session, storage: &Storage) -> Result<Option<String>> {
+    let Some(user) = session.username.as_deref() else { return Ok(None) };
+    let now = Utc::now();
+    Ok(storage.sanction(SanctionKind::Mute, user, now).await?.map(|m| sanction_notice("You are muted", &m, now)))
+}
+
 fn self_topic_can_post(user_level: u8, topic: &str, storage: &Storage) -> bool {
     // Use runtime topic configuration for permission checks
     if let Some(topic_config) = storage.get_topic_config(topic) {
@@ -98,6 +107,10 @@ impl CommandProcessor {
                 parts.push("Reply".into());
             }
             SessionState::ConfirmDelete => { parts.push("Confirm".into()); }
+            SessionState::MailInbox => parts.push("Mail".into()),
+            SessionState::MailRead => { parts.push("Mail".into()); parts.push("Read".into()); }
+            SessionState::MailCompose => { parts.push("Mail".into()); parts.push("Compose".into()); }
+            SessionState::SearchResults => parts.push("Search".into()),
             SessionState::UserMenu => parts.push("User".into()),
             SessionState::ReadingMessages => {
                 parts.push("Topics".into());
@@ -137,6 +150,10 @@ impl CommandProcessor {
             SessionState::ComposeNewBody => self.handle_compose_new_body(session, raw, storage, config).await,
             SessionState::ComposeReply => self.handle_compose_reply(session, raw, storage, config).await,
             SessionState::ConfirmDelete => self.handle_confirm_delete(session, raw, storage, config).await,
+            SessionState::MailInbox => self.handle_mail_inbox(session, raw, &cmd_upper, storage, config).await,
+            SessionState::MailRead => self.handle_mail_read(session, raw, &cmd_upper, storage, config).await,
+            SessionState::MailCompose => self.handle_mail_compose(session, raw, storage, config).await,
+            SessionState::SearchResults => self.handle_search_results(session, raw, &cmd_upper, storage, config).await,
             SessionState::MessageTopics => {
                 if let Some(resp) = self.try_inline_message_command(session, raw, &cmd_upper, storage, config).await? { return Ok(resp); }
                 self.handle_message_topics(session, &cmd_upper, storage, config).await
@@ -148,12 +165,77 @@ impl CommandProcessor {
         }
     }
 
+    /// `NODES` lists the user's trusted nodes, `TRUST` adds the node of this session and
+    /// `UNTRUST <n>` removes entry n of the list.
+    async fn handle_trusted_nodes(&self, session: &mut Session, upper: &str, storage: &mut Storage) -> Result<String> {
+        let username = session.display_name();
+        let Some(user) = storage.get_user(&username).await? else { return Ok("Session user missing.\n".into()) };
+        if upper == "TRUST" {
+            // The long name from the node cache; without an entry it is only the hex id
+            let label = session.long_label.clone().filter(|l| !l.starts_with("0x"));
+            if user.trusts_node(&session.node_id) {
+                if label.is_some() { storage.trust_node(&username, &session.node_id, label.as_deref()).await?; }
+                return Ok("This node is already trusted.\n".into());
+            }
+            match storage.trust_node(&username, &session.node_id, label.as_deref()).await {
+                Ok(_) => {
+                    sec_log!("TRUST by {}: node {}", username, session.node_id);
+                    Ok(format!("Trusted this node ({}). NODES lists them.\n", label.unwrap_or_else(|| session.node_id.clone())))
+                }
+                Err(e) => Ok(format!("Trust failed: {}\n", e)),
+            }
+        } else if let Some(arg) = upper.strip_prefix("UNTRUST") {
+            let nodes = user.known_nodes();
+            let picked = arg.trim().parse::<usize>().ok().and_then(|n| n.checked_sub(1)).and_then(|i| nodes.get(i));
+            let Some(node) = picked else { return Ok("Usage: UNTRUST <n> (see NODES)\n".into()) };
+            storage.untrust_node(&username, &node.node_id).await?;
+            sec_log!("UNTRUST by {}: node {}", username, node.node_id);
+            Ok(format!("Node {} is no longer trusted.\n", node.node_id))
+        } else {
+            let nodes = user.known_nodes();
+            if nodes.is_empty() { return Ok("No trusted nodes. TRUST adds this one.\n".into()); }
+            let mut out = String::from("Trusted nodes:\n");
+            for (i, node) in nodes.iter().enumerate() {
+                let mut line = format!("{}. {}", i + 1, node.node_id);
+                if let Some(label) = &node.label { line.push(' '); line.extend(label.chars().take(20)); }
+                if user.node_id.as_deref() == Some(node.node_id.as_str()) { line.push_str(" [home]"); }
+                if node.node_id == session.node_id { line.push_str(" [this]"); }
+                out.push_str(&line);
+                out.push('\n');
+            }
+            out.push_str("TRUST adds this node, UNTRUST <n> removes\n");
+            Ok(out)
+        }
+    }
+
     async fn try_inline_message_command(&self, session: &mut Session, raw: &str, upper: &str, storage: &mut Storage, config: &Config) -> Result<Option<String>> {
         // WHERE-AM-I breadcrumb (global)
         if upper == "WHERE" || upper == "W" {
             let here = self.where_am_i(session, config);
             return Ok(Some(format!("[BBS] You are at: {}\n", here)));
         }
+        if upper == "MAIL" || upper == "SEND" || upper.starts_with("SEND ") {
+            if !session.is_logged_in() { return Ok(Some("Log in to use mail.\n".into())); }
+            if upper == "MAIL" {
+                session.state = SessionState::MailInbox;
+                session.list_page = 1;
+                return Ok(Some(self.render_mail_inbox(session, storage).await?));
+            }
+            return Ok(Some(self.handle_send(session, raw, storage).await?));
+        }
+        if upper == "NODES" || upper == "TRUST" || upper == "UNTRUST" || upper.starts_with("UNTRUST ") {
+            if !session.is_logged_in() { return Ok(Some("Log in to manage trusted nodes.\n".into())); }
+            return Ok(Some(self.handle_trusted_nodes(session, upper, storage).await?));
+        }
+        let composing = matches!(session.state, SessionState::ComposeNewTitle | SessionState::ComposeNewBody | SessionState::ComposeReply | SessionState::MailCompose);
+        if (upper == "SEARCH" || upper.starts_with("SEARCH ")) && !composing {
+            let query = raw.get(6..).unwrap_or("").trim();
+            if query.is_empty() { return Ok(Some("Usage: SEARCH <words>\n".into())); }
+            session.search_query = Some(query.to_string());
+            session.list_page = 1;
+            session.state = SessionState::SearchResults;
+            return Ok(Some(self.render_search_results(session, storage).await?));
+        }
         if upper.starts_with("READ") {
             let raw_topic = raw.split_whitespace().nth(1).unwrap_or("general");
             
@@ -216,6 +298,10 @@ impl CommandProcessor {
                 return Ok(Some("Permission denied.\n".into())); 
             }
             
+            if let Some(notice) = mute_notice(session, storage).await? {
+                return Ok(Some(notice));
+            }
+            
             let author = session.display_name();
             storage.store_message(&topic, &author, &sanitized_content).await?;
             return Ok(Some(format!("Posted to {}.\n", topic)));
@@ -299,15 +385,15 @@ impl CommandProcessor {
                 // Build compact contextual help to fit within 230 bytes
                 let mut out = String::new();
                 if !session.is_logged_in() {
-                    out.push_str("AUTH: REGISTER <u> <p> | LOGIN <u> <p>\n");
+                    out.push_str("AUTH: REGISTER <u> <p> | LOGIN <u> <p> | RESET\n");
                     return Ok(out);
                 }
-                out.push_str("ACCT: SETPASS <new> | CHPASS <old> <new> | LOGOUT\n");
+                out.push_str("ACCT: SETPASS/CHPASS | NODES/TRUST | LOGOUT\n");
                 // Terse navigation + legacy commands
-                out.push_str("MSG: M topics; 1-9 pick; U up; +/-; F <txt>; READ/POST/TOPICS\n");
-                if session.user_level >= 5 { out.push_str("MOD: D <area> <id> | K lock | DELLOG/DL [p]\n"); }
-                if session.user_level >= 10 { out.push_str("ADM: PROMOTE/DEMOTE <u> | SYSLOG <lvl> <msg>\n"); }
-                out.push_str("OTHER: WHERE | U | Q\n");
+                out.push_str("MSG: M topics; 1-9 pick; U up; +/-; F <txt>; SEARCH <w>\n");
+                if session.user_level >= 5 { out.push_str("MOD: D/K/DELLOG | RESETPW | BAN/MUTE/BLOCKNODE/BANS\n"); }
+                if session.user_level >= 10 { out.push_str("ADM: PROMOTE/DEMOTE/UNLOCKUSER | SYSLOG | RELOAD\n"); }
+                out.push_str("OTHER: MAIL | WHERE | U | Q\n");
                 // Ensure length <=230 (should already be compact; final guard)
                 const MAX: usize = 230;
                 if out.len() > MAX { out.truncate(MAX); }
@@ -663,6 +749,8 @@ impl CommandProcessor {
     }
 
     async fn handle_threads(&self, session: &mut Session, raw: &str, upper: &str, storage: &mut Storage, config: &Config) -> Result<String> {
+        // Browsing a topic's threads ends any SEARCH context
+        session.search_query = None;
         match upper {
             "H" | "HELP" | "?" => {
                 let mut s = "Threads: 1-9 read, N new, L more, B back, F filter, M topics, X exit".to_string();
@@ -810,8 +898,7 @@ impl CommandProcessor {
     async fn render_thread_read(&self, session: &Session, storage: &mut Storage, config: &Config) -> Result<String> {
     let topic = session.current_topic.clone().unwrap_or_else(|| "general".into());
     let id = if let Some(id) = &session.current_thread_id { id.clone() } else { return self.render_threads_list(session, storage, config).await };
-        let msgs = storage.get_messages(&topic, 200).await?;
-        if let Some(m) = msgs.into_iter().find(|mm| mm.id == id) {
+        if let Some(m) = storage.get_message(&topic, &id).await.ok().flatten() {
             let topic_disp = config.message_topics.get(&topic).map(|c| c.name.clone()).unwrap_or_else(|| topic.clone());
             let title = ui::utf8_truncate(m.content.lines().next().unwrap_or(""), 24);
             let locked_note = if storage.is_topic_locked(&topic) { " [locked]" } else { "" };
@@ -839,6 +926,11 @@ impl CommandProcessor {
 
     async fn handle_thread_read(&self, session: &mut Session, raw: &str, upper: &str, storage: &mut Storage, config: &Config) -> Result<String> {
         match upper {
+            "B" if session.search_query.is_some() => {
+                // Opened from SEARCH results: go back to them
+                session.state = SessionState::SearchResults;
+                return self.render_search_results(session, storage).await;
+            }
             "B" => { 
                 // From read, go back to threads; threads handler will handle further 'B'
                 session.state = SessionState::Threads; 
@@ -921,6 +1013,7 @@ impl CommandProcessor {
         let topic = session.current_topic.clone().unwrap_or_else(|| "general".into());
         if storage.is_topic_locked(&topic) { session.state = SessionState::Threads; return Ok("Topic locked.\n".into()); }
         if !self_topic_can_post(session.user_level, &topic, storage) { session.state = SessionState::Threads; return Ok("Permission denied.\n".into()); }
+        if let Some(notice) = mute_notice(session, storage).await? { session.state = SessionState::Threads; return Ok(notice); }
         let title = session.filter_text.clone().unwrap_or_else(|| "New thread".into());
         let body = raw.trim();
         if body.is_empty() { return Ok("Body required.\n".into()); }
@@ -937,8 +1030,14 @@ impl CommandProcessor {
         let id = if let Some(id) = &session.current_thread_id { id.clone() } else { session.state = SessionState::Threads; return self.render_threads_list(session, storage, config).await };
         if storage.is_topic_locked(&topic) { session.state = SessionState::ThreadRead; return Ok("Topic locked.\n".into()); }
         if !self_topic_can_post(session.user_level, &topic, storage) { session.state = SessionState::ThreadRead; return Ok("Permission denied.\n".into()); }
+        if let Some(notice) = mute_notice(session, storage).await? { session.state = SessionState::ThreadRead; return Ok(notice); }
         let author = session.display_name();
         storage.append_reply(&topic, &id, &author, raw.trim()).await?;
+        if let Ok(Some(parent)) = storage.get_message(&topic, &id).await {
+            if parent.author != author {
+                session.notices.push((parent.author, format!("💬 {} replied to your post in {}.", author, topic)));
+            }
+        }
         session.state = SessionState::ThreadRead;
         self.render_thread_read(session, storage, config).await
     }
@@ -954,10 +1053,14 @@ impl CommandProcessor {
         match answer.as_str() {
             "Y" | "YES" => {
                 if let Some(id) = session.current_thread_id.clone() {
+                    let original = storage.get_message(&topic, &id).await.ok().flatten();
                     let ok = storage.delete_message(&topic, &id).await.unwrap_or(false);
                     if ok {
                         let actor = session.username.as_deref().unwrap_or(&session.display_name()).to_string();
                         let _ = storage.append_deletion_audit(&topic, &id, &actor).await;
+                        if let Some(msg) = original.filter(|m| m.author != actor) {
+                            session.notices.push((msg.author, format!("🛡️ Your post in {} was removed by a moderator.", topic)));
+                        }
                     }
                 }
                 session.state = SessionState::Threads;
@@ -1052,6 +1155,11 @@ impl CommandProcessor {
                 return Ok("Message content cannot be empty after sanitization. Try again or type '.' to cancel:\n".to_string());
             }
             
+            if let Some(notice) = mute_notice(session, storage).await? {
+                session.state = SessionState::MessageTopics;
+                return Ok(notice);
+            }
+            
             let author = session.display_name();
             storage.store_message(&topic, &author, &sanitized_content).await?;
             session.state = SessionState::MessageTopics;
@@ -1078,6 +1186,223 @@ impl CommandProcessor {
     }
 }
 
+// Private mail: MAIL opens the inbox, SEND @user <text> works anywhere once logged in
+impl CommandProcessor {
+    /// SEND @user <text> delivers at once; SEND @user alone starts a compose step
+    async fn handle_send(&self, session: &mut Session, raw: &str, storage: &mut Storage) -> Result<String> {
+        let mut parts = raw.splitn(3, char::is_whitespace);
+        parts.next(); // skip "SEND"
+        let to = parts.next().map(|t| t.trim_start_matches('@')).unwrap_or("");
+        if to.is_empty() { return Ok("Usage: SEND @user <text>\n".into()); }
+        let text = parts.next().unwrap_or("").trim();
+        if !text.is_empty() { return Ok(self.deliver_mail(session, to, text, storage).await); }
+        match storage.get_user(to).await {
+            Ok(Some(user)) => {
+                session.mail_to = Some(user.username.clone());
+                session.state = SessionState::MailCompose;
+                Ok(format!("[BBS] Mail to {} (single message, . to cancel):\n", user.username))
+            }
+            _ => Ok(format!("No such user '{}'.\n", to)),
+        }
+    }
+
+    async fn deliver_mail(&self, session: &mut Session, to: &str, text: &str, storage: &mut Storage) -> String {
+        match storage.send_mail(&session.display_name(), to, text).await {
+            Ok(mail) => {
+                session.notices.push((mail.to.clone(), format!("📬 New mail from {}. MAIL to read.", mail.from)));
+                format!("Mail sent to {}.\n", mail.to)
+            }
+            Err(e) => format!("Mail not sent: {}\n", e),
+        }
+    }
+
+    /// n-th (1-based) message on the current inbox page
+    async fn mail_on_page(&self, session: &Session, storage: &Storage, n: usize) -> Result<Option<MailMessage>> {
+        let idx = (session.list_page.saturating_sub(1)) * 5 + n.saturating_sub(1);
+        Ok(storage.list_mail(&session.display_name()).await?.into_iter().nth(idx))
+    }
+
+    async fn render_mail_inbox(&self, session: &Session, storage: &Storage) -> Result<String> {
+        let mail = storage.list_mail(&session.display_name()).await?;
+        let unread = mail.iter().filter(|m| !m.read).count();
+        let mut out = format!("[BBS] Mail: {} msgs, {} unread\n", mail.len(), unread);
+        if mail.is_empty() { out.push_str("Inbox empty.\n"); }
+        let start = (session.list_page.saturating_sub(1)) * 5;
+        for (i, m) in mail.iter().skip(start).take(5).enumerate() {
+            let marker = if m.read { " " } else { "*" };
+            let preview = ui::utf8_truncate(m.content.lines().next().unwrap_or(""), 20);
+            out.push_str(&format!("{}{} {} {} {}\n", i + 1, marker, m.from, m.timestamp.format("%m/%d"), preview));
+        }
+        out.push_str("Reply: 1-5 read, DEL n, SEND @u <text>, L more, B back\n");
+        Ok(out)
+    }
+
+    async fn render_mail_read(&self, session: &Session, storage: &Storage) -> Result<String> {
+        let id = session.current_mail_id.clone().unwrap_or_default();
+        let Some(m) = storage.list_mail(&session.display_name()).await?.into_iter().find(|m| m.id == id) else {
+            return Ok("Mail missing. B back.\n".into());
+        };
+        Ok(format!(
+            "[BBS] Mail from {} {}\n{}\nReply: Y reply, DEL delete, + next, - prev, B back\n",
+            m.from, m.timestamp.format("%m/%d %H:%M"), m.content
+        ))
+    }
+
+    /// Open a message in MailRead and mark it read
+    async fn open_mail(&self, session: &mut Session, storage: &mut Storage, mail: MailMessage) -> Result<String> {
+        storage.mark_mail_read(&session.display_name(), &mail.id).await?;
+        session.current_mail_id = Some(mail.id);
+        session.state = SessionState::MailRead;
+        self.render_mail_read(session, storage).await
+    }
+
+    async fn handle_mail_inbox(&self, session: &mut Session, raw: &str, upper: &str, storage: &mut Storage, _config: &Config) -> Result<String> {
+        match upper {
+            "H" | "HELP" | "?" => return Ok("Mail: 1-5 read, DEL n delete, SEND @u <text>, L more, B back, X exit\n".into()),
+            "B" => { session.state = SessionState::MainMenu; return Ok("Main Menu:\n[M]essages [U]ser [Q]uit\n".into()); }
+            "X" => { session.state = SessionState::Disconnected; return Ok("Goodbye! 73s".into()); }
+            "L" => {
+                let total = storage.list_mail(&session.display_name()).await?.len();
+                if session.list_page * 5 < total { session.list_page += 1; }
+                return self.render_mail_inbox(session, storage).await;
+            }
+            _ => {}
+        }
+        if let Some(arg) = upper.strip_prefix("DEL") {
+            let Ok(n) = arg.trim().parse::<usize>() else { return Ok("Usage: DEL <n>\n".into()) };
+            let Some(mail) = self.mail_on_page(session, storage, n).await? else { return Ok("No such message.\n".into()) };
+            storage.delete_mail(&session.display_name(), &mail.id).await?;
+            let mut out = String::from("Deleted.\n");
+            out.push_str(&self.render_mail_inbox(session, storage).await?);
+            return Ok(out);
+        }
+        if let Ok(n) = raw.trim().parse::<usize>() {
+            if (1..=5).contains(&n) {
+                if let Some(mail) = self.mail_on_page(session, storage, n).await? {
+                    return self.open_mail(session, storage, mail).await;
+                }
+            }
+            return Ok("No such message.\n".into());
+        }
+        Ok("Mail: 1-5 read, DEL n, SEND @u <text>, L more, B back\n".into())
+    }
+
+    async fn handle_mail_read(&self, session: &mut Session, _raw: &str, upper: &str, storage: &mut Storage, _config: &Config) -> Result<String> {
+        let username = session.display_name();
+        match upper {
+            "B" => { session.state = SessionState::MailInbox; self.render_mail_inbox(session, storage).await }
+            "H" | "HELP" | "?" => Ok("Mail read: Y reply, DEL delete, + next, - prev, B back\n".into()),
+            "Y" | "R" => {
+                let id = session.current_mail_id.clone().unwrap_or_default();
+                match storage.list_mail(&username).await?.into_iter().find(|m| m.id == id) {
+                    Some(m) => {
+                        session.state = SessionState::MailCompose;
+                        let reply = format!("[BBS] Reply to {} (single message, . to cancel):\n", m.from);
+                        session.mail_to = Some(m.from);
+                        Ok(reply)
+                    }
+                    None => { session.state = SessionState::MailInbox; self.render_mail_inbox(session, storage).await }
+                }
+            }
+            "D" | "DEL" | "DELETE" => {
+                if let Some(id) = session.current_mail_id.take() { storage.delete_mail(&username, &id).await?; }
+                session.state = SessionState::MailInbox;
+                let mut out = String::from("Deleted.\n");
+                out.push_str(&self.render_mail_inbox(session, storage).await?);
+                Ok(out)
+            }
+            "+" | "-" => {
+                let mail = storage.list_mail(&username).await?;
+                let pos = mail.iter().position(|m| Some(&m.id) == session.current_mail_id.as_ref()).unwrap_or(0);
+                // Inbox is newest first: + moves to the next (older) message
+                let new_pos = if upper == "+" { pos + 1 } else { pos.saturating_sub(1) };
+                match mail.into_iter().nth(new_pos) {
+                    Some(m) => self.open_mail(session, storage, m).await,
+                    None => self.render_mail_read(session, storage).await,
+                }
+            }
+            _ => Ok("Mail read: Y reply, DEL delete, + next, - prev, B back\n".into()),
+        }
+    }
+
+    async fn handle_mail_compose(&self, session: &mut Session, raw: &str, storage: &mut Storage, _config: &Config) -> Result<String> {
+        let to = session.mail_to.take();
+        session.state = SessionState::MailInbox;
+        let text = raw.trim();
+        match to {
+            Some(_) if text == "." => Ok("Mail cancelled.\n".into()),
+            Some(to) => Ok(self.deliver_mail(session, &to, text, storage).await),
+            None => self.render_mail_inbox(session, storage).await,
+        }
+    }
+
+    /// SEARCH hits in topics the session may read
+    async fn readable_search_hits(&self, session: &Session, storage: &Storage) -> Result<Vec<SearchHit>> {
+        let query = session.search_query.as_deref().unwrap_or("");
+        let hits = storage.search_messages(query).await?;
+        Ok(hits.into_iter().filter(|h| self_topic_can_read(session.user_level, &h.topic, storage)).collect())
+    }
+
+    /// One page of SEARCH results, sized so that it and the prompt fit a single frame
+    async fn render_search_results(&self, session: &Session, storage: &Storage) -> Result<String> {
+        const MAX: usize = 230;
+        let query = ui::utf8_truncate(session.search_query.as_deref().unwrap_or(""), 16);
+        let hits = self.readable_search_hits(session, storage).await?;
+        if hits.is_empty() {
+            return Ok(format!("[BBS] No posts match {}.\nSEARCH <words> again, B back\n", query));
+        }
+        let pages = hits.len().div_ceil(5);
+        let page = session.list_page.clamp(1, pages);
+        let items = &hits[(page - 1) * 5..(page * 5).min(hits.len())];
+        let header = format!("[BBS] {}: {} found p{}/{}\n", query, hits.len(), page, pages);
+        let footer = if page < pages { "Reply: 1-5 read, L more, B back\n" } else { "Reply: 1-5 read, B back\n" };
+        // Share what is left of the frame between the result lines
+        let budget = MAX.saturating_sub(session.build_prompt().len() + header.len() + footer.len());
+        let per_line = budget / items.len();
+        let mut out = header;
+        for (i, hit) in items.iter().enumerate() {
+            let prefix = format!("{} {}: ", i + 1, ui::utf8_truncate(&hit.topic, 10));
+            // newline plus a possible '…'
+            let room = per_line.saturating_sub(prefix.len() + 4).max(8);
+            out.push_str(&format!("{}{}\n", prefix, ui::utf8_truncate(&hit.title, room)));
+        }
+        out.push_str(footer);
+        Ok(out)
+    }
+
+    async fn handle_search_results(&self, session: &mut Session, raw: &str, upper: &str, storage: &mut Storage, config: &Config) -> Result<String> {
+        match upper {
+            "H" | "HELP" | "?" => return Ok("Search: 1-5 read, L more, B back, SEARCH <words> new search\n".into()),
+            "B" => {
+                session.search_query = None;
+                session.state = SessionState::MainMenu;
+                return Ok("Main Menu:\n[M]essages [U]ser [Q]uit\n".into());
+            }
+            "X" => { session.state = SessionState::Disconnected; return Ok("Goodbye! 73s".into()); }
+            "L" => {
+                let total = self.readable_search_hits(session, storage).await?.len();
+                if session.list_page * 5 < total { session.list_page += 1; }
+                return self.render_search_results(session, storage).await;
+            }
+            _ => {}
+        }
+        if let Ok(n) = raw.trim().parse::<usize>() {
+            let hits = self.readable_search_hits(session, storage).await?;
+            let idx = (session.list_page.saturating_sub(1)) * 5 + n.saturating_sub(1);
+            let Some(hit) = hits.into_iter().nth(idx).filter(|_| (1..=5).contains(&n)) else {
+                return Ok("No such result.\n".into());
+            };
+            session.current_topic = Some(hit.topic);
+            session.current_thread_id = Some(hit.id);
+            session.post_index = 1;
+            session.slice_index = 1;
+            session.state = SessionState::ThreadRead;
+            return self.render_thread_read(session, storage, config).await;
+        }
+        self.render_search_results(session, storage).await
+    }
+}
+
 impl Default for CommandProcessor {
     fn default() -> Self { Self::new() }
 }
\ No newline at end of file
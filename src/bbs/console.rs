@@ -0,0 +1,125 @@
+//! Local console session (`meshbbs console`): use the BBS from a terminal without a radio.
+//!
+//! Each input line is delivered to [`BbsServer::route_text_event`] exactly like a direct
+//! message from a mesh node with the console's synthetic node id, so menus, sessions,
+//! 230-byte chunking and prompts behave as they do over the air. Lines starting with `^`
+//! are sent on the primary public channel instead (`^HELP`, `^WEATHER`, ...).
+//!
+//! Every packet the BBS transmits is printed as one block: replies to the console node
+//! as-is, public broadcasts prefixed with `[public ch N]` and messages to other nodes with
+//! `[to 0x........]`. With [`ConsoleOptions::pace`] packets go through the outbound
+//! scheduler and are printed with their offset from the last input line, as a node on the
+//! mesh would see them arrive.
+//!
+//! The console opens the configured data directory directly; stop a running server first.
+
+use anyhow::Result;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+
+use super::BbsServer;
+use crate::meshtastic::{OutgoingMessage, TextEvent};
+
+/// Node id used for the console unless overridden (shown as `0xc0de0001`).
+pub const DEFAULT_CONSOLE_NODE: u32 = 0xC0DE_0001;
+
+/// How long a paced console waits for further output after the scheduler has drained.
+const SETTLE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+pub struct ConsoleOptions {
+    /// Synthetic node id the console's messages appear to come from
+    pub node_id: u32,
+    /// Deliver output at radio pace through the outbound scheduler
+    pub pace: bool,
+}
+
+impl Default for ConsoleOptions {
+    fn default() -> Self {
+        ConsoleOptions { node_id: DEFAULT_CONSOLE_NODE, pace: false }
+    }
+}
+
+/// Text event for one console input line. Console lines never cross the air, so they count
+/// as PKI-encrypted for `security.pki.require_for_passwords`.
+pub fn line_event(node_id: u32, line: &str) -> TextEvent {
+    TextEvent { source: node_id, dest: None, is_direct: !line.starts_with('^'), channel: None, content: line.to_string(), pki_encrypted: true, public_key: None }
+}
+
+/// Run a console session until `input` reaches end of file, then wait for queued output.
+pub async fn run<R, W>(server: &mut BbsServer, opts: &ConsoleOptions, input: R, out: &mut W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut packets = server.attach_console(opts.pace);
+    let mut lines = input.lines();
+    let mut since = Instant::now();
+    loop {
+        tokio::select! {
+            line = lines.next_line() => {
+                let Some(line) = line? else { break };
+                let line = line.trim();
+                if line.is_empty() { continue; }
+                since = Instant::now();
+                server.route_text_event(line_event(opts.node_id, line)).await?;
+                if !opts.pace {
+                    while let Ok(msg) = packets.try_recv() {
+                        print_packet(out, opts, &msg, None).await?;
+                    }
+                }
+            }
+            Some(msg) = packets.recv() => {
+                print_packet(out, opts, &msg, opts.pace.then(|| since.elapsed())).await?;
+            }
+        }
+    }
+    drain(server, opts, &mut packets, since, out).await
+}
+
+/// Print what is still queued once input has ended.
+async fn drain<W: AsyncWrite + Unpin>(
+    server: &mut BbsServer,
+    opts: &ConsoleOptions,
+    packets: &mut mpsc::UnboundedReceiver<OutgoingMessage>,
+    since: Instant,
+    out: &mut W,
+) -> Result<()> {
+    let elapsed = |pace: bool| pace.then(|| since.elapsed());
+    if let Some(scheduler) = server.scheduler_handle() {
+        loop {
+            match tokio::time::timeout(SETTLE, packets.recv()).await {
+                Ok(Some(msg)) => print_packet(out, opts, &msg, elapsed(opts.pace)).await?,
+                Ok(None) => break,
+                Err(_) => {
+                    if scheduler.snapshot().await.map(|s| s.queued).unwrap_or(0) == 0 { break; }
+                }
+            }
+        }
+        scheduler.shutdown().await;
+    }
+    // Delayed sends (e.g. HELP broadcasts) hold their own sender; the channel closes after the last one
+    server.detach_console();
+    while let Some(msg) = packets.recv().await {
+        print_packet(out, opts, &msg, elapsed(opts.pace)).await?;
+    }
+    Ok(())
+}
+
+async fn print_packet<W: AsyncWrite + Unpin>(out: &mut W, opts: &ConsoleOptions, msg: &OutgoingMessage, elapsed: Option<Duration>) -> Result<()> {
+    let mut text = String::new();
+    if let Some(elapsed) = elapsed {
+        text.push_str(&format!("[+{:.1}s] ", elapsed.as_secs_f32()));
+    }
+    match msg.to_node {
+        Some(node) if node == opts.node_id => {}
+        Some(node) => text.push_str(&format!("[to 0x{node:08x}] ")),
+        None => text.push_str(&format!("[public ch {}] ", msg.channel)),
+    }
+    text.push_str(&msg.content);
+    text.push_str("\n\n");
+    out.write_all(text.as_bytes()).await?;
+    out.flush().await?;
+    Ok(())
+}
@@ -23,7 +23,7 @@
 //! * Migrate all DM + broadcast sends through scheduler.
 //! * Per‑category pacing (e.g. system vs user vs maintenance).
 //! * Retry / ACK re‑enqueue integration (remove scattered timers).
-//! * Metrics export (queue length, deferrals, latency).
+//! * Metrics export of deferrals and latency (queue depth, drops and escalations are exported).
 //! * Optional token bucket or weighted fairness.
 //! * Cancellation / priority aging.
 //!
@@ -38,20 +38,19 @@ use tokio::sync::{mpsc, oneshot};
 use crate::meshtastic::OutgoingMessage;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-#[allow(dead_code)] // Some categories not yet emitted in Phase 2; reserved for Phase 3+ (retries, system, maintenance)
+#[allow(dead_code)] // Some categories not yet emitted in Phase 2; reserved for Phase 3+ (retries, system)
 pub enum MessageCategory {
     Direct,          // Direct user DM (interactive)
     Broadcast,       // Public/channel broadcast
     System,          // System/service messages (welcome, prompts)
     Admin,           // Administrative notices / moderation actions
     Retry,           // Retries / re-sends (future Phase 3)
-    Maintenance,     // Background sync / housekeeping
+    Maintenance,     // Background sync / housekeeping (retention notices)
     HelpBroadcast,   // Specific labelled variant (can be merged into Broadcast later)
     DirectHelp,      // Specific labelled variant for help DM (High priority labeling aid)
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-#[allow(dead_code)] // Background priority reserved for future maintenance tasks
 pub enum Priority { High, Normal, Low, Background }
 
 #[derive(Debug)]
@@ -96,6 +95,8 @@ pub enum ScheduleCommand {
     Enqueue(MessageEnvelope),
     #[allow(dead_code)] Snapshot(oneshot::Sender<SchedulerStats>),
     #[allow(dead_code)] Shutdown(oneshot::Sender<()>),
+    /// Replace pacing and queue settings (config reload); queued messages are kept
+    Reconfigure(SchedulerConfig),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -115,11 +116,12 @@ pub struct SchedulerHandle {
 impl SchedulerHandle {
     pub fn enqueue(&self, env: MessageEnvelope) { let _ = self.tx.send(ScheduleCommand::Enqueue(env)); }
     #[allow(dead_code)] pub async fn shutdown(&self) { let (tx, rx) = oneshot::channel(); let _ = self.tx.send(ScheduleCommand::Shutdown(tx)); let _ = rx.await; }
+    pub fn reconfigure(&self, cfg: SchedulerConfig) { let _ = self.tx.send(ScheduleCommand::Reconfigure(cfg)); }
     #[allow(dead_code)] pub async fn snapshot(&self) -> Option<SchedulerStats> { let (tx, rx) = oneshot::channel(); if self.tx.send(ScheduleCommand::Snapshot(tx)).is_ok() { rx.await.ok() } else { None } }
 }
 
 pub fn start_scheduler(
-    cfg: SchedulerConfig,
+    mut cfg: SchedulerConfig,
     outgoing: mpsc::UnboundedSender<OutgoingMessage>,
 ) -> SchedulerHandle {
     let (tx, mut rx) = mpsc::unbounded_channel::<ScheduleCommand>();
@@ -155,10 +157,15 @@ pub fn start_scheduler(
                         },
                         ScheduleCommand::Snapshot(resp) => { let _ = resp.send(SchedulerStats { queued: queue.len(), ..stats }); },
                         ScheduleCommand::Shutdown(done) => { let _ = done.send(()); break; }
+                        ScheduleCommand::Reconfigure(new_cfg) => {
+                            log::info!("scheduler reconfigured: min_send_gap_ms={} max_queue={}", new_cfg.min_send_gap_ms, new_cfg.max_queue);
+                            cfg = new_cfg;
+                        }
                     }
                 }
                 _ = tokio::time::sleep(TICK) => {}
             }
+            crate::metrics::set_scheduler(queue.len(), stats.dispatched_total, stats.dropped_total, stats.dropped_overflow, stats.escalations);
             if queue.is_empty() { continue; }
             let now = Instant::now();
 
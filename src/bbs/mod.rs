@@ -10,6 +10,8 @@
 //! - [`commands`] - Command processing and execution engine
 //! - [`public`] - Public channel command parsing and discovery protocols
 //! - [`roles`] - User role definitions and permission management
+//! - [`admin`] - Administrative requests from the web admin UI
+//! - [`console`] - Local terminal session without a radio (`meshbbs console`)
 //!
 //! ## Architecture
 //!
@@ -68,6 +70,9 @@ pub mod session;
 pub mod commands;
 pub mod public;
 pub mod roles;
+pub mod admin;
+#[cfg(feature = "meshtastic-proto")]
+pub mod console;
 pub mod dispatch;
 pub mod slotmachine;
 pub mod eightball;
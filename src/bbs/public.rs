@@ -163,3 +163,87 @@ pub enum PublicCommand {
     Unknown,
     Invalid(String),
 }
+
+impl PublicCommand {
+    /// Canonical command name used by per-channel rules (`None` for unrecognized input).
+    /// `Invalid` only arises from a malformed `^LOGIN`, so it is governed by the LOGIN rule.
+    pub fn rule_name(&self) -> Option<&'static str> {
+        match self {
+            PublicCommand::Help => Some("HELP"),
+            PublicCommand::Login(_) | PublicCommand::Invalid(_) => Some("LOGIN"),
+            PublicCommand::Weather => Some("WEATHER"),
+            PublicCommand::SlotMachine => Some("SLOT"),
+            PublicCommand::SlotStats => Some("SLOTSTATS"),
+            PublicCommand::EightBall => Some("8BALL"),
+            PublicCommand::Fortune => Some("FORTUNE"),
+            PublicCommand::Unknown => None,
+        }
+    }
+}
+
+/// Normalize a configured command name (`^slotmachine`, `Slot`, `?`) to its rule name.
+fn normalize_rule_name(raw: &str) -> String {
+    let upper = raw.trim().trim_start_matches('^').to_ascii_uppercase();
+    match upper.as_str() {
+        "?" => "HELP".to_string(),
+        "SLOTMACHINE" => "SLOT".to_string(),
+        _ => upper,
+    }
+}
+
+/// True when a configured command name (any spelling [`PublicChannelPolicy`] accepts) names a
+/// public command.
+pub fn is_known_command_name(raw: &str) -> bool {
+    crate::metrics::PUBLIC_COMMANDS.contains(&normalize_rule_name(raw).as_str())
+}
+
+/// Which channels accept public commands and which commands each channel enables.
+#[derive(Debug, Clone)]
+pub struct PublicChannelPolicy {
+    // channel index -> enabled rule names (None = all commands)
+    channels: HashMap<u32, Option<Vec<String>>>,
+    primary: u32,
+}
+
+impl PublicChannelPolicy {
+    /// Build from `[meshtastic]` config. Without `public_channels`, only `channel` is
+    /// listened on and every public command is enabled there.
+    pub fn from_config(cfg: &crate::config::MeshtasticConfig) -> Self {
+        let primary = cfg.channel as u32;
+        let mut channels = HashMap::new();
+        match &cfg.public_channels {
+            Some(list) if !list.is_empty() => {
+                for ch in list {
+                    let rules = if ch.commands.is_empty() { None } else { Some(ch.commands.iter().map(|c| normalize_rule_name(c)).collect()) };
+                    channels.insert(ch.index, rules);
+                }
+            }
+            _ => { channels.insert(primary, None); }
+        }
+        Self { channels, primary }
+    }
+
+    /// Channel used for broadcasts that are not replies to a specific request.
+    pub fn primary_channel(&self) -> u32 { self.primary }
+
+    pub fn listens_on(&self, channel: u32) -> bool { self.channels.contains_key(&channel) }
+
+    /// True when `cmd` is enabled on `channel`. Unknown input is never allowed.
+    pub fn allows(&self, channel: u32, cmd: &PublicCommand) -> bool {
+        let Some(name) = cmd.rule_name() else { return false };
+        self.allows_name(channel, name)
+    }
+
+    /// Rule-name variant of [`allows`](Self::allows), used when listing commands in HELP.
+    pub fn allows_name(&self, channel: u32, name: &str) -> bool {
+        match self.channels.get(&channel) {
+            Some(None) => true,
+            Some(Some(list)) => list.iter().any(|c| c == name),
+            None => false,
+        }
+    }
+}
+
+impl Default for PublicChannelPolicy {
+    fn default() -> Self { Self { channels: HashMap::from([(0, None)]), primary: 0 } }
+}
@@ -5,16 +5,17 @@ use tokio::time::{Instant, Duration};
 use tokio::sync::mpsc;
 use std::collections::HashMap;
 
-use crate::config::Config;
-use crate::meshtastic::{MeshtasticDevice, OutgoingMessage, MessagePriority, ControlMessage};
+use crate::config::{Config, ReloadReport};
+use crate::meshtastic::{MeshtasticDevice, OutgoingMessage, MessagePriority, ControlMessage, DeliveryEvent};
 #[cfg(feature = "meshtastic-proto")]
 use crate::meshtastic::TextEvent;
-use crate::storage::Storage;
+use crate::storage::{HeldMessage, Lockout, LockoutTarget, Sanction, SanctionKind, Storage, LOCKOUT_ACTOR, RESET_CODE_TTL_MINUTES};
 use crate::logutil::escape_log;
 use crate::validation::validate_sysop_name;
 use super::session::Session;
-use super::public::{PublicState, PublicCommandParser, PublicCommand};
-use super::roles::{LEVEL_MODERATOR, LEVEL_USER, role_name};
+use super::public::{PublicState, PublicCommandParser, PublicCommand, PublicChannelPolicy};
+use super::roles::{LEVEL_MODERATOR, LEVEL_SYSOP, LEVEL_USER, role_name};
+use super::admin::{AdminCall, AdminHandle, AdminRequest, NotFound, SessionView, TopicView, UserView, AUDIT_PAGE_SIZE};
 
 macro_rules! sec_log {
     ($($arg:tt)*) => { log::warn!(target: "security", $($arg)*); };
@@ -97,13 +98,25 @@ pub struct BbsServer {
     #[cfg(feature = "meshtastic-proto")]
     outgoing_tx: Option<mpsc::UnboundedSender<OutgoingMessage>>,
     #[cfg(feature = "meshtastic-proto")]
+    delivery_rx: Option<mpsc::UnboundedReceiver<DeliveryEvent>>,
+    // Store-and-forward messages sent again and awaiting the outcome, keyed by (node, content)
+    redelivering: HashMap<(String, String), HeldMessage>,
+    admin_rx: Option<mpsc::UnboundedReceiver<AdminCall>>,
+    /// Where the configuration is re-read from on `SIGHUP` / `RELOAD`
+    config_loader: Option<crate::config::ConfigLoader>,
+    #[cfg(feature = "meshtastic-proto")]
     scheduler: Option<crate::bbs::dispatch::SchedulerHandle>,
     #[cfg(feature = "meshtastic-proto")]
     reader_control_tx: Option<mpsc::UnboundedSender<ControlMessage>>,
     #[cfg(feature = "meshtastic-proto")]
     writer_control_tx: Option<mpsc::UnboundedSender<ControlMessage>>,
+    link_status: Option<crate::meshtastic::link::SharedLinkStatus>,
     public_state: PublicState,
     public_parser: PublicCommandParser,
+    public_channels: PublicChannelPolicy,
+    reply_channels: HashMap<String, (u32, Instant)>, // node_id -> channel index of its most recent request, and when
+    pending_keys: HashMap<u32, String>, // node -> changed PKI key (base64) awaiting ACCEPTKEY
+    blocked_nodes: Option<HashMap<String, Option<chrono::DateTime<Utc>>>>, // node -> block expiry; None until (re)loaded from storage
     #[cfg(feature = "weather")]
     weather_cache: Option<(Instant, String)>, // (fetched_at, value)
     #[cfg(feature = "weather")]
@@ -112,25 +125,65 @@ pub struct BbsServer {
     pending_direct: Vec<(u32, u32, String)>, // queue of (dest_node_id, channel, message) awaiting our node id
     #[cfg(feature = "meshtastic-proto")]
     node_cache_last_cleanup: Instant, // track when we last cleaned up stale nodes
+    retention_last_run: Instant, // last topic retention pass (or startup)
+    backup_last_run: Instant, // last scheduled backup (or startup)
     #[allow(dead_code)]
     #[doc(hidden)]
     pub(crate) test_messages: Vec<(String,String)>, // collected outbound messages (testing)
 }
 
+/// Actor recorded in the admin audit for node key changes
+const PKI_ACTOR: &str = "pki";
+
+/// DM commands that carry a password; `security.pki.require_for_passwords` refuses them
+/// unless the DM was PKI-encrypted
+const PASSWORD_COMMANDS: [&str; 5] = ["LOGIN", "REGISTER", "CHPASS", "SETPASS", "RESET"];
+
 // Verbose HELP material & chunker (outside impl so usable without Self scoping issues during compilation ordering)
 const VERBOSE_HELP: &str = concat!(
     "Meshbbs Extended Help\n",
-    "Authentication:\n  REGISTER <name> <pass>  Create account\n  LOGIN <name> <pass>     Log in\n  SETPASS <new>           Set first password\n  CHPASS <old> <new>      Change password\n  LOGOUT                  End session\n\n",
+    "Authentication:\n  REGISTER <name> <pass>  Create account\n  LOGIN <name> <pass>     Log in\n  SETPASS <new>           Set first password\n  CHPASS <old> <new>      Change password\n  RESET <name> <code> <new>  Use a reset code from staff\n  NODES / TRUST / UNTRUST <n>  List, add this, drop trusted node\n  LOGOUT                  End session\n\n",
     "Compact Navigation:\n  M       Topics menu (paged)\n  1-9     Pick item on page\n  L       More items\n  U/B     Up/back (to parent)\n  X       Exit\n  WHERE/W Where am I breadcrumb\n\n",
     "Topics → Subtopics → Threads → Read:\n  In Subtopics: 1-9 pick, U up\n  In Threads:   1-9 read, N new, F <text> filter, U up\n  In Read:      + next, - prev, Y reply\n\n",
-    "Moderator (level 5+):\n  Threads:  D<n> delete  P<n> pin/unpin  R<n> <title> rename  K lock/unlock area\n  Read:     D delete     P pin/unpin     R <title>            K lock/unlock area\n\n",
-    "Sysop (level 10):\n  G @user=LEVEL|ROLE      Grant level (1/5/10) or USER/MOD/SYSOP\n\n",
+    "Moderator (level 5+):\n  Threads:  D<n> delete  P<n> pin/unpin  R<n> <title> rename  K lock/unlock area\n  Read:     D delete     P pin/unpin     R <title>            K lock/unlock area\n  DELLOG/DL [page]        Deletion log\n  RESETPW <user>          One-time password reset code\n  BAN/MUTE <user> <t> [why]  No login / no posting; t=30m 12h 7d PERM OFF\n  BLOCKNODE <node> <t> [why]  Ignore a node entirely\n  BANS                    List bans, mutes and blocks\n\n",
+    "Sysop (level 10):\n  G @user=LEVEL|ROLE      Grant level (1/5/10) or USER/MOD/SYSOP\n  UNLOCKUSER <user|node>  Clear a failed-login lockout\n\n",
     "Administration (mod/sysop):\n  USERS [pattern]         List users (filter optional)\n  WHO                     Show logged-in users\n  USERINFO <user>         Detailed user info\n  SESSIONS                List all sessions\n  KICK <user>             Force logout user\n  BROADCAST <msg>         Broadcast to all\n  ADMIN / DASHBOARD       System overview\n\n",
+    "Search:\n  SEARCH <words>          Find posts in all topics (1-5 read, L more)\n\n",
+    "Mail:\n  MAIL                    Open your inbox (1-5 read, DEL n delete)\n  SEND @user <text>       Send private mail\n  SEND @user              Compose on the next line\n\n",
     "Legacy commands (compat):\n  TOPICS / LIST           List topics\n  READ <topic>            Read recent messages\n  POST <topic> <text>     Post a message\n\n",
     "Misc:\n  HELP        Compact help\n  HELP+ / HELP V  Verbose help (this)\n  Weather (public)       Send WEATHER on public channel\n  Slot Machine (public)  ^SLOT or ^SLOTMACHINE to play\n  Slot Stats (public)    ^SLOTSTATS\n  Magic 8-Ball (public)  ^8BALL\n  Fortune (public)       ^FORTUNE for classic Unix wisdom\n\n",
     "Limits:\n  Max frame ~230 bytes; verbose help auto-splits.\n"
 );
 
+/// `SIGHUP` listener for config reload; never fires on platforms without the signal.
+struct Hangup {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangup {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            let signal = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .map_err(|e| warn!("SIGHUP reload unavailable: {}", e))
+                .ok();
+            Hangup { signal }
+        }
+        #[cfg(not(unix))]
+        Hangup {}
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() { return; }
+            self.signal = None;
+        }
+        std::future::pending::<()>().await
+    }
+}
+
 fn chunk_verbose_help() -> Vec<String> {
     const MAX: usize = 230;
     let mut chunks = Vec::new();
@@ -147,6 +200,42 @@ fn chunk_verbose_help() -> Vec<String> {
     chunks
 }
 
+/// Node id from its decimal or `0x` hex form
+fn parse_node_id(node: &str) -> Option<u32> {
+    match node.strip_prefix("0x").or_else(|| node.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => node.parse().ok(),
+    }
+}
+
+/// Length of a ban, mute or node block: `PERM`, or a count of minutes, hours, days or
+/// weeks (`30m`, `12h`, `7d`, `2w`). `None` if unparseable; `Some(None)` for permanent.
+fn parse_sanction_span(text: &str) -> Option<Option<chrono::Duration>> {
+    let text = text.to_ascii_lowercase();
+    if text == "perm" || text == "permanent" { return Some(None); }
+    let unit = text.chars().last()?;
+    let count: u32 = text[..text.len() - 1].parse().ok().filter(|n| *n > 0)?;
+    let minutes = count as i64 * match unit { 'm' => 1, 'h' => 60, 'd' => 1440, 'w' => 10080, _ => return None };
+    chrono::Duration::try_minutes(minutes).map(Some)
+}
+
+/// Time left, rounded up to whole minutes, hours or days: `45m`, `3h`, `2d`
+fn format_span(left: chrono::Duration) -> String {
+    let minutes = (left.num_seconds().max(0) + 59) / 60;
+    match minutes {
+        m if m < 120 => format!("{}m", m),
+        m if m < 24 * 60 => format!("{}h", (m + 59) / 60),
+        m => format!("{}d", (m + 1439) / 1440),
+    }
+}
+
+/// What a sanctioned user is told, e.g. "Account banned for 2d: spam\n"
+pub(crate) fn sanction_notice(what: &str, sanction: &Sanction, now: chrono::DateTime<Utc>) -> String {
+    let span = sanction.expires_at.map(|until| format!(" for {}", format_span(until - now))).unwrap_or_default();
+    let reason = if sanction.reason.is_empty() { ".".to_string() } else { format!(": {}", sanction.reason) };
+    format!("{}{}{}\n", what, span, reason)
+}
+
 impl BbsServer {
     /// Chunk a UTF-8 string into <= max_bytes segments without splitting codepoints.
     /// Attempts to split on newline boundaries preferentially, then falls back to byte slicing.
@@ -256,23 +345,15 @@ impl BbsServer {
 
         // Build optional Argon2 params from config
         let storage = {
-            use argon2::Params;
-            if let Some(sec) = &config.security {
-                if let Some(a) = &sec.argon2 {
-                    let builder = Params::DEFAULT;
-                    let mem = a.memory_kib.unwrap_or(builder.m_cost());
-                    let time = a.time_cost.unwrap_or(builder.t_cost());
-                    let para = a.parallelism.unwrap_or(builder.p_cost());
-                    let params = Params::new(mem, time, para, None).ok();
-                    Storage::new_with_params(&config.storage.data_dir, params).await?
-                } else {
-                    Storage::new(&config.storage.data_dir).await?
-                }
-            } else {
-                Storage::new(&config.storage.data_dir).await?
-            }
+            let params = config.security.as_ref().and_then(|sec| sec.argon2.as_ref())
+                .map(|a| a.params()).transpose()
+                .map_err(|e| anyhow::anyhow!("[security.argon2]: {}", e))?;
+            let encryption = config.security.as_ref().and_then(|sec| sec.encryption.as_ref());
+            let cipher = crate::storage::open_data_key(&config.storage.data_dir, config.storage.backend, encryption)?;
+            Storage::open(&config.storage.data_dir, config.storage.backend, params, cipher).await?
         };
 
+        let public_channels = PublicChannelPolicy::from_config(&config.meshtastic);
         let mut server = Self {
             config,
             storage,
@@ -284,16 +365,26 @@ impl BbsServer {
             #[cfg(feature = "meshtastic-proto")]
             outgoing_tx: None,
             #[cfg(feature = "meshtastic-proto")]
+            delivery_rx: None,
+            redelivering: HashMap::new(),
+            admin_rx: None,
+            config_loader: None,
+            #[cfg(feature = "meshtastic-proto")]
             scheduler: None,
             #[cfg(feature = "meshtastic-proto")]
             reader_control_tx: None,
             #[cfg(feature = "meshtastic-proto")]
             writer_control_tx: None,
+            link_status: None,
             public_state: PublicState::new(
                 std::time::Duration::from_secs(20),
                 std::time::Duration::from_secs(300)
             ),
             public_parser: PublicCommandParser::new(),
+            public_channels,
+            reply_channels: HashMap::new(),
+            pending_keys: HashMap::new(),
+            blocked_nodes: None,
             #[cfg(feature = "weather")]
             weather_cache: None,
             #[cfg(feature = "weather")]
@@ -302,6 +393,8 @@ impl BbsServer {
             pending_direct: Vec::new(),
             #[cfg(feature = "meshtastic-proto")]
             node_cache_last_cleanup: Instant::now() - Duration::from_secs(3601),
+            retention_last_run: Instant::now(),
+            backup_last_run: Instant::now(),
             test_messages: Vec::new(),
         };
         // Legacy compatibility: previously, topics could be defined in TOML.
@@ -313,53 +406,83 @@ impl BbsServer {
         Ok(server)
     }
 
-    /// Connect to a Meshtastic device using the new reader/writer pattern
+    /// Connect to a Meshtastic device using the new reader/writer pattern. The link is
+    /// supervised: if the device is missing or later lost, it is reopened with backoff
+    /// (see [`crate::meshtastic::link`]). Only an invalid device address is an error.
     #[cfg(feature = "meshtastic-proto")]
     pub async fn connect_device(&mut self, port: &str) -> Result<()> {
         info!("Connecting to Meshtastic device on {} using reader/writer pattern", port);
-        
-        // Build writer tuning from config (with enforced 2s minimum)
-        let mcfg = &self.config.meshtastic;
-        let mut min_send_gap_ms = mcfg.min_send_gap_ms.unwrap_or(2000);
-        if min_send_gap_ms < 2000 {
-            warn!("Configured min_send_gap_ms={}ms is below 2000ms; clamping to 2000ms", min_send_gap_ms);
-            min_send_gap_ms = 2000;
-        }
-        let mut backoffs = mcfg.dm_resend_backoff_seconds.clone().unwrap_or_else(|| vec![4, 8, 16]);
-        if backoffs.is_empty() { backoffs = vec![4, 8, 16]; }
-        // sanitize non-positive entries
-        backoffs.retain(|&s| s > 0);
-        if backoffs.is_empty() { backoffs = vec![4, 8, 16]; }
-        let tuning = crate::meshtastic::WriterTuning {
-            min_send_gap_ms,
-            dm_resend_backoff_seconds: backoffs,
-            post_dm_broadcast_gap_ms: mcfg.post_dm_broadcast_gap_ms.unwrap_or(1200),
-            dm_to_dm_gap_ms: mcfg.dm_to_dm_gap_ms.unwrap_or(600),
+        crate::meshtastic::TransportAddr::parse(port)?;
+        let opener = crate::meshtastic::link::device_opener(port, self.config.meshtastic.baud_rate);
+        self.connect_supervised(port, opener).await
+    }
+
+    /// Open a transport with `opener` and start the reader/writer under a link supervisor.
+    /// If the first open fails the server still starts and the supervisor keeps retrying.
+    #[cfg(feature = "meshtastic-proto")]
+    pub async fn connect_supervised(&mut self, target: &str, mut opener: crate::meshtastic::link::TransportOpener) -> Result<()> {
+        use crate::meshtastic::link::{LinkState, LinkStatus, LinkSupervisor};
+        let (transport, status): (Box<dyn crate::meshtastic::Transport>, _) = match opener().await {
+            Ok(t) => (t, LinkStatus::new(target, LinkState::Connected)),
+            Err(e) => {
+                warn!("Failed to open Meshtastic device {}: {} (will keep retrying)", target, e);
+                let mut status = LinkStatus::new(target, LinkState::Reconnecting);
+                status.attempts = 1;
+                status.last_error = Some(e.to_string());
+                (Box::new(crate::meshtastic::transport::NullTransport), status)
+            }
         };
+        let shared: crate::meshtastic::SharedTransport = std::sync::Arc::new(std::sync::Mutex::new(transport));
+        self.connect_transport(shared.clone()).await?;
+        let status = std::sync::Arc::new(std::sync::Mutex::new(status));
+        self.link_status = Some(status.clone());
+        let (Some(reader_ctrl), Some(writer_ctrl)) = (self.reader_control_tx.clone(), self.writer_control_tx.clone()) else {
+            return Err(anyhow!("reader/writer control channels missing"));
+        };
+        let supervisor = LinkSupervisor::new(shared, opener, status, reader_ctrl, writer_ctrl, Some(self.config.storage.data_dir.clone()));
+        tokio::spawn(supervisor.run());
+        Ok(())
+    }
+
+    /// Current radio link status, if a device (or simulator) is attached.
+    pub fn link_status(&self) -> Option<crate::meshtastic::link::LinkStatus> {
+        self.link_status.as_ref().map(|s| s.lock().unwrap().clone())
+    }
+
+    /// Start the reader/writer tasks and scheduler on an already opened transport
+    /// (a device opened by [`connect_device`](Self::connect_device) or the simulator in
+    /// [`crate::meshtastic::sim`]).
+    #[cfg(feature = "meshtastic-proto")]
+    pub async fn connect_transport(&mut self, transport: crate::meshtastic::SharedTransport) -> Result<()> {
+        let tuning = self.writer_tuning();
+
+        if self.link_status.is_none() {
+            use crate::meshtastic::link::{LinkState, LinkStatus};
+            let target = transport.lock().unwrap().describe();
+            self.link_status = Some(std::sync::Arc::new(std::sync::Mutex::new(LinkStatus::new(&target, LinkState::Connected))));
+        }
 
         // Create the reader/writer system
         let tuning_clone = tuning.clone();
         let (reader, writer, text_event_rx, outgoing_tx, reader_control_tx, writer_control_tx) = 
-            crate::meshtastic::create_reader_writer_system(port, self.config.meshtastic.baud_rate, tuning_clone).await?;
+            crate::meshtastic::create_reader_writer_system_with_transport(transport, tuning_clone).await?;
         
         // Store the channels in the server
         self.text_event_rx = Some(text_event_rx);
         // Start scheduler (phase 1) before storing outgoing for general use
-        let help_delay_ms = mcfg.help_broadcast_delay_ms.unwrap_or(3500);
-        let sched_cfg = crate::bbs::dispatch::SchedulerConfig {
-            min_send_gap_ms: tuning.min_send_gap_ms,
-            post_dm_broadcast_gap_ms: tuning.post_dm_broadcast_gap_ms,
-            help_broadcast_delay_ms: help_delay_ms,
-            max_queue: mcfg.scheduler_max_queue.unwrap_or(512),
-            aging_threshold_ms: mcfg.scheduler_aging_threshold_ms.unwrap_or(5000),
-            stats_interval_ms: mcfg.scheduler_stats_interval_ms.unwrap_or(10000),
-        };
+        let sched_cfg = self.scheduler_config(tuning.min_send_gap_ms);
         let scheduler_handle = crate::bbs::dispatch::start_scheduler(sched_cfg, outgoing_tx.clone());
         self.scheduler = Some(scheduler_handle);
         self.outgoing_tx = Some(outgoing_tx);
         self.reader_control_tx = Some(reader_control_tx);
         self.writer_control_tx = Some(writer_control_tx);
 
+        // Store-and-forward: undelivered durable DMs and "node heard" events come back here
+        let (delivery_tx, delivery_rx) = mpsc::unbounded_channel();
+        if let Some(ctrl) = &self.reader_control_tx { let _ = ctrl.send(ControlMessage::SetDeliveryMonitor(delivery_tx.clone())); }
+        if let Some(ctrl) = &self.writer_control_tx { let _ = ctrl.send(ControlMessage::SetDeliveryMonitor(delivery_tx)); }
+        self.delivery_rx = Some(delivery_rx);
+
         // Provide scheduler handle to writer for retry scheduling (best-effort)
         if let (Some(sched), Some(ctrl)) = (&self.scheduler, &self.writer_control_tx) {
             let _ = ctrl.send(crate::meshtastic::ControlMessage::SetSchedulerHandle(sched.clone()));
@@ -382,6 +505,204 @@ impl BbsServer {
         Ok(())
     }
 
+    /// Build writer tuning from config (with enforced 2s minimum)
+    #[cfg(feature = "meshtastic-proto")]
+    fn writer_tuning(&self) -> crate::meshtastic::WriterTuning {
+        let mcfg = &self.config.meshtastic;
+        let mut min_send_gap_ms = mcfg.min_send_gap_ms.unwrap_or(2000);
+        if min_send_gap_ms < 2000 {
+            warn!("Configured min_send_gap_ms={}ms is below 2000ms; clamping to 2000ms", min_send_gap_ms);
+            min_send_gap_ms = 2000;
+        }
+        let mut backoffs = mcfg.dm_resend_backoff_seconds.clone().unwrap_or_else(|| vec![4, 8, 16]);
+        if backoffs.is_empty() { backoffs = vec![4, 8, 16]; }
+        // sanitize non-positive entries
+        backoffs.retain(|&s| s > 0);
+        if backoffs.is_empty() { backoffs = vec![4, 8, 16]; }
+        crate::meshtastic::WriterTuning {
+            min_send_gap_ms,
+            dm_resend_backoff_seconds: backoffs,
+            post_dm_broadcast_gap_ms: mcfg.post_dm_broadcast_gap_ms.unwrap_or(1200),
+            dm_to_dm_gap_ms: mcfg.dm_to_dm_gap_ms.unwrap_or(600),
+            pki_dms: self.config.security.as_ref().is_none_or(|s| s.pki.encrypt_dms),
+        }
+    }
+
+    /// Outbound scheduler settings from `[meshtastic]`; `min_send_gap_ms` is passed in already clamped.
+    #[cfg(feature = "meshtastic-proto")]
+    fn scheduler_config(&self, min_send_gap_ms: u64) -> crate::bbs::dispatch::SchedulerConfig {
+        let mcfg = &self.config.meshtastic;
+        crate::bbs::dispatch::SchedulerConfig {
+            min_send_gap_ms,
+            post_dm_broadcast_gap_ms: mcfg.post_dm_broadcast_gap_ms.unwrap_or(1200),
+            help_broadcast_delay_ms: mcfg.help_broadcast_delay_ms.unwrap_or(3500),
+            max_queue: mcfg.scheduler_max_queue.unwrap_or(512),
+            aging_threshold_ms: mcfg.scheduler_aging_threshold_ms.unwrap_or(5000),
+            stats_interval_ms: mcfg.scheduler_stats_interval_ms.unwrap_or(10000),
+        }
+    }
+
+    /// Route outgoing traffic to a local console instead of a radio (see [`crate::bbs::console`]).
+    ///
+    /// Every packet the BBS would transmit arrives on the returned receiver. With `paced`, packets
+    /// first pass through the outbound scheduler with the configured send gaps, so they arrive
+    /// at the rate a node on the mesh would receive them.
+    #[cfg(feature = "meshtastic-proto")]
+    pub fn attach_console(&mut self, paced: bool) -> mpsc::UnboundedReceiver<OutgoingMessage> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.scheduler = if paced {
+            let min_send_gap_ms = self.config.meshtastic.min_send_gap_ms.unwrap_or(2000).max(2000);
+            Some(crate::bbs::dispatch::start_scheduler(self.scheduler_config(min_send_gap_ms), tx.clone()))
+        } else {
+            None
+        };
+        self.outgoing_tx = Some(tx);
+        rx
+    }
+
+    /// Undo [`attach_console`](Self::attach_console); the receiver closes once packets
+    /// already in flight have been delivered. Stop the scheduler first to keep its queue.
+    #[cfg(feature = "meshtastic-proto")]
+    pub fn detach_console(&mut self) {
+        self.scheduler = None;
+        self.outgoing_tx = None;
+    }
+
+    /// Remember how the configuration was loaded (file and overrides), for [`reload_config`](Self::reload_config).
+    pub fn set_config_loader(&mut self, loader: crate::config::ConfigLoader) { self.config_loader = Some(loader); }
+
+    /// [`set_config_loader`](Self::set_config_loader) for a plain file without overrides.
+    pub fn set_config_path(&mut self, path: impl Into<String>) { self.set_config_loader(crate::config::ConfigLoader::new(path)); }
+
+    pub fn config(&self) -> &Config { &self.config }
+
+    /// Re-read the configuration file with the startup overrides (on `SIGHUP` or the sysop
+    /// `RELOAD` command), apply it with [`apply_config`](Self::apply_config) and record the
+    /// outcome in the admin audit log.
+    pub async fn reload_config(&mut self, actor: &str) -> Result<ReloadReport> {
+        let loader = self.config_loader.clone().ok_or_else(|| anyhow!("No configuration file to reload"))?;
+        let new = loader.load().await?.config;
+        let report = self.apply_config(new).await?;
+        info!("Configuration reloaded from {} by {}: {}", loader.path(), actor, report.summary());
+        if let Err(e) = self.storage.log_admin_action("RELOAD", None, actor, Some(&report.summary())).await {
+            warn!("Failed to log admin action: {}", e);
+        }
+        Ok(report)
+    }
+
+    /// Switch a running server to `new` without dropping sessions.
+    ///
+    /// The configuration is validated first; on error nothing changes, warnings are logged.
+    /// Settings in [`RESTART_REQUIRED`](crate::config::RESTART_REQUIRED) keep their current
+    /// values and are listed in the report. Everything else takes effect immediately: limits
+    /// and timeouts are read per request, and the public channel rules, log level, legacy TOML
+    /// topics and the writer/scheduler pacing are rebuilt here.
+    pub async fn apply_config(&mut self, new: Config) -> Result<ReloadReport> {
+        new.validate()?;
+        for finding in new.check().warnings() {
+            warn!("Config {}", finding);
+        }
+        let (next, report) = self.config.reload_plan(new)?;
+        let changed = |section: &str| report.applied.iter().any(|s| s == section || s.starts_with(&format!("{section}.")));
+        self.config = next;
+        if changed("meshtastic.channel") || changed("meshtastic.public_channels") {
+            self.public_channels = PublicChannelPolicy::from_config(&self.config.meshtastic);
+        }
+        if changed("logging.level") {
+            crate::logutil::apply_level(&self.config.logging.level);
+        }
+        if changed("message_topics") {
+            Self::merge_toml_topics_to_runtime(&mut self.storage, &self.config).await?;
+        }
+        #[cfg(feature = "meshtastic-proto")]
+        if changed("meshtastic") {
+            let tuning = self.writer_tuning();
+            if let Some(scheduler) = &self.scheduler {
+                scheduler.reconfigure(self.scheduler_config(tuning.min_send_gap_ms));
+            }
+            if let Some(ctrl) = &self.writer_control_tx {
+                let _ = ctrl.send(ControlMessage::SetTuning(tuning));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Housekeeping run from the main loop: a retention pass every
+    /// `storage.retention_interval_minutes` and a backup every `backup.interval_hours` (the
+    /// first of each an interval after startup).
+    async fn run_maintenance(&mut self) {
+        self.prune_reply_channels();
+        let minutes = self.config.storage.retention_interval_minutes.unwrap_or(60);
+        if minutes > 0 && self.retention_last_run.elapsed() >= Duration::from_secs(minutes * 60) {
+            self.retention_last_run = Instant::now();
+            match self.run_retention().await {
+                Ok(report) if report.total() > 0 => info!("Retention removed {}", report.summary()),
+                Ok(_) => debug!("Retention pass: nothing expired"),
+                Err(e) => warn!("Retention pass failed: {e:#}"),
+            }
+        }
+        let Some(backup) = self.config.backup.clone() else { return };
+        if backup.interval_hours > 0 && self.backup_last_run.elapsed() >= Duration::from_secs(backup.interval_hours * 3600) {
+            self.backup_last_run = Instant::now();
+            let data_dir = self.config.storage.data_dir.clone();
+            let kind = self.config.storage.backend;
+            // Off the async workers: the JSON tree stays locked while the archive is written
+            let written = tokio::task::spawn_blocking(move || {
+                crate::storage::scheduled_backup(&data_dir, kind, std::path::Path::new(&backup.dir), backup.keep, Utc::now())
+            }).await;
+            match written {
+                Ok(Ok(path)) => info!("Backup written to {}", path.display()),
+                Ok(Err(e)) => warn!("Scheduled backup failed: {e:#}"),
+                Err(e) => warn!("Scheduled backup task failed: {e}"),
+            }
+        }
+    }
+
+    /// Apply every topic's retention limits now. Removals are in the deletion log; logged-in
+    /// sysops get a one-line summary.
+    pub async fn run_retention(&mut self) -> Result<crate::storage::RetentionReport> {
+        let report = self.storage.apply_retention(Utc::now(), self.config.storage.archive_expired).await?;
+        if report.total() > 0 {
+            let mut notice = format!("[BBS] Retention removed {}\n", report.summary());
+            if notice.len() > 230 {
+                notice = format!("[BBS] Retention removed {} thread(s); see DELLOG\n", report.total());
+            }
+            self.notify_sysops(&notice).await;
+        }
+        Ok(report)
+    }
+
+    /// Send `notice` to every logged-in sysop as a maintenance notice.
+    async fn notify_sysops(&mut self, notice: &str) {
+        let sysops: Vec<String> = self.sessions.values()
+            .filter(|s| s.is_logged_in() && s.user_level >= LEVEL_SYSOP)
+            .map(|s| s.node_id.clone())
+            .collect();
+        for node in sysops {
+            if let Err(e) = self.send_maintenance_notice(&node, notice).await {
+                debug!("Sysop notice to {node} not sent: {e:?}");
+            }
+        }
+    }
+
+    /// Log and audit lockouts that just started, and tell the logged-in sysops.
+    async fn report_lockouts(&mut self, lockouts: Vec<Lockout>) {
+        for lockout in &lockouts {
+            sec_log!("LOCKOUT {} for {} min after {} failed logins", lockout.target, lockout.minutes, lockout.attempts);
+            let details = format!("{} locked {} min after {} failed logins", lockout.target, lockout.minutes, lockout.attempts);
+            if let Err(e) = self.storage.log_admin_action("LOCKOUT", Some(lockout.target.key()), LOCKOUT_ACTOR, Some(&details)).await {
+                warn!("Lockout audit failed: {}", e);
+            }
+        }
+        let Some(first) = lockouts.first() else { return };
+        let targets: Vec<String> = lockouts.iter().map(|l| l.target.to_string()).collect();
+        let mut notice = format!("[BBS] Failed logins: {} locked {} min. UNLOCKUSER to clear\n", targets.join(", "), first.minutes);
+        if notice.len() > 230 {
+            notice = format!("[BBS] Failed logins: {} locked {} min\n", first.target, first.minutes);
+        }
+        self.notify_sysops(&notice).await;
+    }
+
     #[allow(dead_code)]
     #[doc(hidden)]
     pub fn test_messages(&self) -> &Vec<(String,String)> { &self.test_messages }
@@ -445,6 +766,31 @@ impl BbsServer {
         
         let (tx, mut rx) = mpsc::unbounded_channel();
         self.message_tx = Some(tx);
+
+        // Gauges for the metrics endpoint are refreshed on a timer while it is served
+        let web_enabled = self.config.web.as_ref().is_some_and(|w| w.enabled);
+        #[cfg(feature = "web")]
+        if let Some(web) = self.config.web.clone().filter(|w| w.enabled) {
+            let admin = crate::web::AdminAccess {
+                handle: self.admin_handle(),
+                sysop: self.config.bbs.sysop.clone(),
+                password_hash: self.config.bbs.sysop_password_hash.clone(),
+            };
+            match crate::web::start(&web, Some(admin)).await {
+                Ok(addr) => info!("Web admin at http://{addr}/ (metrics at /metrics)"),
+                Err(e) => {
+                    self.admin_rx = None;
+                    warn!("Web server disabled: {e:?}");
+                }
+            }
+        }
+        #[cfg(not(feature = "web"))]
+        if web_enabled {
+            warn!("[web] is enabled but meshbbs was built without the 'web' feature");
+        }
+        let mut metrics_tick = tokio::time::interval(Duration::from_secs(15));
+        let mut maintenance_tick = tokio::time::interval(Duration::from_secs(60));
+        let mut hangup = Hangup::new();
         
         // Main message processing loop
         loop {
@@ -481,12 +827,57 @@ impl BbsServer {
                             warn!("Text event channel closed");
                         }
                     }
+
+                    // Store-and-forward feedback from the reader/writer
+                    delivery_event = async {
+                        if let Some(ref mut rx) = self.delivery_rx {
+                            rx.recv().await
+                        } else {
+                            std::future::pending().await
+                        }
+                    } => {
+                        if let Some(event) = delivery_event {
+                            if let Err(e) = self.handle_delivery_event(event).await {
+                                warn!("Store-and-forward error: {e:?}");
+                            }
+                        } else {
+                            self.delivery_rx = None;
+                        }
+                    }
                     
                     msg = rx.recv() => {
                         if let Some(internal_msg) = msg {
                             debug!("Processing internal message: {}", internal_msg);
                         }
                     }
+
+                    admin_call = async {
+                        if let Some(ref mut rx) = self.admin_rx {
+                            rx.recv().await
+                        } else {
+                            std::future::pending().await
+                        }
+                    } => {
+                        match admin_call {
+                            Some(call) => self.handle_admin_call(call).await,
+                            None => self.admin_rx = None,
+                        }
+                    }
+
+                    _ = metrics_tick.tick(), if web_enabled => {
+                        if let Err(e) = self.publish_metrics().await {
+                            debug!("Failed to publish metrics: {e:?}");
+                        }
+                    }
+
+                    _ = maintenance_tick.tick() => self.run_maintenance().await,
+                    
+                    _ = hangup.recv() => {
+                        info!("Received SIGHUP, reloading configuration");
+                        if let Err(e) = self.reload_config("SIGHUP").await {
+                            warn!("Configuration reload failed, keeping current settings: {e:#}");
+                        }
+                    }
                     
                     _ = tokio::signal::ctrl_c() => {
                         info!("Received shutdown signal");
@@ -512,6 +903,34 @@ impl BbsServer {
                             debug!("Processing internal message: {}", internal_msg);
                         }
                     }
+
+                    admin_call = async {
+                        if let Some(ref mut rx) = self.admin_rx {
+                            rx.recv().await
+                        } else {
+                            std::future::pending().await
+                        }
+                    } => {
+                        match admin_call {
+                            Some(call) => self.handle_admin_call(call).await,
+                            None => self.admin_rx = None,
+                        }
+                    }
+
+                    _ = metrics_tick.tick(), if web_enabled => {
+                        if let Err(e) = self.publish_metrics().await {
+                            debug!("Failed to publish metrics: {e:?}");
+                        }
+                    }
+
+                    _ = maintenance_tick.tick() => self.run_maintenance().await,
+                    
+                    _ = hangup.recv() => {
+                        info!("Received SIGHUP, reloading configuration");
+                        if let Err(e) = self.reload_config("SIGHUP").await {
+                            warn!("Configuration reload failed, keeping current settings: {e:#}");
+                        }
+                    }
                     
                     _ = tokio::signal::ctrl_c() => {
                         info!("Received shutdown signal");
@@ -533,6 +952,7 @@ impl BbsServer {
                             priority: MessagePriority::High,
                             kind: crate::meshtastic::OutgoingKind::Normal,
                             request_ack: false,
+                            durable: false,
                         };
                         let env = crate::bbs::dispatch::MessageEnvelope::new(
                             crate::bbs::dispatch::MessageCategory::Direct,
@@ -550,6 +970,7 @@ impl BbsServer {
                             priority: MessagePriority::High,
                             kind: crate::meshtastic::OutgoingKind::Normal,
                             request_ack: false,
+                            durable: false,
                         };
                         if tx.send(outgoing).is_err() { warn!("Failed to send pending DM to {dest} on channel {channel}"); still_pending.push((dest, channel, msg)); }
                     } else {
@@ -574,7 +995,7 @@ impl BbsServer {
 
     async fn prune_idle_sessions(&mut self) {
         let timeout_min = self.config.bbs.session_timeout as i64;
-        if timeout_min == 0 { return; }
+        if timeout_min == 0 { self.prune_reply_channels(); return; }
         let mut to_logout = Vec::new();
         for (k,s) in &self.sessions {
             if s.is_logged_in() && s.is_inactive(timeout_min) { to_logout.push(k.clone()); }
@@ -586,6 +1007,22 @@ impl BbsServer {
             if let Some(s) = self.sessions.get_mut(&k) { let _ = s.logout().await; }
             info!("Session {} (user {}) logged out due to inactivity", k, username);
         }
+        self.prune_reply_channels();
+    }
+
+    /// Forget the reply channel of nodes that have no logged-in session and sent nothing
+    /// for a session timeout (at least 10 minutes), so one-off public requests don't pile up.
+    fn prune_reply_channels(&mut self) {
+        let idle = Duration::from_secs(u64::from(self.config.bbs.session_timeout.max(10)) * 60);
+        let sessions = &self.sessions;
+        self.reply_channels.retain(|node, (_, at)| at.elapsed() < idle || sessions.get(node).is_some_and(|s| s.is_logged_in()));
+    }
+
+    /// Refresh the session and storage gauges served at `/metrics`.
+    pub async fn publish_metrics(&self) -> Result<()> {
+        crate::metrics::set_sessions(self.sessions.len(), self.logged_in_session_count());
+        crate::metrics::set_storage(self.storage.sizes().await?);
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -616,10 +1053,11 @@ impl BbsServer {
         }
         
         if let Some(node_id) = target_node {
-            let _ = self.send_message(&node_id, "You have been disconnected by an administrator.").await;
+            let _ = self.send_durable_message(&node_id, "You have been disconnected by an administrator.").await;
             if let Some(session) = self.sessions.get_mut(&node_id) {
                 let _ = session.logout().await;
             }
+            self.reply_channels.remove(&node_id);
             info!("User {} forcibly logged out by administrator", username);
             Ok(true)
         } else {
@@ -627,6 +1065,93 @@ impl BbsServer {
         }
     }
 
+    /// `BAN`/`MUTE <user>` and `BLOCKNODE <node>`, each followed by `<span|PERM|OFF> [reason]`,
+    /// from staff member `actor` at `actor_level` on `actor_node`. Staff can't sanction the
+    /// sysop, themselves or (unless sysop) a peer; a ban ends the user's sessions and a node
+    /// block drops the node's session. Returns the reply.
+    async fn sanction_command(&mut self, kind: SanctionKind, actor: &str, actor_level: u8, actor_node: &str, args: &[&str]) -> Result<String> {
+        let noun = if kind == SanctionKind::BlockNode { "node" } else { "user" };
+        let usage = format!("Usage: {} <{}> <30m|12h|7d|PERM|OFF> [reason]\n", kind.command(), noun);
+        let (Some(target), Some(span)) = (args.first(), args.get(1)) else { return Ok(usage) };
+        let target = if kind == SanctionKind::BlockNode {
+            let Some(id) = parse_node_id(target) else { return Ok("Invalid node id.\n".into()) };
+            if id.to_string() == actor_node { return Ok("Cannot block your own node.\n".into()); }
+            id.to_string()
+        } else {
+            match self.storage.get_user(target).await? {
+                None => return Ok("User not found.\n".into()),
+                Some(u) if u.username == self.config.bbs.sysop => return Ok("Cannot modify sysop.\n".into()),
+                Some(u) if u.username == actor => return Ok("Cannot sanction yourself.\n".into()),
+                Some(u) if actor != self.config.bbs.sysop && u.user_level >= actor_level => return Ok("Permission denied.\n".into()),
+                Some(u) => u.username,
+            }
+        };
+        if span.eq_ignore_ascii_case("off") {
+            let Some(target) = self.storage.lift_sanction(kind, &target).await? else { return Ok(format!("No {} on {}.\n", kind, target)) };
+            if kind == SanctionKind::BlockNode { self.blocked_nodes = None; }
+            let action = format!("UN{}", kind.command());
+            sec_log!("{} by {}: {}", action, actor, target);
+            self.storage.log_admin_action(&action, Some(&target), actor, None).await?;
+            return Ok(format!("Lifted {} on {}.\n", kind, target));
+        }
+        let now = Utc::now();
+        let expires_at = match parse_sanction_span(span) {
+            None => return Ok(usage),
+            Some(None) => None,
+            Some(Some(length)) => match now.checked_add_signed(length) { Some(until) => Some(until), None => return Ok(usage) },
+        };
+        let sanction = Sanction { reason: args[2..].join(" "), issued_by: actor.to_string(), issued_at: now, expires_at };
+        let (target, sanction) = self.storage.add_sanction(kind, &target, sanction).await?;
+        let length = expires_at.map_or_else(|| "permanently".to_string(), |until| format!("for {}", format_span(until - now)));
+        let details = if sanction.reason.is_empty() { length.clone() } else { format!("{}: {}", length, sanction.reason) };
+        sec_log!("{} by {}: {} {}", kind.command(), actor, target, details);
+        self.storage.log_admin_action(kind.command(), Some(&target), actor, Some(&details)).await?;
+        let done = match kind {
+            SanctionKind::Ban => {
+                while self.force_logout_user(&target).await? {}
+                "Banned"
+            }
+            SanctionKind::Mute => "Muted",
+            SanctionKind::BlockNode => {
+                self.blocked_nodes = None;
+                self.sessions.remove(&target);
+                self.reply_channels.remove(&target);
+                "Blocked node"
+            }
+        };
+        Ok(format!("{} {} {}.\n", done, target, length))
+    }
+
+    /// True if text from `node` should be dropped. The blocklist is kept in memory, loaded
+    /// from storage on first use and again after `BLOCKNODE`; an expired block is dropped
+    /// from it on the next packet. If storage can't be read the packet is let through.
+    async fn node_blocked(&mut self, node: &str) -> bool {
+        let now = Utc::now();
+        if self.blocked_nodes.is_none() {
+            match self.storage.active_sanctions(now).await {
+                Ok(active) => {
+                    self.blocked_nodes = Some(active.into_iter()
+                        .filter(|(kind, _, _)| *kind == SanctionKind::BlockNode)
+                        .map(|(_, target, sanction)| (target, sanction.expires_at))
+                        .collect());
+                }
+                Err(e) => {
+                    warn!("Failed to load the node blocklist: {}", e);
+                    return false;
+                }
+            }
+        }
+        let Some(blocked) = self.blocked_nodes.as_mut() else { return false };
+        match blocked.get(node) {
+            None => false,
+            Some(Some(until)) if *until <= now => {
+                blocked.remove(node);
+                false
+            }
+            Some(_) => true,
+        }
+    }
+
     /// Send broadcast message to all logged-in users
     pub async fn broadcast_message(&mut self, message: &str, sender: &str) -> Result<usize> {
         let mut sent_count = 0;
@@ -654,13 +1179,91 @@ impl BbsServer {
         /// When unread == 0 -> "There are no new messages.\n"
         /// When unread == 1 -> "1 new message since your last login.\n"
         /// When unread > 1 -> "<n> new messages since your last login.\n"
-        fn format_unread_line(unread: u32) -> String {
-            match unread {
+        /// Unread private mail adds "You have <n> unread mail. MAIL to read.\n"
+        fn format_unread_line(unread: u32, unread_mail: u32) -> String {
+            let mut line = match unread {
                 0 => "There are no new messages.\n".to_string(),
                 1 => "1 new message since your last login.\n".to_string(),
                 n => format!("{} new messages since your last login.\n", n)
+            };
+            if unread_mail > 0 {
+                line.push_str(&format!("You have {} unread mail. MAIL to read.\n", unread_mail));
+            }
+            line
+        }
+
+    /// Deliver notices for other users (new mail, replies, moderator actions) to every
+    /// node they are logged in on, or else to the node bound to their account. Sent
+    /// durable, so a node that is out of range gets them when it is next heard.
+    async fn deliver_notices(&mut self, notices: Vec<(String, String)>) {
+        for (username, text) in notices {
+            let mut nodes: Vec<String> = self.sessions
+                .iter()
+                .filter(|(_, s)| s.is_logged_in() && s.username.as_deref().is_some_and(|u| u.eq_ignore_ascii_case(&username)))
+                .map(|(node_id, _)| node_id.clone())
+                .collect();
+            if nodes.is_empty() {
+                if let Ok(Some(user)) = self.storage.get_user(&username).await { nodes.extend(user.node_id); }
+            }
+            for node_id in nodes {
+                if let Err(e) = self.send_durable_message(&node_id, &text).await {
+                    log::warn!("Failed to send notice to {}: {}", node_id, e);
+                }
+            }
+        }
+    }
+
+    /// Store-and-forward: hold durable DMs the radio gave up on, and send a node's held
+    /// messages again when it is heard. Holding honours
+    /// `meshtastic.store_forward_ttl_hours` and `store_forward_max_per_node`. A node whose
+    /// public key changed is reported to the security log, the audit log and the sysops.
+    pub async fn handle_delivery_event(&mut self, event: DeliveryEvent) -> Result<()> {
+        match event {
+            DeliveryEvent::Undelivered { to, content, .. } => {
+                let node_id = to.to_string();
+                let now = Utc::now();
+                let ttl = chrono::Duration::hours(self.config.meshtastic.store_forward_ttl_hours.unwrap_or(72) as i64);
+                let mut held = self.redelivering.remove(&(node_id.clone(), content.clone())).unwrap_or_else(|| HeldMessage {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    node_id,
+                    content,
+                    queued_at: now,
+                    expires_at: now + ttl,
+                    attempts: 0,
+                });
+                held.attempts += 1;
+                if held.expires_at <= now {
+                    info!("Store-and-forward: message for {} expired after {} attempts", held.node_id, held.attempts);
+                    return Ok(());
+                }
+                let max_per_node = self.config.meshtastic.store_forward_max_per_node.unwrap_or(10);
+                info!("Store-and-forward: holding message for {} until it is heard again", held.node_id);
+                let dropped = self.storage.hold_message(held, max_per_node).await?;
+                if dropped > 0 { warn!("Store-and-forward: outbox full, dropped {} oldest message(s)", dropped); }
+            }
+            DeliveryEvent::NodeHeard(node) => {
+                let node_id = node.to_string();
+                let held = self.storage.take_held_messages(&node_id).await?;
+                if held.is_empty() { return Ok(()); }
+                let now = Utc::now();
+                self.redelivering.retain(|_, m| m.expires_at > now);
+                info!("Store-and-forward: {} heard, redelivering {} held message(s)", node_id, held.len());
+                for msg in held {
+                    self.send_durable_message(&node_id, &msg.content).await?;
+                    self.redelivering.insert((node_id.clone(), msg.content.clone()), msg);
+                }
+            }
+            DeliveryEvent::KeyChanged { node, previous, current } => {
+                let node_id = node.to_string();
+                sec_log!("PKI key of node {} changed from {} to {}", node_id, previous, current);
+                let details = format!("announced public key {} instead of {}", current, previous);
+                self.storage.log_admin_action("PKI_KEY_CHANGED", Some(&node_id), PKI_ACTOR, Some(&details)).await?;
+                self.pending_keys.insert(node, current);
+                self.notify_sysops(&format!("[BBS] Node {} changed its PKI key; it may be a new radio or an impostor. ACCEPTKEY {} to trust it\n", node_id, node_id)).await;
             }
         }
+        Ok(())
+    }
 
     /// Ensure sysop user exists / synchronized with config (extracted for testability)
     pub async fn seed_sysop(&mut self) -> Result<()> {
@@ -672,10 +1275,7 @@ impl BbsServer {
                     if u.user_level < 10 { u.user_level = 10; needs_write = true; }
                     if u.password_hash.as_deref() != Some(hash.as_str()) { u.password_hash = Some(hash.clone()); needs_write = true; }
                     if needs_write {
-                        let users_dir = std::path::Path::new(self.storage.base_dir()).join("users");
-                        let user_file = users_dir.join(format!("{}.json", sysop_name));
-                        let json_content = serde_json::to_string_pretty(&u)?;
-                        tokio::fs::write(user_file, json_content).await?;
+                        self.storage.save_user(&u).await?;
                         info!("Sysop user '{}' synchronized from config.", sysop_name);
                     }
                 }
@@ -684,19 +1284,17 @@ impl BbsServer {
                     let user = crate::storage::User {
                         username: sysop_name.clone(),
                         node_id: None,
+                        trusted_nodes: Vec::new(),
                         user_level: 10,
                         password_hash: Some(hash.clone()),
+                        password_reset: None,
                         first_login: now,
                         last_login: now,
                         total_messages: 0,
                         welcome_shown_on_registration: true,  // Sysop doesn't need welcome messages
                         welcome_shown_on_first_login: true,
                     };
-                    let users_dir = std::path::Path::new(self.storage.base_dir()).join("users");
-                    tokio::fs::create_dir_all(&users_dir).await?;
-                    let user_file = users_dir.join(format!("{}.json", sysop_name));
-                    let json_content = serde_json::to_string_pretty(&user)?;
-                    tokio::fs::write(user_file, json_content).await?;
+                    self.storage.save_user(&user).await?;
                     info!("Sysop user '{}' created from config.", sysop_name);
                 }
             }
@@ -758,11 +1356,15 @@ impl BbsServer {
 
     // Moderator / sysop internal helpers
     pub async fn moderator_delete_message(&mut self, topic: &str, id: &str, actor: &str) -> Result<bool> {
+        let original = self.storage.get_message(topic, id).await.ok().flatten();
         let deleted = self.storage.delete_message(topic, id).await?;
         if deleted {
             sec_log!("DELETE by {}: {}/{}", actor, topic, id);
             // Fire and forget audit append; if it fails, surface as error to caller
             self.storage.append_deletion_audit(topic, id, actor).await?;
+            if let Some(msg) = original.filter(|m| m.author != actor) {
+                self.deliver_notices(vec![(msg.author, format!("🛡️ Your post in {} was removed by a moderator.", topic))]).await;
+            }
         }
         Ok(deleted)
     }
@@ -777,696 +1379,269 @@ impl BbsServer {
         Ok(())
     }
 
+    /// Channel for administrative requests (web admin UI); they are executed by [`run`](Self::run).
+    /// Calling this again replaces the previous channel.
+    pub fn admin_handle(&mut self) -> AdminHandle {
+        let (handle, rx) = AdminHandle::channel();
+        self.admin_rx = Some(rx);
+        handle
+    }
+
+    async fn handle_admin_call(&mut self, call: AdminCall) {
+        let result = self.handle_admin(&call.actor, call.request).await;
+        if let Err(e) = &result {
+            debug!("Admin request by {} failed: {}", call.actor, e);
+        }
+        let _ = call.reply.send(result);
+    }
 
-    #[cfg(feature = "meshtastic-proto")]
-    #[cfg_attr(test, allow(dead_code))]
-    pub async fn route_text_event(&mut self, ev: TextEvent) -> Result<()> {
-        // Trace-log every text event for debugging purposes
-    trace!("TextEvent BEGIN src={} direct={} channel={:?} content='{}'", ev.source, ev.is_direct, ev.channel, ev.content);
-        // Source node id string form
-        let node_key = ev.source.to_string();
-        if ev.is_direct {
-            // Direct (private) path: ensure session exists, finalize pending login if any
-            if !self.sessions.contains_key(&node_key) {
-                trace!("Creating new session for direct node {}", node_key);
-                let mut session = Session::new(node_key.clone(), node_key.clone());
-                // Pending public login auto-apply path
-                if let Some(username) = self.public_state.take_pending(&node_key) {
-                    let current = self.logged_in_session_count();
-                    if (current as u32) >= self.config.bbs.max_users {
-                        let _ = self.send_message(&node_key, "All available sessions are in use, please wait and try again later.").await;
-                    } else {
-                        // Security check: verify if user has a password set
-                        if let Ok(Some(user)) = self.storage.get_user(&username).await {
-                            if user.password_hash.is_some() {
-                                // User has a password - require proper authentication
-                                trace!("User '{}' has password, requiring authentication via DM for node {}", username, node_key);
-                                let _ = self.send_message(&node_key, &format!("Welcome! To complete login as '{}', please enter: LOGIN {} <password>", username, username)).await;
-                                // Put the pending login back so they can complete it with password
-                                self.public_state.set_pending(&node_key, username);
-                            } else {
-                                // User has no password - allow auto-login for backward compatibility
-                                trace!("Auto-applying pending public login '{}' (no password) to new DM session {}", username, node_key);
-                                session.login(username.clone(), 1).await?;
-                                let prev_last = user.last_login;
-                                if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(prev_last); }
-                                let unread = self.storage.count_messages_since(prev_last).await.unwrap_or(0);
-                                let _ = self.storage.record_user_login(&username).await; // update last_login
-                                let summary = Self::format_unread_line(unread);
-                                let _ = self.send_session_message(&node_key, &format!("Welcome, {} you are now logged in.\n{}", username, summary), true).await;
-                            }
-                        } else {
-                            // New user case - create user without password (they can set one later)
-                            trace!("Auto-applying pending public login '{}' (new user) to new DM session {}", username, node_key);
-                            session.login(username.clone(), 1).await?;
-                            if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(Utc::now()); }
-                            self.storage.create_or_update_user(&username, &node_key).await?;
-                            let summary = Self::format_unread_line(0);
-                            let _ = self.send_session_message(&node_key, &format!("Welcome, {} you are now logged in.\n{}", username, summary), true).await;
-                        }
-                    }
-                } else {
-                    // Removed first-contact guidance banner (Option B) to avoid duplicate initial messages.
+    /// Execute one administrative request on behalf of `actor` (the sysop).
+    pub async fn handle_admin(&mut self, actor: &str, request: AdminRequest) -> Result<serde_json::Value> {
+        use serde_json::json;
+        fn not_found(what: String) -> anyhow::Error { NotFound(what).into() }
+        match request {
+            AdminRequest::Overview => {
+                let stats = self.storage.get_statistics().await?;
+                Ok(json!({
+                    "name": self.config.bbs.name,
+                    "sysop": self.config.bbs.sysop,
+                    "total_users": stats.total_users,
+                    "total_messages": stats.total_messages,
+                    "moderators": stats.moderator_count,
+                    "recent_registrations": stats.recent_registrations,
+                    "topics": self.storage.list_configured_topics().len(),
+                    "active_sessions": self.sessions.len(),
+                    "logged_in_users": self.logged_in_session_count(),
+                    "max_users": self.config.bbs.max_users,
+                    "session_timeout_minutes": self.config.bbs.session_timeout,
+                    "radio_link": self.link_status().map(|l| l.summary()).unwrap_or_else(|| "not connected".into()),
+                }))
+            }
+            AdminRequest::ListUsers => {
+                let users: Vec<UserView> = self.storage.list_all_users().await?.into_iter().map(UserView::from).collect();
+                Ok(serde_json::to_value(users)?)
+            }
+            AdminRequest::SetUserLevel { username, level } => {
+                if ![LEVEL_USER, LEVEL_MODERATOR, LEVEL_SYSOP].contains(&level) {
+                    return Err(anyhow!("Level must be {}, {} or {}", LEVEL_USER, LEVEL_MODERATOR, LEVEL_SYSOP));
                 }
-                self.sessions.insert(node_key.clone(), session);
-            }
-                // New consolidated DM command handling with max_users and idle pruning
-                self.prune_idle_sessions().await; // always prune first
-                let raw_content = ev.content.trim().to_string();
-                let upper = raw_content.to_uppercase();
-                // Count current logged in sessions (excluding the session for this node if it is not yet logged in)
-                let logged_in_count = self.sessions.values().filter(|s| s.is_logged_in()).count();
-                enum PostAction { None, Delete{area:String,id:String,actor:String}, Lock{area:String,actor:String}, Unlock{area:String,actor:String}, Broadcast{message:String,sender:String} }
-                let mut post_action = PostAction::None;
-                let mut deferred_reply: Option<String> = None;
-
-                // Track if this message was fully handled by registration logic to avoid re-processing.
-                let mut handled_registration = false;
-                // Handle REGISTER early without holding mutable borrow on session to simplify chunking logic.
-                if upper.starts_with("REGISTER ") {
-                    let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                    if parts.len() < 3 { deferred_reply = Some("Usage: REGISTER <username> <password>\n".into()); }
-                    else {
-                        let user = parts[1]; let pass = parts[2];
-                        if pass.len() < 8 { deferred_reply = Some("Password too short (minimum 8 characters).\n".into()); }
-                        else {
-                            match self.storage.register_user(user, pass, Some(&node_key)).await {
-                                Ok(_) => {
-                                    if let Some(session) = self.sessions.get_mut(&node_key) { session.login(user.to_string(), 1).await?; session.unread_since = Some(Utc::now()); }
-                                    let summary = Self::format_unread_line(0);
-                                    // Compact single-frame friendly welcome (omit BBS name & HELP+ reference)
-                                    // Example (unread 0): "Registered as alice. 0 new msgs. HELP LIST READ POST WHO"
-                                    // Keeps under 230 bytes even with 30-char username.
-                                    let unread_snip = if summary.contains("no new") || summary.contains("no new messages") {
-                                        // summary format_unread_line(0) typically "There are no new messages." -> shorten
-                                        "0 new msgs.".to_string()
-                                    } else if let Some(num) = summary.split_whitespace().find(|w| w.chars().all(|c| c.is_ascii_digit())) {
-                                        format!("{} unread msgs.", num)
-                                    } else {
-                                        summary.trim().to_string()
-                                    };
-                                    let full_welcome = format!(
-                                        "Registered as {u}. {unread} HELP LIST READ POST WHO\n",
-                                        u=user, unread=unread_snip
-                                    );
-                                    let max_bytes = self.config.storage.max_message_size;
-                                    let parts_vec = self.chunk_utf8(&full_welcome, max_bytes);
-                                    if parts_vec.len() > 1 { warn!("Registration welcome chunked into {} parts ({} bytes total)", parts_vec.len(), full_welcome.len()); }
-                                    if parts_vec.len() == 1 {
-                                        deferred_reply = Some(parts_vec[0].clone());
-                                    } else {
-                                        let total = parts_vec.len();
-                                        for (i, chunk) in parts_vec.into_iter().enumerate() {
-                                            let last = i + 1 == total; // only append prompt after final
-                                            self.send_session_message(&node_key, &chunk, last).await?;
-                                        }
-                                    }
-                                    if let Err(e) = self.storage.mark_welcome_shown(user, true, false).await {
-                                        eprintln!("Failed to mark welcome shown for {}: {}", user, e);
-                                    }
-                                    handled_registration = true;
-                                }
-                                Err(e) => { deferred_reply = Some(format!("Register failed: {}\n", e)); }
-                            }
-                        }
-                    }
+                if username == self.config.bbs.sysop { return Err(anyhow!("Cannot modify sysop")); }
+                if self.storage.get_user(&username).await?.is_none() { return Err(not_found(format!("User {} not found", username))); }
+                let user = self.storage.update_user_level(&username, level, actor).await?;
+                for s in self.sessions.values_mut().filter(|s| s.username.as_deref() == Some(username.as_str())) {
+                    s.user_level = level;
                 }
-
-                // If registration fully handled (multi-part or single reply prepared), skip further command processing
-                if handled_registration {
-                    if let Some(msg) = deferred_reply { self.send_session_message(&node_key, &msg, true).await?; }
-                    return Ok(());
+                Ok(json!({ "username": user.username, "level": user.user_level, "role": role_name(user.user_level) }))
+            }
+            AdminRequest::ResetPassword { username, password } => {
+                if username == self.config.bbs.sysop {
+                    return Err(anyhow!("The sysop password is set in the config (meshbbs sysop-passwd)"));
                 }
-
-                if let Some(session) = self.sessions.get_mut(&node_key) {
-                    session.update_activity();
-                    #[cfg(feature = "meshtastic-proto")]
-                    if let (Some(dev), Ok(idnum)) = (&self.device, node_key.parse::<u32>()) {
-                        let (short,long) = dev.format_node_combined(idnum);
-                        session.update_labels(Some(short), Some(long));
-                    }
-                    if upper == "HELP+" || upper == "HELP V" || upper == "HELP  V" || upper == "HELP  +" { // tolerate minor spacing variants
-                        let chunks = chunk_verbose_help();
-                        let total = chunks.len();
-                        for (i, chunk) in chunks.into_iter().enumerate() {
-                            let last = i + 1 == total;
-                            // For multi-part help, suppress prompt until final
-                            self.send_session_message(&node_key, &chunk, last).await?;
-                        }
-                    } else if upper.starts_with("LOGIN ") {
-                        // Enforce max_users only if this session is not yet logged in
-                        if !session.is_logged_in() && (logged_in_count as u32) >= self.config.bbs.max_users {
-                            deferred_reply = Some("All available sessions are in use, please wait and try again later.\n".into());
-                        } else if session.is_logged_in() {
-                            deferred_reply = Some(format!("Already logged in as {}.\n", session.display_name()));
-                        } else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: LOGIN <username> [password]\n".into()); }
-                            else {
-                                let user = parts[1];
-                                let password_opt = if parts.len() >= 3 { Some(parts[2]) } else { None };
-                                match self.storage.get_user(user).await? {
-                                    None => deferred_reply = Some("No such user. Use REGISTER <u> <p>.\n".into()),
-                                    Some(u) => {
-                                        let has_password = u.password_hash.is_some();
-                                        let node_bound = u.node_id.as_deref() == Some(&node_key);
-                                        if !has_password {
-                                            // User must set a password on first login attempt
-                                            if let Some(pass) = password_opt {
-                                                if pass.len() < 8 { deferred_reply = Some("Password too short (minimum 8 characters).\n".into()); }
-                                                else {
-                                                    let updated_user = self.storage.set_user_password(user, pass).await?;
-                                                    let updated = if !node_bound { self.storage.bind_user_node(user, &node_key).await? } else { updated_user };
-                                                    session.login(updated.username.clone(), updated.user_level).await?;
-                                                    if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(Utc::now()); }
-                                                    // First-time password set; unread messages prior to this first authenticated login are based on prior last_login value.
-                                                    // set_user_password already bumped last_login, so computing unread would yield zero. This is acceptable; show none.
-                                                    let _ = self.storage.record_user_login(user).await; // ensure fresh timestamp after full login
-                                                    // No unread count expected here (legacy first login)
-                                                    let summary = Self::format_unread_line(0); // first login after setting password shows no unread
-                                                    
-                                                    // Check if this is the first login after registration and show follow-up welcome
-                                                    let mut login_msg = format!("Password set. Welcome, {} you are now logged in.\n{}", updated.username, summary);
-                                                    if updated.welcome_shown_on_registration && !updated.welcome_shown_on_first_login {
-                                                        login_msg.push_str("\n💡 Quick tip: Since this is your first time back, try these commands:\n• 'LIST' - Browse available message boards\n• 'WHO' - See who's currently online\n• 'RECENT' - Check the latest activity\n\nHappy posting!\n");
-                                                        // Mark first login welcome as shown
-                                                        if let Err(e) = self.storage.mark_welcome_shown(user, false, true).await {
-                                                            eprintln!("Failed to mark first login welcome shown for {}: {}", user, e);
-                                                        }
-                                                    }
-                                                    deferred_reply = Some(login_msg);
-                                                }
-                                            } else {
-                                                deferred_reply = Some("Password not set. LOGIN <user> <newpass> to set your password.\n".into());
-                                            }
-                                        } else {
-                                            // Has password: require it if not bound or if password provided
-                                            if let Some(pass) = password_opt {
-                                                let (_maybe, ok) = self.storage.verify_user_password(user, pass).await?;
-                                                if !ok { deferred_reply = Some("Invalid password.\n".into()); }
-                                                else {
-                                                    let updated = if !node_bound { self.storage.bind_user_node(user, &node_key).await? } else { u };
-                                                    session.login(updated.username.clone(), updated.user_level).await?;
-                                                    let prev_last = updated.last_login; // captured before we update last_login again
-                                                    if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(prev_last); }
-                                                    let unread = self.storage.count_messages_since(prev_last).await.unwrap_or(0);
-                                                    let updated2 = self.storage.record_user_login(user).await.unwrap_or(updated);
-                                                    let summary = Self::format_unread_line(unread);
-                                                    
-                                                    // Check if this is the first login after registration and show follow-up welcome
-                                                    let mut login_msg = format!("Welcome, {} you are now logged in.\n{}", updated2.username, summary);
-                                                    if updated2.welcome_shown_on_registration && !updated2.welcome_shown_on_first_login {
-                                                        login_msg.push_str("\n💡 Quick tip: Since this is your first time back, try these commands:\n• 'LIST' - Browse available message boards\n• 'WHO' - See who's currently online\n• 'RECENT' - Check the latest activity\n\nHappy posting!\n");
-                                                        // Mark first login welcome as shown
-                                                        if let Err(e) = self.storage.mark_welcome_shown(user, false, true).await {
-                                                            eprintln!("Failed to mark first login welcome shown for {}: {}", user, e);
-                                                        }
-                                                    }
-                                                    deferred_reply = Some(login_msg);
-                                                }
-                                            } else { deferred_reply = Some("Password required: LOGIN <user> <pass>\n".into()); }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("CHPASS ") {
-                        if session.username.as_deref() == Some(&self.config.bbs.sysop) {
-                            deferred_reply = Some("Sysop password managed externally. Use sysop-passwd CLI.\n".into());
-                        } else if session.is_logged_in() {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 3 {
-                                deferred_reply = Some("Usage: CHPASS <old> <new>\n".into());
-                            } else {
-                                let old = parts[1];
-                                let newp = parts[2];
-                                if newp.len() < 8 {
-                                    deferred_reply = Some("New password too short (min 8).\n".into());
-                                } else if newp.len() > 128 {
-                                    deferred_reply = Some("New password too long.\n".into());
-                                } else if let Some(user_name) = &session.username {
-                                    match self.storage.get_user(user_name).await? {
-                                        Some(u) => {
-                                            if u.password_hash.is_none() {
-                                                deferred_reply = Some("No existing password. Use SETPASS <new>.\n".into());
-                                            } else {
-                                                let (_u2, ok) = self.storage.verify_user_password(user_name, old).await?;
-                                                if !ok {
-                                                    deferred_reply = Some("Invalid password.\n".into());
-                                                } else if old == newp {
-                                                    deferred_reply = Some("New password must differ.\n".into());
-                                                } else {
-                                                    self.storage.update_user_password(user_name, newp).await?;
-                                                    deferred_reply = Some("Password changed.\n".into());
-                                                }
-                                            }
-                                        }
-                                        None => deferred_reply = Some("Session user missing.\n".into()),
-                                    }
-                                } else {
-                                    deferred_reply = Some("Not logged in.\n".into());
-                                }
-                            }
-                        } else {
-                            deferred_reply = Some("Not logged in.\n".into());
-                        }
-                    } else if upper.starts_with("SETPASS ") {
-                        if session.username.as_deref() == Some(&self.config.bbs.sysop) {
-                            deferred_reply = Some("Sysop password managed externally. Use sysop-passwd CLI.\n".into());
-                        } else if session.is_logged_in() {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 {
-                                deferred_reply = Some("Usage: SETPASS <new>\n".into());
-                            } else {
-                                let newp = parts[1];
-                                if newp.len() < 8 {
-                                    deferred_reply = Some("New password too short (min 8).\n".into());
-                                } else if newp.len() > 128 {
-                                    deferred_reply = Some("New password too long.\n".into());
-                                } else if let Some(user_name) = &session.username {
-                                    match self.storage.get_user(user_name).await? {
-                                            Some(u) => {
-                                                if u.password_hash.is_some() {
-                                                    deferred_reply = Some("Password already set. Use CHPASS <old> <new>.\n".into());
-                                                } else {
-                                                    self.storage.update_user_password(user_name, newp).await?;
-                                                    deferred_reply = Some("Password set.\n".into());
-                                                }
-                                            }
-                                            None => deferred_reply = Some("Session user missing.\n".into()),
-                                        }
-                                } else {
-                                    deferred_reply = Some("Not logged in.\n".into());
-                                }
-                            }
-                        } else {
-                            deferred_reply = Some("Not logged in.\n".into());
-                        }
-                    } else if upper.starts_with("PROMOTE ") {
-                        if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: PROMOTE <user>\n".into()); }
-                            else {
-                                let target = parts[1];
-                                match self.storage.get_user(target).await? {
-                                    None => deferred_reply = Some("User not found.\n".into()),
-                                    Some(u) => {
-                                        if u.username == self.config.bbs.sysop { deferred_reply = Some("Cannot modify sysop.\n".into()); }
-                                        else if u.user_level >= LEVEL_MODERATOR { deferred_reply = Some("Already moderator or higher.\n".into()); }
-                                        else { self.storage.update_user_level(&u.username, LEVEL_MODERATOR, session.username.as_deref().unwrap_or("unknown")).await?; deferred_reply = Some(format!("{} promoted to {}.\n", u.username, role_name(LEVEL_MODERATOR))); }
-                                    }
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("DEMOTE ") {
-                        if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: DEMOTE <user>\n".into()); }
-                            else {
-                                let target = parts[1];
-                                match self.storage.get_user(target).await? {
-                                    None => deferred_reply = Some("User not found.\n".into()),
-                                    Some(u) => {
-                                        if u.username == self.config.bbs.sysop { deferred_reply = Some("Cannot modify sysop.\n".into()); }
-                                        else if u.user_level <= LEVEL_USER { deferred_reply = Some("Already at base level.\n".into()); }
-                                        else { self.storage.update_user_level(&u.username, LEVEL_USER, session.username.as_deref().unwrap_or("unknown")).await?; deferred_reply = Some(format!("{} demoted to {}.\n", u.username, role_name(LEVEL_USER))); }
-                                    }
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("CREATETOPIC ") {
-                        if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 4 { deferred_reply = Some("Usage: CREATETOPIC <id> <name> <description> [read_level] [post_level]\n".into()); }
-                            else {
-                                let topic_id = parts[1].to_lowercase();
-                                let name = parts[2];
-                                let description = parts[3..].join(" ");
-                                let read_level = 0u8; // Default read level
-                                let post_level = 0u8; // Default post level
-                                let creator = session.username.as_deref().unwrap_or("sysop");
-                                
-                                match self.storage.create_topic(&topic_id, name, &description, read_level, post_level, creator).await {
-                                    Ok(()) => deferred_reply = Some(format!("Topic '{}' created successfully.\n", topic_id)),
-                                    Err(e) => deferred_reply = Some(format!("Failed to create topic: {}\n", e)),
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("MODIFYTOPIC ") {
-                        if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 3 { deferred_reply = Some("Usage: MODIFYTOPIC <id> name=<name> | desc=<desc> | read=<level> | post=<level>\n".into()); }
-                            else {
-                                let topic_id = parts[1].to_lowercase();
-                                let mut name: Option<&str> = None;
-                                let mut description: Option<String> = None;
-                                let mut read_level: Option<u8> = None;
-                                let mut post_level: Option<u8> = None;
-                                
-                                // Parse key=value pairs
-                                for part in &parts[2..] {
-                                    if let Some((key, value)) = part.split_once('=') {
-                                        match key.to_lowercase().as_str() {
-                                            "name" => name = Some(value),
-                                            "desc" | "description" => description = Some(value.to_string()),
-                                            "read" => read_level = value.parse().ok(),
-                                            "post" => post_level = value.parse().ok(),
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                                
-                                match self.storage.modify_topic(&topic_id, name, description.as_deref(), read_level, post_level).await {
-                                    Ok(()) => deferred_reply = Some(format!("Topic '{}' modified successfully.\n", topic_id)),
-                                    Err(e) => deferred_reply = Some(format!("Failed to modify topic: {}\n", e)),
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("DELETETOPIC ") {
-                        if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: DELETETOPIC <id>\n".into()); }
-                            else {
-                                let topic_id = parts[1].to_lowercase();
-                                
-                                match self.storage.delete_topic(&topic_id).await {
-                                    Ok(()) => deferred_reply = Some(format!("Topic '{}' deleted successfully.\n", topic_id)),
-                                    Err(e) => deferred_reply = Some(format!("Failed to delete topic: {}\n", e)),
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("DELETE ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 3 { deferred_reply = Some("Usage: DELETE <area> <id>\n".into()); }
-                            else {
-                                let area = parts[1].to_lowercase();
-                                let id = parts[2].to_string();
-                                let actor = session.username.clone().unwrap_or("?".into());
-                                post_action = PostAction::Delete{area,id,actor};
-                            }
-                        }
-                    } else if upper.starts_with("LOCK ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: LOCK <area>\n".into()); }
-                            else {
-                                let area = parts[1].to_lowercase();
-                                let actor = session.username.clone().unwrap_or("?".into());
-                                post_action = PostAction::Lock{area:area.clone(), actor};
-                                deferred_reply = Some(format!("Area {} locked.\n", area));
-                            }
-                        }
-                    } else if upper.starts_with("UNLOCK ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: UNLOCK <area>\n".into()); }
-                            else {
-                                let area = parts[1].to_lowercase();
-                                let actor = session.username.clone().unwrap_or("?".into());
-                                post_action = PostAction::Unlock{area:area.clone(), actor};
-                                deferred_reply = Some(format!("Area {} unlocked.\n", area));
-                            }
-                        }
-                    } else if upper.starts_with("DELLOG") || upper == "DL" || upper.starts_with("DL ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            let page = if parts.len() >= 2 { parts[1].parse::<usize>().unwrap_or(1) } else { 1 };
-                            match self.storage.get_deletion_audit_page(page, 10).await {
-                                Ok(entries) => {
-                                    if entries.is_empty() { deferred_reply = Some("No entries.\n".into()); }
-                                    else { let mut out = String::from("Deletion Log:\n"); for e in entries { out.push_str(&format!("{} {} {} {}\n", e.timestamp, e.actor, e.topic, e.id)); } deferred_reply = Some(out); }
-                                }
-                                Err(e) => deferred_reply = Some(format!("Failed: {}\n", e)),
-                            }
-                        }
-                    } else if upper.starts_with("ADMINLOG") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            let page = if parts.len() >= 2 { parts[1].parse::<usize>().unwrap_or(1) } else { 1 };
-                            match self.storage.get_admin_audit_page(page, 10).await {
-                                Ok(entries) => {
-                                    if entries.is_empty() { deferred_reply = Some("No admin audit entries.\n".into()); }
-                                    else { 
-                                        let mut out = String::from("Admin Audit Log:\n");
-                                        for e in entries {
-                                            let target_str = e.target.as_deref().unwrap_or("-");
-                                            let details_str = e.details.as_deref().unwrap_or("");
-                                            out.push_str(&format!("{} {} {} {} {}\n", 
-                                                e.timestamp.format("%m/%d %H:%M"), 
-                                                e.actor, 
-                                                e.action, 
-                                                target_str,
-                                                details_str
-                                            ));
-                                        }
-                                        deferred_reply = Some(out);
-                                    }
-                                }
-                                Err(e) => deferred_reply = Some(format!("Failed: {}\n", e)),
-                            }
-                        }
-                    } else if upper.starts_with("USERS") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            let pattern = if parts.len() >= 2 { Some(parts[1].to_lowercase()) } else { None };
-                            
-                            match self.storage.list_all_users().await {
-                                Ok(mut users) => {
-                                    // Filter users by pattern if provided
-                                    if let Some(ref p) = pattern {
-                                        users.retain(|u| u.username.to_lowercase().contains(p));
-                                    }
-                                    
-                                    let logged_in_usernames: std::collections::HashSet<&str> = self.get_logged_in_users()
-                                        .iter()
-                                        .filter_map(|s| s.username.as_deref())
-                                        .collect();
-                                    
-                                    let mut response = if let Some(ref p) = pattern {
-                                        format!("Users matching '{}' ({} found):\n", p, users.len())
-                                    } else {
-                                        format!("Registered Users ({}/{}):\n", users.len(), self.config.bbs.max_users)
-                                    };
-                                    
-                                    for user in users {
-                                        let status = if logged_in_usernames.contains(user.username.as_str()) { "Online" } else { "Offline" };
-                                        let role = super::roles::role_name(user.user_level);
-                                        response.push_str(&format!("  {} ({}, Level {}) - {}\n", user.username, role, user.user_level, status));
-                                    }
-                                    
-                                    if pattern.is_none() {
-                                        response.push_str(&format!("\nActive Sessions: {}\n", self.logged_in_session_count()));
-                                    }
-                                    deferred_reply = Some(response);
-                                }
-                                Err(e) => deferred_reply = Some(format!("Failed to list users: {}\n", e)),
-                            }
-                        }
-                    } else if upper == "WHO" {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let logged_in = self.get_logged_in_users();
-                            if logged_in.is_empty() {
-                                deferred_reply = Some("No users currently logged in.\n".into());
-                            } else {
-                                let mut response = format!("Logged In Users ({}):\n", logged_in.len());
-                                for session in logged_in {
-                                    let username = session.username.as_deref().unwrap_or("Guest");
-                                    let role = super::roles::role_name(session.user_level);
-                                    let duration = session.session_duration().num_minutes();
-                                    let state = match session.state {
-                                        super::session::SessionState::MainMenu => "Main Menu",
-                                        super::session::SessionState::MessageTopics => "Message Areas",
-                                        super::session::SessionState::ReadingMessages => "Reading",
-                                        super::session::SessionState::PostingMessage => "Posting",
-                                        super::session::SessionState::UserMenu => "User Menu",
-                                        _ => "Other",
-                                    };
-                                    response.push_str(&format!("  {} ({}) - {} - {}m - {}\n", username, role, session.node_id, duration, state));
-                                }
-                                deferred_reply = Some(response);
-                            }
-                        }
-                    } else if upper.starts_with("USERINFO ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: USERINFO <user>\n".into()); }
-                            else {
-                                let target = parts[1];
-                                match self.storage.get_user_details(target).await? {
-                                    None => deferred_reply = Some("User not found.\n".into()),
-                                    Some(user) => {
-                                        let post_count = self.storage.count_user_posts(&user.username).await.unwrap_or(0);
-                                        let is_online = self.get_logged_in_users().iter().any(|s| s.username.as_deref() == Some(&user.username));
-                                        let role = super::roles::role_name(user.user_level);
-                                        
-                                        let mut response = format!("User Information for {}:\n", user.username);
-                                        response.push_str(&format!("  Role: {} (Level {})\n", role, user.user_level));
-                                        response.push_str(&format!("  Status: {}\n", if is_online { "Online" } else { "Offline" }));
-                                        response.push_str(&format!("  First Login: {}\n", user.first_login.format("%Y-%m-%d %H:%M UTC")));
-                                        response.push_str(&format!("  Last Login: {}\n", user.last_login.format("%Y-%m-%d %H:%M UTC")));
-                                        response.push_str(&format!("  Total Posts: {}\n", post_count));
-                                        if let Some(node_id) = &user.node_id {
-                                            response.push_str(&format!("  Node ID: {}\n", node_id));
-                                        }
-                                        deferred_reply = Some(response);
-                                    }
-                                }
-                            }
-                        }
-                    } else if upper == "SESSIONS" {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let all_sessions = self.get_active_sessions();
-                            let mut response = format!("Active Sessions ({}):\n", all_sessions.len());
-                            for s in all_sessions {
-                                let username = s.username.as_deref().unwrap_or("Guest");
-                                let role = super::roles::role_name(s.user_level);
-                                let duration = s.session_duration().num_minutes();
-                                let logged_in = if s.is_logged_in() { "Yes" } else { "No" };
-                                let state = match s.state {
-                                    super::session::SessionState::Connected => "Connected",
-                                    super::session::SessionState::LoggingIn => "Logging In",
-                                    super::session::SessionState::MainMenu => "Main Menu",
-                                    super::session::SessionState::MessageTopics => "Message Areas",
-                                    super::session::SessionState::ReadingMessages => "Reading",
-                                    super::session::SessionState::PostingMessage => "Posting",
-                                    super::session::SessionState::Topics => "Topics",
-                                    super::session::SessionState::Subtopics => "Subtopics",
-                                    super::session::SessionState::Threads => "Threads",
-                                    super::session::SessionState::ThreadRead => "Read",
-                                    super::session::SessionState::ComposeNewTitle => "Compose Title",
-                                    super::session::SessionState::ComposeNewBody => "Compose Body",
-                                    super::session::SessionState::ComposeReply => "Compose Reply",
-                                    super::session::SessionState::ConfirmDelete => "Confirm Delete",
-                                    super::session::SessionState::UserMenu => "User Menu",
-                                    super::session::SessionState::Disconnected => "Disconnected",
-                                };
-                                response.push_str(&format!("  {} ({}) | {} | {}m | Login: {} | {}\n", 
-                                    username, role, s.node_id, duration, logged_in, state));
-                            }
-                            deferred_reply = Some(response);
-                        }
-                    } else if upper.starts_with("KICK ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                            if parts.len() < 2 { deferred_reply = Some("Usage: KICK <user>\n".into()); }
-                            else {
-                                let target = parts[1];
-                                let actor = session.username.as_deref().unwrap_or("unknown").to_string();
-                                if target == actor {
-                                    deferred_reply = Some("Cannot kick yourself.\n".into());
-                                } else if target == self.config.bbs.sysop {
-                                    deferred_reply = Some("Cannot kick sysop.\n".into());
-                                } else {
-                                    match self.force_logout_user(target).await? {
-                                        true => {
-                                            // Log the administrative action
-                                            if let Err(e) = self.storage.log_admin_action("KICK", Some(target), &actor, None).await {
-                                                warn!("Failed to log admin action: {}", e);
-                                            }
-                                            deferred_reply = Some(format!("User {} has been kicked.\n", target));
-                                        },
-                                        false => deferred_reply = Some("User not found or not logged in.\n".into()),
-                                    }
-                                }
-                            }
-                        }
-                    } else if upper.starts_with("BROADCAST ") {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let message = raw_content.strip_prefix("BROADCAST ").unwrap_or("").trim();
-                            if message.is_empty() { deferred_reply = Some("Usage: BROADCAST <message>\n".into()); }
-                            else {
-                                let sender = session.username.as_deref().unwrap_or("System").to_string();
-                                let message = message.to_string();
-                                post_action = PostAction::Broadcast{message, sender};
-                            }
-                        }
-                    } else if upper == "ADMIN" || upper == "DASHBOARD" {
-                        if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
-                        else {
-                            let stats = self.storage.get_statistics().await?;
-                            let active_count = self.get_active_sessions().len();
-                            let logged_in_count = self.logged_in_session_count();
-                            
-                            let mut response = String::from("=== ADMINISTRATIVE DASHBOARD ===\n");
-                            response.push_str("System Status:\n");
-                            response.push_str(&format!("  Total Users: {}\n", stats.total_users));
-                            response.push_str(&format!("  Total Messages: {}\n", stats.total_messages));
-                            response.push_str(&format!("  Active Sessions: {}\n", active_count));
-                            response.push_str(&format!("  Logged In Users: {}\n", logged_in_count));
-                            response.push_str(&format!("  Max Users: {}\n", self.config.bbs.max_users));
-                            response.push_str(&format!("  Session Timeout: {} min\n", self.config.bbs.session_timeout));
-                            response.push_str("\nCommands: USERS, WHO, USERINFO <user>, SESSIONS, KICK <user>, BROADCAST <msg>\n");
-                            deferred_reply = Some(response);
-                        }
-                    } else if upper == "LOGOUT" {
-                        if session.is_logged_in() { let name = session.display_name(); session.logout().await?; deferred_reply = Some(format!("User {} logged out.\n", name)); }
-                        else { deferred_reply = Some("Not logged in.\n".into()); }
-                    } else if upper == "HELP" || upper == "?" || upper == "H" {
-                        // Use existing abbreviated help via command processor (ensures consistent text)
-                        let help_text = session.process_command("HELP", &mut self.storage, &self.config).await?;
-                        if !session.help_seen { session.help_seen = true; }
-                        self.send_session_message(&node_key, &help_text, true).await?;
-                    } else {
-                        let redact = ["REGISTER ", "LOGIN ", "SETPASS ", "CHPASS "];
-                        let log_snippet = if redact.iter().any(|p| upper.starts_with(p)) { "<redacted>" } else { raw_content.as_str() };
-                        trace!("Session {} generic command '{}'", node_key, log_snippet);
-                        let response = session.process_command(&raw_content, &mut self.storage, &self.config).await?;
-                        if !response.is_empty() { deferred_reply = Some(response); }
-                    }
+                if self.storage.get_user(&username).await?.is_none() { return Err(not_found(format!("User {} not found", username))); }
+                self.storage.update_user_password(&username, &password).await?;
+                self.storage.log_admin_action("RESET_PASSWORD", Some(&username), actor, None).await?;
+                sec_log!("RESET_PASSWORD by {}: {}", actor, username);
+                Ok(json!({ "username": username }))
+            }
+            AdminRequest::ShowUser { username } => {
+                let user = self.storage.get_user(&username).await?.ok_or_else(|| not_found(format!("User {} not found", username)))?;
+                Ok(serde_json::to_value(UserView::from(user))?)
+            }
+            AdminRequest::CreateUser { username, password, level, node_id } => {
+                if ![LEVEL_USER, LEVEL_MODERATOR, LEVEL_SYSOP].contains(&level) {
+                    return Err(anyhow!("Level must be {}, {} or {}", LEVEL_USER, LEVEL_MODERATOR, LEVEL_SYSOP));
                 }
-                match post_action {
-                    PostAction::None => {}
-                    PostAction::Delete{area,id,actor} => {
-                        match self.moderator_delete_message(&area, &id, &actor).await {
-                            Ok(true) => { deferred_reply.get_or_insert(format!("Deleted message {} in {}.\n", id, area)); },
-                            Ok(false) => { deferred_reply.get_or_insert("Not found.\n".into()); },
-                            Err(e) => { deferred_reply.get_or_insert(format!("Delete failed: {}\n", e)); }
-                        }
-                    }
-                    PostAction::Lock{area,actor} => {
-                        if let Err(e) = self.moderator_lock_topic(&area, &actor).await { deferred_reply.get_or_insert(format!("Lock failed: {}\n", e)); }
-                    }
-                    PostAction::Unlock{area,actor} => {
-                        if let Err(e) = self.moderator_unlock_topic(&area, &actor).await { deferred_reply.get_or_insert(format!("Unlock failed: {}\n", e)); }
-                    }
-                    PostAction::Broadcast{message,sender} => {
-                        match self.broadcast_message(&message, &sender).await {
-                            Ok(0) => { deferred_reply.get_or_insert("No users online to receive broadcast.\n".into()); },
-                            Ok(count) => { 
-                                // Log the administrative action
-                                if let Err(e) = self.storage.log_admin_action("BROADCAST", None, &sender, Some(&message)).await {
-                                    warn!("Failed to log admin action: {}", e);
-                                }
-                                deferred_reply.get_or_insert(format!("Broadcast sent to {} users.\n", count)); 
-                            },
-                            Err(e) => { deferred_reply.get_or_insert(format!("Broadcast failed: {}\n", e)); }
-                        }
-                    }
+                if username == self.config.bbs.sysop {
+                    return Err(anyhow!("The sysop account comes from the config (meshbbs sysop-passwd)"));
                 }
-                if let Some(msg) = deferred_reply { self.send_session_message(&node_key, &msg, true).await?; }
-            // end direct path handling (removed extra closing brace)
+                self.storage.register_user(&username, &password, node_id.as_deref()).await?;
+                self.storage.log_admin_action("CREATE_USER", Some(&username), actor, None).await?;
+                if level != LEVEL_USER {
+                    self.storage.update_user_level(&username, level, actor).await?;
+                }
+                let user = self.storage.get_user(&username).await?.ok_or_else(|| anyhow!("User {} was not stored", username))?;
+                Ok(serde_json::to_value(UserView::from(user))?)
+            }
+            AdminRequest::UnbindNode { username } => {
+                let Some(user) = self.storage.get_user(&username).await? else { return Err(not_found(format!("User {} not found", username))); };
+                let nodes: Vec<String> = user.known_nodes().into_iter().map(|n| n.node_id).collect();
+                let details = (!nodes.is_empty()).then(|| format!("Was bound to {}", nodes.join(", ")));
+                self.storage.unbind_user_node(&username).await?;
+                self.storage.log_admin_action("UNBIND_NODE", Some(&username), actor, details.as_deref()).await?;
+                Ok(json!({ "username": username, "previous_node": user.node_id, "previous_trusted_nodes": nodes }))
+            }
+            AdminRequest::DeleteUser { username } => {
+                if username == self.config.bbs.sysop { return Err(anyhow!("Cannot delete sysop")); }
+                if self.storage.get_user(&username).await?.is_none() { return Err(not_found(format!("User {} not found", username))); }
+                self.force_logout_user(&username).await?;
+                self.storage.delete_user(&username).await?;
+                self.storage.log_admin_action("DELETE_USER", Some(&username), actor, None).await?;
+                sec_log!("DELETE_USER by {}: {}", actor, username);
+                Ok(json!({ "username": username }))
+            }
+            AdminRequest::ListTopics => {
+                let topics: Vec<TopicView> = self.storage.list_configured_topics().into_iter().filter_map(|id| {
+                    let t = self.storage.get_topic_config(&id)?.clone();
+                    Some(TopicView {
+                        locked: self.storage.is_topic_locked(&id),
+                        id,
+                        name: t.name,
+                        description: t.description,
+                        read_level: t.read_level,
+                        post_level: t.post_level,
+                        parent: t.parent,
+                        created_by: t.created_by,
+                        created_at: t.created_at,
+                        retention: t.retention,
+                    })
+                }).collect();
+                Ok(serde_json::to_value(topics)?)
+            }
+            AdminRequest::CreateTopic(t) => {
+                if self.storage.topic_exists(&t.id) { return Err(anyhow!("Topic {} already exists", t.id)); }
+                match &t.parent {
+                    Some(parent) => self.storage.create_subtopic(&t.id, parent, &t.name, &t.description, t.read_level, t.post_level, actor).await?,
+                    None => self.storage.create_topic(&t.id, &t.name, &t.description, t.read_level, t.post_level, actor).await?,
+                }
+                self.storage.log_admin_action("CREATE_TOPIC", Some(&t.id), actor, None).await?;
+                Ok(json!({ "id": t.id }))
+            }
+            AdminRequest::ModifyTopic { id, changes } => {
+                if !self.storage.topic_exists(&id) { return Err(not_found(format!("Topic {} not found", id))); }
+                self.storage.modify_topic(&id, changes.name.as_deref(), changes.description.as_deref(), changes.read_level, changes.post_level).await?;
+                if let Some(retention) = changes.retention {
+                    self.storage.set_topic_retention(&id, Some(retention)).await?;
+                }
+                self.storage.log_admin_action("MODIFY_TOPIC", Some(&id), actor, None).await?;
+                Ok(json!({ "id": id }))
+            }
+            AdminRequest::DeleteTopic { id } => {
+                if !self.storage.topic_exists(&id) { return Err(not_found(format!("Topic {} not found", id))); }
+                if !self.storage.list_subtopics(&id).is_empty() { return Err(anyhow!("Topic {} has subtopics; delete them first", id)); }
+                self.storage.delete_topic(&id).await?;
+                self.storage.log_admin_action("DELETE_TOPIC", Some(&id), actor, None).await?;
+                sec_log!("DELETE_TOPIC by {}: {}", actor, id);
+                Ok(json!({ "id": id }))
+            }
+            AdminRequest::SetTopicLocked { id, locked } => {
+                if !self.storage.topic_exists(&id) { return Err(not_found(format!("Topic {} not found", id))); }
+                if locked { self.moderator_lock_topic(&id, actor).await?; } else { self.moderator_unlock_topic(&id, actor).await?; }
+                self.storage.log_admin_action(if locked { "LOCK_TOPIC" } else { "UNLOCK_TOPIC" }, Some(&id), actor, None).await?;
+                Ok(json!({ "id": id, "locked": locked }))
+            }
+            AdminRequest::ListMessages { topic, limit } => {
+                if !self.storage.topic_exists(&topic) { return Err(not_found(format!("Topic {} not found", topic))); }
+                Ok(serde_json::to_value(self.storage.get_messages(&topic, limit).await?)?)
+            }
+            AdminRequest::DeleteMessage { topic, id } => {
+                if !self.moderator_delete_message(&topic, &id, actor).await? {
+                    return Err(not_found(format!("Message {} not found in {}", id, topic)));
+                }
+                Ok(json!({ "topic": topic, "id": id }))
+            }
+            AdminRequest::SetMessagePinned { topic, id, pinned } => {
+                if self.storage.get_message(&topic, &id).await?.is_none() {
+                    return Err(not_found(format!("Message {} not found in {}", id, topic)));
+                }
+                self.storage.set_message_pinned(&topic, &id, pinned).await?;
+                Ok(json!({ "topic": topic, "id": id, "pinned": pinned }))
+            }
+            AdminRequest::DeletionAudit { page } => {
+                Ok(serde_json::to_value(self.storage.get_deletion_audit_page(page.max(1), AUDIT_PAGE_SIZE).await?)?)
+            }
+            AdminRequest::AdminAudit { page } => {
+                Ok(serde_json::to_value(self.storage.get_admin_audit_page(page.max(1), AUDIT_PAGE_SIZE).await?)?)
+            }
+            AdminRequest::ListSessions => {
+                let mut sessions: Vec<SessionView> = self.sessions.values().map(|s| SessionView {
+                    node_id: s.node_id.clone(),
+                    username: s.username.clone(),
+                    level: s.user_level,
+                    role: role_name(s.user_level),
+                    state: format!("{:?}", s.state),
+                    login_time: s.login_time,
+                    last_activity: s.last_activity,
+                }).collect();
+                sessions.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+                Ok(serde_json::to_value(sessions)?)
+            }
+            AdminRequest::Kick { username } => {
+                if username == self.config.bbs.sysop { return Err(anyhow!("Cannot kick sysop")); }
+                if !self.force_logout_user(&username).await? {
+                    return Err(not_found(format!("User {} is not logged in", username)));
+                }
+                self.storage.log_admin_action("KICK", Some(&username), actor, None).await?;
+                Ok(json!({ "username": username }))
+            }
+            AdminRequest::Broadcast { message } => {
+                let message = message.trim();
+                if message.is_empty() { return Err(anyhow!("Message is empty")); }
+                let recipients = self.broadcast_message(message, actor).await?;
+                self.storage.log_admin_action("BROADCAST", None, actor, Some(message)).await?;
+                Ok(json!({ "recipients": recipients }))
+            }
+            AdminRequest::LoginLockout { source } => {
+                let policy = self.config.security.as_ref().map(|s| s.lockout.clone()).unwrap_or_default();
+                let until = self.storage.login_lockout(None, &source, &policy, Utc::now()).await?;
+                Ok(json!({ "locked_until": until }))
+            }
+            AdminRequest::LoginAttempt { source, ok: true } => {
+                self.storage.record_login_success(actor, &source).await?;
+                Ok(json!({}))
+            }
+            AdminRequest::LoginAttempt { source, ok: false } => {
+                // Counted per source only, so a stranger can't lock the sysop out
+                let policy = self.config.security.as_ref().map(|s| s.lockout.clone()).unwrap_or_default();
+                let started = self.storage.record_login_failure(None, &source, &policy, Utc::now()).await?;
+                self.report_lockouts(started).await;
+                Ok(json!({}))
+            }
+        }
+    }
+
+
+    #[cfg(feature = "meshtastic-proto")]
+    #[cfg_attr(test, allow(dead_code))]
+    pub async fn route_text_event(&mut self, ev: TextEvent) -> Result<()> {
+        // Trace-log every text event for debugging purposes
+    trace!("TextEvent BEGIN src={} direct={} channel={:?} content='{}'", ev.source, ev.is_direct, ev.channel, ev.content);
+        // Source node id string form
+        let node_key = ev.source.to_string();
+        // Blocked nodes get no reply, public or direct
+        if self.node_blocked(&node_key).await {
+            trace!("Dropping text from blocked node {}", node_key);
+            return Ok(());
+        }
+        // Replies go back out on the channel the request arrived on
+        let ev_channel = ev.channel.unwrap_or_else(|| self.public_channels.primary_channel());
+        if ev.is_direct || self.public_channels.listens_on(ev_channel) {
+            self.reply_channels.insert(node_key.clone(), (ev_channel, Instant::now()));
+        }
+        if ev.is_direct {
+            let require_pki = self.config.security.as_ref().is_some_and(|s| s.pki.require_for_passwords);
+            let command = ev.content.split_whitespace().next().unwrap_or("").to_ascii_uppercase();
+            if require_pki && !ev.pki_encrypted && PASSWORD_COMMANDS.contains(&command.as_str()) {
+                sec_log!("Refused {} from {}: DM not PKI-encrypted", command, node_key);
+                self.send_message(&node_key, "Passwords need an encrypted DM. Update your node firmware (2.5+) and let it learn the BBS key, then retry.").await?;
+                return Ok(());
+            }
+            self.route_direct_text(&node_key, &ev.content).await?;
         } else {
-            // Public channel event: parse lightweight commands
+            // Public channel event: parse lightweight commands (only on channels we listen on)
+            if !self.public_channels.listens_on(ev_channel) {
+                trace!("Ignoring public text from {} on unmonitored channel {}", node_key, ev_channel);
+                return Ok(());
+            }
             self.public_state.prune_expired();
             let cmd = self.public_parser.parse(&ev.content);
             trace!("Public command parse result for node {} => {:?}", node_key, cmd);
+            if cmd != PublicCommand::Unknown && !self.public_channels.allows(ev_channel, &cmd) {
+                debug!("Public command {:?} from {} not enabled on channel {}", cmd.rule_name(), node_key, ev_channel);
+                return Ok(());
+            }
+            if let Some(name) = cmd.rule_name() {
+                crate::metrics::inc_public_command(name);
+            }
             match cmd {
                 PublicCommand::Help => {
                     if self.public_state.should_reply(&node_key) {
@@ -1498,21 +1673,26 @@ impl BbsServer {
                         
                         // Create broadcast message showing available public commands with chunking
                         let mut public_commands = vec![
-                            "^HELP - Show this help",
-                            "^LOGIN <user> - Register for BBS",
+                            ("HELP", "^HELP - Show this help"),
+                            ("LOGIN", "^LOGIN <user> - Register for BBS"),
                         ];
                         
                         // Add optional weather command if enabled
                         #[cfg(feature = "weather")]
-                        public_commands.push("^WEATHER - Current conditions");
+                        public_commands.push(("WEATHER", "^WEATHER - Current conditions"));
                         
                         // Add games and utilities
                         public_commands.extend_from_slice(&[
-                            "^SLOT - Play slot machine",
-                            "^SLOTSTATS - Show your stats",
-                            "^8BALL - Magic 8-Ball oracle",
-                            "^FORTUNE - Random wisdom",
+                            ("SLOT", "^SLOT - Play slot machine"),
+                            ("SLOTSTATS", "^SLOTSTATS - Show your stats"),
+                            ("8BALL", "^8BALL - Magic 8-Ball oracle"),
+                            ("FORTUNE", "^FORTUNE - Random wisdom"),
                         ]);
+                        // Only advertise commands enabled on the channel the request came in on
+                        let public_commands: Vec<&str> = public_commands.into_iter()
+                            .filter(|(name, _)| self.public_channels.allows_name(ev_channel, name))
+                            .map(|(_, text)| text)
+                            .collect();
 
                         // Send DM first, then chunked public notices. This reduces the chance of a transient rate limit
                         // affecting the DM, since the DM is more time-sensitive for onboarding.
@@ -1587,11 +1767,12 @@ impl BbsServer {
                                 debug!("Scheduling HELP public chunk {} in {}ms (text='{}')", i + 1, chunk_delay, escape_log(chunk));
                                 let outgoing = crate::meshtastic::OutgoingMessage { 
                                     to_node: None, 
-                                    channel: 0, 
+                                    channel: ev_channel, 
                                     content: chunk.clone(), 
                                     priority: crate::meshtastic::MessagePriority::Normal, 
                                     kind: crate::meshtastic::OutgoingKind::Normal, 
-                                    request_ack: true 
+                                    request_ack: true,
+                                    durable: false,
                                 };
                                 let env = crate::bbs::dispatch::MessageEnvelope::new(
                                     crate::bbs::dispatch::MessageCategory::HelpBroadcast,
@@ -1613,11 +1794,12 @@ impl BbsServer {
                                         tokio::time::sleep(std::time::Duration::from_millis(chunk_delay)).await;
                                         let outgoing = crate::meshtastic::OutgoingMessage { 
                                             to_node: None, 
-                                            channel: 0, 
+                                            channel: ev_channel, 
                                             content: chunk_content, 
                                             priority: crate::meshtastic::MessagePriority::Normal, 
                                             kind: crate::meshtastic::OutgoingKind::Normal, 
-                                            request_ack: true 
+                                            request_ack: true,
+                                            durable: false,
                                         };
                                         if let Err(e) = tx_clone.send(outgoing) { 
                                             log::warn!("Failed to queue scheduled HELP public chunk: {}", e); 
@@ -1645,7 +1827,7 @@ impl BbsServer {
                         let mut broadcasted = false;
                         #[cfg(feature = "meshtastic-proto")]
                         {
-                            match self.send_broadcast(&weather).await {
+                            match self.send_broadcast_on(ev_channel, &weather).await {
                                 Ok(_) => {
                                     trace!("Broadcasted weather to public channel: '{}'", weather);
                                     broadcasted = true;
@@ -1666,10 +1848,10 @@ impl BbsServer {
                     // Use a lighter, per-node slot cooldown (does not block other public replies)
                     // Broadcast-only: do not DM slot results.
                     if self.public_state.allow_slot(&node_key) {
-                        let base = self.storage.base_dir().to_string();
-                        let (outcome, coins) = crate::bbs::slotmachine::perform_spin(&base, &node_key);
+                        let store = self.storage.backend();
+                        let (outcome, coins) = crate::bbs::slotmachine::perform_spin(store, &node_key);
                         let msg = if outcome.r1 == "⛔" {
-                            let eta = crate::bbs::slotmachine::next_refill_eta(&base, &node_key)
+                            let eta = crate::bbs::slotmachine::next_refill_eta(store, &node_key)
                                 .map(|(h,m)| format!(" Next refill in ~{}h {}m.", h.max(0), m.max(0)))
                                 .unwrap_or_default();
                             format!(
@@ -1690,7 +1872,7 @@ impl BbsServer {
                         // Broadcast result for room visibility (best-effort)
                         #[cfg(feature = "meshtastic-proto")]
                         {
-                            if let Err(e) = self.send_broadcast(&msg).await { warn!("Slot result broadcast failed (best-effort): {e:?}"); }
+                            if let Err(e) = self.send_broadcast_on(ev_channel, &msg).await { warn!("Slot result broadcast failed (best-effort): {e:?}"); }
                         }
                     }
                 }
@@ -1701,7 +1883,7 @@ impl BbsServer {
                         let msg = format!("^8BALL ⟶ {}", answer);
                         #[cfg(feature = "meshtastic-proto")]
                         {
-                            if let Err(e) = self.send_broadcast(&msg).await { warn!("8BALL broadcast failed (best-effort): {e:?}"); }
+                            if let Err(e) = self.send_broadcast_on(ev_channel, &msg).await { warn!("8BALL broadcast failed (best-effort): {e:?}"); }
                         }
                     }
                 }
@@ -1712,15 +1894,15 @@ impl BbsServer {
                         let msg = format!("^FORTUNE ⟶ {}", fortune);
                         #[cfg(feature = "meshtastic-proto")]
                         {
-                            if let Err(e) = self.send_broadcast(&msg).await { warn!("FORTUNE broadcast failed (best-effort): {e:?}"); }
+                            if let Err(e) = self.send_broadcast_on(ev_channel, &msg).await { warn!("FORTUNE broadcast failed (best-effort): {e:?}"); }
                         }
                     }
                 }
                 PublicCommand::SlotStats => {
                     if self.public_state.should_reply(&node_key) {
-                        let base = self.storage.base_dir().to_string();
-                        let summary = crate::bbs::slotmachine::get_player_summary(&base, &node_key);
-                        let j = crate::bbs::slotmachine::get_jackpot_summary(&base);
+                        let store = self.storage.backend();
+                        let summary = crate::bbs::slotmachine::get_player_summary(store, &node_key);
+                        let j = crate::bbs::slotmachine::get_jackpot_summary(store);
                         let jdate = j.last_win_date.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "—".into());
                         let jwinner_short = if let Some(id_str) = j.last_win_node.as_deref() {
                             self.lookup_short_name_from_cache(id_str.parse().ok().unwrap_or(0))
@@ -1738,7 +1920,7 @@ impl BbsServer {
                         let mut broadcasted = false;
                         #[cfg(feature = "meshtastic-proto")]
                         {
-                            if let Err(e) = self.send_broadcast(&msg).await { warn!("Slot stats broadcast failed: {e:?} (will fallback DM)"); } else { broadcasted = true; }
+                            if let Err(e) = self.send_broadcast_on(ev_channel, &msg).await { warn!("Slot stats broadcast failed: {e:?} (will fallback DM)"); } else { broadcasted = true; }
                         }
                         if !broadcasted { let _ = self.send_message(&node_key, &msg).await; }
                     }
@@ -1757,15 +1939,927 @@ impl BbsServer {
         Ok(())
     }
 
+    /// Direct (private) message path: session lifecycle, login/registration and the
+    /// per-session command processor. Shared by live text events and test harnesses.
+    async fn route_direct_text(&mut self, node_key: &str, content: &str) -> Result<()> {
+        let node_key = node_key.to_string();
+        // Direct (private) path: ensure session exists, finalize pending login if any
+        if !self.sessions.contains_key(&node_key) {
+            trace!("Creating new session for direct node {}", node_key);
+            let mut session = Session::new(node_key.clone(), node_key.clone());
+            // Pending public login auto-apply path
+            if let Some(username) = self.public_state.take_pending(&node_key) {
+                let current = self.logged_in_session_count();
+                if (current as u32) >= self.config.bbs.max_users {
+                    let _ = self.send_message(&node_key, "All available sessions are in use, please wait and try again later.").await;
+                } else {
+                    // Security check: verify if user has a password set
+                    if let Ok(Some(user)) = self.storage.get_user(&username).await {
+                        let now = Utc::now();
+                        if let Some(ban) = self.storage.sanction(SanctionKind::Ban, &user.username, now).await? {
+                            let _ = self.send_message(&node_key, &sanction_notice("Account banned", &ban, now)).await;
+                        } else if user.password_hash.is_some() {
+                            // User has a password - require proper authentication
+                            trace!("User '{}' has password, requiring authentication via DM for node {}", username, node_key);
+                            let _ = self.send_message(&node_key, &format!("Welcome! To complete login as '{}', please enter: LOGIN {} <password>", username, username)).await;
+                            // Put the pending login back so they can complete it with password
+                            self.public_state.set_pending(&node_key, username);
+                        } else {
+                            // User has no password - allow auto-login for backward compatibility
+                            trace!("Auto-applying pending public login '{}' (no password) to new DM session {}", username, node_key);
+                            session.login(username.clone(), 1).await?;
+                            let prev_last = user.last_login;
+                            if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(prev_last); }
+                            let unread = self.storage.count_messages_since(prev_last).await.unwrap_or(0);
+                            let unread_mail = self.storage.count_unread_mail(&username).await.unwrap_or(0);
+                            let _ = self.storage.record_user_login(&username).await; // update last_login
+                            let summary = Self::format_unread_line(unread, unread_mail);
+                            let _ = self.send_session_message(&node_key, &format!("Welcome, {} you are now logged in.\n{}", username, summary), true).await;
+                        }
+                    } else {
+                        // New user case - create user without password (they can set one later)
+                        trace!("Auto-applying pending public login '{}' (new user) to new DM session {}", username, node_key);
+                        session.login(username.clone(), 1).await?;
+                        if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(Utc::now()); }
+                        self.storage.create_or_update_user(&username, &node_key).await?;
+                        let summary = Self::format_unread_line(0, 0);
+                        let _ = self.send_session_message(&node_key, &format!("Welcome, {} you are now logged in.\n{}", username, summary), true).await;
+                    }
+                }
+            } else {
+                // Removed first-contact guidance banner (Option B) to avoid duplicate initial messages.
+            }
+            self.sessions.insert(node_key.clone(), session);
+        }
+            // New consolidated DM command handling with max_users and idle pruning
+            self.prune_idle_sessions().await; // always prune first
+            let raw_content = content.trim().to_string();
+            let upper = raw_content.to_uppercase();
+            // Count current logged in sessions (excluding the session for this node if it is not yet logged in)
+            let logged_in_count = self.sessions.values().filter(|s| s.is_logged_in()).count();
+            enum PostAction { None, Delete{area:String,id:String,actor:String}, Lock{area:String,actor:String}, Unlock{area:String,actor:String}, Broadcast{message:String,sender:String}, Notify{notices:Vec<(String,String)>}, Reload{actor:String}, Lockouts{lockouts:Vec<Lockout>} }
+            let mut post_action = PostAction::None;
+            let mut deferred_reply: Option<String> = None;
+
+            // Track if this message was fully handled by registration logic to avoid re-processing.
+            let mut handled_registration = false;
+            // Handle REGISTER early without holding mutable borrow on session to simplify chunking logic.
+            if upper.starts_with("REGISTER ") {
+                let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                if parts.len() < 3 { deferred_reply = Some("Usage: REGISTER <username> <password>\n".into()); }
+                else {
+                    let user = parts[1]; let pass = parts[2];
+                    if pass.len() < 8 { deferred_reply = Some("Password too short (minimum 8 characters).\n".into()); }
+                    else {
+                        match self.storage.register_user(user, pass, Some(&node_key)).await {
+                            Ok(_) => {
+                                if let Some(session) = self.sessions.get_mut(&node_key) { session.login(user.to_string(), 1).await?; session.unread_since = Some(Utc::now()); }
+                                let summary = Self::format_unread_line(0, 0);
+                                // Compact single-frame friendly welcome (omit BBS name & HELP+ reference)
+                                // Example (unread 0): "Registered as alice. 0 new msgs. HELP LIST READ POST WHO"
+                                // Keeps under 230 bytes even with 30-char username.
+                                let unread_snip = if summary.contains("no new") || summary.contains("no new messages") {
+                                    // summary format_unread_line(0) typically "There are no new messages." -> shorten
+                                    "0 new msgs.".to_string()
+                                } else if let Some(num) = summary.split_whitespace().find(|w| w.chars().all(|c| c.is_ascii_digit())) {
+                                    format!("{} unread msgs.", num)
+                                } else {
+                                    summary.trim().to_string()
+                                };
+                                let full_welcome = format!(
+                                    "Registered as {u}. {unread} HELP LIST READ POST WHO\n",
+                                    u=user, unread=unread_snip
+                                );
+                                let max_bytes = self.config.storage.max_message_size;
+                                let parts_vec = self.chunk_utf8(&full_welcome, max_bytes);
+                                if parts_vec.len() > 1 { warn!("Registration welcome chunked into {} parts ({} bytes total)", parts_vec.len(), full_welcome.len()); }
+                                if parts_vec.len() == 1 {
+                                    deferred_reply = Some(parts_vec[0].clone());
+                                } else {
+                                    let total = parts_vec.len();
+                                    for (i, chunk) in parts_vec.into_iter().enumerate() {
+                                        let last = i + 1 == total; // only append prompt after final
+                                        self.send_session_message(&node_key, &chunk, last).await?;
+                                    }
+                                }
+                                if let Err(e) = self.storage.mark_welcome_shown(user, true, false).await {
+                                    eprintln!("Failed to mark welcome shown for {}: {}", user, e);
+                                }
+                                handled_registration = true;
+                            }
+                            Err(e) => { deferred_reply = Some(format!("Register failed: {}\n", e)); }
+                        }
+                    }
+                }
+            }
+
+            // If registration fully handled (multi-part or single reply prepared), skip further command processing
+            if handled_registration {
+                if let Some(msg) = deferred_reply { self.send_session_message(&node_key, &msg, true).await?; }
+                return Ok(());
+            }
+
+            if let Some(session) = self.sessions.get_mut(&node_key) {
+                session.update_activity();
+                #[cfg(feature = "meshtastic-proto")]
+                if let (Some(dev), Ok(idnum)) = (&self.device, node_key.parse::<u32>()) {
+                    let (short,long) = dev.format_node_combined(idnum);
+                    session.update_labels(Some(short), Some(long));
+                }
+                if upper == "HELP+" || upper == "HELP V" || upper == "HELP  V" || upper == "HELP  +" { // tolerate minor spacing variants
+                    let chunks = chunk_verbose_help();
+                    let total = chunks.len();
+                    for (i, chunk) in chunks.into_iter().enumerate() {
+                        let last = i + 1 == total;
+                        // For multi-part help, suppress prompt until final
+                        self.send_session_message(&node_key, &chunk, last).await?;
+                    }
+                } else if upper.starts_with("LOGIN ") {
+                    // Enforce max_users only if this session is not yet logged in
+                    if !session.is_logged_in() && (logged_in_count as u32) >= self.config.bbs.max_users {
+                        deferred_reply = Some("All available sessions are in use, please wait and try again later.\n".into());
+                    } else if session.is_logged_in() {
+                        deferred_reply = Some(format!("Already logged in as {}.\n", session.display_name()));
+                    } else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: LOGIN <username> [password]\n".into()); }
+                        else {
+                            let user = parts[1];
+                            let password_opt = if parts.len() >= 3 { Some(parts[2]) } else { None };
+                            let policy = self.config.security.as_ref().map(|s| s.lockout.clone()).unwrap_or_default();
+                            let now = Utc::now();
+                            let existing = self.storage.get_user(user).await?;
+                            let locked_until = self.storage.login_lockout(existing.as_ref().map(|u| u.username.as_str()), &node_key, &policy, now).await?;
+                            let ban = match &existing {
+                                Some(u) => self.storage.sanction(SanctionKind::Ban, &u.username, now).await?,
+                                None => None,
+                            };
+                            match existing {
+                                _ if locked_until.is_some() => {
+                                    let minutes = locked_until.map_or(0, |until| (until - now).num_minutes() + 1);
+                                    deferred_reply = Some(format!("Too many failed logins. Try again in {} min.\n", minutes));
+                                }
+                                _ if ban.is_some() => {
+                                    deferred_reply = ban.map(|b| sanction_notice("Account banned", &b, now));
+                                }
+                                None => {
+                                    let started = self.storage.record_login_failure(None, &node_key, &policy, now).await?;
+                                    if !started.is_empty() { post_action = PostAction::Lockouts{lockouts: started}; }
+                                    deferred_reply = Some("No such user. Use REGISTER <u> <p>.\n".into());
+                                }
+                                Some(u) => {
+                                    let has_password = u.password_hash.is_some();
+                                    let node_bound = u.trusts_node(&node_key);
+                                    if !has_password {
+                                        // User must set a password on first login attempt
+                                        if let Some(pass) = password_opt {
+                                            if pass.len() < 8 { deferred_reply = Some("Password too short (minimum 8 characters).\n".into()); }
+                                            else {
+                                                let updated_user = self.storage.set_user_password(user, pass).await?;
+                                                let updated = if !node_bound { self.storage.bind_user_node(user, &node_key).await? } else { updated_user };
+                                                session.login(updated.username.clone(), updated.user_level).await?;
+                                                if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(Utc::now()); }
+                                                // First-time password set; unread messages prior to this first authenticated login are based on prior last_login value.
+                                                // set_user_password already bumped last_login, so computing unread would yield zero. This is acceptable; show none.
+                                                let _ = self.storage.record_user_login(user).await; // ensure fresh timestamp after full login
+                                                // No unread count expected here (legacy first login)
+                                                let summary = Self::format_unread_line(0, self.storage.count_unread_mail(user).await.unwrap_or(0)); // first login after setting password shows no unread posts
+                                                
+                                                // Check if this is the first login after registration and show follow-up welcome
+                                                let mut login_msg = format!("Password set. Welcome, {} you are now logged in.\n{}", updated.username, summary);
+                                                if updated.welcome_shown_on_registration && !updated.welcome_shown_on_first_login {
+                                                    login_msg.push_str("\n💡 Quick tip: Since this is your first time back, try these commands:\n• 'LIST' - Browse available message boards\n• 'WHO' - See who's currently online\n• 'RECENT' - Check the latest activity\n\nHappy posting!\n");
+                                                    // Mark first login welcome as shown
+                                                    if let Err(e) = self.storage.mark_welcome_shown(user, false, true).await {
+                                                        eprintln!("Failed to mark first login welcome shown for {}: {}", user, e);
+                                                    }
+                                                }
+                                                deferred_reply = Some(login_msg);
+                                            }
+                                        } else {
+                                            deferred_reply = Some("Password not set. LOGIN <user> <newpass> to set your password.\n".into());
+                                        }
+                                    } else {
+                                        // Has password: required unless trusted-node login is on and this node is trusted
+                                        let trusted = u.trusts_node(&node_key);
+                                        if !trusted && u.node_id.is_some() {
+                                            sec_log!("LOGIN for {} from untrusted node {}", u.username, node_key);
+                                        }
+                                        let trusted_login = trusted && u.username != self.config.bbs.sysop
+                                            && self.config.security.as_ref().is_some_and(|s| s.trusted_node_login);
+                                        let verified = match password_opt {
+                                            Some(pass) => {
+                                                let (_maybe, ok) = self.storage.verify_user_password(user, pass).await?;
+                                                if !ok {
+                                                    sec_log!("LOGIN failed for {} from {}", u.username, node_key);
+                                                    let started = self.storage.record_login_failure(Some(&u.username), &node_key, &policy, now).await?;
+                                                    deferred_reply = Some(match started.iter().find(|l| l.target == LockoutTarget::Account(u.username.clone())).or(started.first()) {
+                                                        Some(l) => format!("Invalid password. Locked for {} min.\n", l.minutes),
+                                                        None => "Invalid password.\n".into(),
+                                                    });
+                                                    if !started.is_empty() { post_action = PostAction::Lockouts{lockouts: started}; }
+                                                }
+                                                ok
+                                            }
+                                            None if trusted_login => true,
+                                            None => { deferred_reply = Some("Password required: LOGIN <user> <pass>\n".into()); false }
+                                        };
+                                        if verified {
+                                            // Capture last_login before binding the node (binding counts as activity)
+                                            let prev_last = u.last_login;
+                                            let updated = if !node_bound { self.storage.bind_user_node(user, &node_key).await? } else { u };
+                                            self.storage.record_login_success(&updated.username, &node_key).await?;
+                                            session.login(updated.username.clone(), updated.user_level).await?;
+                                            if let Some(sess) = self.sessions.get_mut(&node_key) { sess.unread_since = Some(prev_last); }
+                                            let unread = self.storage.count_messages_since(prev_last).await.unwrap_or(0);
+                                            let unread_mail = self.storage.count_unread_mail(user).await.unwrap_or(0);
+                                            let updated2 = self.storage.record_user_login(user).await.unwrap_or(updated);
+                                            let summary = Self::format_unread_line(unread, unread_mail);
+                                            
+                                            // Check if this is the first login after registration and show follow-up welcome
+                                            let mut login_msg = format!("Welcome, {} you are now logged in.\n{}", updated2.username, summary);
+                                            if updated2.welcome_shown_on_registration && !updated2.welcome_shown_on_first_login {
+                                                login_msg.push_str("\n💡 Quick tip: Since this is your first time back, try these commands:\n• 'LIST' - Browse available message boards\n• 'WHO' - See who's currently online\n• 'RECENT' - Check the latest activity\n\nHappy posting!\n");
+                                                // Mark first login welcome as shown
+                                                if let Err(e) = self.storage.mark_welcome_shown(user, false, true).await {
+                                                    eprintln!("Failed to mark first login welcome shown for {}: {}", user, e);
+                                                }
+                                            }
+                                            deferred_reply = Some(login_msg);
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                } else if upper == "RESET" || upper.starts_with("RESET ") {
+                    // Redeem a RESETPW code; works without being logged in
+                    let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                    if parts.len() < 4 { deferred_reply = Some("Usage: RESET <user> <code> <newpass>\n".into()); }
+                    else {
+                        let (user, code, newp) = (parts[1], parts[2], parts[3]);
+                        let policy = self.config.security.as_ref().map(|s| s.lockout.clone()).unwrap_or_default();
+                        let now = Utc::now();
+                        // Only the node is locked out, so guessing codes cannot lock the account
+                        if let Some(until) = self.storage.login_lockout(None, &node_key, &policy, now).await? {
+                            deferred_reply = Some(format!("Too many failed attempts. Try again in {} min.\n", (until - now).num_minutes() + 1));
+                        } else if newp.len() < 8 {
+                            deferred_reply = Some("New password too short (min 8).\n".into());
+                        } else if newp.len() > 128 {
+                            deferred_reply = Some("New password too long.\n".into());
+                        } else if self.storage.redeem_password_reset(user, code, newp, now).await? {
+                            let username = self.storage.get_user(user).await?.map(|u| u.username).unwrap_or_else(|| user.to_string());
+                            self.storage.record_login_success(&username, &node_key).await?;
+                            sec_log!("RESET password for {} from {}", username, node_key);
+                            self.storage.log_admin_action("RESETPW_USED", Some(&username), &username, Some(&format!("from node {}", node_key))).await?;
+                            deferred_reply = Some(format!("Password changed. LOGIN {} <newpass>\n", username));
+                        } else {
+                            sec_log!("RESET failed for {} from {}", user, node_key);
+                            let started = self.storage.record_login_failure(None, &node_key, &policy, now).await?;
+                            if !started.is_empty() { post_action = PostAction::Lockouts{lockouts: started}; }
+                            deferred_reply = Some("Invalid or expired code.\n".into());
+                        }
+                    }
+                } else if upper.starts_with("CHPASS ") {
+                    if session.username.as_deref() == Some(&self.config.bbs.sysop) {
+                        deferred_reply = Some("Sysop password managed externally. Use sysop-passwd CLI.\n".into());
+                    } else if session.is_logged_in() {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 3 {
+                            deferred_reply = Some("Usage: CHPASS <old> <new>\n".into());
+                        } else {
+                            let old = parts[1];
+                            let newp = parts[2];
+                            if newp.len() < 8 {
+                                deferred_reply = Some("New password too short (min 8).\n".into());
+                            } else if newp.len() > 128 {
+                                deferred_reply = Some("New password too long.\n".into());
+                            } else if let Some(user_name) = &session.username {
+                                match self.storage.get_user(user_name).await? {
+                                    Some(u) => {
+                                        if u.password_hash.is_none() {
+                                            deferred_reply = Some("No existing password. Use SETPASS <new>.\n".into());
+                                        } else {
+                                            let (_u2, ok) = self.storage.verify_user_password(user_name, old).await?;
+                                            if !ok {
+                                                deferred_reply = Some("Invalid password.\n".into());
+                                            } else if old == newp {
+                                                deferred_reply = Some("New password must differ.\n".into());
+                                            } else {
+                                                self.storage.update_user_password(user_name, newp).await?;
+                                                deferred_reply = Some("Password changed.\n".into());
+                                            }
+                                        }
+                                    }
+                                    None => deferred_reply = Some("Session user missing.\n".into()),
+                                }
+                            } else {
+                                deferred_reply = Some("Not logged in.\n".into());
+                            }
+                        }
+                    } else {
+                        deferred_reply = Some("Not logged in.\n".into());
+                    }
+                } else if upper.starts_with("SETPASS ") {
+                    if session.username.as_deref() == Some(&self.config.bbs.sysop) {
+                        deferred_reply = Some("Sysop password managed externally. Use sysop-passwd CLI.\n".into());
+                    } else if session.is_logged_in() {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 {
+                            deferred_reply = Some("Usage: SETPASS <new>\n".into());
+                        } else {
+                            let newp = parts[1];
+                            if newp.len() < 8 {
+                                deferred_reply = Some("New password too short (min 8).\n".into());
+                            } else if newp.len() > 128 {
+                                deferred_reply = Some("New password too long.\n".into());
+                            } else if let Some(user_name) = &session.username {
+                                match self.storage.get_user(user_name).await? {
+                                        Some(u) => {
+                                            if u.password_hash.is_some() {
+                                                deferred_reply = Some("Password already set. Use CHPASS <old> <new>.\n".into());
+                                            } else {
+                                                self.storage.update_user_password(user_name, newp).await?;
+                                                deferred_reply = Some("Password set.\n".into());
+                                            }
+                                        }
+                                        None => deferred_reply = Some("Session user missing.\n".into()),
+                                    }
+                            } else {
+                                deferred_reply = Some("Not logged in.\n".into());
+                            }
+                        }
+                    } else {
+                        deferred_reply = Some("Not logged in.\n".into());
+                    }
+                } else if upper.starts_with("PROMOTE ") {
+                    if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: PROMOTE <user>\n".into()); }
+                        else {
+                            let target = parts[1];
+                            match self.storage.get_user(target).await? {
+                                None => deferred_reply = Some("User not found.\n".into()),
+                                Some(u) => {
+                                    if u.username == self.config.bbs.sysop { deferred_reply = Some("Cannot modify sysop.\n".into()); }
+                                    else if u.user_level >= LEVEL_MODERATOR { deferred_reply = Some("Already moderator or higher.\n".into()); }
+                                    else { self.storage.update_user_level(&u.username, LEVEL_MODERATOR, session.username.as_deref().unwrap_or("unknown")).await?; deferred_reply = Some(format!("{} promoted to {}.\n", u.username, role_name(LEVEL_MODERATOR))); }
+                                }
+                            }
+                        }
+                    }
+                } else if upper.starts_with("DEMOTE ") {
+                    if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: DEMOTE <user>\n".into()); }
+                        else {
+                            let target = parts[1];
+                            match self.storage.get_user(target).await? {
+                                None => deferred_reply = Some("User not found.\n".into()),
+                                Some(u) => {
+                                    if u.username == self.config.bbs.sysop { deferred_reply = Some("Cannot modify sysop.\n".into()); }
+                                    else if u.user_level <= LEVEL_USER { deferred_reply = Some("Already at base level.\n".into()); }
+                                    else { self.storage.update_user_level(&u.username, LEVEL_USER, session.username.as_deref().unwrap_or("unknown")).await?; deferred_reply = Some(format!("{} demoted to {}.\n", u.username, role_name(LEVEL_USER))); }
+                                }
+                            }
+                        }
+                    }
+                } else if upper == "UNLOCKUSER" || upper.starts_with("UNLOCKUSER ") {
+                    if session.user_level < LEVEL_SYSOP { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 {
+                            // No target: list what is locked right now
+                            let now = Utc::now();
+                            let locked = self.storage.active_lockouts(now).await?;
+                            let mut reply = if locked.is_empty() { "No lockouts.\n".to_string() } else { "Locked:\n".to_string() };
+                            for (target, until) in locked {
+                                let line = format!("{} {}m\n", target, (until - now).num_minutes() + 1);
+                                if reply.len() + line.len() > 200 { reply.push_str("...\n"); break; }
+                                reply.push_str(&line);
+                            }
+                            reply.push_str("Usage: UNLOCKUSER <user|node>\n");
+                            deferred_reply = Some(reply);
+                        } else {
+                            let target = parts[1];
+                            let cleared = self.storage.clear_lockout(target).await?;
+                            if cleared.is_empty() { deferred_reply = Some(format!("No failed logins recorded for {}.\n", target)); }
+                            else {
+                                let actor = session.username.clone().unwrap_or_default();
+                                let what: Vec<String> = cleared.iter().map(|t| t.to_string()).collect();
+                                sec_log!("UNLOCKUSER by {}: {}", actor, what.join(", "));
+                                self.storage.log_admin_action("UNLOCKUSER", Some(cleared[0].key()), &actor, Some(&what.join(", "))).await?;
+                                deferred_reply = Some(format!("Unlocked {}.\n", what.join(", ")));
+                            }
+                        }
+                    }
+                } else if upper == "ACCEPTKEY" || upper.starts_with("ACCEPTKEY ") {
+                    if session.user_level < LEVEL_SYSOP { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        match parts.get(1).and_then(|n| parse_node_id(n)) {
+                            None => {
+                                let mut nodes: Vec<String> = self.pending_keys.keys().map(|n| n.to_string()).collect();
+                                nodes.sort();
+                                let pending = if nodes.is_empty() { "none".to_string() } else { nodes.join(", ") };
+                                deferred_reply = Some(format!("Pending key changes: {}\nUsage: ACCEPTKEY <node>\n", pending));
+                            }
+                            Some(node) => match self.pending_keys.remove(&node) {
+                                None => deferred_reply = Some(format!("No key change pending for node {}.\n", node)),
+                                Some(key) => {
+                                    #[cfg(feature = "meshtastic-proto")]
+                                    if let Some(ctrl) = &self.reader_control_tx { let _ = ctrl.send(ControlMessage::AcceptKey(node)); }
+                                    let actor = session.username.clone().unwrap_or_default();
+                                    sec_log!("ACCEPTKEY by {}: node {} now pinned to {}", actor, node, key);
+                                    let details = format!("public key {}", key);
+                                    self.storage.log_admin_action("PKI_KEY_ACCEPTED", Some(&node.to_string()), &actor, Some(&details)).await?;
+                                    deferred_reply = Some(format!("DMs to node {} now use its new key.\n", node));
+                                }
+                            },
+                        }
+                    }
+                } else if upper == "RESETPW" || upper.starts_with("RESETPW ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: RESETPW <user>\n".into()); }
+                        else {
+                            let actor = session.username.clone().unwrap_or_default();
+                            match self.storage.get_user(parts[1]).await? {
+                                None => deferred_reply = Some("User not found.\n".into()),
+                                Some(u) if u.username == self.config.bbs.sysop => {
+                                    deferred_reply = Some("Sysop password managed externally. Use sysop-passwd CLI.\n".into());
+                                }
+                                Some(u) if actor != self.config.bbs.sysop && u.user_level >= session.user_level => {
+                                    deferred_reply = Some("Permission denied.\n".into());
+                                }
+                                Some(u) => {
+                                    let code = self.storage.issue_password_reset(&u.username, &actor, Utc::now()).await?;
+                                    sec_log!("RESETPW for {} issued by {}", u.username, actor);
+                                    let details = format!("code expires in {} min", RESET_CODE_TTL_MINUTES);
+                                    self.storage.log_admin_action("RESETPW_ISSUED", Some(&u.username), &actor, Some(&details)).await?;
+                                    deferred_reply = Some(format!("Reset code for {}: {} ({} min). They send: RESET {} <code> <newpass>\n",
+                                        u.username, code, RESET_CODE_TTL_MINUTES, u.username));
+                                }
+                            }
+                        }
+                    }
+                } else if let Some(kind) = [SanctionKind::Ban, SanctionKind::Mute, SanctionKind::BlockNode].into_iter()
+                    .find(|k| upper == k.command() || upper.starts_with(&format!("{} ", k.command()))) {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let actor = session.username.clone().unwrap_or_default();
+                        let level = session.user_level;
+                        let args: Vec<&str> = raw_content.split_whitespace().skip(1).collect();
+                        deferred_reply = Some(self.sanction_command(kind, &actor, level, &node_key, &args).await?);
+                    }
+                } else if upper == "BANS" {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let now = Utc::now();
+                        let active = self.storage.active_sanctions(now).await?;
+                        let mut reply = if active.is_empty() { "No bans, mutes or blocks.\n".to_string() } else { "Sanctions:\n".to_string() };
+                        for (kind, target, sanction) in active {
+                            let span = sanction.expires_at.map_or_else(|| "perm".to_string(), |until| format_span(until - now));
+                            let line = format!("{} {} {} {}", kind, target, span, sanction.reason);
+                            let line = format!("{}\n", line.trim_end());
+                            if reply.len() + line.len() > 200 { reply.push_str("...\n"); break; }
+                            reply.push_str(&line);
+                        }
+                        deferred_reply = Some(reply);
+                    }
+                } else if upper.starts_with("CREATETOPIC ") {
+                    if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 4 { deferred_reply = Some("Usage: CREATETOPIC <id> <name> <description> [read_level] [post_level]\n".into()); }
+                        else {
+                            let topic_id = parts[1].to_lowercase();
+                            let name = parts[2];
+                            let description = parts[3..].join(" ");
+                            let read_level = 0u8; // Default read level
+                            let post_level = 0u8; // Default post level
+                            let creator = session.username.as_deref().unwrap_or("sysop");
+                            
+                            match self.storage.create_topic(&topic_id, name, &description, read_level, post_level, creator).await {
+                                Ok(()) => deferred_reply = Some(format!("Topic '{}' created successfully.\n", topic_id)),
+                                Err(e) => deferred_reply = Some(format!("Failed to create topic: {}\n", e)),
+                            }
+                        }
+                    }
+                } else if upper.starts_with("MODIFYTOPIC ") {
+                    if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 3 { deferred_reply = Some("Usage: MODIFYTOPIC <id> name=<name> | desc=<desc> | read=<level> | post=<level> | maxage=<days> | maxthreads=<n> | keeppinned=yes|no\n".into()); }
+                        else {
+                            let topic_id = parts[1].to_lowercase();
+                            let mut name: Option<&str> = None;
+                            let mut description: Option<String> = None;
+                            let mut read_level: Option<u8> = None;
+                            let mut post_level: Option<u8> = None;
+                            let mut retention = self.storage.get_topic_config(&topic_id).and_then(|t| t.retention.clone());
+                            
+                            // Parse key=value pairs; a retention limit of 0 or "off" removes it
+                            for part in &parts[2..] {
+                                if let Some((key, value)) = part.split_once('=') {
+                                    match key.to_lowercase().as_str() {
+                                        "name" => name = Some(value),
+                                        "desc" | "description" => description = Some(value.to_string()),
+                                        "read" => read_level = value.parse().ok(),
+                                        "post" => post_level = value.parse().ok(),
+                                        "maxage" => retention.get_or_insert_with(Default::default).max_age_days = value.parse().ok().filter(|n| *n > 0),
+                                        "maxthreads" => retention.get_or_insert_with(Default::default).max_threads = value.parse().ok().filter(|n| *n > 0),
+                                        "keeppinned" => retention.get_or_insert_with(Default::default).keep_pinned = !matches!(value.to_lowercase().as_str(), "no" | "off" | "false"),
+                                        _ => {}
+                                    }
+                                }
+                            }
+                            
+                            let result = match self.storage.modify_topic(&topic_id, name, description.as_deref(), read_level, post_level).await {
+                                Ok(()) => self.storage.set_topic_retention(&topic_id, retention).await,
+                                Err(e) => Err(e),
+                            };
+                            match result {
+                                Ok(()) => deferred_reply = Some(format!("Topic '{}' modified successfully.\n", topic_id)),
+                                Err(e) => deferred_reply = Some(format!("Failed to modify topic: {}\n", e)),
+                            }
+                        }
+                    }
+                } else if upper.starts_with("DELETETOPIC ") {
+                    if session.username.as_deref() != Some(&self.config.bbs.sysop) { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: DELETETOPIC <id>\n".into()); }
+                        else {
+                            let topic_id = parts[1].to_lowercase();
+                            
+                            match self.storage.delete_topic(&topic_id).await {
+                                Ok(()) => deferred_reply = Some(format!("Topic '{}' deleted successfully.\n", topic_id)),
+                                Err(e) => deferred_reply = Some(format!("Failed to delete topic: {}\n", e)),
+                            }
+                        }
+                    }
+                } else if upper.starts_with("DELETE ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 3 { deferred_reply = Some("Usage: DELETE <area> <id>\n".into()); }
+                        else {
+                            let area = parts[1].to_lowercase();
+                            let id = parts[2].to_string();
+                            let actor = session.username.clone().unwrap_or("?".into());
+                            post_action = PostAction::Delete{area,id,actor};
+                        }
+                    }
+                } else if upper.starts_with("LOCK ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: LOCK <area>\n".into()); }
+                        else {
+                            let area = parts[1].to_lowercase();
+                            let actor = session.username.clone().unwrap_or("?".into());
+                            post_action = PostAction::Lock{area:area.clone(), actor};
+                            deferred_reply = Some(format!("Area {} locked.\n", area));
+                        }
+                    }
+                } else if upper.starts_with("UNLOCK ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: UNLOCK <area>\n".into()); }
+                        else {
+                            let area = parts[1].to_lowercase();
+                            let actor = session.username.clone().unwrap_or("?".into());
+                            post_action = PostAction::Unlock{area:area.clone(), actor};
+                            deferred_reply = Some(format!("Area {} unlocked.\n", area));
+                        }
+                    }
+                } else if upper.starts_with("DELLOG") || upper == "DL" || upper.starts_with("DL ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        let page = if parts.len() >= 2 { parts[1].parse::<usize>().unwrap_or(1) } else { 1 };
+                        match self.storage.get_deletion_audit_page(page, 10).await {
+                            Ok(entries) => {
+                                if entries.is_empty() { deferred_reply = Some("No entries.\n".into()); }
+                                else { let mut out = String::from("Deletion Log:\n"); for e in entries { out.push_str(&format!("{} {} {} {}\n", e.timestamp, e.actor, e.topic, e.id)); } deferred_reply = Some(out); }
+                            }
+                            Err(e) => deferred_reply = Some(format!("Failed: {}\n", e)),
+                        }
+                    }
+                } else if upper.starts_with("ADMINLOG") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        let page = if parts.len() >= 2 { parts[1].parse::<usize>().unwrap_or(1) } else { 1 };
+                        match self.storage.get_admin_audit_page(page, 10).await {
+                            Ok(entries) => {
+                                if entries.is_empty() { deferred_reply = Some("No admin audit entries.\n".into()); }
+                                else { 
+                                    let mut out = String::from("Admin Audit Log:\n");
+                                    for e in entries {
+                                        let target_str = e.target.as_deref().unwrap_or("-");
+                                        let details_str = e.details.as_deref().unwrap_or("");
+                                        out.push_str(&format!("{} {} {} {} {}\n", 
+                                            e.timestamp.format("%m/%d %H:%M"), 
+                                            e.actor, 
+                                            e.action, 
+                                            target_str,
+                                            details_str
+                                        ));
+                                    }
+                                    deferred_reply = Some(out);
+                                }
+                            }
+                            Err(e) => deferred_reply = Some(format!("Failed: {}\n", e)),
+                        }
+                    }
+                } else if upper.starts_with("USERS") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        let pattern = if parts.len() >= 2 { Some(parts[1].to_lowercase()) } else { None };
+                        
+                        match self.storage.list_all_users().await {
+                            Ok(mut users) => {
+                                // Filter users by pattern if provided
+                                if let Some(ref p) = pattern {
+                                    users.retain(|u| u.username.to_lowercase().contains(p));
+                                }
+                                
+                                let logged_in_usernames: std::collections::HashSet<&str> = self.get_logged_in_users()
+                                    .iter()
+                                    .filter_map(|s| s.username.as_deref())
+                                    .collect();
+                                
+                                let mut response = if let Some(ref p) = pattern {
+                                    format!("Users matching '{}' ({} found):\n", p, users.len())
+                                } else {
+                                    format!("Registered Users ({}/{}):\n", users.len(), self.config.bbs.max_users)
+                                };
+                                
+                                for user in users {
+                                    let status = if logged_in_usernames.contains(user.username.as_str()) { "Online" } else { "Offline" };
+                                    let role = super::roles::role_name(user.user_level);
+                                    response.push_str(&format!("  {} ({}, Level {}) - {}\n", user.username, role, user.user_level, status));
+                                }
+                                
+                                if pattern.is_none() {
+                                    response.push_str(&format!("\nActive Sessions: {}\n", self.logged_in_session_count()));
+                                }
+                                deferred_reply = Some(response);
+                            }
+                            Err(e) => deferred_reply = Some(format!("Failed to list users: {}\n", e)),
+                        }
+                    }
+                } else if upper == "WHO" {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let logged_in = self.get_logged_in_users();
+                        if logged_in.is_empty() {
+                            deferred_reply = Some("No users currently logged in.\n".into());
+                        } else {
+                            let mut response = format!("Logged In Users ({}):\n", logged_in.len());
+                            for session in logged_in {
+                                let username = session.username.as_deref().unwrap_or("Guest");
+                                let role = super::roles::role_name(session.user_level);
+                                let duration = session.session_duration().num_minutes();
+                                let state = match session.state {
+                                    super::session::SessionState::MainMenu => "Main Menu",
+                                    super::session::SessionState::MessageTopics => "Message Areas",
+                                    super::session::SessionState::ReadingMessages => "Reading",
+                                    super::session::SessionState::PostingMessage => "Posting",
+                                    super::session::SessionState::UserMenu => "User Menu",
+                                    _ => "Other",
+                                };
+                                response.push_str(&format!("  {} ({}) - {} - {}m - {}\n", username, role, session.node_id, duration, state));
+                            }
+                            deferred_reply = Some(response);
+                        }
+                    }
+                } else if upper.starts_with("USERINFO ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: USERINFO <user>\n".into()); }
+                        else {
+                            let target = parts[1];
+                            match self.storage.get_user_details(target).await? {
+                                None => deferred_reply = Some("User not found.\n".into()),
+                                Some(user) => {
+                                    let post_count = self.storage.count_user_posts(&user.username).await.unwrap_or(0);
+                                    let is_online = self.get_logged_in_users().iter().any(|s| s.username.as_deref() == Some(&user.username));
+                                    let role = super::roles::role_name(user.user_level);
+                                    
+                                    let mut response = format!("User Information for {}:\n", user.username);
+                                    response.push_str(&format!("  Role: {} (Level {})\n", role, user.user_level));
+                                    response.push_str(&format!("  Status: {}\n", if is_online { "Online" } else { "Offline" }));
+                                    response.push_str(&format!("  First Login: {}\n", user.first_login.format("%Y-%m-%d %H:%M UTC")));
+                                    response.push_str(&format!("  Last Login: {}\n", user.last_login.format("%Y-%m-%d %H:%M UTC")));
+                                    response.push_str(&format!("  Total Posts: {}\n", post_count));
+                                    if let Some(node_id) = &user.node_id {
+                                        response.push_str(&format!("  Node ID: {}\n", node_id));
+                                    }
+                                    let trusted: Vec<String> = user.known_nodes().into_iter().skip(1).map(|n| n.node_id).collect();
+                                    if !trusted.is_empty() {
+                                        response.push_str(&format!("  Also Trusted: {}\n", trusted.join(", ")));
+                                    }
+                                    deferred_reply = Some(response);
+                                }
+                            }
+                        }
+                    }
+                } else if upper == "SESSIONS" {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let all_sessions = self.get_active_sessions();
+                        let mut response = format!("Active Sessions ({}):\n", all_sessions.len());
+                        for s in all_sessions {
+                            let username = s.username.as_deref().unwrap_or("Guest");
+                            let role = super::roles::role_name(s.user_level);
+                            let duration = s.session_duration().num_minutes();
+                            let logged_in = if s.is_logged_in() { "Yes" } else { "No" };
+                            let state = match s.state {
+                                super::session::SessionState::Connected => "Connected",
+                                super::session::SessionState::LoggingIn => "Logging In",
+                                super::session::SessionState::MainMenu => "Main Menu",
+                                super::session::SessionState::MessageTopics => "Message Areas",
+                                super::session::SessionState::ReadingMessages => "Reading",
+                                super::session::SessionState::PostingMessage => "Posting",
+                                super::session::SessionState::Topics => "Topics",
+                                super::session::SessionState::Subtopics => "Subtopics",
+                                super::session::SessionState::Threads => "Threads",
+                                super::session::SessionState::ThreadRead => "Read",
+                                super::session::SessionState::ComposeNewTitle => "Compose Title",
+                                super::session::SessionState::ComposeNewBody => "Compose Body",
+                                super::session::SessionState::ComposeReply => "Compose Reply",
+                                super::session::SessionState::ConfirmDelete => "Confirm Delete",
+                                super::session::SessionState::MailInbox => "Mail",
+                                super::session::SessionState::MailRead => "Mail Read",
+                                super::session::SessionState::MailCompose => "Mail Compose",
+                                super::session::SessionState::SearchResults => "Search",
+                                super::session::SessionState::UserMenu => "User Menu",
+                                super::session::SessionState::Disconnected => "Disconnected",
+                            };
+                            response.push_str(&format!("  {} ({}) | {} | {}m | Login: {} | {}\n", 
+                                username, role, s.node_id, duration, logged_in, state));
+                        }
+                        deferred_reply = Some(response);
+                    }
+                } else if upper.starts_with("KICK ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let parts: Vec<&str> = raw_content.split_whitespace().collect();
+                        if parts.len() < 2 { deferred_reply = Some("Usage: KICK <user>\n".into()); }
+                        else {
+                            let target = parts[1];
+                            let actor = session.username.as_deref().unwrap_or("unknown").to_string();
+                            if target == actor {
+                                deferred_reply = Some("Cannot kick yourself.\n".into());
+                            } else if target == self.config.bbs.sysop {
+                                deferred_reply = Some("Cannot kick sysop.\n".into());
+                            } else {
+                                match self.force_logout_user(target).await? {
+                                    true => {
+                                        // Log the administrative action
+                                        if let Err(e) = self.storage.log_admin_action("KICK", Some(target), &actor, None).await {
+                                            warn!("Failed to log admin action: {}", e);
+                                        }
+                                        deferred_reply = Some(format!("User {} has been kicked.\n", target));
+                                    },
+                                    false => deferred_reply = Some("User not found or not logged in.\n".into()),
+                                }
+                            }
+                        }
+                    }
+                } else if upper.starts_with("BROADCAST ") {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let message = raw_content.strip_prefix("BROADCAST ").unwrap_or("").trim();
+                        if message.is_empty() { deferred_reply = Some("Usage: BROADCAST <message>\n".into()); }
+                        else {
+                            let sender = session.username.as_deref().unwrap_or("System").to_string();
+                            let message = message.to_string();
+                            post_action = PostAction::Broadcast{message, sender};
+                        }
+                    }
+                } else if upper == "ADMIN" || upper == "DASHBOARD" {
+                    if session.user_level < LEVEL_MODERATOR { deferred_reply = Some("Permission denied.\n".into()); }
+                    else {
+                        let stats = self.storage.get_statistics().await?;
+                        let active_count = self.get_active_sessions().len();
+                        let logged_in_count = self.logged_in_session_count();
+                        
+                        let mut response = String::from("=== ADMINISTRATIVE DASHBOARD ===\n");
+                        response.push_str("System Status:\n");
+                        response.push_str(&format!("  Total Users: {}\n", stats.total_users));
+                        response.push_str(&format!("  Total Messages: {}\n", stats.total_messages));
+                        response.push_str(&format!("  Active Sessions: {}\n", active_count));
+                        response.push_str(&format!("  Logged In Users: {}\n", logged_in_count));
+                        response.push_str(&format!("  Max Users: {}\n", self.config.bbs.max_users));
+                        response.push_str(&format!("  Session Timeout: {} min\n", self.config.bbs.session_timeout));
+                        let link = self.link_status().map(|l| l.summary()).unwrap_or_else(|| "not connected".into());
+                        response.push_str(&format!("  Radio Link: {}\n", link));
+                        response.push_str("\nCommands: USERS, WHO, USERINFO <user>, SESSIONS, KICK <user>, BROADCAST <msg>\n");
+                        deferred_reply = Some(response);
+                    }
+                } else if upper == "RELOAD" {
+                    if session.user_level < LEVEL_SYSOP { deferred_reply = Some("Permission denied.\n".into()); }
+                    else { post_action = PostAction::Reload{actor: session.username.clone().unwrap_or_default()}; }
+                } else if upper == "LOGOUT" {
+                    if session.is_logged_in() { let name = session.display_name(); session.logout().await?; deferred_reply = Some(format!("User {} logged out.\n", name)); }
+                    else { deferred_reply = Some("Not logged in.\n".into()); }
+                } else if upper == "HELP" || upper == "?" || upper == "H" {
+                    // Use existing abbreviated help via command processor (ensures consistent text)
+                    let mut help_text = session.process_command("HELP", &mut self.storage, &self.config).await?;
+                    if !session.help_seen {
+                        session.help_seen = true;
+                        help_text.push_str("Shortcuts: M=areas U=user Q=quit\n");
+                    }
+                    self.send_session_message(&node_key, &help_text, true).await?;
+                } else {
+                    let redact = ["REGISTER ", "LOGIN ", "SETPASS ", "CHPASS ", "RESET "];
+                    let log_snippet = if redact.iter().any(|p| upper.starts_with(p)) { "<redacted>" } else { raw_content.as_str() };
+                    trace!("Session {} generic command '{}'", node_key, log_snippet);
+                    let response = session.process_command(&raw_content, &mut self.storage, &self.config).await?;
+                    if !response.is_empty() { deferred_reply = Some(response); }
+                    if !session.notices.is_empty() {
+                        post_action = PostAction::Notify{notices: std::mem::take(&mut session.notices)};
+                    }
+                }
+            }
+            match post_action {
+                PostAction::None => {}
+                PostAction::Delete{area,id,actor} => {
+                    match self.moderator_delete_message(&area, &id, &actor).await {
+                        Ok(true) => { deferred_reply.get_or_insert(format!("Deleted message {} in {}.\n", id, area)); },
+                        Ok(false) => { deferred_reply.get_or_insert("Not found.\n".into()); },
+                        Err(e) => { deferred_reply.get_or_insert(format!("Delete failed: {}\n", e)); }
+                    }
+                }
+                PostAction::Lock{area,actor} => {
+                    if let Err(e) = self.moderator_lock_topic(&area, &actor).await { deferred_reply.get_or_insert(format!("Lock failed: {}\n", e)); }
+                }
+                PostAction::Unlock{area,actor} => {
+                    if let Err(e) = self.moderator_unlock_topic(&area, &actor).await { deferred_reply.get_or_insert(format!("Unlock failed: {}\n", e)); }
+                }
+                PostAction::Broadcast{message,sender} => {
+                    match self.broadcast_message(&message, &sender).await {
+                        Ok(0) => { deferred_reply.get_or_insert("No users online to receive broadcast.\n".into()); },
+                        Ok(count) => { 
+                            // Log the administrative action
+                            if let Err(e) = self.storage.log_admin_action("BROADCAST", None, &sender, Some(&message)).await {
+                                warn!("Failed to log admin action: {}", e);
+                            }
+                            deferred_reply.get_or_insert(format!("Broadcast sent to {} users.\n", count)); 
+                        },
+                        Err(e) => { deferred_reply.get_or_insert(format!("Broadcast failed: {}\n", e)); }
+                    }
+                }
+                PostAction::Notify{notices} => {
+                    self.deliver_notices(notices).await;
+                }
+                PostAction::Reload{actor} => {
+                    let reply = match self.reload_config(&actor).await {
+                        Ok(report) => format!("Config reloaded: {}\n", report.brief()),
+                        Err(e) => format!("Reload failed: {}\n", e),
+                    };
+                    deferred_reply = Some(reply);
+                }
+                PostAction::Lockouts{lockouts} => {
+                    self.report_lockouts(lockouts).await;
+                }
+            }
+            if let Some(msg) = deferred_reply { self.send_session_message(&node_key, &msg, true).await?; }
+        Ok(())
+    }
+
     /// Send a message to a specific node
     pub async fn send_message(&mut self, to_node: &str, message: &str) -> Result<()> {
+        self.send_direct(to_node, message, false).await
+    }
+
+    /// Send a notice that should reach the node eventually (mail, replies, moderator
+    /// notices). If the radio gives up on it, it is held and redelivered when the node is
+    /// next heard; see [`handle_delivery_event`](Self::handle_delivery_event).
+    pub async fn send_durable_message(&mut self, to_node: &str, message: &str) -> Result<()> {
+        self.send_direct(to_node, message, true).await
+    }
+
+    async fn send_direct(&mut self, to_node: &str, message: &str, durable: bool) -> Result<()> {
+        #[cfg(not(feature = "meshtastic-proto"))]
+        let _ = durable;
         #[cfg(feature = "meshtastic-proto")]
         {
             // If we have an active scheduler prefer enqueue path, else fallback to direct channel
             if let Some(scheduler) = &self.scheduler {
-                let node_id = if let Some(hex) = to_node.strip_prefix("0x").or_else(|| to_node.strip_prefix("0X")) { u32::from_str_radix(hex, 16).ok() } else { to_node.parse::<u32>().ok() };
+                let node_id = parse_node_id(to_node);
                 if let Some(id) = node_id {
-                    let outgoing = OutgoingMessage { to_node: Some(id), channel: 0, content: message.to_string(), priority: MessagePriority::High, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: false };
+                    let outgoing = OutgoingMessage { to_node: Some(id), channel: self.reply_channel(to_node), content: message.to_string(), priority: MessagePriority::High, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: false, durable };
                     let env = crate::bbs::dispatch::MessageEnvelope::new(
                         crate::bbs::dispatch::MessageCategory::Direct,
                         crate::bbs::dispatch::Priority::High,
@@ -1778,10 +2872,10 @@ impl BbsServer {
                 }
             } else if let Some(ref tx) = self.outgoing_tx {
                 // Parse node ID from string when actually sending to radio
-                let node_id = if let Some(hex) = to_node.strip_prefix("0x").or_else(|| to_node.strip_prefix("0X")) { u32::from_str_radix(hex, 16).ok() } else { to_node.parse::<u32>().ok() };
+                let node_id = parse_node_id(to_node);
 
                 if let Some(id) = node_id {
-                    let outgoing = OutgoingMessage { to_node: Some(id), channel: 0, content: message.to_string(), priority: MessagePriority::High, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: false };
+                    let outgoing = OutgoingMessage { to_node: Some(id), channel: self.reply_channel(to_node), content: message.to_string(), priority: MessagePriority::High, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: false, durable };
 
                     match tx.send(outgoing) {
                         Ok(_) => {
@@ -1818,11 +2912,43 @@ impl BbsServer {
         Ok(())
     }
 
-    /// Send a broadcast message to the public channel
+    /// Send a housekeeping notice. It is queued behind all interactive traffic
+    /// (`Maintenance` category, `Background` priority) when the scheduler runs.
+    async fn send_maintenance_notice(&mut self, to_node: &str, message: &str) -> Result<()> {
+        #[cfg(feature = "meshtastic-proto")]
+        if let Some(scheduler) = &self.scheduler {
+            let id = parse_node_id(to_node).ok_or_else(|| anyhow!("Invalid node ID format: {}", to_node))?;
+            let outgoing = OutgoingMessage { to_node: Some(id), channel: self.reply_channel(to_node), content: message.to_string(), priority: MessagePriority::High, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: false, durable: false };
+            scheduler.enqueue(crate::bbs::dispatch::MessageEnvelope::new(
+                crate::bbs::dispatch::MessageCategory::Maintenance,
+                crate::bbs::dispatch::Priority::Background,
+                Duration::from_millis(0),
+                outgoing
+            ));
+            self.test_messages.push((to_node.to_string(), message.to_string()));
+            return Ok(());
+        }
+        self.send_direct(to_node, message, false).await
+    }
+
+    /// Channel index for direct replies to `node_id`: the channel its most recent request
+    /// arrived on, falling back to the configured primary channel.
+    fn reply_channel(&self, node_id: &str) -> u32 {
+        self.reply_channels.get(node_id).map(|(channel, _)| *channel).unwrap_or_else(|| self.public_channels.primary_channel())
+    }
+
+    /// Send a broadcast message to the configured primary channel
     #[cfg(feature = "meshtastic-proto")]
     pub async fn send_broadcast(&mut self, message: &str) -> Result<()> {
+        let channel = self.public_channels.primary_channel();
+        self.send_broadcast_on(channel, message).await
+    }
+
+    /// Send a broadcast message on a specific channel index
+    #[cfg(feature = "meshtastic-proto")]
+    pub async fn send_broadcast_on(&mut self, channel: u32, message: &str) -> Result<()> {
         if let Some(scheduler) = &self.scheduler {
-            let outgoing = OutgoingMessage { to_node: None, channel: 0, content: message.to_string(), priority: MessagePriority::Normal, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: true };
+            let outgoing = OutgoingMessage { to_node: None, channel, content: message.to_string(), priority: MessagePriority::Normal, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: true, durable: false };
             let env = crate::bbs::dispatch::MessageEnvelope::new(
                 crate::bbs::dispatch::MessageCategory::Broadcast,
                 crate::bbs::dispatch::Priority::Low,
@@ -1832,7 +2958,7 @@ impl BbsServer {
             scheduler.enqueue(env);
             Ok(())
         } else {
-            let outgoing = OutgoingMessage { to_node: None, channel: 0, content: message.to_string(), priority: MessagePriority::Normal, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: true };
+            let outgoing = OutgoingMessage { to_node: None, channel, content: message.to_string(), priority: MessagePriority::Normal, kind: crate::meshtastic::OutgoingKind::Normal, request_ack: true, durable: false };
             if let Some(ref tx) = self.outgoing_tx {
                 match tx.send(outgoing) { Ok(_) => { debug!("Queued broadcast message: {}", escape_log(message)); Ok(()) }, Err(e) => { warn!("Failed to queue broadcast message: {}", e); Err(anyhow!("Failed to queue broadcast: {}", e)) } }
             } else {
@@ -1903,57 +3029,11 @@ impl BbsServer {
     #[allow(dead_code)] // Accessed by some legacy / external integration harnesses; keep until removed.
     pub fn exported_test_messages(&self) -> &Vec<(String,String)> { &self.test_messages }
 
-    /// Lightweight direct-message routing helper for tests (no meshtastic-proto TextEvent needed)
+    /// Direct-message routing helper for tests (no meshtastic-proto TextEvent needed).
+    /// Runs the same DM path as live traffic; `node_key` need not be numeric.
     #[allow(dead_code)]
     pub async fn route_test_text_direct(&mut self, node_key: &str, content: &str) -> Result<()> {
-        // Minimal emulation of direct path portion of route_text_event without meshtastic-proto TextEvent struct
-        if !self.sessions.contains_key(node_key) {
-            let session = Session::new(node_key.to_string(), node_key.to_string());
-            self.sessions.insert(node_key.to_string(), session);
-        }
-    // Inline simplified logic: replicate relevant subset from route_text_event for test
-    let raw_content = content.trim().to_string();
-        let upper = raw_content.to_uppercase();
-        let logged_in_count = self.sessions.values().filter(|s| s.is_logged_in()).count();
-        let mut deferred_reply: Option<String> = None;
-        if let Some(session) = self.sessions.get_mut(node_key) {
-            session.update_activity();
-            if upper == "HELP+" || upper == "HELP V" || upper == "HELP  V" || upper == "HELP  +" {
-                let chunks = chunk_verbose_help();
-                let total = chunks.len();
-                for (i, chunk) in chunks.into_iter().enumerate() { let last = i + 1 == total; self.send_session_message(node_key, &chunk, last).await?; }
-                return Ok(());
-            } else if upper == "HELP" || upper == "?" || upper == "H" {
-                let mut help_text = session.process_command("HELP", &mut self.storage, &self.config).await?;
-                if !session.help_seen {
-                    session.help_seen = true;
-                    help_text.push_str("Shortcuts: M=areas U=user Q=quit\n");
-                }
-                self.send_session_message(node_key, &help_text, true).await?;
-                return Ok(());
-            } else if upper.starts_with("LOGIN ") {
-                if !session.is_logged_in() && (logged_in_count as u32) >= self.config.bbs.max_users { deferred_reply = Some("All available sessions are in use, please wait and try again later.\n".into()); }
-                else if session.is_logged_in() { deferred_reply = Some(format!("Already logged in as {}.\n", session.display_name())); }
-                else {
-                    let parts: Vec<&str> = raw_content.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        let user = parts[1];
-                        // For tests, initialize unread_since from stored user's last_login if present
-                        let mut prev_last_opt = None;
-                        let mut level = 1u8;
-                        if let Ok(Some(u)) = self.storage.get_user(user).await { prev_last_opt = Some(u.last_login); level = u.user_level; }
-                        session.login(user.to_string(), level).await?;
-                        if let Some(prev) = prev_last_opt { if let Some(s2) = self.sessions.get_mut(node_key) { s2.unread_since = Some(prev); } }
-                        deferred_reply = Some(format!("Welcome, {} you are now logged in.\n{}", user, Self::format_unread_line(0)));
-                    }
-                }
-            } else {
-                let response = session.process_command(&raw_content, &mut self.storage, &self.config).await?;
-                if !response.is_empty() { deferred_reply = Some(response); }
-            }
-        }
-        if let Some(msg) = deferred_reply { self.send_session_message(node_key, &msg, true).await?; }
-        Ok(())
+        self.route_direct_text(node_key, content).await
     }
 
     #[allow(unused)]
@@ -2070,6 +3150,12 @@ impl BbsServer {
         } else {
             println!("Meshtastic Device: Not connected");
         }
+        // Live status when attached, otherwise whatever a running server last recorded
+        let link = self.link_status().or_else(|| crate::meshtastic::link::LinkStatus::load(&self.config.storage.data_dir));
+        match link {
+            Some(l) => println!("Radio Link: {}", l.summary()),
+            None => println!("Radio Link: unknown (server not running)"),
+        }
         
         // Storage statistics
         let stats = self.storage.get_statistics().await?;
@@ -2101,6 +3187,13 @@ impl BbsServer {
             }
         }
         
+        if let Some(status) = &self.link_status {
+            let mut l = status.lock().unwrap();
+            l.state = crate::meshtastic::link::LinkState::Stopped;
+            l.since = Utc::now();
+            l.persist(&self.config.storage.data_dir);
+        }
+        
         // Disconnect device (fallback for non-proto mode)
         if let Some(device) = &mut self.device {
             device.disconnect().await?;
@@ -78,8 +78,17 @@ pub struct Session {
     pub slice_index: usize,
     /// Optional filter text for list/search context (e.g., F <text>)
     pub filter_text: Option<String>,
+    /// Query of the `SEARCH` results being browsed; B in a thread opened from them returns there
+    pub search_query: Option<String>,
     /// Baseline timestamp for unread indicators (captured as previous last_login when user logs in)
     pub unread_since: Option<DateTime<Utc>>,
+    /// Mail message open in MailRead
+    pub current_mail_id: Option<String>,
+    /// Recipient while composing mail
+    pub mail_to: Option<String>,
+    /// Notices for other users raised by the last command, as (username, text); the
+    /// server delivers them store-and-forward
+    pub notices: Vec<(String, String)>,
     pub login_time: DateTime<Utc>,
     pub last_activity: DateTime<Utc>,
     pub state: SessionState,
@@ -102,6 +111,10 @@ pub enum SessionState {
     ComposeNewBody,  // Two-step compose (step 2)
     ComposeReply,    // Reply compose to current thread
     ConfirmDelete,   // Confirm delete of selected entity
+    MailInbox,       // Private mailbox list
+    MailRead,        // Reading one mail message
+    MailCompose,     // Mail body to mail_to
+    SearchResults,   // Paged SEARCH hits across topics
     UserMenu,
     Disconnected,
 }
@@ -125,7 +138,11 @@ impl Session {
             post_index: 1,
             slice_index: 1,
             filter_text: None,
+            search_query: None,
             unread_since: None,
+            current_mail_id: None,
+            mail_to: None,
+            notices: Vec::new(),
             login_time: now,
             last_activity: now,
             state: SessionState::Connected,
@@ -165,6 +182,9 @@ impl Session {
         self.username = None;
         self.user_level = 0;
         self.current_topic = None;
+        self.current_mail_id = None;
+        self.mail_to = None;
+        self.search_query = None;
         self.state = SessionState::Disconnected;
         
         Ok(())
@@ -224,6 +244,7 @@ impl Session {
     /// - Main/menu (logged in): `"username (lvl1)>"`
     /// - Reading messages/in topic: `"username@topic>"` (topic truncated to 20 chars)
     /// - Posting: `"post@topic>"` (falls back to `"post>"` if no topic)
+    /// - Mailbox: `"username@mail>"`; composing mail: `"mail@recipient>"`
     pub fn build_prompt(&self) -> String {
         // Unauthenticated
         if !self.is_logged_in() {
@@ -241,6 +262,11 @@ impl Session {
             SessionState::ConfirmDelete => {
                 format!("confirm@{}>", self.current_topic.as_deref().unwrap_or("bbs"))
             }
+            SessionState::MailInbox | SessionState::MailRead => format!("{}@mail>", self.display_name()),
+            SessionState::SearchResults => format!("{}@search>", self.display_name()),
+            SessionState::MailCompose => {
+                if let Some(to) = &self.mail_to { format!("mail@{}>", Self::truncate_topic(to)) } else { "mail>".into() }
+            }
             SessionState::MainMenu | SessionState::UserMenu | SessionState::LoggingIn | SessionState::Connected => {
                 format!("{} (lvl{})>", self.display_name(), level)
             }
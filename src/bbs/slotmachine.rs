@@ -3,8 +3,9 @@
 //! Overview
 //! - Emoji reels with fixed distributions and deterministic payout table
 //! - Economy: 100 coins starting balance, 5 coins per spin, 24h refill when balance reaches 0
-//! - Persistence: JSON file at `<data_dir>/slotmachine/players.json` keyed by Meshtastic node ID
-//! - Concurrency: file access guarded with fs2 file locks (shared for read, exclusive for write)
+//! - Persistence: player records keyed by Meshtastic node ID, kept by the storage backend
+//!   (`<data_dir>/slotmachine/players.json` with the JSON backend)
+//! - Concurrency: the backend serializes writes; jackpot updates are atomic read-modify-write
 //! - Stats: total spins, wins, jackpots, last spin and last jackpot timestamps
 //!
 //! Public commands (handled by `bbs::server`):
@@ -34,10 +35,7 @@ use chrono::{DateTime, Utc, Duration as ChronoDuration};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::io::{Read, Write};
-use fs2::FileExt;
-use std::path::{Path, PathBuf};
+use crate::storage::StorageBackend;
 
 /// Fixed bet cost per spin (coins deducted before spin)
 pub const BET_COINS: u32 = 5;
@@ -77,132 +75,63 @@ pub struct PlayerState {
     pub last_jackpot: Option<DateTime<Utc>>,
 }
 
-/// On‑disk file schema for all players. Stored at `<data_dir>/slotmachine/players.json`.
+/// All players. With the JSON backend this is the schema of `<data_dir>/slotmachine/players.json`.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PlayersFile {
     pub players: HashMap<String, PlayerState>,
 }
 
-fn ensure_dir(path: &Path) -> std::io::Result<()> {
-    if !path.exists() {
-        fs::create_dir_all(path)?;
-    }
-    Ok(())
-}
-
-fn players_file_path(base_dir: &str) -> PathBuf {
-    Path::new(base_dir).join("slotmachine").join("players.json")
-}
-
-fn jackpot_file_path(base_dir: &str) -> PathBuf {
-    Path::new(base_dir).join("slotmachine").join("jackpot.json")
-}
-
-fn load_players(base_dir: &str) -> PlayersFile {
-    let dir = Path::new(base_dir).join("slotmachine");
-    if let Err(e) = ensure_dir(&dir) {
-        log::warn!("slotmachine: unable to ensure dir {:?}: {}", dir, e);
-    }
-    let path = players_file_path(base_dir);
-    if let Ok(mut f) = fs::OpenOptions::new().read(true).open(&path) {
-        // Try shared lock for read
-        let _ = f.lock_shared();
-        let mut s = String::new();
-        if let Err(e) = f.read_to_string(&mut s) {
-            log::warn!("slotmachine: failed reading players.json: {}", e);
-            return PlayersFile::default();
-        }
-        serde_json::from_str(&s).unwrap_or_default()
-    } else {
-        PlayersFile::default()
-    }
-}
-
-fn save_players(base_dir: &str, players: &PlayersFile) {
-    let dir = Path::new(base_dir).join("slotmachine");
-    if let Err(e) = ensure_dir(&dir) {
-        log::warn!("slotmachine: unable to ensure dir {:?}: {}", dir, e);
-        return;
-    }
-    let path = players_file_path(base_dir);
-    match serde_json::to_string_pretty(players) {
-        Ok(data) => {
-            if let Ok(mut f) = fs::OpenOptions::new().create(true).write(true).truncate(true).open(&path) {
-                if f.lock_exclusive().is_ok() {
-                    let _ = f.write_all(data.as_bytes());
-                    let _ = f.flush();
-                    let _ = f.unlock();
-                }
-            }
-        }
-        Err(e) => log::warn!("slotmachine: serialize error: {}", e),
-    }
-}
-
+/// Global progressive jackpot state shared by all players.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
-struct GlobalJackpot {
+pub struct GlobalJackpot {
     /// Count of losing spins across all players since the last jackpot payout
     #[serde(default)]
-    losses: u64,
+    pub losses: u64,
     /// Time of last jackpot payout
     #[serde(default)]
-    last_win: Option<DateTime<Utc>>,
+    pub last_win: Option<DateTime<Utc>>,
     /// Node ID string of last jackpot winner (Meshtastic node id as string)
     #[serde(default)]
-    last_win_node: Option<String>,
+    pub last_win_node: Option<String>,
 }
 
-// load/save helpers removed in favor of atomic read-modify-write functions below
+fn load_players(store: &dyn StorageBackend) -> PlayersFile {
+    store.load_slot_players().unwrap_or_else(|e| {
+        log::warn!("slotmachine: failed reading players: {}", e);
+        PlayersFile::default()
+    })
+}
 
-fn jackpot_payout_and_reset(base_dir: &str, now: DateTime<Utc>, winner: &str) -> u32 {
-    let dir = Path::new(base_dir).join("slotmachine");
-    let _ = ensure_dir(&dir);
-    let path = jackpot_file_path(base_dir);
-    // Open with read/write to allow locking and persistence
-    let mut jackpot = GlobalJackpot::default();
-    if let Ok(mut f) = fs::OpenOptions::new().create(true).read(true).write(true).truncate(false).open(&path) {
-        let _ = f.lock_exclusive();
-        // Read current
-        let mut s = String::new();
-        let _ = f.read_to_string(&mut s);
-        if !s.is_empty() {
-            jackpot = serde_json::from_str(&s).unwrap_or_default();
-        }
-    // Compute payout: 500 base + losses * BET_COINS
-    let mut payout_u64 = 500u64 + jackpot.losses.saturating_mul(BET_COINS as u64);
-        if payout_u64 > u32::MAX as u64 { payout_u64 = u32::MAX as u64; }
-        let payout = payout_u64 as u32;
-    // Reset and save
+fn save_players(store: &dyn StorageBackend, players: &PlayersFile) {
+    if let Err(e) = store.save_slot_players(players) {
+        log::warn!("slotmachine: failed saving players: {}", e);
+    }
+}
+
+fn jackpot_amount(jackpot: &GlobalJackpot) -> u64 {
+    500u64 + jackpot.losses.saturating_mul(BET_COINS as u64)
+}
+
+fn jackpot_payout_and_reset(store: &dyn StorageBackend, now: DateTime<Utc>, winner: &str) -> u32 {
+    // Compute payout (500 base + losses * BET_COINS) and reset in one atomic update
+    let mut payout = 500u32;
+    let res = store.update_jackpot(&mut |jackpot| {
+        payout = jackpot_amount(jackpot).min(u32::MAX as u64) as u32;
         jackpot.losses = 0;
         jackpot.last_win = Some(now);
-    jackpot.last_win_node = Some(winner.to_string());
-        let data = serde_json::to_string_pretty(&jackpot).unwrap_or_else(|_| "{}".to_string());
-        let _ = f.set_len(0);
-        let _ = f.write_all(data.as_bytes());
-        let _ = f.flush();
-        let _ = f.unlock();
-        payout
-    } else {
-        // File open failed, still honor minimum jackpot
-        500
+        jackpot.last_win_node = Some(winner.to_string());
+    });
+    if let Err(e) = res {
+        // Still honor the minimum jackpot
+        log::warn!("slotmachine: jackpot update failed: {}", e);
+        return 500;
     }
+    payout
 }
 
-fn jackpot_record_loss(base_dir: &str) {
-    let dir = Path::new(base_dir).join("slotmachine");
-    let _ = ensure_dir(&dir);
-    let path = jackpot_file_path(base_dir);
-    if let Ok(mut f) = fs::OpenOptions::new().create(true).read(true).write(true).truncate(false).open(&path) {
-        let _ = f.lock_exclusive();
-        let mut s = String::new();
-        let _ = f.read_to_string(&mut s);
-        let mut jackpot: GlobalJackpot = if s.is_empty() { GlobalJackpot::default() } else { serde_json::from_str(&s).unwrap_or_default() };
-        jackpot.losses = jackpot.losses.saturating_add(1);
-        let data = serde_json::to_string_pretty(&jackpot).unwrap_or_else(|_| "{}".to_string());
-        let _ = f.set_len(0);
-        let _ = f.write_all(data.as_bytes());
-        let _ = f.flush();
-        let _ = f.unlock();
+fn jackpot_record_loss(store: &dyn StorageBackend) {
+    if let Err(e) = store.update_jackpot(&mut |jackpot| jackpot.losses = jackpot.losses.saturating_add(1)) {
+        log::warn!("slotmachine: jackpot update failed: {}", e);
     }
 }
 
@@ -216,24 +145,11 @@ pub struct JackpotSummary {
     pub last_win_node: Option<String>,
 }
 
-/// Read the current global jackpot summary. This uses a shared lock for consistency.
-pub fn get_jackpot_summary(base_dir: &str) -> JackpotSummary {
-    let dir = Path::new(base_dir).join("slotmachine");
-    let _ = ensure_dir(&dir);
-    let path = jackpot_file_path(base_dir);
-    let mut jackpot: GlobalJackpot = GlobalJackpot::default();
-    if let Ok(mut f) = fs::OpenOptions::new().read(true).open(&path) {
-        let _ = f.lock_shared();
-        let mut s = String::new();
-        let _ = f.read_to_string(&mut s);
-        if !s.is_empty() {
-            jackpot = serde_json::from_str(&s).unwrap_or_default();
-        }
-        let _ = f.unlock();
-    }
-    let amt = 500u64 + jackpot.losses.saturating_mul(BET_COINS as u64);
+/// Read the current global jackpot summary.
+pub fn get_jackpot_summary(store: &dyn StorageBackend) -> JackpotSummary {
+    let jackpot = store.load_jackpot().unwrap_or_default();
     JackpotSummary {
-        amount: amt,
+        amount: jackpot_amount(&jackpot),
         last_win_date: jackpot.last_win.map(|t| t.date_naive()),
         last_win_node: jackpot.last_win_node,
     }
@@ -284,14 +200,14 @@ fn evaluate(r1: &str, r2: &str, r3: &str) -> (u32, String) {
 /// Perform a single spin for `player_id`.
 ///
 /// Contract:
-/// - Input: `base_dir` is the configured storage base dir; `player_id` is a stable node ID
-/// - Side effects: updates `<base_dir>/slotmachine/players.json` with coin balance and stats
+/// - Input: `store` is the BBS storage backend; `player_id` is a stable node ID
+/// - Side effects: updates the stored player record with coin balance and stats
 /// - Behavior: deducts [`BET_COINS`], spins reels, applies payout, updates stats
 /// - Refill: if balance is 0 and `REFILL_HOURS` elapsed since `last_reset`, grants [`DAILY_GRANT`]
 /// - Returns: `(SpinOutcome, balance_after)`; if unable to afford, `r1=r2=r3="⛔"` and no changes
-pub fn perform_spin(base_dir: &str, player_id: &str) -> (SpinOutcome, u32) {
+pub fn perform_spin(store: &dyn StorageBackend, player_id: &str) -> (SpinOutcome, u32) {
     // Load players
-    let mut file = load_players(base_dir);
+    let mut file = load_players(store);
     let now = Utc::now();
 
     // Compute outcome within a limited scope to avoid borrow conflicts
@@ -338,14 +254,14 @@ pub fn perform_spin(base_dir: &str, player_id: &str) -> (SpinOutcome, u32) {
             let winnings: u32;
             if mult == 100 {
                 // Jackpot payout: number of losses (coins) with a floor of 500 coins, atomically reset
-                winnings = jackpot_payout_and_reset(base_dir, now, player_id);
+                winnings = jackpot_payout_and_reset(store, now, player_id);
                 entry.coins = entry.coins.saturating_add(winnings);
             } else {
                 winnings = BET_COINS.saturating_mul(mult);
                 entry.coins = entry.coins.saturating_add(winnings);
                 // Accumulate pot on losses only (multiplier == 0)
                 if mult == 0 {
-                    jackpot_record_loss(base_dir);
+                    jackpot_record_loss(store);
                 }
             }
             // Stats
@@ -365,7 +281,7 @@ pub fn perform_spin(base_dir: &str, player_id: &str) -> (SpinOutcome, u32) {
     };
 
     // Persist after mutation
-    save_players(base_dir, &file);
+    save_players(store, &file);
     // Jackpot state already updated atomically above if needed
 
     (outcome, balance_after)
@@ -373,8 +289,8 @@ pub fn perform_spin(base_dir: &str, player_id: &str) -> (SpinOutcome, u32) {
 
 /// If `player_id` is out of coins, return `(hours, minutes)` until the next daily refill.
 /// Returns `None` if the player has coins or does not exist.
-pub fn next_refill_eta(base_dir: &str, player_id: &str) -> Option<(i64, i64)> {
-    let file = load_players(base_dir);
+pub fn next_refill_eta(store: &dyn StorageBackend, player_id: &str) -> Option<(i64, i64)> {
+    let file = load_players(store);
     let entry = file.players.get(player_id)?;
     if entry.coins > 0 { return None; }
     let now = Utc::now();
@@ -394,8 +310,8 @@ pub struct PlayerSummary {
 }
 
 /// Load and return the `PlayerSummary` for `player_id`, or `None` if no record exists.
-pub fn get_player_summary(base_dir: &str, player_id: &str) -> Option<PlayerSummary> {
-    let file = load_players(base_dir);
+pub fn get_player_summary(store: &dyn StorageBackend, player_id: &str) -> Option<PlayerSummary> {
+    let file = load_players(store);
     let p = file.players.get(player_id)?;
     Some(PlayerSummary {
         coins: p.coins,
@@ -411,16 +327,8 @@ pub fn get_player_summary(base_dir: &str, player_id: &str) -> Option<PlayerSumma
 mod tests {
     use super::*;
     use chrono::Duration;
+    use crate::storage::JsonBackend;
     use tempfile::tempdir;
-    use std::fs;
-
-    fn write_players(base: &str, players: &PlayersFile) {
-        let dir = Path::new(base).join("slotmachine");
-        let _ = fs::create_dir_all(&dir);
-        let path = dir.join("players.json");
-        let data = serde_json::to_string_pretty(players).unwrap();
-        std::fs::write(path, data).unwrap();
-    }
 
     #[test]
     fn out_of_coins_blocks_spin() {
@@ -431,8 +339,9 @@ mod tests {
             "node1".to_string(),
             PlayerState { coins: 0, last_reset: Utc::now(), total_spins: 0, total_wins: 0, jackpots: 0, last_spin: None, last_jackpot: None }
         );
-        write_players(base, &file);
-        let (out, bal) = perform_spin(base, "node1");
+        let store = JsonBackend::open(base, None).unwrap();
+        store.save_slot_players(&file).unwrap();
+        let (out, bal) = perform_spin(&store, "node1");
         assert_eq!(out.r1, "⛔");
         assert_eq!(bal, 0);
         assert!(out.description.contains("Out of coins"));
@@ -447,8 +356,9 @@ mod tests {
             "node2".to_string(),
             PlayerState { coins: 0, last_reset: Utc::now() - Duration::hours(REFILL_HOURS + 1), total_spins: 0, total_wins: 0, jackpots: 0, last_spin: None, last_jackpot: None }
         );
-        write_players(base, &file);
-        let (_out, bal) = perform_spin(base, "node2");
+        let store = JsonBackend::open(base, None).unwrap();
+        store.save_slot_players(&file).unwrap();
+        let (_out, bal) = perform_spin(&store, "node2");
         // After refill and one spin, balance should be at least DAILY_GRANT - BET
         assert!(bal >= DAILY_GRANT - BET_COINS);
     // Upper bound for fresh state: jackpot minimum equals BET*100 (500 coins). Pot can be larger over time.
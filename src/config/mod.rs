@@ -8,8 +8,9 @@
 //! - **Structured Configuration**: Type-safe configuration with serde serialization
 //! - **Validation**: Comprehensive validation of all configuration values
 //! - **Defaults**: Sensible default values for all configuration options
-//! - **Hot Reloading**: Support for runtime configuration updates
-//! - **Environment Integration**: Integration with environment variables and CLI args
+//! - **Hot Reloading**: `SIGHUP` or the sysop `RELOAD` command re-reads the file and applies
+//!   what is safe at runtime (see [`Config::reload_plan`] and [`RESTART_REQUIRED`])
+//! - **Environment Integration**: `MESHBBS_*` environment variables and `--set` override any setting
 //!
 //! ## Configuration Structure
 //!
@@ -60,12 +61,18 @@
 //! baud_rate = 115200
 //! channel = 0
 //!
+//! # Optional: accept public commands on several channels with per-channel rules
+//! [[meshtastic.public_channels]]
+//! index = 0
+//! commands = ["HELP", "LOGIN"]
+//!
 //! # Note: message topics are initialized into data/topics.json during `meshbbs init`
 //! ```
 //!
 //! ## Validation and Security
 //!
-//! - **Input Validation**: All configuration values are validated on load
+//! - **Input Validation**: [`Config::check`] reports errors and warnings; startup refuses to
+//!   run with errors and `meshbbs config validate` exits non-zero on them
 //! - **Type Safety**: Strong typing prevents configuration errors
 //! - **Secure Defaults**: Default values are chosen for security and stability
 //! - **Sanitization**: String values are sanitized to prevent injection attacks
@@ -73,12 +80,21 @@
 //! ## Environment Integration
 //!
 //! Configuration values can be overridden via environment variables and CLI arguments,
-//! following a clear precedence order: CLI args > Environment > Config file > Defaults
+//! following a clear precedence order: CLI args > Environment > Config file > Defaults.
+//! [`ConfigLoader`] implements the layering: `MESHBBS_STORAGE__DATA_DIR=/srv/bbs` or
+//! `--set storage.data_dir=/srv/bbs` override `storage.data_dir`, and `meshbbs config show
+//! --effective` prints every setting with its source.
 
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::fs;
+use crate::storage::BackendKind;
+
+mod overrides;
+mod validate;
+pub use overrides::{ConfigLoader, LoadedConfig, ValueSource, ENV_PREFIX};
+pub use validate::{Finding, Severity, ValidationReport};
 
 /// Main configuration structure
 
@@ -104,6 +120,10 @@ pub struct Config {
     pub message_topics: HashMap<String, MessageTopicConfig>,
     pub logging: LoggingConfig,
     pub security: Option<SecurityConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web: Option<WebConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,12 +158,41 @@ pub struct MeshtasticConfig {
     /// Interval (ms) for periodic scheduler stats logging (0 disables periodic stats logs).
     #[serde(default)]
     pub scheduler_stats_interval_ms: Option<u64>,
+    /// Channels on which public (`^`) commands are accepted, with per-channel command rules.
+    /// When unset, public commands are only accepted on `channel` and all commands are enabled.
+    #[serde(default)]
+    pub public_channels: Option<Vec<PublicChannelConfig>>,
+    /// Store-and-forward: hours an undelivered notice is held for its node (default 72)
+    #[serde(default)]
+    pub store_forward_ttl_hours: Option<u64>,
+    /// Store-and-forward: most notices held per node; the oldest are dropped (default 10)
+    #[serde(default)]
+    pub store_forward_max_per_node: Option<usize>,
+}
+
+/// Public command rules for a single channel index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublicChannelConfig {
+    /// Meshtastic channel index (0 = primary)
+    pub index: u32,
+    /// Public commands enabled on this channel, e.g. `["HELP", "LOGIN"]`. Empty enables all.
+    #[serde(default)]
+    pub commands: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
     pub data_dir: String,
     pub max_message_size: usize,
+    /// Persistence backend: `json` (default) or `sqlite`
+    #[serde(default)]
+    pub backend: BackendKind,
+    /// Minutes between topic retention passes (default 60, 0 disables them)
+    #[serde(default)]
+    pub retention_interval_minutes: Option<u64>,
+    /// Write expired threads to `data/archive/*.jsonl.gz` before removing them
+    #[serde(default)]
+    pub archive_expired: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,10 +221,141 @@ pub struct Argon2Config {
     pub parallelism: Option<u32>,
 }
 
+impl Argon2Config {
+    /// Password hashing parameters; unset values fall back to the Argon2 defaults.
+    pub fn params(&self) -> Result<argon2::Params> {
+        let defaults = argon2::Params::DEFAULT;
+        argon2::Params::new(
+            self.memory_kib.unwrap_or(defaults.m_cost()),
+            self.time_cost.unwrap_or(defaults.t_cost()),
+            self.parallelism.unwrap_or(defaults.p_cost()),
+            None,
+        ).map_err(|e| anyhow!("invalid Argon2 parameters: {}", e))
+    }
+}
+
+/// At-rest encryption of the data directory. Set exactly one key source.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EncryptionConfig {
+    /// Operator passphrase the data key is derived from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+    /// File whose contents the data key is derived from (keep it outside `data_dir`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keyfile: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SecurityConfig {
     #[serde(default)]
     pub argon2: Option<Argon2Config>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<EncryptionConfig>,
+    #[serde(default)]
+    pub lockout: LockoutConfig,
+    /// `LOGIN <user>` without the password from one of the user's trusted nodes (see
+    /// `TRUST`). Never applies to the sysop; node IDs are not authenticated on the mesh.
+    #[serde(default)]
+    pub trusted_node_login: bool,
+    #[serde(default)]
+    pub pki: PkiConfig,
+}
+
+/// Meshtastic public-key (PKI) direct messages, firmware 2.5 and later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PkiConfig {
+    /// Ask the radio to PKI-encrypt DMs to nodes whose public key is known, pinned to that
+    /// key. Older firmware ignores the request.
+    #[serde(default = "default_pki_encrypt_dms")]
+    pub encrypt_dms: bool,
+    /// Refuse `LOGIN`, `REGISTER`, `CHPASS`, `SETPASS` and `RESET` in DMs that arrived
+    /// encrypted with the channel key only, so passwords never cross the mesh that way.
+    #[serde(default)]
+    pub require_for_passwords: bool,
+}
+
+fn default_pki_encrypt_dms() -> bool { true }
+
+impl Default for PkiConfig {
+    fn default() -> Self {
+        PkiConfig { encrypt_dms: default_pki_encrypt_dms(), require_for_passwords: false }
+    }
+}
+
+/// Failed-login lockout. After `max_attempts` failed logins an account, and separately the
+/// node they came from, is locked for `lockout_minutes`; each further lockout doubles that,
+/// up to `max_lockout_minutes`. The count starts over once `reset_after_minutes` pass
+/// without a failure (or after a lockout ends).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockoutConfig {
+    #[serde(default = "default_lockout_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_lockout_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_lockout_minutes")]
+    pub lockout_minutes: u64,
+    #[serde(default = "default_max_lockout_minutes")]
+    pub max_lockout_minutes: u64,
+    #[serde(default = "default_lockout_reset_after_minutes")]
+    pub reset_after_minutes: u64,
+}
+
+fn default_lockout_enabled() -> bool { true }
+fn default_lockout_max_attempts() -> u32 { 5 }
+fn default_lockout_minutes() -> u64 { 5 }
+fn default_max_lockout_minutes() -> u64 { 1440 }
+fn default_lockout_reset_after_minutes() -> u64 { 60 }
+
+impl Default for LockoutConfig {
+    fn default() -> Self {
+        LockoutConfig {
+            enabled: default_lockout_enabled(),
+            max_attempts: default_lockout_max_attempts(),
+            lockout_minutes: default_lockout_minutes(),
+            max_lockout_minutes: default_max_lockout_minutes(),
+            reset_after_minutes: default_lockout_reset_after_minutes(),
+        }
+    }
+}
+
+/// Embedded HTTP server (requires the `web` feature).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Listen address. Keep it on localhost unless something else guards access.
+    #[serde(default = "default_web_bind")]
+    pub bind: String,
+}
+
+fn default_web_bind() -> String { "127.0.0.1:9090".to_string() }
+
+impl Default for WebConfig {
+    fn default() -> Self { WebConfig { enabled: false, bind: default_web_bind() } }
+}
+
+/// Scheduled backups written by the running server (same archive as `meshbbs backup`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Directory the `meshbbs-<time>.tar.gz` archives go to (keep it outside `data_dir`)
+    #[serde(default = "default_backup_dir")]
+    pub dir: String,
+    /// Hours between backups; 0 disables them
+    #[serde(default = "default_backup_interval_hours")]
+    pub interval_hours: u64,
+    /// Number of archives kept; older ones are deleted after each backup
+    #[serde(default = "default_backup_keep")]
+    pub keep: usize,
+}
+
+fn default_backup_dir() -> String { "./backups".to_string() }
+fn default_backup_interval_hours() -> u64 { 24 }
+fn default_backup_keep() -> usize { 7 }
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        BackupConfig { dir: default_backup_dir(), interval_hours: default_backup_interval_hours(), keep: default_backup_keep() }
+    }
 }
 
 impl Config {
@@ -190,6 +370,67 @@ impl Config {
         Ok(config)
     }
 
+    /// Reject settings the server cannot run with (the errors of [`check`](Self::check)).
+    pub fn validate(&self) -> Result<()> {
+        let report = self.check();
+        if !report.has_errors() { return Ok(()); }
+        let errors: Vec<String> = report.errors().map(|f| format!("{}: {}", f.setting, f.message)).collect();
+        Err(anyhow!("Invalid configuration: {}", errors.join("; ")))
+    }
+
+    /// Dotted paths (e.g. `bbs.max_users`) of the settings that differ from `other`.
+    /// Lists are compared as a whole.
+    pub fn changed_settings(&self, other: &Config) -> Result<Vec<String>> {
+        fn walk(path: &str, a: &serde_json::Value, b: &serde_json::Value, out: &mut Vec<String>) {
+            match (a, b) {
+                (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+                    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+                    keys.sort();
+                    keys.dedup();
+                    for key in keys {
+                        let child = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+                        let null = serde_json::Value::Null;
+                        walk(&child, a.get(key).unwrap_or(&null), b.get(key).unwrap_or(&null), out);
+                    }
+                }
+                (a, b) if a != b => out.push(path.to_string()),
+                _ => {}
+            }
+        }
+        let mut out = Vec::new();
+        walk("", &serde_json::to_value(self)?, &serde_json::to_value(other)?, &mut out);
+        Ok(out)
+    }
+
+    /// Work out what a reload from `new` can change on a running server.
+    ///
+    /// Returns the configuration to continue with — `new`, except that settings listed in
+    /// [`RESTART_REQUIRED`] keep their current values — and a report of what changed.
+    pub fn reload_plan(&self, new: Config) -> Result<(Config, ReloadReport)> {
+        let mut report = ReloadReport::default();
+        for setting in self.changed_settings(&new)? {
+            if RESTART_REQUIRED.iter().any(|r| setting == *r || setting.starts_with(&format!("{r}."))) {
+                report.restart_required.push(setting);
+            } else {
+                report.applied.push(setting);
+            }
+        }
+        let mut next = serde_json::to_value(&new)?;
+        let current = serde_json::to_value(self)?;
+        for path in RESTART_REQUIRED {
+            let (section, key) = path.split_once('.').map_or((*path, None), |(s, k)| (s, Some(k)));
+            match key {
+                None => { next[section] = current[section].clone(); }
+                Some(key) => {
+                    if current[section].is_object() && next[section].is_object() {
+                        next[section][key] = current[section][key].clone();
+                    }
+                }
+            }
+        }
+        Ok((serde_json::from_value(next)?, report))
+    }
+
     /// Create a default configuration file
     pub async fn create_default(path: &str) -> Result<()> {
         let config = Config::default();
@@ -203,6 +444,52 @@ impl Config {
     }
 }
 
+/// Settings that are only read at startup (open files, sockets, storage, sysop identity).
+pub const RESTART_REQUIRED: &[&str] = &[
+    "bbs.sysop",
+    "bbs.sysop_password_hash",
+    "meshtastic.port",
+    "meshtastic.baud_rate",
+    "storage.data_dir",
+    "storage.backend",
+    "logging.file",
+    "logging.security_file",
+    "security",
+    "web",
+];
+
+/// Outcome of a configuration reload, as dotted setting paths.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    /// Changed and now in effect
+    pub applied: Vec<String>,
+    /// Changed in the file but ignored until the next restart
+    pub restart_required: Vec<String>,
+}
+
+impl ReloadReport {
+    /// One-line summary for logs and the `RELOAD` reply.
+    pub fn summary(&self) -> String {
+        let mut out = if self.applied.is_empty() {
+            "no runtime changes".to_string()
+        } else {
+            format!("applied {}", self.applied.join(", "))
+        };
+        if !self.restart_required.is_empty() {
+            out.push_str(&format!("; restart needed for {}", self.restart_required.join(", ")));
+        }
+        out
+    }
+    /// Counts instead of the applied list, to fit a radio reply.
+    pub fn brief(&self) -> String {
+        let mut out = format!("{} setting(s) applied", self.applied.len());
+        if !self.restart_required.is_empty() {
+            out.push_str(&format!("; restart needed: {}", self.restart_required.join(", ")));
+        }
+        out
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         let mut message_topics = HashMap::new();
@@ -252,10 +539,16 @@ impl Default for Config {
                 scheduler_max_queue: Some(512),
                 scheduler_aging_threshold_ms: Some(5000),
                 scheduler_stats_interval_ms: Some(10000),
+                public_channels: None,
+                store_forward_ttl_hours: Some(72),
+                store_forward_max_per_node: Some(10),
             },
             storage: StorageConfig {
                 data_dir: "./data".to_string(),
                 max_message_size: 230,
+                backend: BackendKind::Json,
+                retention_interval_minutes: Some(60),
+                archive_expired: false,
             },
             message_topics,
             logging: LoggingConfig {
@@ -264,6 +557,8 @@ impl Default for Config {
                 security_file: Some("meshbbs-security.log".to_string()),
             },
             security: Some(SecurityConfig::default()),
+            web: None,
+            backup: None,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,273 @@
+//! Layered configuration: defaults < config file < `MESHBBS_*` environment < `--set`.
+//!
+//! Every field of [`Config`] can be overridden. Environment variables name the setting path
+//! in upper case with `__` between levels, so `storage.data_dir` is `MESHBBS_STORAGE__DATA_DIR`
+//! and `security.encryption.keyfile` is `MESHBBS_SECURITY__ENCRYPTION__KEYFILE`. The CLI
+//! takes the dotted path: `--set meshtastic.port=tcp://10.0.0.5`.
+//!
+//! Values are read as TOML (`5`, `true`, `[4, 8, 16]`, `[{ index = 2 }]`), except that
+//! settings which are strings always take the raw text. An override naming a setting that
+//! does not exist is an error. Settings missing from the file get the same serde defaults as
+//! a plain parse, so optional ones (`logging.file`, `[web]`, ...) stay unset; only settings
+//! the file cannot do without are filled from [`Config::default`].
+
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use toml::Value;
+
+use super::Config;
+
+/// Prefix of environment overrides.
+pub const ENV_PREFIX: &str = "MESHBBS_";
+
+/// Settings printed as `<redacted>` by [`LoadedConfig::render_effective`].
+const SECRET_KEYS: &[&str] = &["passphrase", "sysop_password_hash"];
+
+/// Where an effective setting came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSource {
+    Default,
+    File,
+    /// Environment variable name
+    Env(String),
+    /// `--set` on the command line
+    Cli,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Default => f.write_str("default"),
+            ValueSource::File => f.write_str("file"),
+            ValueSource::Env(var) => write!(f, "env {var}"),
+            ValueSource::Cli => f.write_str("--set"),
+        }
+    }
+}
+
+/// Loads a [`Config`] from its file plus overrides; cheap to clone and re-run on reload.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    path: String,
+    env: Vec<(String, String)>,
+    sets: Vec<String>,
+}
+
+/// A loaded configuration and the source of each setting.
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: Config,
+    pub path: String,
+    sources: BTreeMap<String, ValueSource>,
+}
+
+impl ConfigLoader {
+    pub fn new(path: impl Into<String>) -> Self {
+        ConfigLoader { path: path.into(), ..Default::default() }
+    }
+
+    /// Use the `MESHBBS_*` entries of `vars` as environment overrides.
+    pub fn env(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env = vars.into_iter().filter(|(k, _)| k.starts_with(ENV_PREFIX)).collect();
+        self.env.sort();
+        self
+    }
+
+    /// Environment overrides from this process (non-UTF-8 variables are skipped).
+    pub fn process_env(self) -> Self {
+        let vars = std::env::vars_os().filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)));
+        self.env(vars)
+    }
+
+    /// `section.key=value` overrides from the command line, applied last.
+    pub fn sets(mut self, sets: impl IntoIterator<Item = String>) -> Self {
+        self.sets = sets.into_iter().collect();
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub async fn load(&self) -> Result<LoadedConfig> {
+        let content = tokio::fs::read_to_string(&self.path).await
+            .map_err(|e| anyhow!("Failed to read config file {}: {}", self.path, e))?;
+        self.load_str(&content)
+    }
+
+    /// [`load`](Self::load) with the file contents already read.
+    pub fn load_str(&self, content: &str) -> Result<LoadedConfig> {
+        let mut sources = BTreeMap::new();
+        let defaults = Value::try_from(Config::default())?;
+        let mut merged = required_defaults(&defaults);
+        record(&mut sources, "", &merged, &ValueSource::Default);
+
+        let file: Value = toml::from_str(content)
+            .map_err(|e| anyhow!("Failed to parse config file {}: {}", self.path, e))?;
+        record(&mut sources, "", &file, &ValueSource::File);
+        merge(&mut merged, file);
+
+        let mut overrides = Vec::new();
+        for (var, raw) in &self.env {
+            let path = env_path(var).ok_or_else(|| anyhow!("Invalid override variable {var}"))?;
+            overrides.push((path, raw.as_str(), ValueSource::Env(var.clone())));
+        }
+        for set in &self.sets {
+            let (path, raw) = set.split_once('=')
+                .ok_or_else(|| anyhow!("Invalid --set '{set}': expected section.key=value"))?;
+            overrides.push((path.trim().to_string(), raw, ValueSource::Cli));
+        }
+        for (path, raw, source) in &overrides {
+            set_path(&mut merged, path, raw, lookup(&defaults, path)).map_err(|e| anyhow!("{source}: {e}"))?;
+            sources.retain(|p, _| p != path && !p.starts_with(&format!("{path}.")));
+            sources.insert(path.clone(), source.clone());
+        }
+
+        let config: Config = merged.try_into()
+            .map_err(|e| anyhow!("Invalid configuration in {} after overrides: {}", self.path, e))?;
+        // Overrides of settings that do not exist would otherwise be dropped silently
+        let effective = Value::try_from(&config)?;
+        for (path, _, source) in &overrides {
+            if lookup(&effective, path).is_none() {
+                return Err(anyhow!("{source}: unknown setting '{path}'"));
+            }
+        }
+        Ok(LoadedConfig { config, path: self.path.clone(), sources })
+    }
+}
+
+impl LoadedConfig {
+    /// Source of a setting, e.g. `bbs.max_users`; lists and tables set as a whole report the
+    /// source of the whole value.
+    pub fn source_of(&self, path: &str) -> ValueSource {
+        let mut key = path;
+        loop {
+            if let Some(source) = self.sources.get(key) {
+                return source.clone();
+            }
+            match key.rsplit_once('.') {
+                Some((parent, _)) => key = parent,
+                None => return ValueSource::Default,
+            }
+        }
+    }
+
+    /// `path = value  # source` for every effective setting, with secrets redacted.
+    pub fn render_effective(&self) -> Result<String> {
+        let mut leaves = Vec::new();
+        flatten("", &Value::try_from(&self.config)?, &mut leaves);
+        let mut out = String::new();
+        for (path, value) in leaves {
+            let secret = SECRET_KEYS.iter().any(|k| path == *k || path.ends_with(&format!(".{k}")));
+            let shown = if secret { "\"<redacted>\"".to_string() } else { value.to_string() };
+            let source = match self.source_of(&path) {
+                ValueSource::File => format!("file {}", self.path),
+                other => other.to_string(),
+            };
+            out.push_str(&format!("{path} = {shown}  # {source}\n"));
+        }
+        Ok(out)
+    }
+}
+
+/// `MESHBBS_STORAGE__DATA_DIR` -> `storage.data_dir`
+fn env_path(var: &str) -> Option<String> {
+    let rest = var.strip_prefix(ENV_PREFIX)?;
+    let parts: Vec<String> = rest.split("__").map(|p| p.to_ascii_lowercase()).collect();
+    if parts.iter().any(|p| p.is_empty()) { return None; }
+    Some(parts.join("."))
+}
+
+/// Override value: the raw text for string settings, otherwise parsed as TOML when possible.
+fn parse_value(raw: &str, current: Option<&Value>) -> Value {
+    if let Some(Value::String(_)) = current {
+        return Value::String(raw.to_string());
+    }
+    #[derive(serde::Deserialize)]
+    struct Wrapper { v: Value }
+    toml::from_str::<Wrapper>(&format!("v = {raw}")).map(|w| w.v).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Set `path` to `raw`; `hint` is the default value, for the type of a setting not yet present.
+fn set_path(root: &mut Value, path: &str, raw: &str, hint: Option<&Value>) -> Result<()> {
+    let keys: Vec<&str> = path.split('.').collect();
+    if keys.iter().any(|k| k.is_empty()) { return Err(anyhow!("invalid setting '{path}'")); }
+    let (last, parents) = keys.split_last().expect("split yields at least one key");
+    let mut table = root.as_table_mut().expect("config root is a table");
+    for (i, key) in parents.iter().enumerate() {
+        let entry = table.entry(key.to_string()).or_insert_with(|| Value::Table(Default::default()));
+        table = entry.as_table_mut()
+            .ok_or_else(|| anyhow!("'{}' is not a section", keys[..=i].join(".")))?;
+    }
+    let value = parse_value(raw, table.get(*last).or(hint));
+    table.insert(last.to_string(), value);
+    Ok(())
+}
+
+/// `defaults` reduced to the settings [`Config`] cannot be parsed without. Everything else is
+/// dropped so that serde's own default applies, as it does for a file parsed on its own.
+fn required_defaults(defaults: &Value) -> Value {
+    let mut base = defaults.clone();
+    drop_optional(&mut base, &mut Vec::new());
+    base
+}
+
+fn drop_optional(root: &mut Value, path: &mut Vec<String>) {
+    let Some(keys) = table_at(root, path).map(|t| t.keys().cloned().collect::<Vec<_>>()) else { return };
+    for key in keys {
+        let mut trial = root.clone();
+        let removed = table_at(&mut trial, path).and_then(|t| t.remove(&key));
+        if trial.clone().try_into::<Config>().is_ok() {
+            *root = trial;
+        } else if matches!(removed, Some(Value::Table(_))) {
+            path.push(key);
+            drop_optional(root, path);
+            path.pop();
+        }
+    }
+}
+
+fn table_at<'a>(root: &'a mut Value, path: &[String]) -> Option<&'a mut toml::Table> {
+    path.iter().try_fold(root, |v, key| v.as_table_mut()?.get_mut(key))?.as_table_mut()
+}
+
+fn lookup<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(root, |v, key| v.as_table()?.get(key))
+}
+
+/// Deep merge: tables merge key by key, anything else is replaced.
+fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Table(base), Value::Table(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => { base.insert(key, value); }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// Leaf settings as `(dotted path, value)`; lists count as one value.
+fn flatten(prefix: &str, value: &Value, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Table(table) => {
+            for (key, value) in table {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+                flatten(&path, value, out);
+            }
+        }
+        other => out.push((prefix.to_string(), other.clone())),
+    }
+}
+
+fn record(sources: &mut BTreeMap<String, ValueSource>, prefix: &str, value: &Value, source: &ValueSource) {
+    let mut leaves = Vec::new();
+    flatten(prefix, value, &mut leaves);
+    for (path, _) in leaves {
+        sources.insert(path, source.clone());
+    }
+}
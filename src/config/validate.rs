@@ -0,0 +1,235 @@
+//! Semantic checks of a parsed [`Config`].
+//!
+//! Parsing only proves the file has the right shape. [`Config::check`] looks at values and
+//! combinations: errors are settings the server cannot run with, warnings are settings it
+//! runs with but corrects or ignores at runtime (e.g. a send gap below the 2s floor).
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::Config;
+
+/// Largest payload a Meshtastic text frame carries.
+const MAX_FRAME_BYTES: usize = 230;
+/// Hard floor the writer enforces between text sends.
+const MIN_SEND_GAP_MS: u64 = 2000;
+/// Shortest passphrase or keyfile the data key is derived from.
+const MIN_SECRET_BYTES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One problem with one setting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Dotted setting path, e.g. `meshtastic.min_send_gap_ms`
+    pub setting: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity { Severity::Error => "error", Severity::Warning => "warning" };
+        write!(f, "{label}: {}: {}", self.setting, self.message)
+    }
+}
+
+/// Result of [`Config::check`], findings in the order the settings appear in the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|f| f.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|f| f.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// e.g. `2 error(s), 1 warning(s)`
+    pub fn summary(&self) -> String {
+        format!("{} error(s), {} warning(s)", self.errors().count(), self.warnings().count())
+    }
+
+    fn error(&mut self, setting: &str, message: impl Into<String>) {
+        self.findings.push(Finding { severity: Severity::Error, setting: setting.to_string(), message: message.into() });
+    }
+
+    fn warning(&mut self, setting: &str, message: impl Into<String>) {
+        self.findings.push(Finding { severity: Severity::Warning, setting: setting.to_string(), message: message.into() });
+    }
+}
+
+impl Config {
+    /// Check every setting; see [`validate`](Self::validate) for the pass/fail form.
+    pub fn check(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.check_bbs(&mut report);
+        self.check_meshtastic(&mut report);
+        self.check_storage(&mut report);
+        if crate::logutil::parse_level(&self.logging.level).is_none() {
+            report.error("logging.level", format!("'{}' is not one of off, error, warn, info, debug, trace", self.logging.level));
+        }
+        self.check_security(&mut report);
+        if let Some(web) = &self.web {
+            if !valid_bind(&web.bind) {
+                report.error("web.bind", format!("'{}' is not a host:port address", web.bind));
+            }
+            if web.enabled && self.bbs.sysop_password_hash.is_none() {
+                report.warning("web.enabled", "the admin dashboard answers 503 until bbs.sysop_password_hash is set");
+            }
+            #[cfg(not(feature = "web"))]
+            if web.enabled {
+                report.warning("web.enabled", "meshbbs was built without the 'web' feature; /metrics and the dashboard are not served");
+            }
+        }
+        self.check_backup(&mut report);
+        report
+    }
+
+    fn check_bbs(&self, report: &mut ValidationReport) {
+        let bbs = &self.bbs;
+        if bbs.name.trim().is_empty() {
+            report.error("bbs.name", "must not be empty");
+        }
+        if let Err(e) = crate::validation::validate_sysop_name(&bbs.sysop) {
+            report.error("bbs.sysop", format!("'{}' is invalid: {}", bbs.sysop, e));
+        }
+        if bbs.max_users == 0 {
+            report.error("bbs.max_users", "must be at least 1");
+        }
+        if let Some(hash) = &bbs.sysop_password_hash {
+            if let Err(e) = password_hash::PasswordHash::new(hash) {
+                report.error("bbs.sysop_password_hash", format!("not a password hash ({e}); set it with `meshbbs sysop-passwd`"));
+            }
+        }
+    }
+
+    fn check_meshtastic(&self, report: &mut ValidationReport) {
+        let m = &self.meshtastic;
+        if m.port.trim().is_empty() {
+            report.warning("meshtastic.port", "empty; the server starts without a radio unless --port is given");
+        }
+        let min_gap = m.min_send_gap_ms.unwrap_or(MIN_SEND_GAP_MS);
+        if min_gap < MIN_SEND_GAP_MS {
+            report.warning("meshtastic.min_send_gap_ms", format!("{min_gap}ms is below the {MIN_SEND_GAP_MS}ms minimum and is raised to it"));
+        }
+        match &m.dm_resend_backoff_seconds {
+            Some(list) if list.is_empty() => {
+                report.warning("meshtastic.dm_resend_backoff_seconds", "empty; the default [4, 8, 16] is used");
+            }
+            Some(list) if list.contains(&0) => {
+                report.warning("meshtastic.dm_resend_backoff_seconds", "zero entries are ignored");
+            }
+            _ => {}
+        }
+        let post_dm_gap = m.post_dm_broadcast_gap_ms.unwrap_or(1200);
+        let help_delay = m.help_broadcast_delay_ms.unwrap_or(3500);
+        if help_delay < post_dm_gap {
+            report.warning(
+                "meshtastic.help_broadcast_delay_ms",
+                format!("{help_delay}ms is below post_dm_broadcast_gap_ms ({post_dm_gap}ms); the HELP broadcast waits for the gap instead"),
+            );
+        }
+        if m.scheduler_max_queue == Some(0) {
+            report.error("meshtastic.scheduler_max_queue", "must be at least 1");
+        }
+        if m.store_forward_max_per_node == Some(0) {
+            report.warning("meshtastic.store_forward_max_per_node", "0 holds no notices for offline nodes");
+        }
+        if let Some(channels) = &m.public_channels {
+            let mut seen = HashSet::new();
+            for (i, ch) in channels.iter().enumerate() {
+                if !seen.insert(ch.index) {
+                    report.error(&format!("meshtastic.public_channels[{i}].index"), format!("channel {} is listed more than once", ch.index));
+                }
+                for name in ch.commands.iter().filter(|c| !crate::bbs::public::is_known_command_name(c)) {
+                    report.error(
+                        &format!("meshtastic.public_channels[{i}].commands"),
+                        format!("unknown public command '{}' (known: {})", name, crate::metrics::PUBLIC_COMMANDS.join(", ")),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_storage(&self, report: &mut ValidationReport) {
+        let storage = &self.storage;
+        if storage.data_dir.trim().is_empty() {
+            report.error("storage.data_dir", "must not be empty");
+        }
+        if storage.max_message_size == 0 {
+            report.error("storage.max_message_size", "must be at least 1");
+        } else if storage.max_message_size > MAX_FRAME_BYTES {
+            report.error("storage.max_message_size", format!("{} exceeds the {MAX_FRAME_BYTES}-byte radio frame", storage.max_message_size));
+        }
+        #[cfg(not(feature = "sqlite"))]
+        if storage.backend == crate::storage::BackendKind::Sqlite {
+            report.error("storage.backend", "'sqlite' needs meshbbs built with the 'sqlite' feature");
+        }
+    }
+
+    fn check_security(&self, report: &mut ValidationReport) {
+        let Some(security) = &self.security else { return };
+        if let Some(argon2) = &security.argon2 {
+            if let Err(e) = argon2.params() {
+                report.error("security.argon2", e.to_string());
+            }
+        }
+        let lockout = &security.lockout;
+        if lockout.enabled {
+            if lockout.max_attempts == 0 {
+                report.error("security.lockout.max_attempts", "must be at least 1");
+            }
+            if lockout.lockout_minutes == 0 {
+                report.error("security.lockout.lockout_minutes", "must be at least 1");
+            } else if lockout.max_lockout_minutes < lockout.lockout_minutes {
+                report.error("security.lockout.max_lockout_minutes", "must be at least lockout_minutes");
+            }
+        }
+        let Some(enc) = &security.encryption else { return };
+        match (&enc.passphrase, &enc.keyfile) {
+            (Some(_), Some(_)) => report.error("security.encryption", "set either passphrase or keyfile, not both"),
+            (None, None) => report.error("security.encryption", "passphrase or keyfile is required"),
+            (Some(pass), None) if pass.len() < MIN_SECRET_BYTES => {
+                report.error("security.encryption.passphrase", format!("must be at least {MIN_SECRET_BYTES} bytes"));
+            }
+            (None, Some(path)) if !std::path::Path::new(path).is_file() => {
+                // Often checked on a machine other than the server, so not fatal here
+                report.warning("security.encryption.keyfile", format!("{path} does not exist on this machine"));
+            }
+            _ => {}
+        }
+    }
+
+    fn check_backup(&self, report: &mut ValidationReport) {
+        let Some(backup) = &self.backup else { return };
+        if backup.dir.trim().is_empty() {
+            report.error("backup.dir", "must not be empty");
+        } else if std::path::Path::new(&backup.dir).starts_with(&self.storage.data_dir) {
+            report.warning("backup.dir", "is inside storage.data_dir; keep backups on other storage");
+        }
+        if backup.keep == 0 {
+            report.error("backup.keep", "must be at least 1");
+        }
+    }
+}
+
+/// `host:port` with a numeric port; the host is resolved when the server binds.
+fn valid_bind(bind: &str) -> bool {
+    match bind.rsplit_once(':') {
+        Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+        None => false,
+    }
+}
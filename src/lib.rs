@@ -38,6 +38,8 @@
 //! - [`config`] - Configuration management and validation
 //! - [`validation`] - Input validation and sanitization utilities
 //! - [`protobuf`] - Protocol buffer definitions for Meshtastic integration
+//! - [`metrics`] - Counters and gauges with Prometheus text rendering
+//! - `web` - Embedded HTTP server: `/metrics` and the sysop admin dashboard (feature `web`)
 //!
 //! ## Architecture
 //!
@@ -72,5 +74,7 @@ pub mod config;
 pub mod storage;
 pub mod validation;
 pub mod protobuf; // always declare; internal stubs handle feature gating
-pub mod metrics; // new metrics module (Phase 3 scaffold)
+pub mod metrics;
+#[cfg(feature = "web")]
+pub mod web;
 pub mod logutil;
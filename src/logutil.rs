@@ -1,5 +1,9 @@
 //! Logging utilities for sanitizing multi-line user/content strings so logs stay single-line.
 //! Escapes control characters that otherwise break log readability.
+//! Also holds the runtime log level so `logging.level` can change on config reload.
+
+use log::LevelFilter;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Escape a string for single-line logging:
 /// - `\n` => `\\n`
@@ -27,6 +31,27 @@ pub fn escape_log(s: &str) -> String {
     out
 }
 
+/// Most verbose level requested with `-v` on the command line; `logging.level` cannot go below it.
+static CLI_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+/// Parse a `logging.level` value (`off`, `error`, `warn`, `info`, `debug`, `trace`).
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    level.trim().parse().ok()
+}
+
+/// Record the verbosity forced on the command line (`LevelFilter::Off` when none).
+pub fn set_cli_level(level: LevelFilter) {
+    CLI_LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+/// Apply `logging.level` to the running logger (startup and config reload).
+pub fn apply_level(level: &str) -> Option<LevelFilter> {
+    let configured = parse_level(level)?;
+    let effective = LevelFilter::iter().nth(CLI_LEVEL.load(Ordering::Relaxed)).unwrap_or(LevelFilter::Off).max(configured);
+    log::set_max_level(effective);
+    Some(effective)
+}
+
 #[cfg(test)]
 mod tests {
     use super::escape_log;
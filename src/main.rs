@@ -3,9 +3,11 @@ use log::{info, warn};
 use clap::{Parser, Subcommand};
 
 // Use the published library crate modules instead of redefining them here.
+use meshbbs::bbs::admin::{AdminRequest, NewTopic, TopicChanges, CLI_ACTOR};
+use meshbbs::bbs::roles::role_name;
 use meshbbs::bbs::BbsServer;
-use meshbbs::config::Config;
-use meshbbs::storage::Storage;
+use meshbbs::config::{Config, ConfigLoader};
+use meshbbs::storage::{self, BackendKind, Storage};
 
 #[derive(Parser)]
 #[command(name = "meshbbs")]
@@ -22,13 +24,17 @@ struct Cli {
     /// Verbose logging (-v, -vv for more; may appear before or after subcommand)
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     verbose: u8,
+
+    /// Override a config setting, e.g. --set bbs.max_users=50 (repeatable; wins over MESHBBS_* env)
+    #[arg(long = "set", value_name = "KEY=VALUE", global = true)]
+    set: Vec<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Start the BBS server
     Start {
-        /// Meshtastic device port (e.g., /dev/ttyUSB0)
+        /// Meshtastic device port (e.g., /dev/ttyUSB0 or tcp://10.0.0.5:4403)
         #[arg(short, long)]
         port: Option<String>,
     },
@@ -50,6 +56,206 @@ enum Commands {
     },
     /// Set or update the sysop (primary administrator) password in the config file
     SysopPasswd,
+    /// Copy all BBS data from one storage backend to another (stop the server first)
+    Migrate {
+        /// Backend to read from (json or sqlite)
+        #[arg(long)]
+        from: BackendKind,
+        /// Backend to write to (json or sqlite); must be empty
+        #[arg(long)]
+        to: BackendKind,
+    },
+    /// Encrypt, re-key or decrypt the data directory in place to match [security.encryption] (stop the server first)
+    Rekey {
+        /// Keyfile the data directory is currently encrypted with
+        #[arg(long, conflicts_with = "old_passphrase")]
+        old_keyfile: Option<String>,
+        /// Prompt for the passphrase the data directory is currently encrypted with
+        #[arg(long)]
+        old_passphrase: bool,
+    },
+    /// Write a consistent snapshot of the data directory to a .tar.gz archive (safe while the server runs)
+    Backup {
+        /// Archive to write, e.g. meshbbs-backup.tar.gz
+        file: String,
+    },
+    /// Replace the data directory with a validated backup archive (stop the server first)
+    Restore {
+        /// Archive written by `meshbbs backup`
+        file: String,
+    },
+    /// Check the data directory for damaged, misplaced or outdated records (exit status 1 if any remain)
+    Fsck {
+        /// Quarantine bad files and fix dangling parents, stale locks and legacy replies (stop the server first)
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Export topics, threads and replies as JSON Lines, mbox or a static HTML site
+    Export {
+        /// jsonl (re-importable), mbox, or html
+        #[arg(short, long, default_value = "jsonl")]
+        format: storage::ExportFormat,
+        /// File to write (a directory for html)
+        #[arg(short, long)]
+        out: String,
+        /// Topic to export (repeat for several; all topics when omitted)
+        #[arg(long = "topic")]
+        topics: Vec<String>,
+        /// Leave out topics whose read level is above this (0 for a public archive)
+        #[arg(long)]
+        max_read_level: Option<u8>,
+    },
+    /// Import a JSON Lines export, keeping authors and timestamps and skipping messages already present (stop the server first)
+    Import {
+        /// File written by `meshbbs export --format jsonl`
+        file: String,
+    },
+    /// Manage user accounts without logging in over the radio (stop the server first)
+    User {
+        /// Print results as JSON
+        #[arg(long, global = true)]
+        json: bool,
+        #[command(subcommand)]
+        action: UserAction,
+    },
+    /// Manage topics and subtopics without logging in over the radio (stop the server first)
+    Topic {
+        /// Print results as JSON
+        #[arg(long, global = true)]
+        json: bool,
+        #[command(subcommand)]
+        action: TopicAction,
+    },
+    /// Run the BBS against a simulated radio and virtual nodes (no hardware needed)
+    Simulate {
+        /// Script to run (see meshtastic::sim docs); reads the script from stdin when omitted
+        #[arg(short, long)]
+        script: Option<String>,
+        /// Number of virtual nodes on the simulated mesh
+        #[arg(short, long, default_value_t = 3)]
+        nodes: usize,
+        /// Probability (0.0-1.0) that a packet sent by the BBS is lost
+        #[arg(long, default_value_t = 0.0)]
+        loss: f32,
+        /// Seed for reproducible packet loss
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Data directory for the simulated BBS (defaults to a fresh temporary directory)
+        #[arg(long)]
+        data_dir: Option<String>,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Use the BBS from this terminal as if it were a mesh node (stop the server first)
+    Console {
+        /// Synthetic node id for the session (decimal or 0x-prefixed hex)
+        #[arg(short, long, value_parser = parse_node_id)]
+        node: Option<u32>,
+        /// Deliver replies at radio pace through the outbound scheduler
+        #[arg(long)]
+        pace: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the config file, or with --effective every setting after defaults and overrides
+    Show {
+        /// Merge defaults, the file, MESHBBS_* variables and --set, and show each value's source
+        #[arg(long)]
+        effective: bool,
+    },
+    /// Check the effective configuration; exits 1 on errors (for CI)
+    Validate {
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum UserAction {
+    /// List all users
+    List,
+    /// Show one user
+    Show { username: String },
+    /// Create a user; prompts for the password unless --password-stdin
+    Create {
+        username: String,
+        /// 1 (user), 5 (moderator) or 10 (sysop)
+        #[arg(long, default_value_t = 1)]
+        level: u8,
+        /// Bind the account to this node id
+        #[arg(long)]
+        node: Option<String>,
+        /// Read the password from the first line of stdin
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Promote or demote a user (1, 5 or 10)
+    SetLevel { username: String, level: u8 },
+    /// Set a new password; prompts unless --password-stdin
+    ResetPassword {
+        username: String,
+        /// Read the password from the first line of stdin
+        #[arg(long)]
+        password_stdin: bool,
+    },
+    /// Clear the user's node binding so the next login binds again
+    UnbindNode { username: String },
+    /// Delete a user and their mailbox (posts keep the author name)
+    Delete { username: String },
+}
+
+#[derive(Subcommand)]
+enum TopicAction {
+    /// List topics and subtopics
+    List,
+    /// Create a topic, or a subtopic with --parent
+    Create {
+        id: String,
+        /// Display name (defaults to the id)
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, default_value = "")]
+        description: String,
+        #[arg(long, default_value_t = 0)]
+        read_level: u8,
+        #[arg(long, default_value_t = 0)]
+        post_level: u8,
+        /// Parent topic id
+        #[arg(long)]
+        parent: Option<String>,
+    },
+    /// Change a topic's name, description or levels
+    Modify {
+        id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long)]
+        description: Option<String>,
+        #[arg(long)]
+        read_level: Option<u8>,
+        #[arg(long)]
+        post_level: Option<u8>,
+    },
+    /// Delete a topic and all its messages (delete its subtopics first)
+    Delete { id: String },
+    /// Stop new posts and replies in a topic
+    Lock { id: String },
+    /// Allow posting in a locked topic again
+    Unlock { id: String },
+}
+
+fn parse_node_id(s: &str) -> std::result::Result<u32, String> {
+    let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse::<u32>(),
+    };
+    parsed.map_err(|e| format!("invalid node id '{s}': {e}"))
 }
 
 #[tokio::main]
@@ -57,17 +263,21 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     
     // Load config early to configure logging (except for Init which writes default later)
-    let pre_config = match cli.command { Commands::Init => None, _ => Config::load(&cli.config).await.ok() };
+    // Defaults < config file < MESHBBS_* environment < --set
+    let loader = ConfigLoader::new(&cli.config).process_env().sets(cli.set.clone());
+    let pre_config = match cli.command { Commands::Init => None, _ => loader.load().await.ok().map(|l| l.config) };
     init_logging(&pre_config, cli.verbose);
 
     info!("Starting Meshbbs v{}", env!("CARGO_PKG_VERSION"));
 
     match cli.command {
         Commands::Start { port } => {
-            let config = pre_config.unwrap_or(Config::load(&cli.config).await?);
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            check_startup_config(&config)?;
             // Capture configured port before moving config into server
             let configured_port = config.meshtastic.port.clone();
             let mut bbs = BbsServer::new(config).await?;
+            bbs.set_config_loader(loader.clone());
 
             // Determine which port to use: CLI overrides config; fallback to config when CLI absent
             let chosen_port = match port {
@@ -76,10 +286,14 @@ async fn main() -> Result<()> {
             };
 
             if let Some(port_path) = chosen_port {
+                // The link is supervised: a missing or lost device is retried in the background
                 match bbs.connect_device(&port_path).await {
-                    Ok(_) => info!("Connected to Meshtastic device on {}", port_path),
+                    Ok(_) => match bbs.link_status() {
+                        Some(link) => info!("Radio link: {}", link.summary()),
+                        None => info!("Connected to Meshtastic device on {}", port_path),
+                    },
                     Err(e) => {
-                        // Fail fast? For now we warn and continue so the BBS can still run (e.g., for web or offline ops)
+                        // Invalid device address; continue so the BBS can still run (e.g., for web or offline ops)
                         warn!("Failed to connect to device on {}: {} (BBS continuing without device)", port_path, e);
                     }
                 }
@@ -101,7 +315,7 @@ async fn main() -> Result<()> {
 
             // Create default topics in runtime JSON (data/topics.json)
             let data_dir = cfg.storage.data_dir.clone();
-            let mut storage = Storage::new(&data_dir).await?;
+            let mut storage = Storage::open(&data_dir, cfg.storage.backend, None, None).await?;
             let defaults = vec![
                 ("technical", "Technical", "Tech, hardware, and administrative discussions"),
                 ("general", "General", "General discussions"),
@@ -115,15 +329,180 @@ async fn main() -> Result<()> {
             info!("Initialized runtime topics at {}/topics.json", data_dir);
         }
         Commands::Status => {
-            let config = pre_config.unwrap_or(Config::load(&cli.config).await?);
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
             let bbs = BbsServer::new(config).await?;
             bbs.show_status().await?;
         }
+        Commands::Migrate { from, to } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            if from == to {
+                return Err(anyhow::anyhow!("--from and --to are both '{}'", from));
+            }
+            let data_dir = &config.storage.data_dir;
+            // Both backends share the data directory's key
+            let encryption = config.security.as_ref().and_then(|s| s.encryption.as_ref());
+            let cipher = storage::open_data_key(data_dir, from, encryption)?;
+            let source = storage::open_backend(data_dir, from, cipher.clone())?;
+            let dest = storage::open_backend(data_dir, to, cipher)?;
+            println!("Migrating {} from {} to {}...", data_dir, from, to);
+            let report = storage::migrate(source.as_ref(), dest.as_ref())?;
+            println!("Copied {}.", report);
+            if config.storage.backend != to {
+                println!("Set `backend = \"{}\"` under [storage] in {} to use the new backend.", to, cli.config);
+            }
+        }
+        Commands::Rekey { old_keyfile, old_passphrase } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let data_dir = &config.storage.data_dir;
+            // The config names the key we want; --old-* names the one the data is under now
+            let new = match config.security.as_ref().and_then(|s| s.encryption.as_ref()) {
+                Some(enc) => Some(storage::KeySource::from_config(enc)?),
+                None => None,
+            };
+            let old = if let Some(path) = old_keyfile {
+                Some(storage::KeySource::Keyfile(path.into()))
+            } else if old_passphrase {
+                Some(storage::KeySource::Passphrase(rpassword::prompt_password("Current passphrase: ")?))
+            } else if storage::EncryptionHeader::load(data_dir)?.is_some() {
+                // Same key (or resuming an interrupted run): re-seal under a fresh salt
+                new.clone()
+            } else {
+                None
+            };
+            println!("Re-sealing {} ({} backend)...", data_dir, config.storage.backend);
+            let report = storage::rekey(data_dir, config.storage.backend, old.as_ref(), new.as_ref())?;
+            println!(
+                "Rewrote {} records; the data directory is now {}.",
+                report.records,
+                if report.encrypted { "encrypted" } else { "unencrypted" }
+            );
+        }
+        Commands::Backup { file } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let manifest = storage::backup(&config.storage.data_dir, config.storage.backend, std::path::Path::new(&file))?;
+            println!(
+                "Backed up {} files from {} ({} backend{}) to {}.",
+                manifest.files,
+                config.storage.data_dir,
+                manifest.backend,
+                if manifest.encrypted { ", encrypted" } else { "" },
+                file
+            );
+        }
+        Commands::Restore { file } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let key = match config.security.as_ref().and_then(|s| s.encryption.as_ref()) {
+                Some(enc) => Some(storage::KeySource::from_config(enc)?),
+                None => None,
+            };
+            let report = storage::restore(&config.storage.data_dir, std::path::Path::new(&file), key.as_ref())?;
+            println!(
+                "Restored {} records from {} (taken {}) into {}.",
+                report.records,
+                file,
+                report.manifest.created_at.format("%Y-%m-%d %H:%M UTC"),
+                config.storage.data_dir
+            );
+            if let Some(previous) = report.previous {
+                println!("The previous data directory was kept as {}.", previous.display());
+            }
+            if config.storage.backend != report.manifest.backend {
+                println!("The backup uses the {} backend; set `backend = \"{}\"` under [storage] in {}.", report.manifest.backend, report.manifest.backend, cli.config);
+            }
+        }
+        Commands::Fsck { repair } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let data_dir = &config.storage.data_dir;
+            let encryption = config.security.as_ref().and_then(|s| s.encryption.as_ref());
+            let cipher = storage::open_data_key(data_dir, config.storage.backend, encryption)?;
+            let report = storage::fsck(data_dir, config.storage.backend, cipher, repair)?;
+            for issue in &report.issues {
+                println!("{issue}");
+            }
+            println!("{}: {}", data_dir, report.summary());
+            if let Some(dir) = &report.quarantine {
+                println!("Bad files were moved to {}.", dir.display());
+            }
+            if report.outstanding().next().is_some() {
+                if !repair {
+                    println!("Run `meshbbs fsck --repair` with the server stopped to fix what can be fixed.");
+                }
+                std::process::exit(1);
+            }
+        }
+        Commands::Export { format, out, topics, max_read_level } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let storage = open_storage(&config).await?;
+            let options = storage::ExportOptions { topics, max_read_level, title: config.bbs.name.clone() };
+            let report = storage.export(format, std::path::Path::new(&out), &options).await?;
+            println!(
+                "Exported {} topics, {} messages and {} replies as {} to {}.",
+                report.topics, report.messages, report.replies, format, out
+            );
+        }
+        Commands::Import { file } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let mut storage = open_storage(&config).await?;
+            let reader = std::io::BufReader::new(std::fs::File::open(&file)
+                .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", file, e))?);
+            let report = storage.import_jsonl(reader).await?;
+            for (line, reason) in &report.rejected {
+                println!("{}:{}: {}", file, line, reason);
+            }
+            println!(
+                "Imported {} messages ({} already present, {} rejected); created {} topics.",
+                report.imported, report.duplicates, report.rejected.len(), report.topics_created
+            );
+            if !report.rejected.is_empty() {
+                std::process::exit(1);
+            }
+        }
+        Commands::User { json, action } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let request = match action {
+                UserAction::List => AdminRequest::ListUsers,
+                UserAction::Show { username } => AdminRequest::ShowUser { username },
+                UserAction::Create { username, level, node, password_stdin } => {
+                    let password = read_new_password(password_stdin)?;
+                    AdminRequest::CreateUser { username, password, level, node_id: node }
+                }
+                UserAction::SetLevel { username, level } => AdminRequest::SetUserLevel { username, level },
+                UserAction::ResetPassword { username, password_stdin } => {
+                    let password = read_new_password(password_stdin)?;
+                    AdminRequest::ResetPassword { username, password }
+                }
+                UserAction::UnbindNode { username } => AdminRequest::UnbindNode { username },
+                UserAction::Delete { username } => AdminRequest::DeleteUser { username },
+            };
+            run_admin_command(config, request, json).await?;
+        }
+        Commands::Topic { json, action } => {
+            let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+            let request = match action {
+                TopicAction::List => AdminRequest::ListTopics,
+                TopicAction::Create { id, name, description, read_level, post_level, parent } => AdminRequest::CreateTopic(NewTopic {
+                    name: name.unwrap_or_else(|| id.clone()),
+                    id,
+                    description,
+                    read_level,
+                    post_level,
+                    parent,
+                }),
+                TopicAction::Modify { id, name, description, read_level, post_level } => AdminRequest::ModifyTopic {
+                    id,
+                    changes: TopicChanges { name, description, read_level, post_level, retention: None },
+                },
+                TopicAction::Delete { id } => AdminRequest::DeleteTopic { id },
+                TopicAction::Lock { id } => AdminRequest::SetTopicLocked { id, locked: true },
+                TopicAction::Unlock { id } => AdminRequest::SetTopicLocked { id, locked: false },
+            };
+            run_admin_command(config, request, json).await?;
+        }
         Commands::SysopPasswd => {
             use password_hash::{PasswordHasher, SaltString};
             use argon2::Argon2;
-            // Read existing config
-            let mut config = pre_config.unwrap_or(Config::load(&cli.config).await?);
+            // Read the file itself: it is written back, and overrides must not end up in it
+            let mut config = Config::load(&cli.config).await?;
             println!("Setting sysop password for '{}'.", config.bbs.sysop);
             // Prompt twice without echo
             let pass1 = rpassword::prompt_password("New password: ")?;
@@ -141,6 +520,89 @@ async fn main() -> Result<()> {
             tokio::fs::write(&cli.config, serialized).await?;
             println!("Sysop password updated successfully.");
         }
+        Commands::Simulate { script, nodes, loss, seed, data_dir } => {
+            #[cfg(not(feature = "meshtastic-proto"))]
+            {
+                let _ = (script, nodes, loss, seed, data_dir);
+                log::error!("Simulate requires the 'meshtastic-proto' feature");
+                std::process::exit(2);
+            }
+            #[cfg(feature = "meshtastic-proto")]
+            {
+                use meshbbs::meshtastic::sim;
+                use std::io::Read;
+                let mut config = pre_config.unwrap_or_default();
+                // Never run a simulation against the live data directory unless asked to
+                let scratch_dir = match data_dir {
+                    Some(dir) => { config.storage.data_dir = dir; None }
+                    None => {
+                        let dir = std::env::temp_dir().join(format!("meshbbs-sim-{}", uuid::Uuid::new_v4()));
+                        config.storage.data_dir = dir.to_string_lossy().to_string();
+                        Some(dir)
+                    }
+                };
+                let script_text = match script {
+                    Some(path) => tokio::fs::read_to_string(&path).await?,
+                    None => { let mut buf = String::new(); std::io::stdin().read_to_string(&mut buf)?; buf }
+                };
+                info!("Simulating {} virtual node(s), loss={:.2}, data_dir={}", nodes, loss, config.storage.data_dir);
+                // The returned stream is unused: the supervised connection opens its own so that
+                // `unplug` / `plug` in scripts go through the reconnect path
+                let (_transport, mut handle) = sim::start(sim::SimConfig { loss_rate: loss, seed, ..sim::SimConfig::with_nodes(nodes) });
+                let mut bbs = BbsServer::new(config).await?;
+                bbs.connect_supervised("sim://virtual-radio", handle.opener()).await?;
+                let mut stdout = std::io::stdout();
+                let outcome = tokio::select! {
+                    res = bbs.run() => res,
+                    res = sim::run_script(&mut handle, &script_text, &mut stdout) => res,
+                };
+                if let Some(dir) = scratch_dir { let _ = std::fs::remove_dir_all(dir); }
+                outcome?;
+                let stats = handle.stats();
+                println!(
+                    "-- delivered={} dropped={} duplicates={} acks={} routing_errors={}",
+                    stats.delivered, stats.dropped, stats.duplicates, stats.acks, stats.routing_errors
+                );
+            }
+        }
+        Commands::Config { action } => match action {
+            ConfigAction::Show { effective: false } => print!("{}", tokio::fs::read_to_string(&cli.config).await?),
+            ConfigAction::Show { effective: true } => print!("{}", loader.load().await?.render_effective()?),
+            ConfigAction::Validate { strict } => {
+                let report = loader.load().await?.config.check();
+                for finding in &report.findings {
+                    println!("{finding}");
+                }
+                println!("{}: {}", cli.config, report.summary());
+                if report.has_errors() || (strict && report.warnings().next().is_some()) {
+                    std::process::exit(1);
+                }
+            }
+        },
+        Commands::Console { node, pace } => {
+            #[cfg(not(feature = "meshtastic-proto"))]
+            {
+                let _ = (node, pace);
+                log::error!("Console requires the 'meshtastic-proto' feature");
+                std::process::exit(2);
+            }
+            #[cfg(feature = "meshtastic-proto")]
+            {
+                use meshbbs::bbs::console::{self, ConsoleOptions, DEFAULT_CONSOLE_NODE};
+                let config = match pre_config { Some(c) => c, None => loader.load().await?.config };
+                check_startup_config(&config)?;
+                let opts = ConsoleOptions { node_id: node.unwrap_or(DEFAULT_CONSOLE_NODE), pace };
+                let mut bbs = BbsServer::new(config).await?;
+                bbs.seed_sysop().await?;
+                eprintln!(
+                    "meshbbs console as node 0x{:08x}{}. Lines starting with ^ go to the public channel; Ctrl-D exits.",
+                    opts.node_id, if pace { " (radio pacing)" } else { "" }
+                );
+                let input = tokio::io::BufReader::new(tokio::io::stdin());
+                let mut stdout = tokio::io::stdout();
+                console::run(&mut bbs, &opts, input, &mut stdout).await?;
+            }
+        }
     Commands::SmokeTest { port, baud, timeout } => {
             #[cfg(not(all(feature = "serial", feature = "meshtastic-proto")))]
             {
@@ -198,12 +660,120 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Log configuration warnings and refuse to run with errors.
+fn check_startup_config(config: &Config) -> Result<()> {
+    for finding in config.check().warnings() {
+        warn!("Config {}", finding);
+    }
+    config.validate()
+}
+
+/// Run a `meshbbs user` / `meshbbs topic` request against the data directory, recorded in
+/// the admin audit log as [`CLI_ACTOR`], and print its result.
+async fn run_admin_command(config: Config, request: AdminRequest, json: bool) -> Result<()> {
+    let describe = match &request {
+        AdminRequest::ListUsers | AdminRequest::ShowUser { .. } | AdminRequest::ListTopics => None,
+        AdminRequest::CreateUser { username, .. } => Some(format!("Created user {username}.")),
+        AdminRequest::SetUserLevel { username, level } => Some(format!("Set {username} to level {level} ({}).", role_name(*level))),
+        AdminRequest::ResetPassword { username, .. } => Some(format!("Password for {username} reset.")),
+        AdminRequest::UnbindNode { username } => Some(format!("Unbound {username} from their node.")),
+        AdminRequest::DeleteUser { username } => Some(format!("Deleted user {username}.")),
+        AdminRequest::CreateTopic(t) => Some(format!("Created topic {}.", t.id)),
+        AdminRequest::ModifyTopic { id, .. } => Some(format!("Modified topic {id}.")),
+        AdminRequest::DeleteTopic { id } => Some(format!("Deleted topic {id}.")),
+        AdminRequest::SetTopicLocked { id, locked } => Some(format!("{} topic {id}.", if *locked { "Locked" } else { "Unlocked" })),
+        _ => None,
+    };
+    let users = matches!(request, AdminRequest::ListUsers);
+    let mut bbs = BbsServer::new(config).await?;
+    let result = bbs.handle_admin(CLI_ACTOR, request).await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+    if let Some(done) = describe {
+        println!("{done}");
+        return Ok(());
+    }
+    fn text(v: &serde_json::Value) -> String {
+        match v {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Null => "-".to_string(),
+            // Trusted nodes: `id (label)`
+            serde_json::Value::Object(o) if o.contains_key("node_id") => match o.get("label").and_then(|l| l.as_str()) {
+                Some(label) => format!("{} ({})", text(&o["node_id"]), label),
+                None => text(&o["node_id"]),
+            },
+            serde_json::Value::Array(items) if !items.is_empty() => items.iter().map(text).collect::<Vec<_>>().join(", "),
+            serde_json::Value::Array(_) => "-".to_string(),
+            other => other.to_string(),
+        }
+    }
+    let time = |v: &serde_json::Value| v.as_str().and_then(|t| t.get(..16)).unwrap_or("-").replace('T', " ");
+    let Some(rows) = result.as_array() else {
+        // A single record (user show): one field per line
+        for (key, value) in result.as_object().into_iter().flatten() {
+            println!("{:<15} {}", format!("{key}:"), text(value));
+        }
+        return Ok(());
+    };
+    if users {
+        println!("{:<20} {:<10} {:>5}  {:<12} {:>6}  LAST LOGIN", "USERNAME", "ROLE", "LEVEL", "NODE", "POSTS");
+        for u in rows {
+            println!(
+                "{:<20} {:<10} {:>5}  {:<12} {:>6}  {}{}",
+                text(&u["username"]), text(&u["role"]), text(&u["level"]), text(&u["node_id"]),
+                text(&u["total_messages"]), time(&u["last_login"]),
+                if u["has_password"] == true { "" } else { "  (no password)" }
+            );
+        }
+    } else {
+        // Subtopics indented under their parent
+        let mut rows = rows.clone();
+        rows.sort_by_key(|t| (t["parent"].as_str().unwrap_or(t["id"].as_str().unwrap_or("")).to_string(), !t["parent"].is_null(), text(&t["id"])));
+        println!("{:<20} {:<24} {:>4} {:>4}  DESCRIPTION", "TOPIC", "NAME", "READ", "POST");
+        for t in &rows {
+            let id = if t["parent"].is_null() { text(&t["id"]) } else { format!("  {}", text(&t["id"])) };
+            println!(
+                "{:<20} {:<24} {:>4} {:>4}  {}{}",
+                id, text(&t["name"]), text(&t["read_level"]), text(&t["post_level"]), text(&t["description"]),
+                if t["locked"] == true { " [locked]" } else { "" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Read a new password from the first line of stdin, or prompt twice without echo.
+fn read_new_password(from_stdin: bool) -> Result<String> {
+    if from_stdin {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        return Ok(line.trim_end_matches(['\r', '\n']).to_string());
+    }
+    let first = rpassword::prompt_password("New password: ")?;
+    if rpassword::prompt_password("Confirm password: ")? != first {
+        anyhow::bail!("Passwords do not match");
+    }
+    Ok(first)
+}
+
+/// Open the configured data directory the way the server does (Argon2 params, encryption).
+async fn open_storage(config: &Config) -> Result<Storage> {
+    let params = config.security.as_ref().and_then(|sec| sec.argon2.as_ref())
+        .map(|a| a.params()).transpose()
+        .map_err(|e| anyhow::anyhow!("[security.argon2]: {}", e))?;
+    let encryption = config.security.as_ref().and_then(|sec| sec.encryption.as_ref());
+    let cipher = storage::open_data_key(&config.storage.data_dir, config.storage.backend, encryption)?;
+    Storage::open(&config.storage.data_dir, config.storage.backend, params, cipher).await
+}
+
 fn init_logging(config: &Option<Config>, verbosity: u8) {
     use std::io::Write;
     let mut builder = env_logger::Builder::new();
-    // Base level from CLI verbosity overrides config
-    let base_level = match verbosity { 0 => log::LevelFilter::Info, 1 => log::LevelFilter::Debug, _ => log::LevelFilter::Trace };
-    builder.filter_level(base_level);
+    // The logger passes everything; the effective level is the global max level, so that a
+    // config reload can change `logging.level` (CLI verbosity acts as a floor)
+    builder.filter_level(log::LevelFilter::Trace);
     if let Some(cfg) = config {
         let security_path = cfg.logging.security_file.clone();
         if let Some(ref file) = cfg.logging.file {
@@ -240,4 +810,10 @@ fn init_logging(config: &Option<Config>, verbosity: u8) {
         });
     }
     let _ = builder.try_init();
+    meshbbs::logutil::set_cli_level(match verbosity { 0 => log::LevelFilter::Off, 1 => log::LevelFilter::Debug, _ => log::LevelFilter::Trace });
+    let level = config.as_ref().map(|c| c.logging.level.as_str()).unwrap_or("info");
+    if meshbbs::logutil::apply_level(level).is_none() {
+        meshbbs::logutil::apply_level("info");
+        warn!("Unknown logging.level '{}'; using info", level);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,260 @@
+//! Radio link supervision.
+//!
+//! The reader and writer report a lost device (unplugged USB radio, rebooting node,
+//! dropped TCP connection) as a [`LinkEvent`]. The [`LinkSupervisor`] then releases the
+//! device, parks the shared transport on a placeholder whose writes fail (so nothing is
+//! silently discarded), tells the writer to hold outbound traffic, and reopens the device
+//! with exponential backoff.
+//! Once the device is back it swaps the fresh stream into the *same*
+//! [`SharedTransport`], so the reader, writer and scheduler keep running; the writer
+//! re-runs the `want_config` handshake and flushes what it held.
+//!
+//! The current [`LinkStatus`] is shared with the server (status output, sysop dashboard)
+//! and mirrored to `<data_dir>/link_status.json` so `meshbbs status` can report it.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::transport::{SharedTransport, Transport};
+use super::ControlMessage;
+
+/// File (inside the data directory) mirroring the last known link status.
+pub const LINK_STATUS_FILE: &str = "link_status.json";
+
+/// First reconnect delay; doubled after each failed attempt up to [`RECONNECT_MAX`].
+const RECONNECT_INITIAL: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+/// Future returned by a [`TransportOpener`].
+pub type OpenFuture = Pin<Box<dyn Future<Output = Result<Box<dyn Transport>>> + Send>>;
+
+/// Opens a fresh stream to the device; called once per (re)connect attempt.
+pub type TransportOpener = Box<dyn FnMut() -> OpenFuture + Send>;
+
+/// Opener for a configured device address (`/dev/ttyUSB0`, `tcp://host:4403`, ...).
+pub fn device_opener(port: &str, baud_rate: u32) -> TransportOpener {
+    let port = port.to_string();
+    Box::new(move || {
+        let port = port.clone();
+        Box::pin(async move { super::open_transport_boxed(&port, baud_rate).await })
+    })
+}
+
+/// Stands in for the device while it is being reopened: reads look idle, writes fail
+/// so the writer keeps the message instead of losing it.
+struct ParkedTransport {
+    target: String,
+}
+
+impl io::Read for ParkedTransport {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "radio link down"))
+    }
+}
+
+impl io::Write for ParkedTransport {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::NotConnected, "radio link down"))
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Transport for ParkedTransport {
+    fn describe(&self) -> String { format!("{} (down)", self.target) }
+}
+
+/// Reported by the reader or writer when the device stream fails.
+#[derive(Debug, Clone)]
+pub enum LinkEvent {
+    Lost(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkState {
+    Connected,
+    Reconnecting,
+    /// Server shut down (only seen in the status file)
+    Stopped,
+}
+
+/// Snapshot of the radio link.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkStatus {
+    pub state: LinkState,
+    /// Device address (`/dev/ttyUSB0`, `tcp://...`)
+    pub target: String,
+    /// When the link entered `state`
+    pub since: DateTime<Utc>,
+    /// Successful reconnects since startup
+    pub reconnects: u32,
+    /// Failed attempts during the current outage
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+impl LinkStatus {
+    pub fn new(target: &str, state: LinkState) -> Self {
+        LinkStatus { state, target: target.to_string(), since: Utc::now(), reconnects: 0, attempts: 0, last_error: None }
+    }
+
+    /// One-line human readable summary.
+    pub fn summary(&self) -> String {
+        let since = self.since.format("%Y-%m-%d %H:%M UTC");
+        match self.state {
+            LinkState::Connected => format!("connected to {} since {} ({} reconnects)", self.target, since, self.reconnects),
+            LinkState::Reconnecting => format!(
+                "DOWN, reconnecting to {} since {} (attempt {}, last error: {})",
+                self.target, since, self.attempts + 1, self.last_error.as_deref().unwrap_or("none")
+            ),
+            LinkState::Stopped => format!("stopped at {} ({})", since, self.target),
+        }
+    }
+
+    /// Read the status last written by a running server in `data_dir`.
+    pub fn load(data_dir: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(PathBuf::from(data_dir).join(LINK_STATUS_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Best-effort write of the status file.
+    pub fn persist(&self, data_dir: &str) {
+        let path = PathBuf::from(data_dir).join(LINK_STATUS_FILE);
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => { if let Err(e) = std::fs::write(&path, json) { debug!("Failed to write {}: {}", path.display(), e); } }
+            Err(e) => debug!("Failed to serialize link status: {}", e),
+        }
+    }
+}
+
+/// Link status shared between the supervisor and the server.
+pub type SharedLinkStatus = Arc<Mutex<LinkStatus>>;
+
+/// Reopens the device after a [`LinkEvent::Lost`]; see the module docs.
+pub struct LinkSupervisor {
+    transport: SharedTransport,
+    opener: TransportOpener,
+    status: SharedLinkStatus,
+    events_rx: mpsc::UnboundedReceiver<LinkEvent>,
+    reader_control_tx: mpsc::UnboundedSender<ControlMessage>,
+    writer_control_tx: mpsc::UnboundedSender<ControlMessage>,
+    data_dir: Option<String>,
+}
+
+impl LinkSupervisor {
+    /// Create a supervisor for `transport`. Registers the returned event sender with the
+    /// reader and writer. If `status` is already `Reconnecting` (initial open failed),
+    /// [`run`](Self::run) starts reconnecting immediately.
+    pub fn new(
+        transport: SharedTransport,
+        opener: TransportOpener,
+        status: SharedLinkStatus,
+        reader_control_tx: mpsc::UnboundedSender<ControlMessage>,
+        writer_control_tx: mpsc::UnboundedSender<ControlMessage>,
+        data_dir: Option<String>,
+    ) -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let _ = reader_control_tx.send(ControlMessage::SetLinkMonitor(events_tx.clone()));
+        let _ = writer_control_tx.send(ControlMessage::SetLinkMonitor(events_tx));
+        let sup = LinkSupervisor { transport, opener, status, events_rx, reader_control_tx, writer_control_tx, data_dir };
+        sup.publish();
+        sup
+    }
+
+    /// Supervise until the reader and writer have both exited.
+    pub async fn run(mut self) {
+        let initially_down = self.status.lock().unwrap().state == LinkState::Reconnecting;
+        if initially_down {
+            self.park();
+            if !self.reconnect().await { return; }
+        }
+        while let Some(LinkEvent::Lost(reason)) = self.events_rx.recv().await {
+            if self.status.lock().unwrap().state != LinkState::Connected { continue; }
+            let target = self.status.lock().unwrap().target.clone();
+            warn!("Radio link to {} lost: {}", target, reason);
+            self.park();
+            self.update(|s| {
+                s.state = LinkState::Reconnecting;
+                s.since = Utc::now();
+                s.attempts = 0;
+                s.last_error = Some(reason.clone());
+            });
+            if !self.reconnect().await { return; }
+        }
+        debug!("Link supervisor exiting (reader and writer gone)");
+    }
+
+    /// Release the failed device and tell the reader/writer the link is down.
+    fn park(&self) {
+        let target = self.status.lock().unwrap().target.clone();
+        *self.transport.lock().unwrap() = Box::new(ParkedTransport { target });
+        let _ = self.reader_control_tx.send(ControlMessage::LinkDown);
+        let _ = self.writer_control_tx.send(ControlMessage::LinkDown);
+    }
+
+    /// Retry with backoff until the device opens. Returns false if the tasks shut down meanwhile.
+    async fn reconnect(&mut self) -> bool {
+        let mut delay = RECONNECT_INITIAL;
+        loop {
+            // Sleep, but notice when the reader and writer have gone away (shutdown)
+            let deadline = tokio::time::sleep(delay);
+            tokio::pin!(deadline);
+            loop {
+                tokio::select! {
+                    _ = &mut deadline => break,
+                    ev = self.events_rx.recv() => if ev.is_none() { return false; },
+                }
+            }
+            match (self.opener)().await {
+                Ok(fresh) => {
+                    *self.transport.lock().unwrap() = fresh;
+                    self.update(|s| {
+                        s.state = LinkState::Connected;
+                        s.since = Utc::now();
+                        s.reconnects += 1;
+                        s.attempts = 0;
+                    });
+                    let target = self.status.lock().unwrap().target.clone();
+                    info!("Radio link to {} re-established; re-running config handshake", target);
+                    let _ = self.reader_control_tx.send(ControlMessage::LinkUp);
+                    let _ = self.writer_control_tx.send(ControlMessage::LinkUp);
+                    return true;
+                }
+                Err(e) => {
+                    let attempts = {
+                        let mut s = self.status.lock().unwrap();
+                        s.attempts += 1;
+                        s.last_error = Some(e.to_string());
+                        s.attempts
+                    };
+                    self.publish();
+                    debug!("Reconnect attempt {} failed: {} (next in {:?})", attempts, e, delay);
+                    if attempts == 1 || attempts % 10 == 0 {
+                        warn!("Radio link still down after {} attempt(s): {}", attempts, e);
+                    }
+                    delay = (delay * 2).min(RECONNECT_MAX);
+                }
+            }
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut LinkStatus)) {
+        f(&mut self.status.lock().unwrap());
+        self.publish();
+    }
+
+    fn publish(&self) {
+        if let Some(dir) = &self.data_dir {
+            self.status.lock().unwrap().persist(dir);
+        }
+    }
+}
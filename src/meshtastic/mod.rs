@@ -7,6 +7,9 @@
 //! ## Features
 //!
 //! - **Serial Communication**: Connect to Meshtastic devices via USB/UART
+//! - **TCP Communication**: Connect to network-attached nodes via the stream API (`tcp://host:4403`)
+//! - **Link Supervision**: Reconnect with backoff after device loss ([`link`])
+//! - **Simulation**: In-memory virtual radio ([`sim`]) for hardware-free end-to-end testing
 //! - **Protocol Support**: Both text parsing and protobuf decoding
 //! - **Event Processing**: Convert raw device messages to structured events
 //! - **SLIP Decoding**: Handle SLIP-encoded protocol buffer frames
@@ -60,8 +63,8 @@
 //!
 //! ```toml
 //! [meshtastic]
-//! port = "/dev/ttyUSB0"
-//! baud_rate = 115200
+//! port = "/dev/ttyUSB0"      # or "tcp://10.0.0.5:4403" for a WiFi/Ethernet node
+//! baud_rate = 115200          # ignored for TCP
 //! node_id = ""
 //! channel = 0
 //! ```
@@ -102,6 +105,22 @@ pub struct OutgoingMessage {
     /// assign a non-zero id for correlation. For direct messages this flag is ignored
     /// because DMs are always sent reliable with want_ack.
     pub request_ack: bool,
+    /// Store-and-forward: if this DM cannot be delivered (non-transient routing error or
+    /// retries exhausted) the writer reports it as [`DeliveryEvent::Undelivered`] instead
+    /// of dropping it. Ignored for broadcasts.
+    pub durable: bool,
+}
+
+/// Delivery feedback from the reader/writer, consumed by the BBS store-and-forward queue
+/// (and its security log, for key changes)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryEvent {
+    /// A durable DM was given up on
+    Undelivered { to: u32, channel: u32, content: String },
+    /// A packet (text, position or node info) was heard from this node
+    NodeHeard(u32),
+    /// A node announced a public key other than the one on file (both base64)
+    KeyChanged { node: u32, previous: String, current: String },
 }
 
 /// Writer tuning parameters, typically sourced from Config
@@ -115,6 +134,8 @@ pub struct WriterTuning {
     pub post_dm_broadcast_gap_ms: u64,
     /// Minimum gap between two consecutive reliable DMs (ms)
     pub dm_to_dm_gap_ms: u64,
+    /// Request PKI encryption for DMs to nodes with a known public key
+    pub pki_dms: bool,
 }
 
 impl Default for WriterTuning {
@@ -124,6 +145,7 @@ impl Default for WriterTuning {
             dm_resend_backoff_seconds: vec![4, 8, 16],
             post_dm_broadcast_gap_ms: 1200,
             dm_to_dm_gap_ms: 600,
+            pki_dms: true,
         }
     }
 }
@@ -146,6 +168,20 @@ pub enum ControlMessage {
     /// Provide scheduler handle to writer after creation (avoids circular ownership at construction)
     #[allow(dead_code)]
     SetSchedulerHandle(crate::bbs::dispatch::SchedulerHandle),
+    /// Register the link supervisor that should be told when the device stream fails
+    SetLinkMonitor(mpsc::UnboundedSender<link::LinkEvent>),
+    /// Device lost; the supervisor is reconnecting (writer holds outbound traffic)
+    LinkDown,
+    /// Device reopened; reset framing state and re-run the config handshake
+    LinkUp,
+    /// Register the receiver of [`DeliveryEvent`]s (sent to both reader and writer)
+    SetDeliveryMonitor(mpsc::UnboundedSender<DeliveryEvent>),
+    /// Replace the writer's pacing and retry settings (config reload)
+    SetTuning(WriterTuning),
+    /// Public key learned for a node; the writer pins PKI DMs to it
+    PeerKey { node: u32, key: Vec<u8> },
+    /// A sysop accepted the changed key of a node; the reader pins it and tells the writer
+    AcceptKey(u32),
 }
 
 #[cfg(feature = "meshtastic-proto")]
@@ -169,6 +205,13 @@ fn fmt_percent(val: f32) -> String {
     }
 }
 
+/// Whether a received packet was PKI-encrypted, and the sender key the radio reported
+#[cfg(feature = "meshtastic-proto")]
+fn packet_pki(pkt: &proto::MeshPacket) -> (bool, Option<Vec<u8>>) {
+    let key = (pkt.pki_encrypted && !pkt.public_key.is_empty()).then(|| pkt.public_key.to_vec());
+    (pkt.pki_encrypted, key)
+}
+
 #[cfg(feature = "meshtastic-proto")]
 fn summarize_known_port_payload(port: proto::PortNum, payload: &[u8]) -> Option<String> {
     use bytes::BytesMut;
@@ -244,6 +287,12 @@ fn summarize_known_port_payload(port: proto::PortNum, payload: &[u8]) -> Option<
 
 #[cfg(feature = "meshtastic-proto")]
 pub mod slip; // restore SLIP decoder (Meshtastic uses SLIP over some transports)
+pub mod transport;
+pub mod link;
+#[cfg(feature = "meshtastic-proto")]
+pub mod sim;
+
+pub use transport::{SharedTransport, Transport, TransportAddr};
 
 #[cfg(feature = "serial")]
 use serialport::SerialPort;
@@ -260,6 +309,25 @@ pub struct CachedNodeInfo {
     pub short_name: String,
     pub last_seen: DateTime<Utc>,
     pub first_seen: DateTime<Utc>,
+    /// PKI public key (base64), once the node has announced one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+    /// A different key the node announced since (base64), held until a sysop accepts it.
+    /// Not saved, so after a restart the next announcement is reported again.
+    #[serde(skip)]
+    pub pending_public_key: Option<String>,
+}
+
+/// Outcome of [`NodeCache::record_public_key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyUpdate {
+    /// Same key as on file
+    Unchanged,
+    /// First key seen for the node
+    New,
+    /// Differs from the key on file, which stays pinned; the new one is held as pending
+    /// until [`NodeCache::accept_public_key`]
+    Changed { previous: String },
 }
 
 /// Node cache for persistent storage
@@ -285,7 +353,10 @@ impl NodeCache {
 
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
         let content = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, content)?;
+        // Written whole and renamed so readers (and `meshbbs backup`) never see a partial file
+        let tmp = path.as_ref().with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, path)?;
         Ok(())
     }
 
@@ -303,10 +374,70 @@ impl NodeCache {
                 short_name,
                 last_seen: now,
                 first_seen: now,
+                public_key: None,
+                pending_public_key: None,
             });
         self.last_updated = now;
     }
 
+    /// Remember `key` as the public key of `node_id`, reporting whether it is new or differs
+    /// from the one on file. A differing key does not replace the pinned one; it is held as
+    /// pending and reported once. An empty key (node without PKI) changes nothing.
+    pub fn record_public_key(&mut self, node_id: u32, key: &[u8]) -> KeyUpdate {
+        use base64::Engine;
+        if key.is_empty() { return KeyUpdate::Unchanged; }
+        let encoded = base64::engine::general_purpose::STANDARD.encode(key);
+        let now = Utc::now();
+        let node = self.nodes.entry(node_id).or_insert_with(|| CachedNodeInfo {
+            node_id,
+            long_name: String::new(),
+            short_name: String::new(),
+            last_seen: now,
+            first_seen: now,
+            public_key: None,
+            pending_public_key: None,
+        });
+        match &node.public_key {
+            Some(known) if *known == encoded => {
+                // Back to the pinned key: forget a pending change
+                node.pending_public_key = None;
+                KeyUpdate::Unchanged
+            }
+            Some(_) if node.pending_public_key.as_deref() == Some(encoded.as_str()) => KeyUpdate::Unchanged,
+            Some(known) => {
+                let previous = known.clone();
+                node.pending_public_key = Some(encoded);
+                KeyUpdate::Changed { previous }
+            }
+            None => {
+                node.public_key = Some(encoded);
+                self.last_updated = now;
+                KeyUpdate::New
+            }
+        }
+    }
+
+    /// Pin the pending key of `node_id` in place of the one on file, once a sysop has
+    /// accepted the change. Returns the new key, decoded, or `None` if none was pending.
+    pub fn accept_public_key(&mut self, node_id: u32) -> Option<Vec<u8>> {
+        let node = self.nodes.get_mut(&node_id)?;
+        node.public_key = Some(node.pending_public_key.take()?);
+        self.last_updated = Utc::now();
+        self.public_key(node_id)
+    }
+
+    /// The public key on file for `node_id`, decoded
+    pub fn public_key(&self, node_id: u32) -> Option<Vec<u8>> {
+        use base64::Engine;
+        let key = self.nodes.get(&node_id)?.public_key.as_ref()?;
+        base64::engine::general_purpose::STANDARD.decode(key).ok()
+    }
+
+    /// Every known public key, decoded, for handing to the writer
+    pub fn public_keys(&self) -> Vec<(u32, Vec<u8>)> {
+        self.nodes.keys().filter_map(|id| self.public_key(*id).map(|k| (*id, k))).collect()
+    }
+
     #[allow(dead_code)]
     pub fn remove_stale_nodes(&mut self, max_age_days: u32) -> usize {
         let cutoff = Utc::now() - chrono::Duration::days(max_age_days as i64);
@@ -364,7 +495,7 @@ pub struct MeshtasticDevice {
 
 /// Structured text event extracted from protobuf packets
 #[cfg(feature = "meshtastic-proto")]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TextEvent {
     pub source: u32,
     #[allow(dead_code)]
@@ -372,13 +503,16 @@ pub struct TextEvent {
     pub is_direct: bool,
     pub channel: Option<u32>,
     pub content: String,
+    /// The packet was encrypted to our public key (PKI) rather than with the channel key
+    pub pki_encrypted: bool,
+    /// Sender's public key, as reported by the radio for PKI packets
+    pub public_key: Option<Vec<u8>>,
 }
 
 /// Reader task for continuous Meshtastic device reading
 #[cfg(feature = "meshtastic-proto")]
 pub struct MeshtasticReader {
-    #[cfg(feature = "serial")]
-    port: Arc<Mutex<Box<dyn SerialPort>>>,
+    port: SharedTransport,
     slip: slip::SlipDecoder,
     rx_buf: Vec<u8>,
     text_event_tx: mpsc::UnboundedSender<TextEvent>,
@@ -389,13 +523,18 @@ pub struct MeshtasticReader {
     nodes: std::collections::HashMap<u32, proto::NodeInfo>,
     our_node_id: Option<u32>,
     binary_frames_seen: bool,
+    // Link supervision: where to report a lost device, and consecutive read failures
+    link_monitor: Option<mpsc::UnboundedSender<link::LinkEvent>>,
+    link_up: bool,
+    read_failures: u32,
+    // Store-and-forward: told whenever a node is heard
+    delivery_monitor: Option<mpsc::UnboundedSender<DeliveryEvent>>,
 }
 
 /// Writer task for Meshtastic device writing
 #[cfg(feature = "meshtastic-proto")]
 pub struct MeshtasticWriter {
-    #[cfg(feature = "serial")]
-    port: Arc<Mutex<Box<dyn SerialPort>>>,
+    port: SharedTransport,
     outgoing_rx: mpsc::UnboundedReceiver<OutgoingMessage>,
     control_rx: mpsc::UnboundedReceiver<ControlMessage>,
     our_node_id: Option<u32>,
@@ -413,8 +552,20 @@ pub struct MeshtasticWriter {
     tuning: WriterTuning,
     // Optional scheduler handle for enqueuing retry envelopes
     scheduler: Option<crate::bbs::dispatch::SchedulerHandle>,
+    // Link supervision: outbound messages held while the device is reconnecting
+    link_monitor: Option<mpsc::UnboundedSender<link::LinkEvent>>,
+    link_up: bool,
+    held: VecDeque<OutgoingMessage>,
+    // Store-and-forward: told when a durable DM is given up on
+    delivery_monitor: Option<mpsc::UnboundedSender<DeliveryEvent>>,
+    // Public keys learned by the reader, for PKI DMs
+    peer_keys: std::collections::HashMap<u32, Vec<u8>>,
 }
 
+/// Maximum number of outbound messages held while the radio link is down
+#[cfg(feature = "meshtastic-proto")]
+const MAX_HELD_WHILE_DOWN: usize = 256;
+
 #[derive(Debug, Clone)]
 struct PendingSend {
     to: u32,
@@ -428,6 +579,8 @@ struct PendingSend {
     backoff_idx: u8,
     // Original send timestamp for latency metrics
     sent_at: std::time::Instant,
+    // Report to the store-and-forward queue if given up on
+    durable: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -843,7 +996,9 @@ impl MeshtasticDevice {
                                     let is_direct = matches!((dest, self.our_node_id), (Some(d), Some(our)) if d == our);
                                     // In current Meshtastic proto, channel is a u32 field (0 = primary). Treat 0 as Some(0) for uniformity.
                                     let channel = Some(pkt.channel);
-                                    self.text_events.push_back(TextEvent { source: pkt.from, dest, is_direct, channel, content: text.to_string() });
+                                    let (pki_encrypted, public_key) = packet_pki(pkt);
+                                    if let Some(key) = &public_key { self.node_cache.record_public_key(pkt.from, key); }
+                                    self.text_events.push_back(TextEvent { source: pkt.from, dest, is_direct, channel, content: text.to_string(), pki_encrypted, public_key });
                                     return Some(format!("TEXT:{}:{}", pkt.from, text));
                                 }
                             }
@@ -856,7 +1011,9 @@ impl MeshtasticDevice {
                                     let dest = if pkt.to != 0 { Some(pkt.to) } else { None };
                                     let is_direct = matches!((dest, self.our_node_id), (Some(d), Some(our)) if d == our);
                                     let channel = Some(pkt.channel);
-                                    self.text_events.push_back(TextEvent { source: pkt.from, dest, is_direct, channel, content: text.clone() });
+                                    let (pki_encrypted, public_key) = packet_pki(pkt);
+                                    if let Some(key) = &public_key { self.node_cache.record_public_key(pkt.from, key); }
+                                    self.text_events.push_back(TextEvent { source: pkt.from, dest, is_direct, channel, content: text.clone(), pki_encrypted, public_key });
                                     return Some(format!("TEXT:{}:{}", pkt.from, text));
                                 } else {
                                     let hex = data_msg.payload.iter().map(|b| format!("{:02x}", b)).collect::<String>();
@@ -876,6 +1033,9 @@ impl MeshtasticDevice {
                 FRPayload::MyInfo(info) => return Some(format!("MYINFO:{}", info.my_node_num)),
                 FRPayload::NodeInfo(n) => {
                     if let Some(user) = &n.user {
+                        if let KeyUpdate::Changed { .. } = self.node_cache.record_public_key(n.num, &user.public_key) {
+                            warn!("Node {} announced a new public key", n.num);
+                        }
                         return Some(format!("NODE:{}:{}:{}", n.num, user.long_name, user.short_name));
                     } else {
                         return Some(format!("NODE:{}:", n.num));
@@ -971,6 +1131,13 @@ impl MeshtasticDevice {
     /// Retrieve next structured text event if available
     #[allow(dead_code)]
     pub fn next_text_event(&mut self) -> Option<TextEvent> { self.text_events.pop_front() }
+    /// PKI fields for a DM to `dest`, pinned to the key in the node cache
+    fn pki_request(&self, dest: u32) -> (bool, bytes::Bytes) {
+        match self.node_cache.public_key(dest) {
+            Some(key) if dest != 0xffffffff => (true, key.into()),
+            _ => (false, bytes::Bytes::new()),
+        }
+    }
     /// Build and send a text message MeshPacket via ToRadio (feature gated).
     /// to: Some(node_id) for direct, None for broadcast
     /// channel: channel index (0 primary)
@@ -1010,6 +1177,7 @@ impl MeshtasticDevice {
             0 // Broadcast packets don't need ID
         };
         
+        let (pki_encrypted, public_key) = self.pki_request(dest);
         let pkt = MeshPacket {
             from: from_node,
             to: dest,
@@ -1021,6 +1189,8 @@ impl MeshtasticDevice {
             hop_limit: 3, // Default hop limit for mesh routing
             want_ack: is_dm, // Request ACK for DMs to trigger immediate transmission
             priority: if is_dm { 70 } else { 0 }, // Use RELIABLE priority (70) for DMs, DEFAULT (0) for broadcasts
+            pki_encrypted,
+            public_key,
             ..Default::default()
         };
         
@@ -1129,9 +1299,9 @@ impl MeshtasticDevice {
     }
 }
 
-/// Create a shared serial port connection for both reader and writer
+/// Open and initialize a serial port connection for the reader and writer
 #[cfg(feature = "serial")]
-async fn create_shared_serial_port(port_name: &str, baud_rate: u32) -> Result<Arc<Mutex<Box<dyn SerialPort>>>> {
+async fn open_serial_transport(port_name: &str, baud_rate: u32) -> Result<Box<dyn Transport>> {
     debug!("Opening shared serial port {} at {} baud", port_name, baud_rate);
     
     let mut builder = serialport::new(port_name, baud_rate)
@@ -1159,14 +1329,43 @@ async fn create_shared_serial_port(port_name: &str, baud_rate: u32) -> Result<Ar
     }
     
     debug!("Shared serial port initialized successfully");
-    Ok(Arc::new(Mutex::new(port)))
+    Ok(Box::new(transport::SerialTransport::new(port, port_name)))
+}
+
+/// Open the transport selected by `port_name` (`tcp://host[:port]` or a serial device path)
+pub async fn open_transport(port_name: &str, baud_rate: u32) -> Result<SharedTransport> {
+    Ok(Arc::new(Mutex::new(open_transport_boxed(port_name, baud_rate).await?)))
+}
+
+/// Open a fresh, unshared transport. The link supervisor uses this to swap a reopened
+/// device into the existing [`SharedTransport`].
+pub(crate) async fn open_transport_boxed(port_name: &str, baud_rate: u32) -> Result<Box<dyn Transport>> {
+    match TransportAddr::parse(port_name)? {
+        TransportAddr::Tcp(addr) => {
+            debug!("Opening TCP transport to {}", addr);
+            let target = addr.clone();
+            let tcp = tokio::task::spawn_blocking(move || transport::TcpTransport::connect(&target))
+                .await
+                .map_err(|e| anyhow!("TCP connect task failed: {}", e))??;
+            info!("Connected to Meshtastic node over TCP at {}", addr);
+            Ok(Box::new(tcp))
+        }
+        #[cfg(feature = "serial")]
+        TransportAddr::Serial(path) => open_serial_transport(&path, baud_rate).await,
+        #[cfg(not(feature = "serial"))]
+        TransportAddr::Serial(path) => {
+            let _ = baud_rate;
+            warn!("Serial not available, using mock transport for {}", path);
+            Ok(Box::new(transport::NullTransport))
+        }
+    }
 }
 
 #[cfg(feature = "meshtastic-proto")]
 impl MeshtasticReader {
     /// Create a new reader task with shared port
     pub async fn new(
-        shared_port: Arc<Mutex<Box<dyn SerialPort>>>,
+        shared_port: SharedTransport,
         text_event_tx: mpsc::UnboundedSender<TextEvent>,
         control_rx: mpsc::UnboundedReceiver<ControlMessage>,
         writer_control_tx: mpsc::UnboundedSender<ControlMessage>,
@@ -1174,7 +1373,6 @@ impl MeshtasticReader {
     debug!("Initializing Meshtastic reader with shared port");
         
         Ok(MeshtasticReader {
-            #[cfg(feature = "serial")]
             port: shared_port,
             slip: slip::SlipDecoder::new(),
             rx_buf: Vec::new(),
@@ -1186,6 +1384,10 @@ impl MeshtasticReader {
             nodes: std::collections::HashMap::new(),
             our_node_id: None,
             binary_frames_seen: false,
+            link_monitor: None,
+            link_up: true,
+            read_failures: 0,
+            delivery_monitor: None,
         })
     }
     
@@ -1199,6 +1401,7 @@ impl MeshtasticReader {
         info!("Initializing mock Meshtastic reader");
         
         Ok(MeshtasticReader {
+            port: Arc::new(Mutex::new(Box::new(transport::NullTransport))),
             slip: slip::SlipDecoder::new(),
             rx_buf: Vec::new(),
             text_event_tx,
@@ -1209,6 +1412,10 @@ impl MeshtasticReader {
             nodes: std::collections::HashMap::new(),
             our_node_id: None,
             binary_frames_seen: false,
+            link_monitor: None,
+            link_up: true,
+            read_failures: 0,
+            delivery_monitor: None,
         })
     }
 
@@ -1220,6 +1427,9 @@ impl MeshtasticReader {
         if let Err(e) = self.load_node_cache() {
             warn!("Failed to load node cache: {}", e);
         }
+        for (node, key) in self.node_cache.public_keys() {
+            let _ = self.writer_control_tx.send(ControlMessage::PeerKey { node, key });
+        }
         
         let mut interval = tokio::time::interval(Duration::from_millis(10));
         
@@ -1236,6 +1446,26 @@ impl MeshtasticReader {
                             debug!("Reader: binary_frames_seen={}, our_node_id={:?}, node_count={}", 
                                    self.binary_frames_seen, self.our_node_id, self.nodes.len());
                         }
+                        Some(ControlMessage::SetLinkMonitor(tx)) => {
+                            self.link_monitor = Some(tx);
+                        }
+                        Some(ControlMessage::SetDeliveryMonitor(tx)) => {
+                            self.delivery_monitor = Some(tx);
+                        }
+                        Some(ControlMessage::AcceptKey(node)) => {
+                            self.accept_public_key(node);
+                        }
+                        Some(ControlMessage::LinkDown) => {
+                            self.link_up = false;
+                        }
+                        Some(ControlMessage::LinkUp) => {
+                            // Fresh stream: drop partial frames and re-learn our node id from MyInfo
+                            self.link_up = true;
+                            self.read_failures = 0;
+                            self.rx_buf.clear();
+                            self.slip = slip::SlipDecoder::new();
+                            self.our_node_id = None;
+                        }
                         Some(_) => {
                             // Other control messages not handled by reader
                         }
@@ -1271,67 +1501,80 @@ impl MeshtasticReader {
     }
 
     async fn read_and_process(&mut self) -> Result<()> {
-        #[cfg(feature = "serial")]
-        {
-            let mut buffer = [0; 1024];
-            let read_result = {
-                let mut port = self.port.lock().unwrap();
-                port.read(&mut buffer)
-            };
-            
-            match read_result {
-                Ok(bytes_read) if bytes_read > 0 => {
-                    let raw_slice = &buffer[..bytes_read];
-                    trace!("RAW {} bytes: {}", bytes_read, hex_snippet(raw_slice, 64));
-                    
-                    // Try length-prefixed framing first: 0x94 0xC3 len_hi len_lo
-                    self.rx_buf.extend_from_slice(raw_slice);
-                    self.process_framed_messages().await?;
-                    
-                    // Try SLIP framing
-                    let frames = self.slip.push(raw_slice);
-                    if !frames.is_empty() { 
-                        self.binary_frames_seen = true; 
-                    }
-                    for frame in frames {
-                        trace!("SLIP frame {} bytes", frame.len());
-                        self.process_protobuf_frame(&frame).await?;
-                    }
-                    
-                    // Fallback: treat as text for legacy compatibility
-                    let message = String::from_utf8_lossy(raw_slice);
-                    if let Some(parsed) = self.parse_legacy_text(&message) {
-                        debug!("Legacy text message: {}", parsed);
-                        // Convert to TextEvent if possible
-                        if let Some(event) = self.text_to_event(&parsed) {
-                            let _ = self.text_event_tx.send(event);
-                        }
-                    }
-                }
-                Ok(_) => {
-                    // No data available, normal
+        use std::io::Read;
+        let mut buffer = [0; 1024];
+        let read_result = {
+            let mut port = self.port.lock().unwrap();
+            port.read(&mut buffer)
+        };
+
+        match read_result {
+            Ok(bytes_read) if bytes_read > 0 => {
+                self.read_failures = 0;
+                let raw_slice = &buffer[..bytes_read];
+                trace!("RAW {} bytes: {}", bytes_read, hex_snippet(raw_slice, 64));
+
+                // Try length-prefixed framing first: 0x94 0xC3 len_hi len_lo
+                self.rx_buf.extend_from_slice(raw_slice);
+                self.process_framed_messages().await?;
+
+                // Try SLIP framing
+                let frames = self.slip.push(raw_slice);
+                if !frames.is_empty() {
+                    self.binary_frames_seen = true;
                 }
-                Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {
-                    // Timeout is normal
+                for frame in frames {
+                    trace!("SLIP frame {} bytes", frame.len());
+                    self.process_protobuf_frame(&frame).await?;
                 }
-                Err(e) => {
-                    // Log the error but don't kill the reader task
-                    warn!("Serial read error (continuing): {}", e);
-                    // Small delay to prevent tight error loops
-                    sleep(Duration::from_millis(50)).await;
+
+                // Fallback: treat as text for legacy compatibility
+                let message = String::from_utf8_lossy(raw_slice);
+                if let Some(parsed) = self.parse_legacy_text(&message) {
+                    debug!("Legacy text message: {}", parsed);
+                    // Convert to TextEvent if possible
+                    if let Some(event) = self.text_to_event(&parsed) {
+                        let _ = self.text_event_tx.send(event);
+                    }
                 }
             }
+            Ok(_) => {
+                // No data available, normal
+            }
+            Err(ref e) if matches!(e.kind(), std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) => {
+                // Timeout is normal (serial timeout or socket read timeout)
+            }
+            Err(e) => {
+                // Log the error but don't kill the reader task
+                warn!("Device read error (continuing): {}", e);
+                self.note_read_failure(&e);
+                // Small delay to prevent tight error loops
+                sleep(Duration::from_millis(50)).await;
+            }
         }
-        
-        #[cfg(not(feature = "serial"))]
-        {
-            // Mock implementation for testing
-            sleep(Duration::from_millis(100)).await;
-        }
-        
+
         Ok(())
     }
 
+    /// Report the device as lost to the link supervisor when the stream is clearly gone
+    /// (closed/reset/unplugged) or keeps failing.
+    fn note_read_failure(&mut self, e: &std::io::Error) {
+        use std::io::ErrorKind;
+        const MAX_CONSECUTIVE_READ_FAILURES: u32 = 3;
+        if !self.link_up { return; }
+        let Some(monitor) = &self.link_monitor else { return; };
+        self.read_failures += 1;
+        let fatal = matches!(
+            e.kind(),
+            ErrorKind::UnexpectedEof | ErrorKind::BrokenPipe | ErrorKind::NotConnected
+                | ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted
+        );
+        if fatal || self.read_failures >= MAX_CONSECUTIVE_READ_FAILURES {
+            self.link_up = false;
+            let _ = monitor.send(link::LinkEvent::Lost(format!("read failed: {}", e)));
+        }
+    }
+
     async fn process_framed_messages(&mut self) -> Result<()> {
         loop {
             if self.rx_buf.len() < 4 { break; }
@@ -1383,6 +1626,13 @@ impl MeshtasticReader {
                     if let Some(MPPayload::Decoded(data_msg)) = &pkt.payload_variant {
                         let port = PortNum::try_from(data_msg.portnum).unwrap_or(PortNum::UnknownApp);
 
+                        // Traffic originated by a node means it is reachable again (store-and-forward)
+                        if matches!(port, PortNum::TextMessageApp | PortNum::TextMessageCompressedApp | PortNum::PositionApp | PortNum::NodeinfoApp)
+                            && Some(pkt.from) != self.our_node_id
+                        {
+                            if let Some(monitor) = &self.delivery_monitor { let _ = monitor.send(DeliveryEvent::NodeHeard(pkt.from)); }
+                        }
+
                         // Correlate explicit ACKs (priority=ACK and reply_id set)
                         if pkt.priority == 120 && data_msg.reply_id != 0 {
                             debug!(
@@ -1430,12 +1680,16 @@ impl MeshtasticReader {
                                     let is_direct = matches!((dest, self.our_node_id), (Some(d), Some(our)) if d == our);
                                     let channel = Some(pkt.channel);
                                     
+                                    let (pki_encrypted, public_key) = packet_pki(pkt);
+                                    if let Some(key) = &public_key { self.learn_public_key(pkt.from, key); }
                                     let event = TextEvent { 
                                         source: pkt.from, 
                                         dest, 
                                         is_direct, 
                                         channel, 
-                                        content: text.to_string() 
+                                        content: text.to_string(),
+                                        pki_encrypted,
+                                        public_key,
                                     };
                                     
                                     let _ = self.text_event_tx.send(event);
@@ -1454,12 +1708,16 @@ impl MeshtasticReader {
                                     let is_direct = matches!((dest, self.our_node_id), (Some(d), Some(our)) if d == our);
                                     let channel = Some(pkt.channel);
                                     
+                                    let (pki_encrypted, public_key) = packet_pki(pkt);
+                                    if let Some(key) = &public_key { self.learn_public_key(pkt.from, key); }
                                     let event = TextEvent { 
                                         source: pkt.from, 
                                         dest, 
                                         is_direct, 
                                         channel, 
-                                        content: text
+                                        content: text,
+                                        pki_encrypted,
+                                        public_key,
                                     };
                                     
                                     let _ = self.text_event_tx.send(event);
@@ -1499,6 +1757,7 @@ impl MeshtasticReader {
                         
                         self.nodes.insert(n.num, n.clone());
                         self.node_cache.update_node(n.num, long_name, short_name);
+                        self.learn_public_key(n.num, &user.public_key);
                         
                         // Save cache (best effort)
                         if let Err(e) = self.save_node_cache() {
@@ -1613,6 +1872,42 @@ impl MeshtasticReader {
         None
     }
 
+    /// Keep a public key a node announced (node info or a PKI packet): hand a first key to
+    /// the writer, and report a change from the key on file as a security event. DMs stay
+    /// pinned to the old key until a sysop accepts the new one ([`ControlMessage::AcceptKey`]).
+    fn learn_public_key(&mut self, node: u32, key: &[u8]) {
+        match self.node_cache.record_public_key(node, key) {
+            KeyUpdate::Unchanged => {}
+            KeyUpdate::New => {
+                debug!("Learned public key of node {}", node);
+                let _ = self.writer_control_tx.send(ControlMessage::PeerKey { node, key: key.to_vec() });
+                if let Err(e) = self.save_node_cache() {
+                    debug!("Failed to save node cache: {}", e);
+                }
+            }
+            KeyUpdate::Changed { previous } => {
+                warn!("Node {} announced a new public key; DMs stay pinned to the old one", node);
+                let current = self.node_cache.nodes.get(&node).and_then(|n| n.pending_public_key.clone()).unwrap_or_default();
+                if let Some(monitor) = &self.delivery_monitor {
+                    let _ = monitor.send(DeliveryEvent::KeyChanged { node, previous, current });
+                }
+            }
+        }
+    }
+
+    /// Pin the pending key of `node` after a sysop accepted it
+    fn accept_public_key(&mut self, node: u32) {
+        let Some(key) = self.node_cache.accept_public_key(node) else {
+            debug!("No pending public key for node {}", node);
+            return;
+        };
+        info!("Accepted new public key of node {}", node);
+        let _ = self.writer_control_tx.send(ControlMessage::PeerKey { node, key });
+        if let Err(e) = self.save_node_cache() {
+            debug!("Failed to save node cache: {}", e);
+        }
+    }
+
     fn text_to_event(&self, text: &str) -> Option<TextEvent> {
         // Parse legacy text format into TextEvent
         if let Some(colon_pos) = text.find(':') {
@@ -1626,6 +1921,7 @@ impl MeshtasticReader {
                     is_direct: false, // Assume public for legacy
                     channel: Some(0), // Assume primary channel
                     content: content.to_string(),
+                    ..Default::default()
                 });
             }
         }
@@ -1679,7 +1975,7 @@ impl MeshtasticReader {
 impl MeshtasticWriter {
     /// Create a new writer task with shared port
     pub async fn new(
-        shared_port: Arc<Mutex<Box<dyn SerialPort>>>,
+        shared_port: SharedTransport,
         outgoing_rx: mpsc::UnboundedReceiver<OutgoingMessage>,
         control_rx: mpsc::UnboundedReceiver<ControlMessage>,
         tuning: WriterTuning,
@@ -1687,7 +1983,6 @@ impl MeshtasticWriter {
     debug!("Initializing Meshtastic writer with shared port");
         
         Ok(MeshtasticWriter {
-            #[cfg(feature = "serial")]
             port: shared_port,
             outgoing_rx,
             control_rx,
@@ -1700,6 +1995,11 @@ impl MeshtasticWriter {
             last_text_send: None,
             tuning,
             scheduler: None,
+            link_monitor: None,
+            link_up: true,
+            held: VecDeque::new(),
+            delivery_monitor: None,
+            peer_keys: std::collections::HashMap::new(),
         })
     }
     
@@ -1713,6 +2013,7 @@ impl MeshtasticWriter {
     debug!("Initializing mock Meshtastic writer");
         
         Ok(MeshtasticWriter {
+            port: Arc::new(Mutex::new(Box::new(transport::NullTransport))),
             outgoing_rx,
             control_rx,
             our_node_id: None,
@@ -1724,6 +2025,11 @@ impl MeshtasticWriter {
             last_text_send: None,
             tuning,
             scheduler: None,
+            link_monitor: None,
+            link_up: true,
+            held: VecDeque::new(),
+            delivery_monitor: None,
+            peer_keys: std::collections::HashMap::new(),
         })
     }
 
@@ -1751,8 +2057,15 @@ impl MeshtasticWriter {
                     match msg {
                         Some(outgoing) => {
                             match outgoing.kind.clone() {
+                                OutgoingKind::Normal if !self.link_up => self.hold(outgoing),
                                 OutgoingKind::Normal => {
-                                    if let Err(e) = self.send_message(&outgoing).await { error!("Failed to send message: {}", e); }
+                                    if let Err(e) = self.send_message(&outgoing).await {
+                                        if self.report_link_error(&e) { self.hold(outgoing); } else { error!("Failed to send message: {}", e); }
+                                    }
+                                }
+                                OutgoingKind::Retry { id } if !self.link_up => {
+                                    // Don't burn attempts against a missing device
+                                    self.defer_retry(id);
                                 }
                                 OutgoingKind::Retry { id } => {
                                     // Only act if still pending and due
@@ -1764,12 +2077,14 @@ impl MeshtasticWriter {
                                                 let expired = self.pending.remove(&id).unwrap();
                                                 metrics::inc_reliable_failed();
                                                 warn!("Failed id={} to=0x{:08x} ({}): max attempts reached (scheduler retry)", id, expired.to, expired.content_preview);
+                                                self.report_undelivered(expired);
                                             } else {
                                                 // perform resend
                                                 let full = ready.full_content.clone();
                                                 let to = ready.to; let channel = ready.channel;
                                                 if let Err(e) = self.resend_text_packet(id, to, channel, &full).await {
                                                     warn!("Resend failed id={} to=0x{:08x}: {}", id, to, e);
+                                                    if self.report_link_error(&e) { self.defer_retry(id); }
                                                 } else {
                                                     // Advance backoff index and schedule next retry if still under attempt limit
                                                     let backoffs = &self.tuning.dm_resend_backoff_seconds;
@@ -1789,7 +2104,7 @@ impl MeshtasticWriter {
                                                                     MessageCategory::Retry,
                                                                     Priority::High,
                                                                     next_delay,
-                                                                    OutgoingMessage { to_node: Some(to), channel, content: String::new(), priority: MessagePriority::High, kind: OutgoingKind::Retry { id }, request_ack: false }
+                                                                    OutgoingMessage { to_node: Some(to), channel, content: String::new(), priority: MessagePriority::High, kind: OutgoingKind::Retry { id }, request_ack: false, durable: false }
                                                                 );
                                                                 sched.enqueue(retry_env);
                                                             }
@@ -1806,7 +2121,7 @@ impl MeshtasticWriter {
                                                     MessageCategory::Retry,
                                                     Priority::High,
                                                     remaining,
-                                                    OutgoingMessage { to_node: Some(ready.to), channel: ready.channel, content: String::new(), priority: MessagePriority::High, kind: OutgoingKind::Retry { id }, request_ack: false }
+                                                    OutgoingMessage { to_node: Some(ready.to), channel: ready.channel, content: String::new(), priority: MessagePriority::High, kind: OutgoingKind::Retry { id }, request_ack: false, durable: false }
                                                 );
                                                 sched.enqueue(retry_env);
                                             }
@@ -1883,6 +2198,7 @@ impl MeshtasticWriter {
                                 if let Some(p) = self.pending.remove(&id) {
                                     metrics::inc_reliable_failed();
                                     warn!("Failed id={} to=0x{:08x} ({}): reason={} ({})", id, p.to, p.content_preview, reason, reason_name);
+                                    self.report_undelivered(p);
                                 } else {
                                     warn!("Failed id={} (routing error, no pending entry): reason={} ({})", id, reason, reason_name);
                                 }
@@ -1907,6 +2223,35 @@ impl MeshtasticWriter {
                             self.scheduler = Some(handle);
                             debug!("Writer: scheduler handle attached for retry scheduling");
                         }
+                        Some(ControlMessage::SetLinkMonitor(tx)) => {
+                            self.link_monitor = Some(tx);
+                        }
+                        Some(ControlMessage::SetDeliveryMonitor(tx)) => {
+                            self.delivery_monitor = Some(tx);
+                        }
+                        Some(ControlMessage::PeerKey { node, key }) => {
+                            self.peer_keys.insert(node, key);
+                        }
+                        Some(ControlMessage::SetTuning(tuning)) => {
+                            info!("Writer: tuning updated (min_send_gap_ms={}, dm_to_dm_gap_ms={}, backoff={:?})", tuning.min_send_gap_ms, tuning.dm_to_dm_gap_ms, tuning.dm_resend_backoff_seconds);
+                            self.tuning = tuning;
+                        }
+                        Some(ControlMessage::LinkDown) => {
+                            if self.link_up { info!("Writer: radio link down, holding outbound messages"); }
+                            self.link_up = false;
+                        }
+                        Some(ControlMessage::LinkUp) => {
+                            self.link_up = true;
+                            let mut id: u32 = rand::random();
+                            if id == 0 { id = 1; }
+                            self.config_request_id = Some(id);
+                            info!("Re-requesting config after reconnect (want_config_id=0x{:08x})", id);
+                            match self.send_want_config(id) {
+                                Err(e) if self.report_link_error(&e) => {}
+                                Err(e) => warn!("Config request after reconnect failed: {}", e),
+                                Ok(()) => self.flush_held().await,
+                            }
+                        }
                         Some(_) => {
                             // Other control messages
                         }
@@ -1919,8 +2264,13 @@ impl MeshtasticWriter {
                 
                 // Periodic heartbeat
                 _ = heartbeat_interval.tick() => {
-                    // periodic heartbeat
-                    if let Err(e) = self.send_heartbeat() { debug!("Heartbeat send error: {:?}", e); }
+                    // periodic heartbeat (skipped while the link is down)
+                    if self.link_up {
+                        if let Err(e) = self.send_heartbeat() {
+                            debug!("Heartbeat send error: {:?}", e);
+                            self.report_link_error(&e);
+                        }
+                    }
                     // expire stale broadcast ack trackers
                     let now = std::time::Instant::now();
                     let expired: Vec<u32> = self.pending_broadcast.iter()
@@ -2007,6 +2357,7 @@ impl MeshtasticWriter {
             }
         }
         
+        let (pki_encrypted, public_key) = self.pki_request(dest);
         let pkt = MeshPacket {
             from: from_node,
             to: dest,
@@ -2018,26 +2369,20 @@ impl MeshtasticWriter {
             hop_limit: 3,
             want_ack: is_dm || wants_ack_broadcast,
             priority,
+            pki_encrypted,
+            public_key,
             ..Default::default()
         };
         
         let toradio = ToRadio { payload_variant: Some(TRPayload::Packet(pkt)) };
         
-        #[cfg(feature = "serial")]
         {
             let mut payload = Vec::with_capacity(128);
             toradio.encode(&mut payload)?;
-            let mut hdr = [0u8; 4];
-            hdr[0] = 0x94;
-            hdr[1] = 0xC3;
-            hdr[2] = ((payload.len() >> 8) & 0xFF) as u8;
-            hdr[3] = (payload.len() & 0xFF) as u8;
             
             {
                 let mut port = self.port.lock().unwrap();
-                port.write_all(&hdr)?;
-                port.write_all(&payload)?;
-                port.flush()?;
+                transport::write_frame(&mut **port, &payload)?;
             }
             
             // Small delay to allow OS to flush the serial buffer
@@ -2080,6 +2425,7 @@ impl MeshtasticWriter {
                     next_due: now + std::time::Duration::from_secs(*self.tuning.dm_resend_backoff_seconds.first().unwrap_or(&4)),
                     backoff_idx: 0,
                     sent_at: now,
+                    durable: msg.durable,
                 });
                 metrics::inc_reliable_sent();
                 // Schedule first retry envelope via central scheduler (Retry category) if handle attached
@@ -2097,6 +2443,7 @@ impl MeshtasticWriter {
                             priority: MessagePriority::High,
                             kind: OutgoingKind::Retry { id: packet_id },
                             request_ack: false,
+                            durable: false,
                         }
                     );
                     sched.enqueue(retry_env);
@@ -2118,11 +2465,6 @@ impl MeshtasticWriter {
             }
         }
         
-        #[cfg(not(feature = "serial"))]
-        {
-            debug!("(mock) Would send TextPacket to 0x{:08x}: '{}'", dest, msg.content);
-        }
-        
         Ok(())
     }
 
@@ -2147,6 +2489,7 @@ impl MeshtasticWriter {
             emoji: 0,
             bitfield: None,
         };
+        let (pki_encrypted, public_key) = self.pki_request(dest);
         let pkt = MeshPacket {
             from: from_node,
             to: dest,
@@ -2158,29 +2501,37 @@ impl MeshtasticWriter {
             hop_limit: 3,
             want_ack: true,
             priority: 70,
+            pki_encrypted,
+            public_key,
             ..Default::default()
         };
         let toradio = ToRadio { payload_variant: Some(TRPayload::Packet(pkt)) };
 
-        #[cfg(feature = "serial")]
         {
             // Enforce minimum gap between text packet transmissions
             let min_gap = std::cmp::max(self.tuning.min_send_gap_ms, 2000);
             self.enforce_min_send_gap(Duration::from_millis(min_gap)).await;
             let mut payload = Vec::with_capacity(128);
             toradio.encode(&mut payload)?;
-            let mut hdr = [0u8; 4];
-            hdr[0] = 0x94; hdr[1] = 0xC3;
-            hdr[2] = ((payload.len() >> 8) & 0xFF) as u8;
-            hdr[3] = (payload.len() & 0xFF) as u8;
-            let mut port = self.port.lock().unwrap();
-            port.write_all(&hdr)?; port.write_all(&payload)?; port.flush()?;
+            {
+                let mut port = self.port.lock().unwrap();
+                transport::write_frame(&mut **port, &payload)?;
+            }
             debug!("Re-sent TextPacket DM: to=0x{:08x} channel={} id={} priority=70 ({} bytes)", dest, channel, packet_id, payload.len());
             self.last_text_send = Some(std::time::Instant::now());
         }
         Ok(())
     }
 
+    /// PKI fields for a packet to `dest`: DMs to a node whose key the reader has learned ask
+    /// the radio for PKI encryption pinned to that key (unless `security.pki.encrypt_dms` is off).
+    fn pki_request(&self, dest: u32) -> (bool, bytes::Bytes) {
+        match self.peer_keys.get(&dest) {
+            Some(key) if self.tuning.pki_dms && dest != 0xffffffff => (true, key.clone().into()),
+            _ => (false, bytes::Bytes::new()),
+        }
+    }
+
     /// Ensure at least `min_gap` has elapsed since the last text packet send
     async fn enforce_min_send_gap(&mut self, min_gap: Duration) {
         if let Some(last) = self.last_text_send {
@@ -2218,30 +2569,88 @@ impl MeshtasticWriter {
     fn send_toradio(&mut self, msg: proto::ToRadio) -> Result<()> {
         use prost::Message;
         
-        #[cfg(feature = "serial")]
         {
             let mut payload = Vec::with_capacity(256);
             msg.encode(&mut payload)?;
             if payload.len() > u16::MAX as usize { 
                 return Err(anyhow!("payload too large")); 
             }
-            let mut hdr = [0u8; 4];
-            hdr[0] = 0x94;
-            hdr[1] = 0xC3;
-            hdr[2] = ((payload.len() >> 8) & 0xFF) as u8;
-            hdr[3] = (payload.len() & 0xFF) as u8;
             
             let mut port = self.port.lock().unwrap();
-            port.write_all(&hdr)?;
-            port.write_all(&payload)?;
-            port.flush()?;
+            transport::write_frame(&mut **port, &payload)?;
             
             debug!("Sent ToRadio LEN frame ({} bytes payload)", payload.len());
         }
         Ok(())
     }
 
-    // Removed ensure_want_config: WantConfigId is now sent only once at startup.
+    // Removed ensure_want_config: WantConfigId is sent at startup and after each reconnect.
+
+    /// If `e` is a device I/O failure and a link supervisor is attached, mark the link
+    /// down and report it. Returns true when the failure was treated as link loss.
+    fn report_link_error(&mut self, e: &anyhow::Error) -> bool {
+        let Some(monitor) = &self.link_monitor else { return false; };
+        if e.downcast_ref::<std::io::Error>().is_none() { return false; }
+        if self.link_up {
+            self.link_up = false;
+            let _ = monitor.send(link::LinkEvent::Lost(format!("write failed: {}", e)));
+        }
+        true
+    }
+
+    /// Hand a durable DM that could not be delivered back to the BBS for store-and-forward.
+    fn report_undelivered(&self, p: PendingSend) {
+        if !p.durable { return; }
+        if let Some(monitor) = &self.delivery_monitor {
+            let _ = monitor.send(DeliveryEvent::Undelivered { to: p.to, channel: p.channel, content: p.full_content });
+        }
+    }
+
+    /// Keep an outbound message until the link is back, dropping the oldest when full.
+    fn hold(&mut self, msg: OutgoingMessage) {
+        if self.held.len() >= MAX_HELD_WHILE_DOWN {
+            if let Some(dropped) = self.held.pop_front() {
+                warn!("Radio link down: outbound queue full, dropping oldest message to {:?}", dropped.to_node);
+            }
+        }
+        self.held.push_back(msg);
+    }
+
+    /// Send everything held during an outage, in order, until done or the link drops again.
+    async fn flush_held(&mut self) {
+        if !self.held.is_empty() {
+            info!("Radio link restored: sending {} held message(s)", self.held.len());
+        }
+        while self.link_up {
+            let Some(msg) = self.held.pop_front() else { break; };
+            if let Err(e) = self.send_message(&msg).await {
+                if self.report_link_error(&e) {
+                    self.held.push_front(msg);
+                } else {
+                    error!("Failed to send held message: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Push a pending reliable send out by its current backoff stage and re-enqueue its retry.
+    fn defer_retry(&mut self, id: u32) {
+        let Some(p) = self.pending.get_mut(&id) else { return; };
+        let backoffs = &self.tuning.dm_resend_backoff_seconds;
+        let stage = backoffs.get(p.backoff_idx as usize).copied().unwrap_or_else(|| *backoffs.last().unwrap_or(&16));
+        let delay = std::time::Duration::from_secs(stage);
+        p.next_due = std::time::Instant::now() + delay;
+        if let Some(sched) = &self.scheduler {
+            use crate::bbs::dispatch::{MessageEnvelope, MessageCategory, Priority};
+            let retry_env = MessageEnvelope::new(
+                MessageCategory::Retry,
+                Priority::High,
+                delay,
+                OutgoingMessage { to_node: Some(p.to), channel: p.channel, content: String::new(), priority: MessagePriority::High, kind: OutgoingKind::Retry { id }, request_ack: false, durable: false }
+            );
+            sched.enqueue(retry_env);
+        }
+    }
 
     #[allow(dead_code)]
     pub fn set_our_node_id(&mut self, node_id: u32) {
@@ -2264,30 +2673,33 @@ pub async fn create_reader_writer_system(
     mpsc::UnboundedSender<ControlMessage>,
     mpsc::UnboundedSender<ControlMessage>,
 )> {
-    // Create shared serial port
-    #[cfg(feature = "serial")]
-    let shared_port = create_shared_serial_port(port_name, baud_rate).await?;
-    
+    // Open the shared transport (serial device or tcp://host:port)
+    let shared_port = open_transport(port_name, baud_rate).await?;
+    create_reader_writer_system_with_transport(shared_port, tuning).await
+}
+
+/// Build the reader/writer pair on an already opened transport (e.g. the simulator in [`sim`])
+#[cfg(feature = "meshtastic-proto")]
+pub async fn create_reader_writer_system_with_transport(
+    shared_port: SharedTransport,
+    tuning: WriterTuning,
+) -> Result<(
+    MeshtasticReader,
+    MeshtasticWriter,
+    mpsc::UnboundedReceiver<TextEvent>,
+    mpsc::UnboundedSender<OutgoingMessage>,
+    mpsc::UnboundedSender<ControlMessage>,
+    mpsc::UnboundedSender<ControlMessage>,
+)> {
     // Create channels
     let (text_event_tx, text_event_rx) = mpsc::unbounded_channel::<TextEvent>();
     let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel::<OutgoingMessage>();
     let (reader_control_tx, reader_control_rx) = mpsc::unbounded_channel::<ControlMessage>();
     let (writer_control_tx, writer_control_rx) = mpsc::unbounded_channel::<ControlMessage>();
 
-    // Create reader and writer with shared port
-    #[cfg(feature = "serial")]
+    // Create reader and writer with shared transport
     let reader = MeshtasticReader::new(shared_port.clone(), text_event_tx, reader_control_rx, writer_control_tx.clone()).await?;
-    #[cfg(feature = "serial")]
-    let writer = MeshtasticWriter::new(shared_port, outgoing_rx, writer_control_rx, tuning.clone()).await?;
-
-    #[cfg(not(feature = "serial"))]
-    let (reader, writer) = {
-        warn!("Serial not available, using mock reader/writer");
-        (
-            MeshtasticReader::new_mock(text_event_tx, reader_control_rx, writer_control_tx.clone()).await?,
-            MeshtasticWriter::new_mock(outgoing_rx, writer_control_rx, tuning).await?,
-        )
-    };
+    let writer = MeshtasticWriter::new(shared_port, outgoing_rx, writer_control_rx, tuning).await?;
 
     Ok((reader, writer, text_event_rx, outgoing_tx, reader_control_tx, writer_control_tx))
 }
@@ -0,0 +1,670 @@
+//! Virtual Meshtastic radio for hardware-free end-to-end testing.
+//!
+//! [`start`] returns a [`SharedTransport`] that speaks the real stream API
+//! (`0x94 0xC3 <len> <protobuf>` carrying `ToRadio` / `FromRadio`) over an in-memory
+//! byte channel, plus a [`SimHandle`] used to play the part of the rest of the mesh.
+//! The transport plugs into the normal reader/writer/scheduler pipeline
+//! (see [`BbsServer::connect_transport`](crate::bbs::BbsServer::connect_transport)),
+//! so everything above the byte stream runs exactly as it does against a radio.
+//!
+//! The emulated radio:
+//! - answers `want_config_id` with `MyNodeInfo`, one `NodeInfo` per virtual node and
+//!   `config_complete_id`
+//! - delivers text packets sent by the BBS to the [`SimHandle`], de-duplicating retries
+//!   of the same packet id like a receiving node would
+//! - answers `want_ack` packets with a routing ACK (`Routing.error_reason = NONE`), or
+//!   with an injected routing error (see [`SimHandle::fail_next`]); DMs to unknown nodes
+//!   get `NO_ROUTE`
+//! - drops outbound packets with a configurable probability (no delivery, no ACK), which
+//!   exercises the writer's retry path
+//! - can be unplugged and plugged back in ([`SimHandle::unplug`]); while unplugged the
+//!   stream fails like a lost USB device and [`SimHandle::opener`] cannot reopen it, which
+//!   exercises the [link supervisor](super::link)
+//! - can play a node with a PKI key ([`SimHandle::announce_key`], [`SimHandle::send_pki_dm`]);
+//!   deliveries record whether the BBS asked for PKI encryption
+//!
+//! # Scripts
+//!
+//! [`run_script`] drives a session from a small line-oriented script and writes a
+//! transcript. It backs the `meshbbs simulate` subcommand and integration tests.
+//!
+//! ```text
+//! # node numbers refer to virtual nodes (1-based) or raw ids as !hex
+//! dm 1 REGISTER alice password123      # node 1 sends a DM to the BBS
+//! expect 1 Registered as alice         # wait for a DM to node 1 containing the text
+//! public 2 0 ^HELP                     # node 2 posts on channel 0
+//! position 2                           # node 2 broadcasts a position report
+//! expect *                            # wait for any broadcast (text is optional)
+//! fail RATE_LIMIT_EXCEEDED             # next reliable packet gets this routing error
+//! loss 0.25                            # change the outbound loss rate
+//! timeout 60                           # seconds to wait in later `expect` lines
+//! sleep 1.5                            # let time pass
+//! unplug                               # drop the device
+//! plug                                 # reattach and wait for the BBS to reconnect
+//! ```
+
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+use prost::Message;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use super::transport::{self, SharedTransport, Transport};
+use crate::protobuf::meshtastic_generated as proto;
+use proto::routing::Error as RoutingError;
+
+/// Default `expect` timeout for scripts. Outgoing text is paced at >= 2s per packet.
+const DEFAULT_EXPECT_TIMEOUT: Duration = Duration::from_secs(30);
+const BROADCAST_ADDR: u32 = 0xffff_ffff;
+
+/// A virtual node on the simulated mesh.
+#[derive(Debug, Clone)]
+pub struct SimNode {
+    pub id: u32,
+    pub long_name: String,
+    pub short_name: String,
+}
+
+/// Simulated radio setup.
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// Node number reported to the BBS in `MyNodeInfo`
+    pub our_node: u32,
+    /// Other nodes on the mesh
+    pub nodes: Vec<SimNode>,
+    /// Probability (0.0-1.0) that a packet sent by the BBS is lost
+    pub loss_rate: f32,
+    /// Seed for the loss RNG (random when `None`)
+    pub seed: Option<u64>,
+}
+
+impl SimConfig {
+    /// Configuration with `count` virtual nodes named `Sim Node 1..=count`.
+    pub fn with_nodes(count: usize) -> Self {
+        let nodes = (1..=count)
+            .map(|i| SimNode {
+                id: 0x5100_0000 + i as u32,
+                long_name: format!("Sim Node {}", i),
+                short_name: format!("SIM{}", i),
+            })
+            .collect();
+        SimConfig { our_node: 0x0BB5_0001, nodes, loss_rate: 0.0, seed: None }
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self { Self::with_nodes(2) }
+}
+
+/// A text packet the BBS put on the air and the simulated mesh accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimDelivery {
+    pub from: u32,
+    /// Destination node; `None` for broadcasts
+    pub to: Option<u32>,
+    pub channel: u32,
+    pub text: String,
+    /// The BBS asked the radio to PKI-encrypt it
+    pub pki_encrypted: bool,
+    /// The key it was pinned to (empty without PKI)
+    pub public_key: Vec<u8>,
+}
+
+/// Counters kept by the simulated radio.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimStats {
+    /// Text packets delivered to the mesh (retries of a delivered packet excluded)
+    pub delivered: u64,
+    /// Text packets lost to the configured loss rate
+    pub dropped: u64,
+    /// Retries of an already delivered packet (ACKed again, not re-delivered)
+    pub duplicates: u64,
+    /// Routing ACKs sent back to the BBS
+    pub acks: u64,
+    /// Routing errors sent back to the BBS
+    pub routing_errors: u64,
+    /// `want_config` handshakes answered (one per connect/reconnect)
+    pub handshakes: u64,
+}
+
+struct SimState {
+    loss_rate: f32,
+    rng: StdRng,
+    fail_next: VecDeque<RoutingError>,
+    stats: SimStats,
+    /// Device plugged in; when false the stream fails and cannot be reopened
+    attached: bool,
+    /// Bumped on unplug so streams opened before it stay dead after a replug
+    generation: u64,
+}
+
+/// Start a simulated radio. Returns the transport for the BBS side and a handle for the mesh side.
+pub fn start(config: SimConfig) -> (SharedTransport, SimHandle) {
+    let (to_bbs_tx, to_bbs_rx) = std_mpsc::channel::<Vec<u8>>();
+    let (to_radio_tx, to_radio_rx) = std_mpsc::channel::<Vec<u8>>();
+    let (delivery_tx, delivery_rx) = mpsc::unbounded_channel();
+    let rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let state = Arc::new(Mutex::new(SimState {
+        loss_rate: config.loss_rate.clamp(0.0, 1.0),
+        rng,
+        fail_next: VecDeque::new(),
+        stats: SimStats::default(),
+        attached: true,
+        generation: 0,
+    }));
+
+    let radio = SimRadio {
+        config: config.clone(),
+        to_bbs: to_bbs_tx.clone(),
+        deliveries: delivery_tx,
+        state: state.clone(),
+        delivered_ids: HashSet::new(),
+        next_id: 1,
+    };
+    std::thread::Builder::new()
+        .name("meshbbs-sim".into())
+        .spawn(move || radio.run(to_radio_rx))
+        .expect("spawn simulated radio thread");
+
+    let bbs_rx = Arc::new(Mutex::new(to_bbs_rx));
+    let transport = SimTransport { rx: bbs_rx.clone(), tx: to_radio_tx.clone(), pending: VecDeque::new(), state: state.clone(), generation: 0 };
+    let handle = SimHandle {
+        to_bbs: to_bbs_tx,
+        bbs_rx,
+        to_radio: to_radio_tx,
+        deliveries: delivery_rx,
+        state,
+        our_node: config.our_node,
+        nodes: config.nodes.iter().map(|n| n.id).collect(),
+        next_id: 0x8000_0000,
+    };
+    (Arc::new(Mutex::new(Box::new(transport))), handle)
+}
+
+/// BBS side of the in-memory byte stream.
+struct SimTransport {
+    rx: Arc<Mutex<std_mpsc::Receiver<Vec<u8>>>>,
+    tx: std_mpsc::Sender<Vec<u8>>,
+    pending: VecDeque<u8>,
+    state: Arc<Mutex<SimState>>,
+    generation: u64,
+}
+
+impl SimTransport {
+    fn check_attached(&self) -> io::Result<()> {
+        let st = self.state.lock().unwrap();
+        if st.attached && st.generation == self.generation { Ok(()) }
+        else { Err(io::Error::new(io::ErrorKind::BrokenPipe, "simulated radio unplugged")) }
+    }
+}
+
+impl Read for SimTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.check_attached()?;
+        // Never block: the reader holds the shared transport lock while reading and
+        // already polls on a short interval, so the writer must not be kept waiting.
+        {
+            let rx = self.rx.lock().unwrap();
+            while let Ok(chunk) = rx.try_recv() {
+                self.pending.extend(chunk);
+            }
+        }
+        if self.pending.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "no data from simulated radio"));
+        }
+        let n = buf.len().min(self.pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.pending.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
+
+impl Write for SimTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_attached()?;
+        self.tx
+            .send(buf.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "simulated radio stopped"))?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Transport for SimTransport {
+    fn describe(&self) -> String { "sim://virtual-radio".to_string() }
+}
+
+fn encode_from_radio(msg: &proto::FromRadio) -> Vec<u8> {
+    let payload = msg.encode_to_vec();
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    // Writing into a Vec cannot fail
+    let _ = transport::write_frame(&mut frame, &payload);
+    frame
+}
+
+/// Radio side: runs on its own thread until the BBS side of the stream is dropped.
+struct SimRadio {
+    config: SimConfig,
+    to_bbs: std_mpsc::Sender<Vec<u8>>,
+    deliveries: mpsc::UnboundedSender<SimDelivery>,
+    state: Arc<Mutex<SimState>>,
+    delivered_ids: HashSet<(u32, u32)>,
+    next_id: u32,
+}
+
+impl SimRadio {
+    fn run(mut self, rx: std_mpsc::Receiver<Vec<u8>>) {
+        let mut buf: Vec<u8> = Vec::new();
+        while let Ok(chunk) = rx.recv() {
+            buf.extend_from_slice(&chunk);
+            loop {
+                // Resync on the frame header, discarding anything else
+                match buf.windows(2).position(|w| w == [0x94, 0xC3]) {
+                    Some(0) => {}
+                    Some(pos) => { buf.drain(..pos); }
+                    None => { let keep = usize::from(buf.last() == Some(&0x94)); buf.drain(..buf.len() - keep); break; }
+                }
+                if buf.len() < 4 { break; }
+                let len = ((buf[2] as usize) << 8) | buf[3] as usize;
+                if buf.len() < 4 + len { break; }
+                let frame: Vec<u8> = buf.drain(..4 + len).skip(4).collect();
+                match proto::ToRadio::decode(&frame[..]) {
+                    Ok(msg) => self.handle_to_radio(msg),
+                    Err(e) => debug!("sim: undecodable ToRadio frame ({} bytes): {}", len, e),
+                }
+            }
+        }
+        debug!("sim: BBS side closed, simulated radio stopping");
+    }
+
+    fn send(&self, variant: proto::from_radio::PayloadVariant) {
+        let msg = proto::FromRadio { payload_variant: Some(variant), ..Default::default() };
+        let _ = self.to_bbs.send(encode_from_radio(&msg));
+    }
+
+    fn handle_to_radio(&mut self, msg: proto::ToRadio) {
+        use proto::from_radio::PayloadVariant as FR;
+        use proto::to_radio::PayloadVariant as TR;
+        match msg.payload_variant {
+            Some(TR::WantConfigId(id)) => {
+                debug!("sim: config handshake (want_config_id=0x{:08x})", id);
+                self.send(FR::MyInfo(proto::MyNodeInfo { my_node_num: self.config.our_node, ..Default::default() }));
+                for node in &self.config.nodes {
+                    self.send(FR::NodeInfo(proto::NodeInfo {
+                        num: node.id,
+                        user: Some(proto::User {
+                            id: format!("!{:08x}", node.id),
+                            long_name: node.long_name.clone(),
+                            short_name: node.short_name.clone(),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
+                }
+                self.send(FR::ConfigCompleteId(id));
+                self.state.lock().unwrap().stats.handshakes += 1;
+            }
+            Some(TR::Packet(pkt)) => self.handle_packet(pkt),
+            Some(TR::Heartbeat(_)) => trace!("sim: heartbeat"),
+            _ => trace!("sim: ignoring ToRadio message"),
+        }
+    }
+
+    fn handle_packet(&mut self, pkt: proto::MeshPacket) {
+        use proto::mesh_packet::PayloadVariant as MP;
+        let Some(MP::Decoded(data)) = &pkt.payload_variant else { return };
+        if data.portnum != proto::PortNum::TextMessageApp as i32 {
+            trace!("sim: ignoring non-text packet port={}", data.portnum);
+            return;
+        }
+        let to = if pkt.to == BROADCAST_ADDR { None } else { Some(pkt.to) };
+        let outcome = {
+            let mut st = self.state.lock().unwrap();
+            let loss = st.loss_rate;
+            if loss > 0.0 && st.rng.gen::<f32>() < loss {
+                st.stats.dropped += 1;
+                None
+            } else if pkt.want_ack && !st.fail_next.is_empty() {
+                st.stats.routing_errors += 1;
+                st.fail_next.pop_front()
+            } else if matches!(to, Some(dest) if !self.config.nodes.iter().any(|n| n.id == dest)) {
+                st.stats.routing_errors += 1;
+                Some(RoutingError::NoRoute)
+            } else {
+                Some(RoutingError::None)
+            }
+        };
+        let Some(result) = outcome else {
+            debug!("sim: dropped packet id={} to=0x{:08x}", pkt.id, pkt.to);
+            return;
+        };
+        if result == RoutingError::None {
+            let first_copy = pkt.id == 0 || self.delivered_ids.insert((pkt.to, pkt.id));
+            if first_copy {
+                self.state.lock().unwrap().stats.delivered += 1;
+                let _ = self.deliveries.send(SimDelivery {
+                    from: pkt.from,
+                    to,
+                    channel: pkt.channel,
+                    text: String::from_utf8_lossy(&data.payload).to_string(),
+                    pki_encrypted: pkt.pki_encrypted,
+                    public_key: pkt.public_key.to_vec(),
+                });
+            } else {
+                self.state.lock().unwrap().stats.duplicates += 1;
+            }
+        }
+        if pkt.want_ack && pkt.id != 0 {
+            if result == RoutingError::None { self.state.lock().unwrap().stats.acks += 1; }
+            self.send_routing(&pkt, to, result);
+        }
+    }
+
+    /// Answer a reliable packet with a `Routing` message correlated by `request_id`.
+    fn send_routing(&mut self, pkt: &proto::MeshPacket, to: Option<u32>, reason: RoutingError) {
+        use proto::mesh_packet::PayloadVariant as MP;
+        let ack_from = to.or_else(|| self.config.nodes.first().map(|n| n.id)).unwrap_or(self.config.our_node);
+        let routing = proto::Routing { variant: Some(proto::routing::Variant::ErrorReason(reason as i32)) };
+        self.next_id = self.next_id.wrapping_add(1);
+        let reply = proto::MeshPacket {
+            from: ack_from,
+            to: self.config.our_node,
+            channel: pkt.channel,
+            id: self.next_id,
+            priority: 120,
+            payload_variant: Some(MP::Decoded(proto::Data {
+                portnum: proto::PortNum::RoutingApp as i32,
+                payload: routing.encode_to_vec().into(),
+                request_id: pkt.id,
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        debug!("sim: routing {:?} for id={}", reason, pkt.id);
+        self.send(proto::from_radio::PayloadVariant::Packet(reply));
+    }
+}
+
+/// Mesh side of a simulated radio: inject traffic and observe what the BBS sends.
+pub struct SimHandle {
+    to_bbs: std_mpsc::Sender<Vec<u8>>,
+    bbs_rx: Arc<Mutex<std_mpsc::Receiver<Vec<u8>>>>,
+    to_radio: std_mpsc::Sender<Vec<u8>>,
+    deliveries: mpsc::UnboundedReceiver<SimDelivery>,
+    state: Arc<Mutex<SimState>>,
+    our_node: u32,
+    nodes: Vec<u32>,
+    next_id: u32,
+}
+
+impl SimHandle {
+    /// Node number of the BBS radio
+    pub fn our_node(&self) -> u32 { self.our_node }
+
+    /// Virtual node ids in configuration order
+    pub fn nodes(&self) -> &[u32] { &self.nodes }
+
+    /// Id of the `n`th virtual node (1-based)
+    pub fn node(&self, n: usize) -> Option<u32> { n.checked_sub(1).and_then(|i| self.nodes.get(i)).copied() }
+
+    /// Deliver a direct message from `from` to the BBS.
+    pub fn send_dm(&mut self, from: u32, text: &str) -> Result<()> {
+        self.inject_text(from, self.our_node, 0, text)
+    }
+
+    /// Deliver a channel (broadcast) message from `from`.
+    pub fn send_public(&mut self, from: u32, channel: u32, text: &str) -> Result<()> {
+        self.inject_text(from, BROADCAST_ADDR, channel, text)
+    }
+
+    /// Broadcast a position report from `from` (no text; the BBS only learns the node is in range).
+    pub fn send_position(&mut self, from: u32) -> Result<()> {
+        use prost::Message;
+        let position = proto::Position { latitude_i: Some(374_221_000), longitude_i: Some(-1_220_841_000), ..Default::default() };
+        self.inject(from, BROADCAST_ADDR, 0, proto::PortNum::PositionApp, position.encode_to_vec())
+    }
+
+    /// Deliver a PKI-encrypted direct message from `from`, reported with the sender's `key`
+    /// the way the radio does after decrypting it.
+    pub fn send_pki_dm(&mut self, from: u32, key: &[u8], text: &str) -> Result<()> {
+        let mut pkt = self.packet(from, self.our_node, 0, proto::PortNum::TextMessageApp, text.as_bytes().to_vec());
+        pkt.pki_encrypted = true;
+        pkt.public_key = key.to_vec().into();
+        self.push(proto::from_radio::PayloadVariant::Packet(pkt))
+    }
+
+    /// Report fresh node info for `from` carrying public `key`, as the radio does when the
+    /// node announces itself.
+    pub fn announce_key(&mut self, from: u32, key: &[u8]) -> Result<()> {
+        let user = proto::User { id: format!("!{:08x}", from), public_key: key.to_vec().into(), ..Default::default() };
+        self.push(proto::from_radio::PayloadVariant::NodeInfo(proto::NodeInfo { num: from, user: Some(user), ..Default::default() }))
+    }
+
+    fn inject_text(&mut self, from: u32, to: u32, channel: u32, text: &str) -> Result<()> {
+        self.inject(from, to, channel, proto::PortNum::TextMessageApp, text.as_bytes().to_vec())
+    }
+
+    fn inject(&mut self, from: u32, to: u32, channel: u32, port: proto::PortNum, payload: Vec<u8>) -> Result<()> {
+        let pkt = self.packet(from, to, channel, port, payload);
+        self.push(proto::from_radio::PayloadVariant::Packet(pkt))
+    }
+
+    fn packet(&mut self, from: u32, to: u32, channel: u32, port: proto::PortNum, payload: Vec<u8>) -> proto::MeshPacket {
+        use proto::mesh_packet::PayloadVariant as MP;
+        self.next_id = self.next_id.wrapping_add(1);
+        proto::MeshPacket {
+            from,
+            to,
+            channel,
+            id: self.next_id,
+            payload_variant: Some(MP::Decoded(proto::Data {
+                portnum: port as i32,
+                payload: payload.into(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+    }
+
+    fn push(&self, variant: proto::from_radio::PayloadVariant) -> Result<()> {
+        let msg = proto::FromRadio { payload_variant: Some(variant), ..Default::default() };
+        self.to_bbs.send(encode_from_radio(&msg)).map_err(|_| anyhow!("BBS side of the simulated radio is closed"))
+    }
+
+    /// Change the outbound packet loss probability.
+    pub fn set_loss_rate(&self, rate: f32) { self.state.lock().unwrap().loss_rate = rate.clamp(0.0, 1.0); }
+
+    /// Answer the next reliable packet from the BBS with `reason` instead of an ACK.
+    pub fn fail_next(&self, reason: RoutingError) { self.state.lock().unwrap().fail_next.push_back(reason); }
+
+    pub fn stats(&self) -> SimStats { self.state.lock().unwrap().stats.clone() }
+
+    /// Simulate pulling the USB cable: the current stream fails and reopening is refused.
+    pub fn unplug(&self) {
+        let mut st = self.state.lock().unwrap();
+        st.attached = false;
+        st.generation += 1;
+    }
+
+    /// Reattach the radio so the next reopen succeeds.
+    pub fn plug(&self) { self.state.lock().unwrap().attached = true; }
+
+    /// Transport opener for [`BbsServer::connect_supervised`](crate::bbs::BbsServer::connect_supervised);
+    /// fails while the radio is unplugged.
+    pub fn opener(&self) -> super::link::TransportOpener {
+        let rx = self.bbs_rx.clone();
+        let tx = self.to_radio.clone();
+        let state = self.state.clone();
+        Box::new(move || {
+            let result: Result<Box<dyn Transport>> = {
+                let st = state.lock().unwrap();
+                if st.attached {
+                    Ok(Box::new(SimTransport { rx: rx.clone(), tx: tx.clone(), pending: VecDeque::new(), state: state.clone(), generation: st.generation }))
+                } else {
+                    Err(anyhow!("simulated radio unplugged"))
+                }
+            };
+            Box::pin(async move { result })
+        })
+    }
+
+    /// Wait until the BBS has completed the config handshake.
+    pub async fn wait_configured(&self, timeout: Duration) -> Result<()> {
+        self.wait_handshakes(1, timeout).await
+    }
+
+    /// Wait until at least `count` config handshakes (initial connect plus reconnects) happened.
+    pub async fn wait_handshakes(&self, count: u64, timeout: Duration) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while self.state.lock().unwrap().stats.handshakes < count {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(anyhow!("BBS did not request config within {:?}", timeout));
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    }
+
+    /// Next packet the BBS put on the air, if any arrives within `timeout`.
+    pub async fn recv(&mut self, timeout: Duration) -> Option<SimDelivery> {
+        tokio::time::timeout(timeout, self.deliveries.recv()).await.ok().flatten()
+    }
+
+    /// Wait for a packet to `to` (`None` = broadcast) containing `needle`, skipping others.
+    pub async fn expect(&mut self, to: Option<u32>, needle: &str, timeout: Duration) -> Result<SimDelivery> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            match self.recv(remaining).await {
+                Some(d) if d.to == to && d.text.contains(needle) => return Ok(d),
+                Some(_) => continue,
+                None => return Err(anyhow!("timed out waiting for '{}'", needle)),
+            }
+        }
+    }
+
+    fn parse_node(&self, tok: &str) -> Result<u32> {
+        if let Some(hex) = tok.strip_prefix('!') {
+            return u32::from_str_radix(hex, 16).map_err(|_| anyhow!("invalid node id '{}'", tok));
+        }
+        let n: usize = tok.parse().map_err(|_| anyhow!("invalid node '{}'", tok))?;
+        self.node(n).ok_or_else(|| anyhow!("no virtual node {} ({} configured)", n, self.nodes.len()))
+    }
+
+    fn node_label(&self, id: u32) -> String {
+        match self.nodes.iter().position(|&n| n == id) {
+            Some(i) => (i + 1).to_string(),
+            None => format!("!{:08x}", id),
+        }
+    }
+
+    fn write_delivery<W: Write>(&self, out: &mut W, d: &SimDelivery) -> Result<()> {
+        let target = match d.to {
+            Some(id) => self.node_label(id),
+            None => format!("*:ch{}", d.channel),
+        };
+        let mut lines = d.text.trim_end().lines();
+        writeln!(out, "<< [{}] {}", target, lines.next().unwrap_or(""))?;
+        for line in lines {
+            writeln!(out, "   {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run a simulation script (see the module docs) and write a transcript to `out`.
+///
+/// Waits for the config handshake first. Fails on the first malformed line or unmet `expect`.
+pub async fn run_script<W: Write>(handle: &mut SimHandle, script: &str, out: &mut W) -> Result<()> {
+    let mut expect_timeout = DEFAULT_EXPECT_TIMEOUT;
+    handle.wait_configured(Duration::from_secs(10)).await?;
+    for (idx, raw) in script.lines().enumerate() {
+        let lineno = idx + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') { continue; }
+        let (cmd, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let err = |msg: String| anyhow!("script line {}: {}", lineno, msg);
+        match cmd.to_ascii_lowercase().as_str() {
+            "dm" => {
+                let (node, text) = rest.split_once(char::is_whitespace).ok_or_else(|| err("usage: dm <node> <text>".into()))?;
+                let from = handle.parse_node(node).map_err(|e| err(e.to_string()))?;
+                writeln!(out, ">> [{}] {}", node, text.trim())?;
+                handle.send_dm(from, text.trim())?;
+            }
+            "public" => {
+                let mut parts = rest.splitn(3, char::is_whitespace);
+                let (Some(node), Some(ch), Some(text)) = (parts.next(), parts.next(), parts.next()) else {
+                    return Err(err("usage: public <node> <channel> <text>".into()));
+                };
+                let from = handle.parse_node(node).map_err(|e| err(e.to_string()))?;
+                let channel: u32 = ch.parse().map_err(|_| err(format!("invalid channel '{}'", ch)))?;
+                writeln!(out, ">> [{}:ch{}] {}", node, channel, text.trim())?;
+                handle.send_public(from, channel, text.trim())?;
+            }
+            "position" => {
+                let from = handle.parse_node(rest).map_err(|e| err(e.to_string()))?;
+                writeln!(out, ">> [{}] (position)", rest)?;
+                handle.send_position(from)?;
+            }
+            "expect" => {
+                if rest.is_empty() { return Err(err("usage: expect <node|*> [text]".into())); }
+                let (node, needle) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                let to = if node == "*" { None } else { Some(handle.parse_node(node).map_err(|e| err(e.to_string()))?) };
+                let needle = needle.trim();
+                let deadline = tokio::time::Instant::now() + expect_timeout;
+                loop {
+                    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+                    let Some(d) = handle.recv(remaining).await else {
+                        return Err(err(format!("timed out after {:?} waiting for '{}' to {}", expect_timeout, needle, node)));
+                    };
+                    handle.write_delivery(out, &d)?;
+                    if d.to == to && d.text.contains(needle) { break; }
+                }
+            }
+            "sleep" => {
+                let secs: f64 = rest.parse().map_err(|_| err(format!("invalid duration '{}'", rest)))?;
+                let until = tokio::time::Instant::now() + Duration::from_secs_f64(secs.max(0.0));
+                while let Ok(Some(d)) = tokio::time::timeout_at(until, handle.deliveries.recv()).await {
+                    handle.write_delivery(out, &d)?;
+                }
+            }
+            "loss" => {
+                let rate: f32 = rest.parse().map_err(|_| err(format!("invalid loss rate '{}'", rest)))?;
+                handle.set_loss_rate(rate);
+            }
+            "fail" => {
+                let reason = RoutingError::from_str_name(&rest.to_ascii_uppercase())
+                    .ok_or_else(|| err(format!("unknown routing error '{}'", rest)))?;
+                handle.fail_next(reason);
+            }
+            "unplug" => {
+                handle.unplug();
+                writeln!(out, "-- radio unplugged")?;
+            }
+            "plug" => {
+                let seen = handle.stats().handshakes;
+                handle.plug();
+                handle.wait_handshakes(seen + 1, expect_timeout).await.map_err(|e| err(e.to_string()))?;
+                writeln!(out, "-- radio reconnected")?;
+            }
+            "timeout" => {
+                let secs: u64 = rest.parse().map_err(|_| err(format!("invalid timeout '{}'", rest)))?;
+                expect_timeout = Duration::from_secs(secs);
+            }
+            other => return Err(err(format!("unknown command '{}'", other))),
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Byte-stream transports for talking to a Meshtastic node.
+//!
+//! The reader and writer tasks only need a blocking `Read + Write` stream that carries
+//! the Meshtastic stream API (`0x94 0xC3 <len_hi> <len_lo> <protobuf>`). This module
+//! hides whether that stream is a USB/UART serial port or a TCP connection to a
+//! network-attached node (WiFi/Ethernet, stream API on port 4403).
+//!
+//! The transport is selected from the configured `port` string:
+//!
+//! ```toml
+//! [meshtastic]
+//! port = "/dev/ttyUSB0"          # serial
+//! port = "tcp://10.0.0.5:4403"   # TCP (port defaults to 4403 when omitted)
+//! ```
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "serial")]
+use serialport::SerialPort;
+
+/// Default TCP port of the Meshtastic stream API.
+pub const DEFAULT_TCP_PORT: u16 = 4403;
+
+/// URL scheme prefix selecting the TCP transport.
+pub const TCP_SCHEME: &str = "tcp://";
+
+/// Read timeout for TCP sockets. Kept short so the reader does not hold the shared
+/// lock for long while the writer is waiting to send.
+const TCP_READ_TIMEOUT: Duration = Duration::from_millis(100);
+const TCP_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A bidirectional byte stream to a Meshtastic node.
+///
+/// Reads are expected to time out (`TimedOut` / `WouldBlock`) when no data is available
+/// so the reader loop stays responsive to control messages.
+pub trait Transport: Read + Write + Send {
+    /// Human readable description used in logs and status output.
+    fn describe(&self) -> String;
+}
+
+/// Transport shared between the reader and writer tasks.
+pub type SharedTransport = Arc<Mutex<Box<dyn Transport>>>;
+
+/// Parsed form of a configured device address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportAddr {
+    /// Serial device path (e.g. `/dev/ttyUSB0`, `COM3`)
+    Serial(String),
+    /// TCP endpoint as `host:port`
+    Tcp(String),
+}
+
+impl TransportAddr {
+    /// Parse a configured port string. Strings starting with `tcp://` select the TCP
+    /// transport; anything else is treated as a serial device path.
+    pub fn parse(port: &str) -> Result<Self> {
+        let trimmed = port.trim();
+        let scheme_len = TCP_SCHEME.len();
+        if trimmed.len() >= scheme_len && trimmed[..scheme_len].eq_ignore_ascii_case(TCP_SCHEME) {
+            let rest = trimmed[scheme_len..].trim_end_matches('/');
+            if rest.is_empty() {
+                return Err(anyhow!("Missing host in TCP device address '{}'", port));
+            }
+            let has_port = if let Some(stripped) = rest.strip_prefix('[') {
+                // Bracketed IPv6 literal: [::1]:4403
+                stripped.split_once(']').map(|(_, tail)| tail.starts_with(':')).unwrap_or(false)
+            } else {
+                rest.contains(':')
+            };
+            let addr = if has_port { rest.to_string() } else { format!("{}:{}", rest, DEFAULT_TCP_PORT) };
+            return Ok(TransportAddr::Tcp(addr));
+        }
+        Ok(TransportAddr::Serial(trimmed.to_string()))
+    }
+
+    pub fn is_tcp(&self) -> bool { matches!(self, TransportAddr::Tcp(_)) }
+}
+
+/// TCP connection to a node exposing the Meshtastic stream API.
+pub struct TcpTransport {
+    stream: TcpStream,
+    peer: String,
+}
+
+impl TcpTransport {
+    /// Connect to `host:port`, trying each resolved address in turn.
+    pub fn connect(addr: &str) -> Result<Self> {
+        let addrs = addr
+            .to_socket_addrs()
+            .map_err(|e| anyhow!("Failed to resolve {}: {}", addr, e))?;
+        let mut last_err: Option<io::Error> = None;
+        for sock in addrs {
+            match TcpStream::connect_timeout(&sock, TCP_CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(TCP_READ_TIMEOUT))?;
+                    stream.set_write_timeout(Some(TCP_WRITE_TIMEOUT))?;
+                    let _ = stream.set_nodelay(true);
+                    debug!("TCP transport connected to {}", sock);
+                    return Ok(TcpTransport { stream, peer: addr.to_string() });
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        match last_err {
+            Some(e) => Err(anyhow!("Failed to connect to {}: {}", addr, e)),
+            None => Err(anyhow!("No addresses resolved for {}", addr)),
+        }
+    }
+}
+
+impl Read for TcpTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // A zero-length read on a socket means the peer closed the connection;
+            // surface it as an error instead of looking like "no data yet".
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed by peer")),
+            other => other,
+        }
+    }
+}
+
+impl Write for TcpTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.stream.write(buf) }
+    fn flush(&mut self) -> io::Result<()> { self.stream.flush() }
+}
+
+impl Transport for TcpTransport {
+    fn describe(&self) -> String { format!("{}{}", TCP_SCHEME, self.peer) }
+}
+
+/// USB/UART serial port transport.
+#[cfg(feature = "serial")]
+pub struct SerialTransport {
+    port: Box<dyn SerialPort>,
+    path: String,
+}
+
+#[cfg(feature = "serial")]
+impl SerialTransport {
+    pub fn new(port: Box<dyn SerialPort>, path: &str) -> Self { Self { port, path: path.to_string() } }
+}
+
+#[cfg(feature = "serial")]
+impl Read for SerialTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.port.read(buf) }
+}
+
+#[cfg(feature = "serial")]
+impl Write for SerialTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.port.write(buf) }
+    fn flush(&mut self) -> io::Result<()> { self.port.flush() }
+}
+
+#[cfg(feature = "serial")]
+impl Transport for SerialTransport {
+    fn describe(&self) -> String { self.path.clone() }
+}
+
+/// Transport that never yields data and discards writes. Used by mock reader/writer
+/// construction when no device is available.
+pub struct NullTransport;
+
+impl Read for NullTransport {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "null transport"))
+    }
+}
+
+impl Write for NullTransport {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { Ok(buf.len()) }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Transport for NullTransport {
+    fn describe(&self) -> String { "null".to_string() }
+}
+
+/// Write a single length-prefixed stream API frame (`0x94 0xC3 len_hi len_lo payload`).
+pub fn write_frame<W: Write + ?Sized>(w: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > u16::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "payload too large"));
+    }
+    let hdr = [0x94, 0xC3, ((payload.len() >> 8) & 0xFF) as u8, (payload.len() & 0xFF) as u8];
+    w.write_all(&hdr)?;
+    w.write_all(payload)?;
+    w.flush()
+}
@@ -1,5 +1,10 @@
-//! Minimal metrics scaffolding (Phase 3)
-//! This will later be extended with Prometheus exposition and histograms.
+//! Process-wide metrics.
+//!
+//! Counters are bumped in place by the writer and the public command router; gauges
+//! (scheduler queue, sessions, storage sizes) are published periodically by their owners.
+//! [`render_prometheus`] formats everything in the Prometheus text exposition format, served
+//! at `/metrics` by the `web` module (feature `web`).
+use std::fmt::Write as _;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
@@ -12,6 +17,28 @@ static ACK_LATENCY_COUNT: AtomicU64 = AtomicU64::new(0);
 static BROADCAST_ACK_CONFIRMED: AtomicU64 = AtomicU64::new(0);
 static BROADCAST_ACK_EXPIRED: AtomicU64 = AtomicU64::new(0);
 
+/// Upper bounds (ms) of the ACK latency histogram buckets; a final `+Inf` bucket is implied.
+pub const ACK_LATENCY_BUCKETS_MS: [u64; 9] = [250, 500, 1000, 2000, 4000, 8000, 16000, 32000, 64000];
+static ACK_LATENCY_BUCKETS: [AtomicU64; 9] = [const { AtomicU64::new(0) }; 9];
+
+/// Public commands counted by type, by [`PublicCommand::rule_name`](crate::bbs::public::PublicCommand::rule_name).
+pub const PUBLIC_COMMANDS: [&str; 7] = ["HELP", "LOGIN", "WEATHER", "SLOT", "SLOTSTATS", "8BALL", "FORTUNE"];
+static PUBLIC_COMMAND_COUNTS: [AtomicU64; 7] = [const { AtomicU64::new(0) }; 7];
+
+// Gauges, overwritten by whoever owns the value
+static SCHED_QUEUE_DEPTH: AtomicU64 = AtomicU64::new(0);
+static SCHED_DISPATCHED: AtomicU64 = AtomicU64::new(0);
+static SCHED_DROPPED: AtomicU64 = AtomicU64::new(0);
+static SCHED_DROPPED_OVERFLOW: AtomicU64 = AtomicU64::new(0);
+static SCHED_ESCALATIONS: AtomicU64 = AtomicU64::new(0);
+static ACTIVE_SESSIONS: AtomicU64 = AtomicU64::new(0);
+static LOGGED_IN_USERS: AtomicU64 = AtomicU64::new(0);
+static STORAGE_USERS: AtomicU64 = AtomicU64::new(0);
+static STORAGE_TOPICS: AtomicU64 = AtomicU64::new(0);
+static STORAGE_MESSAGES: AtomicU64 = AtomicU64::new(0);
+static STORAGE_HELD: AtomicU64 = AtomicU64::new(0);
+static STORAGE_BYTES: AtomicU64 = AtomicU64::new(0);
+
 #[allow(dead_code)]
 pub fn inc_reliable_sent() { RELIABLE_SENT.fetch_add(1, Ordering::Relaxed); }
 #[allow(dead_code)]
@@ -25,6 +52,10 @@ pub fn observe_ack_latency(sent_at: Instant) {
     let ms = sent_at.elapsed().as_millis() as u64;
     ACK_LATENCY_SUM_MS.fetch_add(ms, Ordering::Relaxed);
     ACK_LATENCY_COUNT.fetch_add(1, Ordering::Relaxed);
+    // Buckets are stored non-cumulative and summed when rendered
+    if let Some(i) = ACK_LATENCY_BUCKETS_MS.iter().position(|&le| ms <= le) {
+        ACK_LATENCY_BUCKETS[i].fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 #[allow(dead_code)]
@@ -32,6 +63,46 @@ pub fn inc_broadcast_ack_confirmed() { BROADCAST_ACK_CONFIRMED.fetch_add(1, Orde
 #[allow(dead_code)]
 pub fn inc_broadcast_ack_expired() { BROADCAST_ACK_EXPIRED.fetch_add(1, Ordering::Relaxed); }
 
+/// Count a public command by its rule name; names outside [`PUBLIC_COMMANDS`] are ignored.
+pub fn inc_public_command(name: &str) {
+    if let Some(i) = PUBLIC_COMMANDS.iter().position(|&c| c == name) {
+        PUBLIC_COMMAND_COUNTS[i].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Scheduler queue depth and lifetime totals, published by the scheduler loop.
+pub fn set_scheduler(queued: usize, dispatched: u64, dropped: u64, dropped_overflow: u64, escalations: u64) {
+    SCHED_QUEUE_DEPTH.store(queued as u64, Ordering::Relaxed);
+    SCHED_DISPATCHED.store(dispatched, Ordering::Relaxed);
+    SCHED_DROPPED.store(dropped, Ordering::Relaxed);
+    SCHED_DROPPED_OVERFLOW.store(dropped_overflow, Ordering::Relaxed);
+    SCHED_ESCALATIONS.store(escalations, Ordering::Relaxed);
+}
+
+pub fn set_sessions(active: usize, logged_in: usize) {
+    ACTIVE_SESSIONS.store(active as u64, Ordering::Relaxed);
+    LOGGED_IN_USERS.store(logged_in as u64, Ordering::Relaxed);
+}
+
+/// Sizes of the data directory, published by the server.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StorageSizes {
+    pub users: u64,
+    pub topics: u64,
+    pub messages: u64,
+    pub held_messages: u64,
+    /// Bytes on disk under the data directory
+    pub bytes: u64,
+}
+
+pub fn set_storage(sizes: StorageSizes) {
+    STORAGE_USERS.store(sizes.users, Ordering::Relaxed);
+    STORAGE_TOPICS.store(sizes.topics, Ordering::Relaxed);
+    STORAGE_MESSAGES.store(sizes.messages, Ordering::Relaxed);
+    STORAGE_HELD.store(sizes.held_messages, Ordering::Relaxed);
+    STORAGE_BYTES.store(sizes.bytes, Ordering::Relaxed);
+}
+
 #[derive(Debug, Default, Clone)]
 #[allow(dead_code)] // Fields read primarily in tests / future metrics endpoint
 pub struct Snapshot {
@@ -64,3 +135,53 @@ pub fn snapshot() -> Snapshot {
         broadcast_ack_expired: bcast_exp,
     }
 }
+
+fn metric(out: &mut String, name: &str, kind: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}");
+}
+
+/// All metrics in the Prometheus text exposition format (version 0.0.4).
+pub fn render_prometheus() -> String {
+    let load = |a: &AtomicU64| a.load(Ordering::Relaxed);
+    let mut out = String::new();
+    metric(&mut out, "meshbbs_reliable_sent_total", "counter", "Reliable DMs sent (first attempt).", load(&RELIABLE_SENT));
+    metric(&mut out, "meshbbs_reliable_acked_total", "counter", "Reliable DMs acknowledged.", load(&RELIABLE_ACKED));
+    metric(&mut out, "meshbbs_reliable_failed_total", "counter", "Reliable DMs given up on.", load(&RELIABLE_FAILED));
+    metric(&mut out, "meshbbs_reliable_retries_total", "counter", "Reliable DM retransmissions.", load(&RELIABLE_RETRIES));
+    metric(&mut out, "meshbbs_broadcast_ack_confirmed_total", "counter", "Broadcasts confirmed by at least one ACK.", load(&BROADCAST_ACK_CONFIRMED));
+    metric(&mut out, "meshbbs_broadcast_ack_expired_total", "counter", "Broadcasts whose ACK window expired.", load(&BROADCAST_ACK_EXPIRED));
+
+    let name = "meshbbs_ack_latency_seconds";
+    let _ = writeln!(out, "# HELP {name} Time from sending a reliable DM to its ACK.\n# TYPE {name} histogram");
+    let mut cumulative = 0;
+    for (le, bucket) in ACK_LATENCY_BUCKETS_MS.iter().zip(ACK_LATENCY_BUCKETS.iter()) {
+        cumulative += load(bucket);
+        let _ = writeln!(out, "{name}_bucket{{le=\"{}\"}} {cumulative}", *le as f64 / 1000.0);
+    }
+    let count = load(&ACK_LATENCY_COUNT);
+    let _ = writeln!(out, "{name}_bucket{{le=\"+Inf\"}} {count}");
+    let _ = writeln!(out, "{name}_sum {}", load(&ACK_LATENCY_SUM_MS) as f64 / 1000.0);
+    let _ = writeln!(out, "{name}_count {count}");
+
+    metric(&mut out, "meshbbs_scheduler_queue_depth", "gauge", "Messages waiting in the dispatch scheduler.", load(&SCHED_QUEUE_DEPTH));
+    metric(&mut out, "meshbbs_scheduler_dispatched_total", "counter", "Messages handed from the scheduler to the writer.", load(&SCHED_DISPATCHED));
+    metric(&mut out, "meshbbs_scheduler_dropped_total", "counter", "Messages dropped by the scheduler.", load(&SCHED_DROPPED));
+    metric(&mut out, "meshbbs_scheduler_dropped_overflow_total", "counter", "Messages dropped because the scheduler queue was full.", load(&SCHED_DROPPED_OVERFLOW));
+    metric(&mut out, "meshbbs_scheduler_escalations_total", "counter", "Priority escalations of aged messages.", load(&SCHED_ESCALATIONS));
+
+    metric(&mut out, "meshbbs_active_sessions", "gauge", "Open DM sessions.", load(&ACTIVE_SESSIONS));
+    metric(&mut out, "meshbbs_logged_in_users", "gauge", "Sessions with a logged-in user.", load(&LOGGED_IN_USERS));
+
+    let name = "meshbbs_public_commands_total";
+    let _ = writeln!(out, "# HELP {name} Public channel commands handled, by command.\n# TYPE {name} counter");
+    for (cmd, count) in PUBLIC_COMMANDS.iter().zip(PUBLIC_COMMAND_COUNTS.iter()) {
+        let _ = writeln!(out, "{name}{{command=\"{cmd}\"}} {}", load(count));
+    }
+
+    metric(&mut out, "meshbbs_storage_users", "gauge", "Registered users.", load(&STORAGE_USERS));
+    metric(&mut out, "meshbbs_storage_topics", "gauge", "Configured topics.", load(&STORAGE_TOPICS));
+    metric(&mut out, "meshbbs_storage_messages", "gauge", "Stored messages across all topics.", load(&STORAGE_MESSAGES));
+    metric(&mut out, "meshbbs_storage_held_messages", "gauge", "Notices held for store-and-forward delivery.", load(&STORAGE_HELD));
+    metric(&mut out, "meshbbs_storage_bytes", "gauge", "Bytes on disk under the data directory.", load(&STORAGE_BYTES));
+    out
+}
@@ -0,0 +1,226 @@
+//! Storage backend abstraction.
+//!
+//! [`Storage`](super::Storage) keeps the BBS rules (validation, permissions, password
+//! hashing, title derivation) and delegates persistence to a [`StorageBackend`]:
+//!
+//! - [`JsonBackend`](super::json::JsonBackend) — the original layout, one JSON file per
+//!   user and per message under the data directory.
+//! - `SqliteBackend` (feature `sqlite`) — a single `meshbbs.db` with indexes on topic,
+//!   timestamp and author so listings and counts no longer scan every message.
+//!
+//! Backends are synchronous and take `&self`; implementations do their own locking.
+//! Both seal records with the data key when at-rest encryption is enabled (see
+//! [`crypto`](super::crypto)). [`migrate`] copies everything from one backend to another
+//! (`meshbbs migrate`).
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use super::crypto::DataCipher;
+use super::{AdminAuditEntry, DeletionAuditEntry, HeldMessage, LockoutState, MailMessage, Message, RuntimeTopicsConfig, SanctionState, User};
+use crate::bbs::slotmachine::{GlobalJackpot, PlayersFile};
+
+/// Which backend holds the data directory's contents (`[storage] backend`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+impl FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(BackendKind::Json),
+            "sqlite" => Ok(BackendKind::Sqlite),
+            other => Err(anyhow!("Unknown storage backend '{}' (expected json or sqlite)", other)),
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BackendKind::Json => "json",
+            BackendKind::Sqlite => "sqlite",
+        })
+    }
+}
+
+/// Persistence operations used by [`Storage`](super::Storage) and the slot machine.
+///
+/// Topic and message ids passed in have already been validated by the caller.
+pub trait StorageBackend: Send + Sync {
+    fn kind(&self) -> BackendKind;
+
+    // Users
+    fn get_user(&self, username: &str) -> Result<Option<User>>;
+    /// Insert or overwrite a user record.
+    fn put_user(&self, user: &User) -> Result<()>;
+    fn list_users(&self) -> Result<Vec<User>>;
+    /// Remove a user record; false if there was none.
+    fn delete_user(&self, username: &str) -> Result<bool>;
+
+    // Topics
+    fn load_topics(&self) -> Result<RuntimeTopicsConfig>;
+    fn save_topics(&self, topics: &RuntimeTopicsConfig) -> Result<()>;
+    /// Prepare storage for a newly created topic.
+    fn create_topic_area(&self, topic: &str) -> Result<()>;
+    /// Remove a topic's messages.
+    fn remove_topic_area(&self, topic: &str) -> Result<()>;
+
+    // Messages and replies
+    fn insert_message(&self, message: &Message) -> Result<()>;
+    fn get_message(&self, topic: &str, id: &str) -> Result<Option<Message>>;
+    /// Rewrite an existing message (replies, pin flag, title).
+    fn update_message(&self, message: &Message) -> Result<()>;
+    /// Returns false if the message did not exist.
+    fn delete_message(&self, topic: &str, id: &str) -> Result<bool>;
+    /// Newest first, at most `limit`.
+    fn recent_messages(&self, topic: &str, limit: usize) -> Result<Vec<Message>>;
+    fn count_messages(&self) -> Result<u32>;
+    /// Messages newer than `since`, in one topic or across all of them.
+    fn count_messages_since(&self, topic: Option<&str>, since: DateTime<Utc>) -> Result<u32>;
+    fn count_user_posts(&self, username: &str) -> Result<u32>;
+
+    // Private mail, one mailbox per recipient
+    fn insert_mail(&self, mail: &MailMessage) -> Result<()>;
+    /// Newest first.
+    fn list_mail(&self, recipient: &str) -> Result<Vec<MailMessage>>;
+    /// Rewrite an existing mail message (read flag).
+    fn update_mail(&self, mail: &MailMessage) -> Result<()>;
+    /// Returns false if the message did not exist.
+    fn delete_mail(&self, recipient: &str, id: &str) -> Result<bool>;
+
+    // Audit trails (returned oldest first)
+    fn append_deletion_audit(&self, entry: &DeletionAuditEntry) -> Result<()>;
+    fn deletion_audit(&self) -> Result<Vec<DeletionAuditEntry>>;
+    fn append_admin_audit(&self, entry: &AdminAuditEntry) -> Result<()>;
+    fn admin_audit(&self) -> Result<Vec<AdminAuditEntry>>;
+
+    // Store-and-forward outbox, oldest first
+    fn load_outbox(&self) -> Result<Vec<HeldMessage>>;
+    fn save_outbox(&self, outbox: &[HeldMessage]) -> Result<()>;
+
+    // Failed-login counts and lockouts
+    fn load_login_failures(&self) -> Result<LockoutState>;
+    fn save_login_failures(&self, state: &LockoutState) -> Result<()>;
+
+    // Bans, mutes and blocked nodes
+    fn load_sanctions(&self) -> Result<SanctionState>;
+    fn save_sanctions(&self, state: &SanctionState) -> Result<()>;
+
+    // Topic locks
+    fn load_locked_topics(&self) -> Result<HashSet<String>>;
+    fn save_locked_topics(&self, locked: &HashSet<String>) -> Result<()>;
+
+    // Slot machine state
+    fn load_slot_players(&self) -> Result<PlayersFile>;
+    fn save_slot_players(&self, players: &PlayersFile) -> Result<()>;
+    fn load_jackpot(&self) -> Result<GlobalJackpot>;
+    /// Atomic read-modify-write of the jackpot; returns the updated state.
+    fn update_jackpot(&self, f: &mut dyn FnMut(&mut GlobalJackpot)) -> Result<GlobalJackpot>;
+
+    /// Rewrite every stored record, as stored (sealed or not), through `f`. Used by
+    /// `meshbbs rekey`; returns the number of records rewritten.
+    fn reseal(&self, f: &mut dyn FnMut(&str) -> Result<String>) -> Result<usize>;
+}
+
+/// Open the backend of the given kind for `data_dir`, sealing records with `cipher` if set.
+pub fn open_backend(data_dir: &str, kind: BackendKind, cipher: Option<DataCipher>) -> Result<Box<dyn StorageBackend>> {
+    match kind {
+        BackendKind::Json => Ok(Box::new(super::json::JsonBackend::open(data_dir, cipher)?)),
+        #[cfg(feature = "sqlite")]
+        BackendKind::Sqlite => Ok(Box::new(super::sqlite::SqliteBackend::open(data_dir, cipher)?)),
+        #[cfg(not(feature = "sqlite"))]
+        BackendKind::Sqlite => { let _ = cipher; Err(anyhow!("SQLite storage requires building meshbbs with the 'sqlite' feature")) }
+    }
+}
+
+/// True if the backend holds no users, topics or messages.
+pub fn is_empty(backend: &dyn StorageBackend) -> Result<bool> {
+    Ok(backend.list_users()?.is_empty() && backend.count_messages()? == 0 && backend.load_topics()?.topics.is_empty())
+}
+
+/// What [`migrate`] copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub users: usize,
+    pub topics: usize,
+    pub messages: usize,
+    pub mail: usize,
+    pub audit_entries: usize,
+    pub held_messages: usize,
+    pub slot_players: usize,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} users, {} topics, {} messages, {} mail, {} audit entries, {} held messages, {} slot players",
+            self.users, self.topics, self.messages, self.mail, self.audit_entries, self.held_messages, self.slot_players
+        )
+    }
+}
+
+/// Copy all data from `from` into `to`. The destination must be empty.
+pub fn migrate(from: &dyn StorageBackend, to: &dyn StorageBackend) -> Result<MigrationReport> {
+    if !is_empty(to)? {
+        return Err(anyhow!("Destination {} storage is not empty; refusing to merge", to.kind()));
+    }
+    let mut report = MigrationReport::default();
+
+    for user in from.list_users()? {
+        to.put_user(&user)?;
+        report.users += 1;
+        for mail in from.list_mail(&user.username)? {
+            to.insert_mail(&mail)?;
+            report.mail += 1;
+        }
+    }
+
+    let topics = from.load_topics()?;
+    to.save_topics(&topics)?;
+    let mut topic_ids: Vec<&String> = topics.topics.keys().collect();
+    topic_ids.sort();
+    for topic in topic_ids {
+        to.create_topic_area(topic)?;
+        for message in from.recent_messages(topic, usize::MAX)? {
+            to.insert_message(&message)?;
+            report.messages += 1;
+        }
+        report.topics += 1;
+    }
+    to.save_locked_topics(&from.load_locked_topics()?)?;
+
+    for entry in from.deletion_audit()? {
+        to.append_deletion_audit(&entry)?;
+        report.audit_entries += 1;
+    }
+    for entry in from.admin_audit()? {
+        to.append_admin_audit(&entry)?;
+        report.audit_entries += 1;
+    }
+
+    let outbox = from.load_outbox()?;
+    report.held_messages = outbox.len();
+    to.save_outbox(&outbox)?;
+    to.save_login_failures(&from.load_login_failures()?)?;
+    to.save_sanctions(&from.load_sanctions()?)?;
+
+    let players = from.load_slot_players()?;
+    report.slot_players = players.players.len();
+    to.save_slot_players(&players)?;
+    let jackpot = from.load_jackpot()?;
+    to.update_jackpot(&mut |j| *j = jackpot.clone())?;
+
+    Ok(report)
+}
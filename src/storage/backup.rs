@@ -0,0 +1,416 @@
+//! `meshbbs backup` / `meshbbs restore`: consistent, versioned archives of the data directory.
+//!
+//! A backup is a gzip-compressed tar. Its first entry is a [`BackupManifest`]
+//! (`meshbbs-backup.json`), followed by the files of the data directory: users, topics,
+//! messages, mail, audit logs, the outbox, the node cache, slot machine state and retention
+//! archives. The copy is consistent while the server runs:
+//!
+//! - JSON backend: every write holds a shared lock on `data/.lock`. The backup holds it
+//!   exclusively while it reads the tree, so no change is caught half made.
+//! - SQLite backend: `meshbbs.db` is copied with `VACUUM INTO`, a transactional snapshot.
+//!
+//! Records are archived as stored, so backups of an encrypted directory stay encrypted and
+//! restoring one needs the same key.
+//!
+//! [`restore`] unpacks into a staging directory next to the data directory and parses every
+//! record, retention archive lines included, with [`secure_json_parse`] (SQLite: integrity
+//! check, then every row). Only then does it swap the directories; the old one is kept as
+//! `<data_dir>.before-restore-<time>`.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use fs2::FileExt;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use super::backend::BackendKind;
+use super::crypto::{DataCipher, EncryptionHeader, KeySource, RecordCodec, HEADER_FILE};
+use super::json::{freeze_tree, MAX_MESSAGE_FILE, MAX_USER_FILE, TREE_LOCK_FILE};
+use super::retention::decode_archive;
+#[cfg(feature = "sqlite")]
+use super::sqlite::{SqliteBackend, DB_FILE};
+use super::{AdminAuditEntry, DeletionAuditEntry, HeldMessage, LockoutState, MailMessage, Message, RuntimeTopicsConfig, SanctionState, User};
+use crate::bbs::slotmachine::{GlobalJackpot, PlayersFile};
+use crate::meshtastic::NodeCache;
+use crate::validation::secure_json_parse;
+
+#[cfg(not(feature = "sqlite"))]
+const DB_FILE: &str = "meshbbs.db";
+
+/// First entry of every backup archive.
+pub const MANIFEST_FILE: &str = "meshbbs-backup.json";
+const BACKUP_FORMAT: &str = "meshbbs-backup";
+/// Newest archive layout this build writes and restores.
+pub const BACKUP_VERSION: u32 = 1;
+
+const MAX_MANIFEST_BYTES: usize = 64 * 1024;
+/// Limit for whole-file records (topics, outbox, node cache, slot players)
+const MAX_RECORD_FILE: usize = 16 * 1024 * 1024;
+
+/// What an archive holds, written before the data files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub meshbbs_version: String,
+    pub backend: BackendKind,
+    /// Records are sealed; restoring needs the data key
+    pub encrypted: bool,
+    /// Data files in the archive, not counting the manifest
+    pub files: usize,
+}
+
+/// Result of [`restore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub manifest: BackupManifest,
+    /// Records parsed and validated
+    pub records: usize,
+    /// Where the replaced data directory was moved, if there was one
+    pub previous: Option<PathBuf>,
+}
+
+/// Write a consistent archive of `data_dir` to `dest` (safe while the server runs).
+pub fn backup(data_dir: &str, kind: BackendKind, dest: &Path) -> Result<BackupManifest> {
+    let root = Path::new(data_dir);
+    if !root.is_dir() {
+        return Err(anyhow!("Data directory {} does not exist", data_dir));
+    }
+    let mut tmp = dest.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let result = write_backup(root, kind, dest, &tmp);
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn write_backup(root: &Path, kind: BackendKind, dest: &Path, tmp: &Path) -> Result<BackupManifest> {
+    let excluded = dest.parent().and_then(|p| nested_dir(root, p));
+    let _frozen = freeze_tree(root).map_err(|e| anyhow!("Failed to lock {}: {}", root.display(), e))?;
+    let files = data_files(root, excluded.as_deref())?;
+    let snapshot = match kind {
+        BackendKind::Sqlite => Some(sqlite_snapshot(root, &tmp.with_extension("db"))?),
+        BackendKind::Json => None,
+    };
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now(),
+        meshbbs_version: env!("CARGO_PKG_VERSION").to_string(),
+        backend: kind,
+        encrypted: root.join(HEADER_FILE).is_file(),
+        files: files.len() + usize::from(snapshot.is_some()),
+    };
+    let mtime = manifest.created_at.timestamp().max(0) as u64;
+
+    let mut tar = tar::Builder::new(GzEncoder::new(create_private(tmp)?, Compression::default()));
+    append(&mut tar, MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?, mtime)?;
+    for name in &files {
+        let content = read_shared(&root.join(name)).map_err(|e| anyhow!("Failed to read {}: {}", name, e))?;
+        append(&mut tar, name, &content, mtime)?;
+    }
+    if let Some(db) = snapshot {
+        append(&mut tar, DB_FILE, &db, mtime)?;
+    }
+    tar.into_inner()?.finish()?.sync_all()?;
+    fs::rename(tmp, dest)?;
+    Ok(manifest)
+}
+
+/// Restore `archive` into `data_dir` (stop the server first). `key` unlocks an encrypted
+/// backup for validation; the records stay sealed under it.
+pub fn restore(data_dir: &str, archive: &Path, key: Option<&KeySource>) -> Result<RestoreReport> {
+    let root: PathBuf = Path::new(data_dir).components().collect();
+    let file = fs::File::open(archive).map_err(|e| anyhow!("Failed to open {}: {}", archive.display(), e))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = tar.entries()?;
+    let manifest = read_manifest(entries.next())?;
+
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let staging = sibling(&root, &format!(".restore-{stamp}"));
+    if staging.exists() {
+        return Err(anyhow!("{} already exists; remove it and try again", staging.display()));
+    }
+    fs::create_dir_all(&staging)?;
+    let validated = unpack(entries, &staging, &manifest).and_then(|()| validate(&staging, &manifest, key));
+    let records = match validated {
+        Ok(n) => n,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    let previous = swap(&root, &staging, &stamp)?;
+    Ok(RestoreReport { manifest, records, previous })
+}
+
+/// Back up into `dir` as `meshbbs-<time>.tar.gz`, then delete all but the newest `keep`
+/// archives there. Used by the server's `[backup]` schedule.
+pub fn scheduled_backup(data_dir: &str, kind: BackendKind, dir: &Path, keep: usize, now: DateTime<Utc>) -> Result<PathBuf> {
+    fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create backup directory {}: {}", dir.display(), e))?;
+    let path = dir.join(format!("meshbbs-{}.tar.gz", now.format("%Y%m%dT%H%M%SZ")));
+    backup(data_dir, kind, &path)?;
+    for old in rotate_backups(dir, keep)? {
+        info!("Removed old backup {}", old.display());
+    }
+    Ok(path)
+}
+
+/// Delete the oldest `meshbbs-*.tar.gz` archives in `dir` beyond the newest `keep` (at
+/// least one is always kept); returns the removed paths.
+pub fn rotate_backups(dir: &Path, keep: usize) -> Result<Vec<PathBuf>> {
+    let mut archives: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("meshbbs-") && n.ends_with(".tar.gz")))
+        .collect();
+    // Names carry a sortable UTC timestamp
+    archives.sort();
+    let excess = archives.len().saturating_sub(keep.max(1));
+    let removed: Vec<PathBuf> = archives.into_iter().take(excess).collect();
+    for path in &removed {
+        fs::remove_file(path)?;
+    }
+    Ok(removed)
+}
+
+/// Files under `root` as archive names (`users/alice.json`), sorted. Skips the tree lock,
+/// temporary files, the live SQLite files (snapshotted instead) and the `exclude` directory.
+fn data_files(root: &Path, exclude: Option<&Path>) -> Result<Vec<String>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(root.join(&dir))? {
+            let entry = entry?;
+            let rel = dir.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if exclude.is_none_or(|x| fs::canonicalize(root.join(&rel)).ok().as_deref() != Some(x)) {
+                    dirs.push(rel);
+                }
+            } else if file_type.is_file() {
+                let name = archive_name(&rel);
+                if !skipped(&name) {
+                    files.push(name);
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn archive_name(rel: &Path) -> String {
+    rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn skipped(name: &str) -> bool {
+    name == TREE_LOCK_FILE
+        || name == MANIFEST_FILE
+        || name == DB_FILE
+        || name.starts_with(&format!("{DB_FILE}-"))
+        || name.ends_with(".tmp")
+}
+
+/// `dir` (canonical) when it lies strictly inside `root`, e.g. backups written to `data/backups`.
+fn nested_dir(root: &Path, dir: &Path) -> Option<PathBuf> {
+    let root = fs::canonicalize(root).ok()?;
+    let dir = fs::canonicalize(dir).ok()?;
+    (dir != root && dir.starts_with(&root)).then_some(dir)
+}
+
+/// `<root><suffix>` next to `root`, e.g. `data.restore-…`.
+fn sibling(root: &Path, suffix: &str) -> PathBuf {
+    let mut name = root.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Read a whole file under a shared lock, as the backends do.
+fn read_shared(path: &Path) -> Result<Vec<u8>> {
+    let mut file = fs::File::open(path)?;
+    FileExt::lock_shared(&file)?;
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// Create (or truncate) a file only the owner can read.
+fn create_private(path: &Path) -> Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path).map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, name: &str, content: &[u8], mtime: u64) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(content.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(mtime);
+    tar.append_data(&mut header, name, content)?;
+    Ok(())
+}
+
+#[cfg(feature = "sqlite")]
+fn sqlite_snapshot(root: &Path, scratch: &Path) -> Result<Vec<u8>> {
+    // VACUUM INTO refuses to overwrite
+    let _ = fs::remove_file(scratch);
+    SqliteBackend::open(&root.to_string_lossy(), None)?.snapshot_into(scratch)?;
+    let content = fs::read(scratch);
+    let _ = fs::remove_file(scratch);
+    Ok(content?)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_snapshot(_root: &Path, _scratch: &Path) -> Result<Vec<u8>> {
+    Err(anyhow!("This build has no SQLite support (feature `sqlite`)"))
+}
+
+fn read_manifest<R: Read>(entry: Option<std::io::Result<tar::Entry<'_, R>>>) -> Result<BackupManifest> {
+    let mut entry = entry.ok_or_else(|| anyhow!("Backup archive is empty"))??;
+    if entry.path()?.as_os_str() != MANIFEST_FILE {
+        return Err(anyhow!("Not a meshbbs backup (no {} at the start)", MANIFEST_FILE));
+    }
+    let mut text = String::new();
+    entry.by_ref().take(MAX_MANIFEST_BYTES as u64 + 1).read_to_string(&mut text)?;
+    let manifest: BackupManifest = secure_json_parse(&text, MAX_MANIFEST_BYTES)
+        .map_err(|e| anyhow!("Corrupt {}: {}", MANIFEST_FILE, e))?;
+    if manifest.format != BACKUP_FORMAT {
+        return Err(anyhow!("Not a meshbbs backup (format '{}')", manifest.format));
+    }
+    if manifest.version > BACKUP_VERSION {
+        return Err(anyhow!("Backup version {} is newer than this build supports ({})", manifest.version, BACKUP_VERSION));
+    }
+    Ok(manifest)
+}
+
+fn unpack<R: Read>(entries: tar::Entries<'_, R>, staging: &Path, manifest: &BackupManifest) -> Result<()> {
+    let mut files = 0;
+    for entry in entries {
+        let mut entry = entry?;
+        let rel = entry.path()?.into_owned();
+        if entry.header().entry_type() != tar::EntryType::Regular {
+            return Err(anyhow!("Backup holds a non-file entry {}", rel.display()));
+        }
+        if rel.as_os_str().is_empty() || rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(anyhow!("Backup holds an unsafe path {}", rel.display()));
+        }
+        let dest = staging.join(&rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut create_private(&dest)?)?;
+        files += 1;
+    }
+    if files != manifest.files {
+        return Err(anyhow!("Backup is incomplete: {} of {} files", files, manifest.files));
+    }
+    Ok(())
+}
+
+/// Parse every record in the unpacked tree; returns the number of records.
+fn validate(staging: &Path, manifest: &BackupManifest, key: Option<&KeySource>) -> Result<usize> {
+    let staging_dir = staging.to_string_lossy();
+    let cipher = match EncryptionHeader::load(&staging_dir)? {
+        Some(header) => {
+            let key = key.ok_or_else(|| anyhow!("Backup is encrypted; configure [security.encryption] with its key to restore it"))?;
+            Some(header.unlock(key).map_err(|e| anyhow!("Cannot unlock backup: {}", e))?)
+        }
+        None if manifest.encrypted => return Err(anyhow!("Backup is marked encrypted but holds no {}", HEADER_FILE)),
+        None => None,
+    };
+    let codec = RecordCodec::new(cipher.clone());
+    let mut records = 0;
+    for name in data_files(staging, None)? {
+        records += validate_file(&staging.join(&name), &name, &codec).map_err(|e| anyhow!("Backup record {}: {}", name, e))?;
+    }
+    if staging.join(DB_FILE).is_file() {
+        records += verify_sqlite(&staging_dir, cipher)?;
+    }
+    Ok(records)
+}
+
+fn validate_file(path: &Path, name: &str, codec: &RecordCodec) -> Result<usize> {
+    let parts: Vec<&str> = name.split('/').collect();
+    let is_json = name.ends_with(".json");
+    match parts.as_slice() {
+        ["archive", file] if file.ends_with(".jsonl.gz") => return Ok(decode_archive(path, codec)?.len()),
+        // Files `fsck --repair` set aside as bad
+        ["archive" | "quarantine", ..] => return Ok(0),
+        _ if !is_json && !name.ends_with(".log") => return Ok(0),
+        _ => {}
+    }
+    let text = fs::read_to_string(path)?;
+    match parts.as_slice() {
+        [HEADER_FILE] => plain::<EncryptionHeader>(&text, MAX_MANIFEST_BYTES),
+        ["node_cache.json"] => plain::<NodeCache>(&text, MAX_RECORD_FILE),
+        ["users", _] => record::<User>(codec, &text, MAX_USER_FILE as usize),
+        ["messages", _, _] => record::<Message>(codec, &text, MAX_MESSAGE_FILE as usize),
+        ["mail", _, _] => record::<MailMessage>(codec, &text, MAX_MESSAGE_FILE as usize),
+        ["topics.json"] => record::<RuntimeTopicsConfig>(codec, &text, MAX_RECORD_FILE),
+        ["locked_topics.json"] => record::<Vec<String>>(codec, &text, MAX_RECORD_FILE),
+        ["outbox.json"] => record::<Vec<HeldMessage>>(codec, &text, MAX_RECORD_FILE),
+        ["login_failures.json"] => record::<LockoutState>(codec, &text, MAX_RECORD_FILE),
+        ["sanctions.json"] => record::<SanctionState>(codec, &text, MAX_RECORD_FILE),
+        ["slotmachine", "players.json"] => record::<PlayersFile>(codec, &text, MAX_RECORD_FILE),
+        ["slotmachine", "jackpot.json"] => record::<GlobalJackpot>(codec, &text, MAX_RECORD_FILE),
+        ["deletion_audit.log"] => lines::<DeletionAuditEntry>(codec, &text),
+        ["admin_audit.log"] => lines::<AdminAuditEntry>(codec, &text),
+        _ if is_json => record::<serde_json::Value>(codec, &text, MAX_RECORD_FILE),
+        _ => Ok(0),
+    }
+}
+
+/// A file that is never sealed.
+fn plain<T: DeserializeOwned>(text: &str, limit: usize) -> Result<usize> {
+    secure_json_parse::<T>(text, limit).map_err(|e| anyhow!("{}", e))?;
+    Ok(1)
+}
+
+fn record<T: DeserializeOwned>(codec: &RecordCodec, text: &str, limit: usize) -> Result<usize> {
+    plain::<T>(&codec.decode(text.to_string())?, limit)
+}
+
+fn lines<T: DeserializeOwned>(codec: &RecordCodec, text: &str) -> Result<usize> {
+    let mut records = 0;
+    for (i, line) in text.lines().filter(|l| !l.trim().is_empty()).enumerate() {
+        records += record::<T>(codec, line, MAX_MESSAGE_FILE as usize).map_err(|e| anyhow!("entry {}: {}", i + 1, e))?;
+    }
+    Ok(records)
+}
+
+#[cfg(feature = "sqlite")]
+fn verify_sqlite(staging: &str, cipher: Option<DataCipher>) -> Result<usize> {
+    SqliteBackend::open(staging, cipher)?.verify()
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn verify_sqlite(_staging: &str, _cipher: Option<DataCipher>) -> Result<usize> {
+    Err(anyhow!("Backup holds a SQLite database but this build has no SQLite support (feature `sqlite`)"))
+}
+
+/// Move `root` aside and `staging` into its place, putting `root` back if that fails.
+fn swap(root: &Path, staging: &Path, stamp: &str) -> Result<Option<PathBuf>> {
+    if !root.exists() {
+        fs::rename(staging, root).map_err(|e| anyhow!("Failed to move {} into place: {}", staging.display(), e))?;
+        return Ok(None);
+    }
+    let previous = sibling(root, &format!(".before-restore-{stamp}"));
+    fs::rename(root, &previous).map_err(|e| anyhow!("Failed to move {} aside: {}", root.display(), e))?;
+    if let Err(e) = fs::rename(staging, root) {
+        let _ = fs::rename(&previous, root);
+        return Err(anyhow!("Failed to move {} into place: {}", staging.display(), e));
+    }
+    Ok(Some(previous))
+}
@@ -0,0 +1,312 @@
+//! At-rest encryption of the data directory.
+//!
+//! When `[security.encryption]` names a passphrase or keyfile, every record the storage
+//! backends write (user files, messages, topics, locks, audit lines, slot state; the
+//! `data` columns of the SQLite backend) is sealed with XChaCha20-Poly1305 under a
+//! 256-bit key derived with Argon2id. A sealed record is the text `enc1:` followed by
+//! base64 of `nonce || ciphertext`, so line-oriented files (audit logs) stay line-oriented.
+//!
+//! `<data_dir>/encryption.json` holds the KDF salt and parameters plus a sealed check
+//! value used to reject a wrong key at startup; it contains no key material. File and
+//! directory names (usernames, topic ids, message ids) are not encrypted.
+//!
+//! [`rekey`] re-seals an existing tree in place, retention archives included: plaintext →
+//! encrypted, key rotation, or encrypted → plaintext. It is restartable: the new header is
+//! staged in `encryption.json.pending` and records under either key are accepted until it
+//! finishes.
+
+use anyhow::{anyhow, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::backend::{is_empty, open_backend, BackendKind};
+use super::retention::reseal_archives;
+use crate::config::EncryptionConfig;
+
+/// Header file inside the data directory.
+pub const HEADER_FILE: &str = "encryption.json";
+const PENDING_SUFFIX: &str = ".pending";
+
+const ENVELOPE_PREFIX: &str = "enc1:";
+const NONCE_LEN: usize = 24;
+const CHECK_VALUE: &str = "meshbbs-data-key";
+
+/// Symmetric cipher for stored records.
+#[derive(Clone)]
+pub struct DataCipher {
+    aead: XChaCha20Poly1305,
+}
+
+impl fmt::Debug for DataCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("DataCipher(..)") }
+}
+
+impl DataCipher {
+    fn from_key(key: &[u8; 32]) -> Self {
+        DataCipher { aead: XChaCha20Poly1305::new(key.into()) }
+    }
+
+    /// Encrypt a record into its `enc1:` text envelope.
+    pub fn seal(&self, plaintext: &str) -> String {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.aead.encrypt(&nonce, plaintext.as_bytes()).expect("XChaCha20-Poly1305 encryption cannot fail");
+        let mut raw = nonce.to_vec();
+        raw.extend_from_slice(&ciphertext);
+        format!("{}{}", ENVELOPE_PREFIX, B64.encode(raw))
+    }
+
+    /// Decrypt an `enc1:` envelope.
+    pub fn open(&self, sealed: &str) -> Result<String> {
+        let body = sealed.trim_end().strip_prefix(ENVELOPE_PREFIX).ok_or_else(|| anyhow!("Record is not encrypted"))?;
+        let raw = B64.decode(body).map_err(|e| anyhow!("Corrupt encrypted record: {}", e))?;
+        if raw.len() < NONCE_LEN { return Err(anyhow!("Corrupt encrypted record: too short")); }
+        let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+        let plaintext = self.aead.decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt record (wrong key or tampered data)"))?;
+        String::from_utf8(plaintext).map_err(|e| anyhow!("Corrupt encrypted record: {}", e))
+    }
+}
+
+/// True if `record` is an `enc1:` envelope.
+pub fn is_sealed(record: &str) -> bool { record.starts_with(ENVELOPE_PREFIX) }
+
+/// Seals records on write and opens them on read; a no-op without a cipher.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RecordCodec {
+    cipher: Option<DataCipher>,
+}
+
+impl RecordCodec {
+    pub(crate) fn new(cipher: Option<DataCipher>) -> Self { RecordCodec { cipher } }
+
+    pub(crate) fn encode(&self, plaintext: String) -> String {
+        match &self.cipher {
+            Some(c) => c.seal(&plaintext),
+            None => plaintext,
+        }
+    }
+
+    pub(crate) fn decode(&self, stored: String) -> Result<String> {
+        match (&self.cipher, is_sealed(&stored)) {
+            (Some(c), true) => c.open(&stored),
+            (Some(_), false) => Err(anyhow!("Unencrypted record in an encrypted data directory (run `meshbbs rekey`)")),
+            (None, true) => Err(anyhow!("Record is encrypted but no [security.encryption] key is configured")),
+            (None, false) => Ok(stored),
+        }
+    }
+}
+
+/// Where the key comes from.
+#[derive(Debug, Clone)]
+pub enum KeySource {
+    Passphrase(String),
+    Keyfile(PathBuf),
+}
+
+impl KeySource {
+    /// Key source named in `[security.encryption]` (exactly one of passphrase / keyfile).
+    pub fn from_config(cfg: &EncryptionConfig) -> Result<Self> {
+        match (&cfg.passphrase, &cfg.keyfile) {
+            (Some(p), None) => Ok(KeySource::Passphrase(p.clone())),
+            (None, Some(k)) => Ok(KeySource::Keyfile(PathBuf::from(k))),
+            (Some(_), Some(_)) => Err(anyhow!("[security.encryption]: set either passphrase or keyfile, not both")),
+            (None, None) => Err(anyhow!("[security.encryption]: passphrase or keyfile is required")),
+        }
+    }
+
+    fn secret(&self) -> Result<Vec<u8>> {
+        let secret = match self {
+            KeySource::Passphrase(p) => p.as_bytes().to_vec(),
+            KeySource::Keyfile(path) => std::fs::read(path)
+                .map_err(|e| anyhow!("Failed to read keyfile {}: {}", path.display(), e))?,
+        };
+        if secret.len() < 8 {
+            return Err(anyhow!("Encryption passphrase/keyfile too short (minimum 8 bytes)"));
+        }
+        Ok(secret)
+    }
+}
+
+/// KDF parameters and key check stored in `encryption.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionHeader {
+    pub version: u32,
+    pub kdf: String,
+    pub salt: String,
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    /// A known value sealed under the key
+    pub check: String,
+}
+
+impl EncryptionHeader {
+    fn path(data_dir: &str) -> PathBuf { Path::new(data_dir).join(HEADER_FILE) }
+
+    fn pending_path(data_dir: &str) -> PathBuf { Path::new(data_dir).join(format!("{}{}", HEADER_FILE, PENDING_SUFFIX)) }
+
+    fn read(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(s) => Ok(Some(serde_json::from_str(&s).map_err(|e| anyhow!("Corrupt {}: {}", path.display(), e))?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow!("Failed to read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Header of an encrypted data directory, if any.
+    pub fn load(data_dir: &str) -> Result<Option<Self>> { Self::read(&Self::path(data_dir)) }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Fresh header (random salt, default Argon2id cost) and its cipher.
+    fn create(source: &KeySource) -> Result<(Self, DataCipher)> {
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let params = Params::DEFAULT;
+        let mut header = EncryptionHeader {
+            version: 1,
+            kdf: "argon2id".into(),
+            salt: B64.encode(salt),
+            m_cost: params.m_cost(),
+            t_cost: params.t_cost(),
+            p_cost: params.p_cost(),
+            check: String::new(),
+        };
+        let cipher = header.derive(source)?;
+        header.check = cipher.seal(CHECK_VALUE);
+        Ok((header, cipher))
+    }
+
+    fn derive(&self, source: &KeySource) -> Result<DataCipher> {
+        if self.version != 1 || self.kdf != "argon2id" {
+            return Err(anyhow!("Unsupported {} (version {}, kdf {})", HEADER_FILE, self.version, self.kdf));
+        }
+        let salt = B64.decode(&self.salt).map_err(|e| anyhow!("Corrupt {} salt: {}", HEADER_FILE, e))?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| anyhow!("Invalid {} parameters: {}", HEADER_FILE, e))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(&source.secret()?, &salt, &mut key)
+            .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+        let cipher = DataCipher::from_key(&key);
+        key.fill(0);
+        Ok(cipher)
+    }
+
+    /// Derive the key and check it against this header.
+    pub fn unlock(&self, source: &KeySource) -> Result<DataCipher> {
+        let cipher = self.derive(source)?;
+        match cipher.open(&self.check) {
+            Ok(v) if v == CHECK_VALUE => Ok(cipher),
+            _ => Err(anyhow!("Data directory key mismatch (wrong passphrase or keyfile)")),
+        }
+    }
+}
+
+/// Resolve the data key for startup.
+///
+/// - encrypted directory + key configured: derive and verify the key
+/// - encrypted directory, no key: error
+/// - plaintext directory with data + key configured: error, run `meshbbs rekey` first
+/// - empty directory + key configured: start a new encrypted directory
+pub fn open_data_key(data_dir: &str, kind: BackendKind, cfg: Option<&EncryptionConfig>) -> Result<Option<DataCipher>> {
+    let header = EncryptionHeader::load(data_dir)?;
+    if EncryptionHeader::pending_path(data_dir).exists() {
+        return Err(anyhow!("An interrupted `meshbbs rekey` left {}{} in {}; run it again to finish", HEADER_FILE, PENDING_SUFFIX, data_dir));
+    }
+    match (header, cfg) {
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(anyhow!("Data directory {} is encrypted but [security.encryption] is not configured", data_dir)),
+        (Some(header), Some(cfg)) => Ok(Some(header.unlock(&KeySource::from_config(cfg)?)?)),
+        (None, Some(cfg)) => {
+            let source = KeySource::from_config(cfg)?;
+            if !is_empty(open_backend(data_dir, kind, None)?.as_ref())? {
+                return Err(anyhow!("Data directory {} holds unencrypted data; run `meshbbs rekey` to encrypt it in place", data_dir));
+            }
+            let (header, cipher) = EncryptionHeader::create(&source)?;
+            std::fs::create_dir_all(data_dir)?;
+            header.write(&EncryptionHeader::path(data_dir))?;
+            Ok(Some(cipher))
+        }
+    }
+}
+
+/// Result of [`rekey`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RekeyReport {
+    /// Records rewritten
+    pub records: usize,
+    /// Whether the directory is encrypted afterwards
+    pub encrypted: bool,
+}
+
+/// Re-seal every record in `data_dir` from the `old` key (None: plaintext) to the `new`
+/// key (None: decrypt). Stop the server first.
+pub fn rekey(data_dir: &str, kind: BackendKind, old: Option<&KeySource>, new: Option<&KeySource>) -> Result<RekeyReport> {
+    let old_cipher = match (EncryptionHeader::load(data_dir)?, old) {
+        (Some(header), Some(source)) => Some(header.unlock(source)?),
+        (Some(_), None) => return Err(anyhow!("Data directory is encrypted; the current key is required")),
+        (None, _) => None,
+    };
+    let pending_path = EncryptionHeader::pending_path(data_dir);
+    // An interrupted run may have sealed records under the staged header, so it is never replaced
+    let (new_header, new_cipher) = match (new, EncryptionHeader::read(&pending_path)?) {
+        (Some(source), Some(staged)) => {
+            let cipher = staged.unlock(source).map_err(|e| anyhow!(
+                "An interrupted `meshbbs rekey` staged {} under another new key ({}); run it again with that key", pending_path.display(), e))?;
+            (Some(staged), Some(cipher))
+        }
+        (Some(source), None) => {
+            let (header, cipher) = EncryptionHeader::create(source)?;
+            header.write(&pending_path)?;
+            (Some(header), Some(cipher))
+        }
+        (None, Some(_)) => return Err(anyhow!(
+            "An interrupted `meshbbs rekey` staged {} under a new key; run it again with that key before decrypting", pending_path.display())),
+        (None, None) => (None, None),
+    };
+    // A previous interrupted run may have left records under the staged key
+    let staged_cipher = new_cipher.clone();
+
+    let backend = open_backend(data_dir, kind, None)?;
+    let mut reseal = |stored: &str| {
+        let plaintext = if is_sealed(stored) {
+            [old_cipher.as_ref(), staged_cipher.as_ref()]
+                .into_iter()
+                .flatten()
+                .find_map(|c| c.open(stored).ok())
+                .ok_or_else(|| anyhow!("Record cannot be decrypted with the current or new key"))?
+        } else {
+            stored.to_string()
+        };
+        Ok(match &new_cipher {
+            Some(c) => c.seal(&plaintext),
+            None => plaintext,
+        })
+    };
+    let records = backend.reseal(&mut reseal)? + reseal_archives(Path::new(data_dir), &mut reseal)?;
+
+    let header_path = EncryptionHeader::path(data_dir);
+    match new_header {
+        Some(header) => {
+            header.write(&header_path)?;
+            std::fs::remove_file(&pending_path)?;
+        }
+        None => {
+            if header_path.exists() { std::fs::remove_file(&header_path)?; }
+        }
+    }
+    Ok(RekeyReport { records, encrypted: new.is_some() })
+}
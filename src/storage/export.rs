@@ -0,0 +1,491 @@
+//! Board export and import.
+//!
+//! [`Storage::export`] writes topics, threads and replies in one of three formats:
+//!
+//! - **JSON Lines** (`jsonl`): a header line, then one line per topic (parents first) and
+//!   one per message with its replies, oldest first. This is the interchange format:
+//!   [`Storage::import_jsonl`] reads it back into another BBS.
+//! - **mbox** (`mbox`, mboxrd quoting): one mail per post and per reply, threaded with
+//!   `In-Reply-To`/`References`, for mail clients and list archivers.
+//! - **HTML** (`html`): a static site directory with `index.html` and one page per topic.
+//!
+//! Import keeps each message's id, author, title, pin and timestamps (including those of its
+//! replies) instead of re-stamping them as [`Storage::store_message`] would, and skips any
+//! message whose id is already on the board, so importing the same file twice is harmless.
+
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::STANDARD as B64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::fsck::invalid_username;
+use super::json::MAX_MESSAGE_FILE;
+use super::{upgrade_legacy_replies, Message, ReplyEntry, RuntimeTopicConfig, Storage};
+use crate::validation::{sanitize_message_content, secure_json_parse, validate_message_id, validate_topic_name};
+
+/// `format` field of the JSON Lines header
+pub const EXPORT_FORMAT: &str = "meshbbs-export";
+/// Version of the JSON Lines layout; import refuses files from a newer version
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Domain used for mbox addresses and message ids (reserved, never resolves)
+const MAIL_DOMAIN: &str = "meshbbs.invalid";
+
+/// Output format of `meshbbs export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Jsonl,
+    Mbox,
+    Html,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "jsonl" | "json" => Ok(ExportFormat::Jsonl),
+            "mbox" => Ok(ExportFormat::Mbox),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(anyhow!("Unknown export format '{}' (expected jsonl, mbox or html)", other)),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Mbox => "mbox",
+            ExportFormat::Html => "html",
+        })
+    }
+}
+
+/// What to export.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Topic ids to export; empty exports every topic
+    pub topics: Vec<String>,
+    /// Leave out topics whose read level is above this (e.g. `Some(0)` for a public archive)
+    pub max_read_level: Option<u8>,
+    /// Board name for the JSONL header and the HTML page titles
+    pub title: String,
+}
+
+/// Counts of what an export wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    pub topics: usize,
+    pub messages: usize,
+    pub replies: usize,
+}
+
+/// Outcome of a JSON Lines import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Topics that did not exist yet and were created from the file
+    pub topics_created: usize,
+    /// Messages added
+    pub imported: usize,
+    /// Messages skipped because their id is already on the board (or earlier in the file)
+    pub duplicates: usize,
+    /// Line number (1-based) and reason of each line that was not imported
+    pub rejected: Vec<(usize, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExportHeader {
+    format: String,
+    version: u32,
+    exported_at: DateTime<Utc>,
+    #[serde(default)]
+    bbs: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TopicRecord {
+    id: String,
+    #[serde(flatten)]
+    config: RuntimeTopicConfig,
+}
+
+/// One line of a JSON Lines export, tagged by `type`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record {
+    Export(ExportHeader),
+    Topic(TopicRecord),
+    Message(Message),
+}
+
+/// A topic selected for export with its threads, oldest first.
+struct ExportTopic<'a> {
+    id: String,
+    config: &'a RuntimeTopicConfig,
+    messages: Vec<Message>,
+}
+
+impl Storage {
+    /// Export the selected topics to `out`: a file for `jsonl` and `mbox`, a directory
+    /// (created if needed) for `html`.
+    pub async fn export(&self, format: ExportFormat, out: &Path, options: &ExportOptions) -> Result<ExportReport> {
+        let topics = self.export_topics(options)?;
+        let report = ExportReport {
+            topics: topics.len(),
+            messages: topics.iter().map(|t| t.messages.len()).sum(),
+            replies: topics.iter().flat_map(|t| &t.messages).map(|m| m.replies.len()).sum(),
+        };
+        match format {
+            ExportFormat::Jsonl => write_file(out, |w| write_jsonl(w, &topics, &options.title))?,
+            ExportFormat::Mbox => write_file(out, |w| write_mbox(w, &topics))?,
+            ExportFormat::Html => write_html(out, &topics, &options.title)?,
+        }
+        Ok(report)
+    }
+
+    /// Topics matching `options`, parents before their subtopics, each with its messages.
+    fn export_topics(&self, options: &ExportOptions) -> Result<Vec<ExportTopic<'_>>> {
+        for id in &options.topics {
+            if !self.topic_exists(id) {
+                bail!("Topic '{}' not found", id);
+            }
+        }
+        let mut selected: Vec<(usize, String)> = self
+            .list_configured_topics()
+            .into_iter()
+            .filter(|id| options.topics.is_empty() || options.topics.contains(id))
+            .filter(|id| {
+                let level = self.get_topic_config(id).map_or(0, |t| t.read_level);
+                options.max_read_level.is_none_or(|max| level <= max)
+            })
+            .map(|id| (self.topic_depth(&id), id))
+            .collect();
+        selected.sort();
+        selected
+            .into_iter()
+            .map(|(_, id)| {
+                let config = self.get_topic_config(&id).ok_or_else(|| anyhow!("Topic '{}' not found", id))?;
+                let mut messages = self.backend.recent_messages(&id, usize::MAX)?;
+                messages.reverse();
+                Ok(ExportTopic { id, config, messages })
+            })
+            .collect()
+    }
+
+    /// Number of ancestors of a topic (bounded, in case of a parent cycle).
+    fn topic_depth(&self, id: &str) -> usize {
+        let mut depth = 0;
+        let mut current = self.get_topic_config(id).and_then(|t| t.parent.as_deref());
+        while let Some(parent) = current {
+            depth += 1;
+            if depth > self.runtime_topics.topics.len() { break; }
+            current = self.get_topic_config(parent).and_then(|t| t.parent.as_deref());
+        }
+        depth
+    }
+
+    /// Import a JSON Lines export. Topics missing here are created with their exported
+    /// settings; existing topics keep theirs. Messages keep their ids, authors and
+    /// timestamps and are skipped when their id is already present. Lines that fail
+    /// validation are reported in [`ImportReport::rejected`] without stopping the import.
+    pub async fn import_jsonl(&mut self, reader: impl BufRead) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        for topic in self.list_configured_topics() {
+            seen.extend(self.backend.recent_messages(&topic, usize::MAX)?.into_iter().map(|m| m.id));
+        }
+        let mut topics_changed = false;
+        for (i, line) in reader.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line?;
+            if line.trim().is_empty() { continue; }
+            let record: Record = match secure_json_parse(&line, MAX_MESSAGE_FILE as usize) {
+                Ok(record) => record,
+                Err(e) => {
+                    report.rejected.push((line_no, e.to_string()));
+                    continue;
+                }
+            };
+            match record {
+                Record::Export(header) => {
+                    if header.format != EXPORT_FORMAT || header.version > EXPORT_VERSION {
+                        bail!("Line {}: unsupported export '{}' version {} (expected {} up to version {})",
+                            line_no, header.format, header.version, EXPORT_FORMAT, EXPORT_VERSION);
+                    }
+                }
+                Record::Topic(topic) => match self.import_topic(topic) {
+                    Ok(true) => {
+                        report.topics_created += 1;
+                        topics_changed = true;
+                    }
+                    Ok(false) => {}
+                    Err(e) => report.rejected.push((line_no, e.to_string())),
+                },
+                Record::Message(msg) => {
+                    let msg = match self.checked_import(msg) {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            report.rejected.push((line_no, e.to_string()));
+                            continue;
+                        }
+                    };
+                    if !seen.insert(msg.id.clone()) {
+                        report.duplicates += 1;
+                        continue;
+                    }
+                    self.backend.insert_message(&msg)?;
+                    self.reindex(|index| index.insert(&msg));
+                    report.imported += 1;
+                }
+            }
+        }
+        if topics_changed {
+            self.save_runtime_topics().await?;
+        }
+        Ok(report)
+    }
+
+    /// Add an exported topic unless one with its id exists; true when it was added.
+    fn import_topic(&mut self, topic: TopicRecord) -> Result<bool> {
+        let id = validate_topic_name(&topic.id).map_err(|e| anyhow!("Invalid topic name: {}", e))?;
+        if id != topic.id {
+            bail!("Invalid topic name: '{}' is not in canonical form", topic.id);
+        }
+        if self.topic_exists(&id) {
+            return Ok(false);
+        }
+        let mut config = topic.config;
+        // A parent left out of the export would dangle; the topic becomes top-level
+        if config.parent.as_deref().is_some_and(|p| !self.topic_exists(p)) {
+            config.parent = None;
+        }
+        self.backend.create_topic_area(&id)?;
+        self.runtime_topics.topics.insert(id, config);
+        Ok(true)
+    }
+
+    /// Validate an imported message the way posting would, without re-stamping it.
+    fn checked_import(&self, mut msg: Message) -> Result<Message> {
+        let topic = validate_topic_name(&msg.topic).map_err(|e| anyhow!("Invalid topic name: {}", e))?;
+        if topic != msg.topic {
+            bail!("Invalid topic name: '{}' is not in canonical form", msg.topic);
+        }
+        if !self.topic_exists(&topic) {
+            bail!("Topic '{}' does not exist", topic);
+        }
+        msg.id = validate_message_id(&msg.id).map_err(|e| anyhow!("Invalid message id: {}", e))?;
+        if let Some(reason) = invalid_username(&msg.author) {
+            bail!("Invalid author '{}': {}", msg.author, reason);
+        }
+        let clean = |text: &str| sanitize_message_content(text, self.max_message_bytes).map_err(|e| anyhow!("Invalid message content: {}", e));
+        msg.content = clean(&msg.content)?;
+        if let Some(title) = msg.title.take() {
+            msg.title = Some(clean(&title)?.replace(['\n', '\t'], " ")).filter(|t| !t.trim().is_empty());
+        }
+        for reply in &mut msg.replies {
+            match reply {
+                ReplyEntry::Reply(r) => {
+                    if let Some(reason) = invalid_username(&r.author) {
+                        bail!("Invalid reply author '{}': {}", r.author, reason);
+                    }
+                    r.content = clean(&r.content)?;
+                }
+                ReplyEntry::Legacy(text) => *text = clean(text)?,
+            }
+        }
+        Ok(msg)
+    }
+}
+
+/// Write through a buffered file at `path`, replacing it only once everything is written.
+fn write_file(path: &Path, f: impl FnOnce(&mut dyn Write) -> Result<()>) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut out = BufWriter::new(fs::File::create(&tmp).map_err(|e| anyhow!("Failed to create {}: {}", tmp.display(), e))?);
+    f(&mut out)?;
+    out.into_inner().map_err(|e| anyhow!("{}", e.error()))?.sync_all()?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn write_record(w: &mut dyn Write, record: &Record) -> Result<()> {
+    serde_json::to_writer(&mut *w, record)?;
+    w.write_all(b"\n")?;
+    Ok(())
+}
+
+fn write_jsonl(w: &mut dyn Write, topics: &[ExportTopic], bbs: &str) -> Result<()> {
+    write_record(w, &Record::Export(ExportHeader {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        exported_at: Utc::now(),
+        bbs: bbs.to_string(),
+    }))?;
+    for topic in topics {
+        write_record(w, &Record::Topic(TopicRecord { id: topic.id.clone(), config: topic.config.clone() }))?;
+    }
+    for topic in topics {
+        for msg in &topic.messages {
+            write_record(w, &Record::Message(msg.clone()))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_mbox(w: &mut dyn Write, topics: &[ExportTopic]) -> Result<()> {
+    for topic in topics {
+        for msg in rendered_threads(topic) {
+            let subject = format!("[{}] {}", topic.id, msg.title.as_deref().unwrap_or("(no title)"));
+            let id = format!("<{}@{}>", msg.id, MAIL_DOMAIN);
+            let mut headers = vec![("Message-ID", id.clone()), ("Subject", encode_header(&subject))];
+            if msg.pinned {
+                headers.push(("X-Meshbbs-Pinned", "yes".to_string()));
+            }
+            write_mail(w, &topic.id, &msg.author, msg.timestamp, &headers, &msg.content)?;
+            for (n, reply) in msg.replies.iter().enumerate() {
+                let ReplyEntry::Reply(reply) = reply else { continue };
+                let headers = vec![
+                    ("Message-ID", format!("<{}.{}@{}>", msg.id, n + 1, MAIL_DOMAIN)),
+                    ("Subject", encode_header(&format!("Re: {subject}"))),
+                    ("In-Reply-To", id.clone()),
+                    ("References", id.clone()),
+                ];
+                write_mail(w, &topic.id, &reply.author, reply.timestamp, &headers, &reply.content)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A topic's threads as rendered for mbox and HTML, with legacy string replies turned
+/// into authored, timestamped replies.
+fn rendered_threads(topic: &ExportTopic) -> Vec<Message> {
+    topic.messages.iter().cloned().map(|mut msg| { upgrade_legacy_replies(&mut msg); msg }).collect()
+}
+
+/// One mboxrd entry: envelope line, headers, blank line, quoted body, blank line.
+fn write_mail(w: &mut dyn Write, topic: &str, author: &str, at: DateTime<Utc>, headers: &[(&str, String)], body: &str) -> Result<()> {
+    let local: String = author.chars().map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' }).collect();
+    writeln!(w, "From {}@{} {}", local, MAIL_DOMAIN, at.format("%a %b %e %H:%M:%S %Y"))?;
+    writeln!(w, "From: {} <{}@{}>", encode_header(author), local, MAIL_DOMAIN)?;
+    writeln!(w, "Date: {}", at.to_rfc2822())?;
+    for (name, value) in headers {
+        writeln!(w, "{}: {}", name, value)?;
+    }
+    writeln!(w, "X-Meshbbs-Topic: {}", topic)?;
+    writeln!(w, "MIME-Version: 1.0")?;
+    writeln!(w, "Content-Type: text/plain; charset=utf-8")?;
+    writeln!(w, "Content-Transfer-Encoding: 8bit")?;
+    writeln!(w)?;
+    for line in body.lines() {
+        // mboxrd: any run of '>' before "From " gains one more
+        if line.trim_start_matches('>').starts_with("From ") {
+            w.write_all(b">")?;
+        }
+        writeln!(w, "{}", line)?;
+    }
+    writeln!(w)?;
+    Ok(())
+}
+
+/// Header text as-is when plain ASCII, else as an RFC 2047 encoded word.
+fn encode_header(text: &str) -> String {
+    let text = text.replace(['\r', '\n'], " ");
+    if text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        text
+    } else {
+        format!("=?utf-8?B?{}?=", B64.encode(text.as_bytes()))
+    }
+}
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:48em;margin:2em auto;padding:0 1em;color:#222}\
+article{border-top:1px solid #ccc;padding:.5em 0}\
+.meta{color:#666;font-size:.9em}\
+.content{white-space:pre-wrap}\
+.reply{margin-left:1.5em;border-left:3px solid #ddd;padding-left:.75em}";
+
+fn write_html(dir: &Path, topics: &[ExportTopic], title: &str) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let mut index = page_start(title, title);
+    index.push_str("<ul>\n");
+    for topic in topics {
+        index.push_str(&format!(
+            "<li><a href=\"{}.html\">{}</a>{} <span class=\"meta\">({} thread{})</span><br>{}</li>\n",
+            topic.id,
+            html_escape(&topic.config.name),
+            topic.config.parent.as_deref().map(|p| format!(" <span class=\"meta\">in {}</span>", html_escape(p))).unwrap_or_default(),
+            topic.messages.len(),
+            if topic.messages.len() == 1 { "" } else { "s" },
+            html_escape(&topic.config.description),
+        ));
+    }
+    index.push_str("</ul>\n");
+    index.push_str(&format!("<p class=\"meta\">Exported {}</p>\n</body>\n</html>\n", Utc::now().format("%Y-%m-%d %H:%M UTC")));
+    write_file(&dir.join("index.html"), |w| Ok(w.write_all(index.as_bytes())?))?;
+
+    for topic in topics {
+        let mut page = page_start(&format!("{} - {}", topic.config.name, title), &topic.config.name);
+        page.push_str(&format!("<p><a href=\"index.html\">{}</a></p>\n", html_escape(title)));
+        if !topic.config.description.is_empty() {
+            page.push_str(&format!("<p>{}</p>\n", html_escape(&topic.config.description)));
+        }
+        // Pinned threads first, then the most recently active, as listed on the BBS
+        let mut threads = rendered_threads(topic);
+        threads.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| b.last_activity().cmp(&a.last_activity())));
+        for msg in &threads {
+            page.push_str(&format!(
+                "<article id=\"{}\">\n<h2>{}{}</h2>\n<p class=\"meta\">{} &middot; {}</p>\n<div class=\"content\">{}</div>\n",
+                msg.id,
+                if msg.pinned { "📌 " } else { "" },
+                html_escape(msg.title.as_deref().unwrap_or("(no title)")),
+                html_escape(&msg.author),
+                msg.timestamp.format("%Y-%m-%d %H:%M UTC"),
+                html_escape(&msg.content),
+            ));
+            for reply in &msg.replies {
+                let ReplyEntry::Reply(reply) = reply else { continue };
+                page.push_str(&format!(
+                    "<div class=\"reply\"><p class=\"meta\">{} &middot; {}</p><div class=\"content\">{}</div></div>\n",
+                    html_escape(&reply.author),
+                    reply.timestamp.format("%Y-%m-%d %H:%M UTC"),
+                    html_escape(&reply.content),
+                ));
+            }
+            page.push_str("</article>\n");
+        }
+        page.push_str("</body>\n</html>\n");
+        write_file(&dir.join(format!("{}.html", topic.id)), |w| Ok(w.write_all(page.as_bytes())?))?;
+    }
+    Ok(())
+}
+
+fn page_start(title: &str, heading: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        html_escape(title),
+        STYLE,
+        html_escape(heading)
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
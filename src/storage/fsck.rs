@@ -0,0 +1,407 @@
+//! `meshbbs fsck`: consistency checks of a data directory, with an optional repair pass.
+//!
+//! The backends skip what they cannot read (`recent_messages` drops unparseable files and
+//! files whose id does not match their name) and keep legacy string replies as they are, so
+//! damage stays invisible. [`fsck`] reports it:
+//!
+//! - JSON layout: topic directories missing from `topics.json`, message, mail and user
+//!   files that are oversized, unparseable or stored under the wrong name, and users with
+//!   names the BBS would not accept
+//! - any backend: subtopics whose `parent` no longer exists, locks on deleted topics,
+//!   messages with legacy string replies (SQLite also runs its integrity check) and
+//!   retention archives that cannot be decompressed, unsealed or parsed
+//!
+//! With `repair`, bad files are moved to `data/quarantine/<time>/` (same relative path),
+//! dangling parents are cleared, stale locks dropped and legacy replies rewritten as
+//! [`Reply`] records. Repair is meant for a stopped server.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::backend::{open_backend, BackendKind, StorageBackend};
+use super::crypto::{DataCipher, RecordCodec};
+use super::json::{MAX_MESSAGE_FILE, MAX_USER_FILE};
+use super::retention::{archive_files, decode_archive};
+use super::{MailMessage, Message, Reply, ReplyEntry, User};
+use crate::validation::{safe_filename, secure_json_parse, validate_sysop_name, validate_user_name};
+
+/// Author given to legacy string replies when they are upgraded.
+pub const LEGACY_REPLY_AUTHOR: &str = "unknown";
+
+/// Kinds of problem `fsck` looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// `messages/<topic>/` with no entry in `topics.json`
+    OrphanTopicDir,
+    /// Record id (or username) does not match the file name
+    NameMismatch,
+    /// Not valid JSON for its record type, or cannot be unsealed
+    Unparseable,
+    /// Over the per-file read limit
+    Oversized,
+    /// Username the BBS would reject at registration
+    InvalidUsername,
+    /// Subtopic whose `parent` is not a topic
+    DanglingParent,
+    /// Lock on a topic that no longer exists
+    StaleLock,
+    /// Message with replies stored as bare strings
+    LegacyReplies,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Problem::OrphanTopicDir => "orphaned topic directory",
+            Problem::NameMismatch => "name mismatch",
+            Problem::Unparseable => "unparseable",
+            Problem::Oversized => "oversized",
+            Problem::InvalidUsername => "invalid username",
+            Problem::DanglingParent => "dangling parent",
+            Problem::StaleLock => "stale lock",
+            Problem::LegacyReplies => "legacy replies",
+        })
+    }
+}
+
+/// One problem at one place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub problem: Problem,
+    /// Path relative to the data directory, or `topic:<id>` / `user:<name>`
+    pub location: String,
+    pub detail: String,
+    /// What the repair pass did, if it fixed this
+    pub repaired: Option<String>,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.problem, self.location, self.detail)?;
+        if let Some(action) = &self.repaired {
+            write!(f, " [{action}]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`fsck`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FsckReport {
+    pub issues: Vec<Issue>,
+    /// Records (files, or rows through the backend) examined
+    pub checked: usize,
+    /// Where bad files were moved, if any were
+    pub quarantine: Option<PathBuf>,
+}
+
+impl FsckReport {
+    /// Issues still present after the run
+    pub fn outstanding(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.repaired.is_none())
+    }
+
+    /// e.g. `812 records checked, 3 problem(s), 2 repaired`
+    pub fn summary(&self) -> String {
+        let repaired = self.issues.len() - self.outstanding().count();
+        format!("{} records checked, {} problem(s), {} repaired", self.checked, self.issues.len(), repaired)
+    }
+
+    fn push(&mut self, problem: Problem, location: impl Into<String>, detail: impl Into<String>) -> &mut Issue {
+        self.issues.push(Issue { problem, location: location.into(), detail: detail.into(), repaired: None });
+        self.issues.last_mut().expect("just pushed")
+    }
+}
+
+/// Check `data_dir`, and with `repair` fix what can be fixed (stop the server first).
+pub fn fsck(data_dir: &str, kind: BackendKind, cipher: Option<DataCipher>, repair: bool) -> Result<FsckReport> {
+    let root = Path::new(data_dir);
+    if !root.is_dir() {
+        return Err(anyhow!("Data directory {} does not exist", data_dir));
+    }
+    let backend = open_backend(data_dir, kind, cipher.clone())?;
+    let mut check = Check {
+        root,
+        backend: backend.as_ref(),
+        codec: RecordCodec::new(cipher.clone()),
+        #[cfg(feature = "sqlite")]
+        cipher,
+        repair,
+        stamp: Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+        report: FsckReport::default(),
+    };
+    let topics = backend.load_topics()?;
+    match kind {
+        BackendKind::Json => {
+            check.message_files(|topic| topics.topics.contains_key(topic))?;
+            check.mail_files()?;
+            check.user_files()?;
+        }
+        BackendKind::Sqlite => check.sqlite()?,
+    }
+    check.topics()?;
+    check.locks()?;
+    check.archives()?;
+    if kind == BackendKind::Sqlite {
+        check.legacy_replies()?;
+    }
+    Ok(check.report)
+}
+
+struct Check<'a> {
+    root: &'a Path,
+    backend: &'a dyn StorageBackend,
+    codec: RecordCodec,
+    #[cfg(feature = "sqlite")]
+    cipher: Option<DataCipher>,
+    repair: bool,
+    stamp: String,
+    report: FsckReport,
+}
+
+impl Check<'_> {
+    fn rel(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(self.root).unwrap_or(path);
+        rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+    }
+
+    /// Record an issue with a file or directory, quarantining it when repairing.
+    fn bad_path(&mut self, problem: Problem, path: &Path, detail: String) -> Result<()> {
+        let rel = self.rel(path);
+        if self.repair {
+            self.quarantine(path, &rel)?;
+        }
+        self.report.push(problem, rel, detail).repaired = self.repair.then(|| "quarantined".to_string());
+        Ok(())
+    }
+
+    fn quarantine(&mut self, path: &Path, rel: &str) -> Result<()> {
+        let dir = self.root.join("quarantine").join(&self.stamp);
+        let dest = dir.join(rel);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(path, &dest).map_err(|e| anyhow!("Failed to quarantine {}: {}", rel, e))?;
+        self.report.quarantine = Some(dir);
+        Ok(())
+    }
+
+    /// Size-check, unseal and parse one record file; `None` after recording a problem.
+    fn read<T: DeserializeOwned>(&mut self, path: &Path, limit: u64) -> Result<Option<T>> {
+        self.report.checked += 1;
+        let size = fs::metadata(path)?.len();
+        if size > limit {
+            self.bad_path(Problem::Oversized, path, format!("{size} bytes, limit {limit}"))?;
+            return Ok(None);
+        }
+        let parsed = fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| self.codec.decode(raw))
+            .and_then(|text| secure_json_parse::<T>(&text, limit as usize).map_err(|e| anyhow!("{}", e)));
+        match parsed {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => {
+                self.bad_path(Problem::Unparseable, path, e.to_string())?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn message_files(&mut self, known_topic: impl Fn(&str) -> bool) -> Result<()> {
+        for dir in subdirs(&self.root.join("messages"))? {
+            let topic = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if !known_topic(&topic) {
+                let files = json_files(&dir)?.len();
+                self.bad_path(Problem::OrphanTopicDir, &dir, format!("'{topic}' is not in topics.json ({files} message file(s))"))?;
+                continue;
+            }
+            for path in json_files(&dir)? {
+                let Some(mut msg) = self.read::<Message>(&path, MAX_MESSAGE_FILE)? else { continue };
+                let stem = file_stem(&path);
+                if msg.id != stem || msg.topic != topic {
+                    self.bad_path(Problem::NameMismatch, &path, format!("holds message {} of topic '{}'", msg.id, msg.topic))?;
+                    continue;
+                }
+                self.upgrade_replies(&mut msg, self.rel(&path))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn mail_files(&mut self) -> Result<()> {
+        for dir in subdirs(&self.root.join("mail"))? {
+            for path in json_files(&dir)? {
+                let Some(mail) = self.read::<MailMessage>(&path, MAX_MESSAGE_FILE)? else { continue };
+                let mailbox = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                if mail.id != file_stem(&path) || safe_filename(&mail.to) != mailbox {
+                    self.bad_path(Problem::NameMismatch, &path, format!("holds mail {} to '{}'", mail.id, mail.to))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn user_files(&mut self) -> Result<()> {
+        for path in json_files(&self.root.join("users"))? {
+            let Some(user) = self.read::<User>(&path, MAX_USER_FILE)? else { continue };
+            if safe_filename(&user.username) != file_stem(&path) {
+                self.bad_path(Problem::NameMismatch, &path, format!("holds user '{}'", user.username))?;
+            } else if let Some(reason) = invalid_username(&user.username) {
+                self.bad_path(Problem::InvalidUsername, &path, format!("'{}': {}", user.username, reason))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Retention archives: every line must unseal and parse as a message.
+    fn archives(&mut self) -> Result<()> {
+        for path in archive_files(self.root)? {
+            self.report.checked += 1;
+            if let Err(e) = decode_archive(&path, &self.codec) {
+                self.bad_path(Problem::Unparseable, &path, e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// SQLite: integrity check and row decoding, then usernames (reported only).
+    fn sqlite(&mut self) -> Result<()> {
+        #[cfg(feature = "sqlite")]
+        {
+            let db = super::sqlite::SqliteBackend::open(&self.root.to_string_lossy(), self.cipher.clone())?;
+            match db.verify() {
+                Ok(rows) => self.report.checked += rows,
+                Err(e) => { self.report.push(Problem::Unparseable, super::sqlite::DB_FILE, e.to_string()); }
+            }
+        }
+        for user in self.backend.list_users()? {
+            if let Some(reason) = invalid_username(&user.username) {
+                self.report.push(Problem::InvalidUsername, format!("user:{}", user.username), reason);
+            }
+        }
+        Ok(())
+    }
+
+    fn topics(&mut self) -> Result<()> {
+        let mut topics = self.backend.load_topics()?;
+        let mut ids: Vec<String> = topics.topics.keys().cloned().collect();
+        ids.sort();
+        let mut changed = false;
+        for id in ids {
+            let Some(parent) = topics.topics[&id].parent.clone() else { continue };
+            if topics.topics.contains_key(&parent) { continue; }
+            let issue = self.report.push(Problem::DanglingParent, format!("topic:{id}"), format!("parent '{parent}' does not exist"));
+            if self.repair {
+                issue.repaired = Some("now a top-level topic".to_string());
+                if let Some(topic) = topics.topics.get_mut(&id) { topic.parent = None; }
+                changed = true;
+            }
+        }
+        if changed {
+            self.backend.save_topics(&topics)?;
+        }
+        Ok(())
+    }
+
+    fn locks(&mut self) -> Result<()> {
+        let topics = self.backend.load_topics()?;
+        let mut locked = self.backend.load_locked_topics()?;
+        let mut stale: Vec<String> = locked.iter().filter(|t| !topics.topics.contains_key(*t)).cloned().collect();
+        stale.sort();
+        for topic in &stale {
+            let issue = self.report.push(Problem::StaleLock, format!("topic:{topic}"), "locked but does not exist");
+            if self.repair {
+                issue.repaired = Some("lock removed".to_string());
+                locked.remove(topic);
+            }
+        }
+        if self.repair && !stale.is_empty() {
+            self.backend.save_locked_topics(&locked)?;
+        }
+        Ok(())
+    }
+
+    /// Backends without per-message files: scan each topic through the backend.
+    fn legacy_replies(&mut self) -> Result<()> {
+        let topics = self.backend.load_topics()?;
+        let mut ids: Vec<&String> = topics.topics.keys().collect();
+        ids.sort();
+        for topic in ids {
+            for mut msg in self.backend.recent_messages(topic, usize::MAX)? {
+                let location = format!("topic:{}/{}", msg.topic, msg.id);
+                self.upgrade_replies(&mut msg, location)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn upgrade_replies(&mut self, msg: &mut Message, location: String) -> Result<()> {
+        let legacy = msg.replies.iter().filter(|r| matches!(r, ReplyEntry::Legacy(_))).count();
+        if legacy == 0 {
+            return Ok(());
+        }
+        let repaired = if self.repair {
+            upgrade_legacy_replies(msg);
+            self.backend.update_message(msg)?;
+            Some("upgraded".to_string())
+        } else {
+            None
+        };
+        self.report.push(Problem::LegacyReplies, location, format!("{legacy} of {} replies", msg.replies.len())).repaired = repaired;
+        Ok(())
+    }
+}
+
+/// Rewrite string replies as [`Reply`] records. `name: text` keeps its author when the
+/// name is a valid username; the time is the post's, the latest the reply is known not to
+/// predate.
+pub fn upgrade_legacy_replies(msg: &mut Message) {
+    for reply in &mut msg.replies {
+        let ReplyEntry::Legacy(text) = reply else { continue };
+        let (author, content) = match text.split_once(": ") {
+            Some((name, rest)) if invalid_username(name).is_none() => (name.to_string(), rest.to_string()),
+            _ => (LEGACY_REPLY_AUTHOR.to_string(), text.clone()),
+        };
+        *reply = ReplyEntry::Reply(Reply { author, timestamp: msg.timestamp, content });
+    }
+}
+
+/// Why a stored username would not be accepted today (the sysop may use reserved names).
+pub(super) fn invalid_username(name: &str) -> Option<String> {
+    match validate_user_name(name) {
+        Ok(_) => None,
+        Err(e) => validate_sysop_name(name).err().map(|_| e.to_string()),
+    }
+}
+
+fn subdirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    if !dir.is_dir() { return Ok(dirs); }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() { dirs.push(entry.path()); }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() { return Ok(files); }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.path().extension().is_some_and(|e| e == "json") {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
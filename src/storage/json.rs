@@ -0,0 +1,593 @@
+//! JSON file backend — the original on-disk layout.
+//!
+//! ```text
+//! data/
+//! ├── users/<name>.json              ← one file per user (percent-encoded name)
+//! ├── messages/<topic>/<uuid>.json   ← one file per message, replies inline
+//! ├── mail/<name>/<uuid>.json        ← one file per private message, by recipient
+//! ├── topics.json                    ← runtime topic configuration
+//! ├── locked_topics.json
+//! ├── outbox.json                    ← store-and-forward queue
+//! ├── login_failures.json            ← failed-login counts and lockouts
+//! ├── sanctions.json                 ← bans, mutes and blocked nodes
+//! ├── deletion_audit.log             ← JSON lines
+//! ├── admin_audit.log                ← JSON lines
+//! └── slotmachine/{players,jackpot}.json
+//! ```
+//!
+//! Writes take an exclusive fs2 lock on the target file, and a shared lock on `data/.lock`
+//! for the duration of the change; `meshbbs backup` takes that one exclusively to copy the
+//! tree as of one instant. Listings and counts scan the topic directories, which is fine
+//! for small boards; see the SQLite backend otherwise.
+//! With at-rest encryption each file (and each audit line) holds a sealed record instead
+//! of plain JSON; file names are unchanged.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use log::warn;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use super::backend::{BackendKind, StorageBackend};
+use super::crypto::{DataCipher, RecordCodec};
+use super::{AdminAuditEntry, DeletionAuditEntry, HeldMessage, LockoutState, MailMessage, Message, RuntimeTopicsConfig, SanctionState, User};
+use crate::bbs::slotmachine::{GlobalJackpot, PlayersFile};
+use crate::validation::{safe_filename, secure_json_parse, secure_message_path, secure_topic_path, validate_file_size, validate_topic_name};
+
+/// Per-file read limits (DoS protection)
+pub(crate) const MAX_USER_FILE: u64 = 100_000;
+pub(crate) const MAX_MESSAGE_FILE: u64 = 1_000_000;
+
+/// Data-directory lock: shared by every write, exclusive while a backup copies the tree.
+pub(crate) const TREE_LOCK_FILE: &str = ".lock";
+
+fn open_tree_lock(data_dir: &Path) -> Result<fs::File> {
+    Ok(fs::OpenOptions::new().create(true).truncate(false).read(true).write(true).open(data_dir.join(TREE_LOCK_FILE))?)
+}
+
+/// Lock `data_dir` against JSON backend writes (from any process) until the file is dropped.
+pub(crate) fn freeze_tree(data_dir: &Path) -> Result<fs::File> {
+    let file = open_tree_lock(data_dir)?;
+    file.lock_exclusive()?;
+    Ok(file)
+}
+
+pub struct JsonBackend {
+    data_dir: String,
+    codec: RecordCodec,
+}
+
+impl JsonBackend {
+    /// Open (creating directories as needed) the JSON layout under `data_dir`, sealing
+    /// records with `cipher` if set.
+    pub fn open(data_dir: &str, cipher: Option<DataCipher>) -> Result<Self> {
+        fs::create_dir_all(data_dir)
+            .map_err(|e| anyhow!("Failed to create data directory {}: {}", data_dir, e))?;
+        fs::create_dir_all(Path::new(data_dir).join("messages"))?;
+        fs::create_dir_all(Path::new(data_dir).join("users"))?;
+        Ok(JsonBackend { data_dir: data_dir.to_string(), codec: RecordCodec::new(cipher) })
+    }
+
+    fn path(&self, name: &str) -> PathBuf { Path::new(&self.data_dir).join(name) }
+
+    fn user_file(&self, username: &str) -> PathBuf {
+        self.path("users").join(format!("{}.json", safe_filename(username)))
+    }
+
+    fn mailbox_dir(&self, recipient: &str) -> PathBuf {
+        self.path("mail").join(safe_filename(recipient))
+    }
+
+    fn mail_file(&self, recipient: &str, id: &str) -> PathBuf {
+        self.mailbox_dir(recipient).join(format!("{}.json", safe_filename(id)))
+    }
+
+    fn slot_file(&self, name: &str) -> Result<PathBuf> {
+        let dir = self.path("slotmachine");
+        fs::create_dir_all(&dir)?;
+        Ok(dir.join(name))
+    }
+
+    /// Shared hold on the tree lock for one change; released when dropped
+    fn tree_guard(&self) -> Result<fs::File> {
+        let file = open_tree_lock(Path::new(&self.data_dir))?;
+        file.lock_shared()?;
+        Ok(file)
+    }
+
+    /// Write content to a file with exclusive locking
+    fn write_file_locked(&self, path: &Path, content: &str) -> Result<()> {
+        let _tree = self.tree_guard()?;
+        let mut file = fs::OpenOptions::new().create(true).write(true).truncate(false).open(path)?;
+        file.lock_exclusive()?;
+        // Truncate only once the lock is held, so readers never see a half-written file
+        file.set_len(0)?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        // Lock is released when the file is dropped
+        Ok(())
+    }
+
+    /// Append content to a file with exclusive locking
+    fn append_file_locked(&self, path: &Path, content: &str) -> Result<()> {
+        let _tree = self.tree_guard()?;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.lock_exclusive()?;
+        file.write_all(content.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+
+    fn read_optional(path: &Path) -> Result<Option<String>> {
+        match fs::read_to_string(path) {
+            Ok(s) => Ok(Some(s)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Serialize (pretty) and seal a record for writing.
+    fn encode<T: serde::Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        Ok(self.codec.encode(serde_json::to_string_pretty(value)?))
+    }
+
+    /// Read and unseal a whole-file record.
+    fn read_record(&self, path: &Path) -> Result<Option<String>> {
+        Self::read_optional(path)?.map(|raw| self.codec.decode(raw)).transpose()
+    }
+
+    fn append_json_line<T: serde::Serialize>(&self, path: &Path, entry: &T) -> Result<()> {
+        self.append_file_locked(path, &(self.codec.encode(serde_json::to_string(entry)?) + "\n"))
+    }
+
+    fn read_json_lines<T: serde::de::DeserializeOwned>(&self, path: &Path) -> Result<Vec<T>> {
+        let content = Self::read_optional(path)?.unwrap_or_default();
+        // Malformed or undecryptable lines are skipped
+        Ok(content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| self.codec.decode(l.to_string()).ok())
+            .filter_map(|l| serde_json::from_str(&l).ok())
+            .collect())
+    }
+
+    /// Read a locked file's record; an empty file reads as None.
+    fn read_locked(&self, f: &mut fs::File) -> Result<Option<String>> {
+        let mut s = String::new();
+        f.read_to_string(&mut s)?;
+        if s.is_empty() { return Ok(None); }
+        self.codec.decode(s).map(Some)
+    }
+
+    /// Rewrite `path` through `f` via a temp file and rename; returns records rewritten.
+    fn reseal_file(path: &Path, per_line: bool, f: &mut dyn FnMut(&str) -> Result<String>) -> Result<usize> {
+        let content = fs::read_to_string(path)?;
+        if content.trim().is_empty() { return Ok(0); }
+        let (out, count) = if per_line {
+            let mut out = String::new();
+            let mut count = 0;
+            for line in content.lines().filter(|l| !l.trim().is_empty()) {
+                out.push_str(&f(line)?);
+                out.push('\n');
+                count += 1;
+            }
+            (out, count)
+        } else {
+            (f(&content)?, 1)
+        };
+        let tmp = path.with_extension("reseal.tmp");
+        fs::write(&tmp, out)?;
+        fs::rename(&tmp, path)?;
+        Ok(count)
+    }
+
+    /// Files in `dir` with the given extension.
+    fn files_with_ext(dir: &Path, ext: &str) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !dir.exists() { return Ok(files); }
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() && entry.path().extension().is_some_and(|e| e == ext) {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Message JSON files in one topic directory (oversized files skipped).
+    fn topic_message_files(&self, topic_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !topic_dir.exists() { return Ok(files); }
+        for entry in fs::read_dir(topic_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") || !entry.file_type()?.is_file() { continue; }
+            if entry.metadata().map(|m| m.len() > MAX_MESSAGE_FILE).unwrap_or(false) {
+                warn!("Skipping oversized message file: {:?}", path);
+                continue;
+            }
+            files.push(path);
+        }
+        Ok(files)
+    }
+
+    /// Directories under `messages/` with valid topic names.
+    fn topic_dirs(&self) -> Result<Vec<PathBuf>> {
+        let messages_dir = self.path("messages");
+        let mut dirs = Vec::new();
+        if !messages_dir.exists() { return Ok(dirs); }
+        for entry in fs::read_dir(&messages_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() { continue; }
+            match entry.file_name().to_str() {
+                Some(name) if validate_topic_name(name).is_ok() => dirs.push(entry.path()),
+                _ => warn!("Skipping invalid area directory: {:?}", entry.path()),
+            }
+        }
+        Ok(dirs)
+    }
+
+    /// Scan every message (or one topic's) and count those matching `pred`.
+    fn count_where(&self, topic: Option<&str>, pred: impl Fn(&Message) -> bool) -> Result<u32> {
+        let dirs = match topic {
+            Some(t) => vec![self.path("messages").join(safe_filename(t))],
+            None => self.topic_dirs()?,
+        };
+        let mut count = 0;
+        for dir in dirs {
+            for path in self.topic_message_files(&dir)? {
+                // Unreadable or unparsable files are skipped
+                if let Ok(Some(content)) = self.read_record(&path) {
+                    if let Ok(msg) = serde_json::from_str::<Message>(&content) {
+                        if pred(&msg) { count += 1; }
+                    }
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    fn message_file(&self, topic: &str, id: &str) -> Result<PathBuf> {
+        secure_message_path(&self.data_dir, topic, id).map_err(|e| anyhow!("Invalid path parameters: {}", e))
+    }
+}
+
+impl StorageBackend for JsonBackend {
+    fn kind(&self) -> BackendKind { BackendKind::Json }
+
+    fn get_user(&self, username: &str) -> Result<Option<User>> {
+        let user_file = self.user_file(username);
+        if !user_file.exists() { return Ok(None); }
+        // Check file size before reading
+        validate_file_size(fs::metadata(&user_file)?.len(), MAX_USER_FILE)
+            .map_err(|e| anyhow!("User file too large: {:?}", e))?;
+        let Some(content) = self.read_record(&user_file)? else { return Ok(None) };
+        let user: User = secure_json_parse(&content, MAX_USER_FILE as usize)
+            .map_err(|e| anyhow!("Failed to parse user file: {:?}", e))?;
+        Ok(Some(user))
+    }
+
+    fn put_user(&self, user: &User) -> Result<()> {
+        self.write_file_locked(&self.user_file(&user.username), &self.encode(user)?)
+    }
+
+    fn list_users(&self) -> Result<Vec<User>> {
+        let users_dir = self.path("users");
+        let mut users = Vec::new();
+        for path in Self::files_with_ext(&users_dir, "json")? {
+            let Some(content) = self.read_record(&path)? else { continue };
+            match serde_json::from_str::<User>(&content) {
+                Ok(user) => users.push(user),
+                Err(e) => warn!("Failed to parse user file {:?}: {}", path, e),
+            }
+        }
+        users.sort_by(|a, b| a.username.cmp(&b.username));
+        Ok(users)
+    }
+
+    fn delete_user(&self, username: &str) -> Result<bool> {
+        let path = self.user_file(username);
+        if path.exists() {
+            let _tree = self.tree_guard()?;
+            fs::remove_file(path)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn load_topics(&self) -> Result<RuntimeTopicsConfig> {
+        match self.read_record(&self.path("topics.json")) {
+            Ok(Some(data)) => serde_json::from_str(&data).map_err(|e| anyhow!("Failed to parse topics.json: {}", e)),
+            Ok(None) => Ok(RuntimeTopicsConfig::default()),
+            Err(e) => Err(anyhow!("Failed reading topics.json: {}", e)),
+        }
+    }
+
+    fn save_topics(&self, topics: &RuntimeTopicsConfig) -> Result<()> {
+        let content = self.encode(topics).map_err(|e| anyhow!("Failed to serialize topics: {}", e))?;
+        self.write_file_locked(&self.path("topics.json"), &content)
+    }
+
+    fn create_topic_area(&self, topic: &str) -> Result<()> {
+        fs::create_dir_all(self.path("messages").join(topic))?;
+        Ok(())
+    }
+
+    fn remove_topic_area(&self, topic: &str) -> Result<()> {
+        let topic_dir = self.path("messages").join(topic);
+        if topic_dir.exists() {
+            let _tree = self.tree_guard()?;
+            fs::remove_dir_all(&topic_dir).map_err(|e| anyhow!("Failed to remove topic directory: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn insert_message(&self, message: &Message) -> Result<()> {
+        // Topic directory should already exist (created by create_topic)
+        let topic_dir = secure_topic_path(&self.data_dir, &message.topic)
+            .map_err(|e| anyhow!("Path validation failed: {}", e))?;
+        if !topic_dir.exists() {
+            return Err(anyhow!("Topic directory missing - topic may need to be recreated by sysop"));
+        }
+        let message_file = secure_message_path(&self.data_dir, &message.topic, &message.id)
+            .map_err(|e| anyhow!("Message path validation failed: {}", e))?;
+        self.write_file_locked(&message_file, &self.encode(message)?)
+    }
+
+    fn get_message(&self, topic: &str, id: &str) -> Result<Option<Message>> {
+        let message_file = self.message_file(topic, id)?;
+        let Some(raw) = self.read_record(&message_file)? else { return Ok(None) };
+        let msg: Message = secure_json_parse(&raw, MAX_MESSAGE_FILE as usize)
+            .map_err(|e| anyhow!("Corrupt message file: {:?}", e))?;
+        Ok(Some(msg))
+    }
+
+    fn update_message(&self, message: &Message) -> Result<()> {
+        let message_file = self.message_file(&message.topic, &message.id)?;
+        self.write_file_locked(&message_file, &self.encode(message)?)
+    }
+
+    fn delete_message(&self, topic: &str, id: &str) -> Result<bool> {
+        let message_file = self.message_file(topic, id)?;
+        if message_file.exists() {
+            let _tree = self.tree_guard()?;
+            fs::remove_file(message_file)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn recent_messages(&self, topic: &str, limit: usize) -> Result<Vec<Message>> {
+        let topic_dir = secure_topic_path(&self.data_dir, topic).map_err(|e| anyhow!("Invalid topic name: {}", e))?;
+        let mut messages = Vec::new();
+        for path in self.topic_message_files(&topic_dir)? {
+            let content = match self.read_record(&path) {
+                Ok(Some(content)) => content,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to read message file {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let Ok(message) = serde_json::from_str::<Message>(&content) else {
+                warn!("Failed to parse message file: {:?}", path);
+                continue;
+            };
+            // The message ID must match the filename
+            if path.file_stem().and_then(|s| s.to_str()) == Some(message.id.as_str()) {
+                messages.push(message);
+            } else {
+                warn!("Message ID mismatch in file: {:?}", path);
+            }
+        }
+        // Newest first
+        messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        messages.truncate(limit);
+        Ok(messages)
+    }
+
+    fn count_messages(&self) -> Result<u32> {
+        let mut total = 0;
+        for dir in self.topic_dirs()? {
+            total += self.topic_message_files(&dir)?.len() as u32;
+        }
+        Ok(total)
+    }
+
+    fn count_messages_since(&self, topic: Option<&str>, since: DateTime<Utc>) -> Result<u32> {
+        self.count_where(topic, |m| m.timestamp > since)
+    }
+
+    fn count_user_posts(&self, username: &str) -> Result<u32> {
+        self.count_where(None, |m| m.author == username)
+    }
+
+    fn insert_mail(&self, mail: &MailMessage) -> Result<()> {
+        fs::create_dir_all(self.mailbox_dir(&mail.to))?;
+        self.write_file_locked(&self.mail_file(&mail.to, &mail.id), &self.encode(mail)?)
+    }
+
+    fn list_mail(&self, recipient: &str) -> Result<Vec<MailMessage>> {
+        let mut mail = Vec::new();
+        for path in Self::files_with_ext(&self.mailbox_dir(recipient), "json")? {
+            let content = match self.read_record(&path) {
+                Ok(Some(content)) => content,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to read mail file {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            match serde_json::from_str::<MailMessage>(&content) {
+                Ok(m) => mail.push(m),
+                Err(e) => warn!("Failed to parse mail file {:?}: {}", path, e),
+            }
+        }
+        // Newest first
+        mail.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(mail)
+    }
+
+    fn update_mail(&self, mail: &MailMessage) -> Result<()> {
+        let path = self.mail_file(&mail.to, &mail.id);
+        if !path.exists() { return Err(anyhow!("Mail not found")); }
+        self.write_file_locked(&path, &self.encode(mail)?)
+    }
+
+    fn delete_mail(&self, recipient: &str, id: &str) -> Result<bool> {
+        let path = self.mail_file(recipient, id);
+        if path.exists() {
+            let _tree = self.tree_guard()?;
+            fs::remove_file(path)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    fn append_deletion_audit(&self, entry: &DeletionAuditEntry) -> Result<()> {
+        self.append_json_line(&self.path("deletion_audit.log"), entry)
+    }
+
+    fn deletion_audit(&self) -> Result<Vec<DeletionAuditEntry>> {
+        self.read_json_lines(&self.path("deletion_audit.log"))
+    }
+
+    fn append_admin_audit(&self, entry: &AdminAuditEntry) -> Result<()> {
+        self.append_json_line(&self.path("admin_audit.log"), entry)
+    }
+
+    fn admin_audit(&self) -> Result<Vec<AdminAuditEntry>> {
+        self.read_json_lines(&self.path("admin_audit.log"))
+    }
+
+    fn load_outbox(&self) -> Result<Vec<HeldMessage>> {
+        match self.read_record(&self.path("outbox.json")) {
+            Ok(Some(data)) => serde_json::from_str(&data).map_err(|e| anyhow!("Corrupt outbox.json: {e}")),
+            Ok(None) => Ok(Vec::new()),
+            Err(e) => Err(anyhow!("Failed reading outbox: {e}")),
+        }
+    }
+
+    fn save_outbox(&self, outbox: &[HeldMessage]) -> Result<()> {
+        self.write_file_locked(&self.path("outbox.json"), &self.encode(outbox)?)
+    }
+
+    fn load_login_failures(&self) -> Result<LockoutState> {
+        match self.read_record(&self.path("login_failures.json")) {
+            Ok(Some(data)) => serde_json::from_str(&data).map_err(|e| anyhow!("Corrupt login_failures.json: {e}")),
+            Ok(None) => Ok(LockoutState::default()),
+            Err(e) => Err(anyhow!("Failed reading login failures: {e}")),
+        }
+    }
+
+    fn save_login_failures(&self, state: &LockoutState) -> Result<()> {
+        self.write_file_locked(&self.path("login_failures.json"), &self.encode(state)?)
+    }
+
+    fn load_sanctions(&self) -> Result<SanctionState> {
+        match self.read_record(&self.path("sanctions.json")) {
+            Ok(Some(data)) => serde_json::from_str(&data).map_err(|e| anyhow!("Corrupt sanctions.json: {e}")),
+            Ok(None) => Ok(SanctionState::default()),
+            Err(e) => Err(anyhow!("Failed reading sanctions: {e}")),
+        }
+    }
+
+    fn save_sanctions(&self, state: &SanctionState) -> Result<()> {
+        self.write_file_locked(&self.path("sanctions.json"), &self.encode(state)?)
+    }
+
+    fn load_locked_topics(&self) -> Result<HashSet<String>> {
+        match self.read_record(&self.path("locked_topics.json")) {
+            Ok(Some(data)) => {
+                let v: Vec<String> = serde_json::from_str(&data).unwrap_or_default();
+                Ok(v.into_iter().collect())
+            }
+            Ok(None) => Ok(HashSet::new()),
+            Err(e) => Err(anyhow!("Failed reading locked topics: {e}")),
+        }
+    }
+
+    fn save_locked_topics(&self, locked: &HashSet<String>) -> Result<()> {
+        let mut list: Vec<&String> = locked.iter().collect();
+        list.sort();
+        self.write_file_locked(&self.path("locked_topics.json"), &self.encode(&list)?)
+    }
+
+    fn load_slot_players(&self) -> Result<PlayersFile> {
+        let path = self.slot_file("players.json")?;
+        let Ok(mut f) = fs::OpenOptions::new().read(true).open(&path) else { return Ok(PlayersFile::default()) };
+        // Shared lock for read
+        let _ = f.lock_shared();
+        let s = self.read_locked(&mut f)?.unwrap_or_default();
+        Ok(serde_json::from_str(&s).unwrap_or_default())
+    }
+
+    fn save_slot_players(&self, players: &PlayersFile) -> Result<()> {
+        self.write_file_locked(&self.slot_file("players.json")?, &self.encode(players)?)
+    }
+
+    fn load_jackpot(&self) -> Result<GlobalJackpot> {
+        let path = self.slot_file("jackpot.json")?;
+        let Ok(mut f) = fs::OpenOptions::new().read(true).open(&path) else { return Ok(GlobalJackpot::default()) };
+        let _ = f.lock_shared();
+        Ok(match self.read_locked(&mut f)? {
+            Some(s) => serde_json::from_str(&s).unwrap_or_default(),
+            None => GlobalJackpot::default(),
+        })
+    }
+
+    fn update_jackpot(&self, update: &mut dyn FnMut(&mut GlobalJackpot)) -> Result<GlobalJackpot> {
+        let path = self.slot_file("jackpot.json")?;
+        // Hold the exclusive lock across read, modify and write
+        let _tree = self.tree_guard()?;
+        let mut f = fs::OpenOptions::new().create(true).read(true).write(true).truncate(false).open(&path)?;
+        f.lock_exclusive()?;
+        let mut jackpot: GlobalJackpot = match self.read_locked(&mut f)? {
+            Some(s) => serde_json::from_str(&s).unwrap_or_default(),
+            None => GlobalJackpot::default(),
+        };
+        update(&mut jackpot);
+        f.set_len(0)?;
+        f.rewind()?;
+        f.write_all(self.encode(&jackpot)?.as_bytes())?;
+        f.flush()?;
+        Ok(jackpot)
+    }
+
+    fn reseal(&self, f: &mut dyn FnMut(&str) -> Result<String>) -> Result<usize> {
+        let mut files: Vec<(PathBuf, bool)> = vec![
+            (self.path("topics.json"), false),
+            (self.path("locked_topics.json"), false),
+            (self.path("outbox.json"), false),
+            (self.path("login_failures.json"), false),
+            (self.path("sanctions.json"), false),
+            (self.path("deletion_audit.log"), true),
+            (self.path("admin_audit.log"), true),
+        ];
+        for dir in [self.path("users"), self.path("slotmachine")] {
+            files.extend(Self::files_with_ext(&dir, "json")?.into_iter().map(|p| (p, false)));
+        }
+        let mailboxes = self.path("mail");
+        let mut dirs = self.topic_dirs()?;
+        if mailboxes.exists() {
+            for entry in fs::read_dir(&mailboxes)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() { dirs.push(entry.path()); }
+            }
+        }
+        for dir in dirs {
+            files.extend(Self::files_with_ext(&dir, "json")?.into_iter().map(|p| (p, false)));
+        }
+        let mut count = 0;
+        for (path, per_line) in files {
+            if path.exists() {
+                count += Self::reseal_file(&path, per_line, f).map_err(|e| anyhow!("{}: {}", path.display(), e))?;
+            }
+        }
+        Ok(count)
+    }
+}
@@ -0,0 +1,186 @@
+//! Failed-login tracking and lockout.
+//!
+//! Failed logins are counted per account and per node ID in one [`LockoutState`] record
+//! (`login_failures.json`, or a row of the SQLite `kv` table), so a restart does not reset
+//! them. When either count reaches the policy's `max_attempts` that account or node is
+//! locked; each further lockout doubles the time up to `max_lockout_minutes`. Entries
+//! with no lockout in force are dropped once `reset_after_minutes` have passed.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::Storage;
+use crate::config::LockoutConfig;
+
+/// Actor recorded in the admin audit for lockouts.
+pub const LOCKOUT_ACTOR: &str = "lockout";
+
+/// Most entries kept per map; the stalest go first. Bounds the record when failures come
+/// from many (possibly spoofed) node IDs.
+const MAX_TRACKED: usize = 4096;
+
+/// Failed-login history of one account or node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginFailures {
+    /// Failures since the last lockout (or reset)
+    pub failures: u32,
+    /// Lockouts so far; sets the length of the next one
+    pub lockouts: u32,
+    pub last_failure: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl LoginFailures {
+    fn locked_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.locked_until.filter(|until| *until > now)
+    }
+
+    /// True once the entry no longer affects anything and can be forgotten.
+    fn expired(&self, policy: &LockoutConfig, now: DateTime<Utc>) -> bool {
+        let last = self.locked_until.map_or(self.last_failure, |until| until.max(self.last_failure));
+        now - last >= Duration::minutes(policy.reset_after_minutes as i64)
+    }
+}
+
+/// Everything tracked, by account name and by node ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockoutState {
+    #[serde(default)]
+    pub accounts: BTreeMap<String, LoginFailures>,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, LoginFailures>,
+}
+
+/// What a lockout applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockoutTarget {
+    Account(String),
+    Node(String),
+}
+
+impl LockoutTarget {
+    /// The account name or node ID
+    pub fn key(&self) -> &str {
+        match self {
+            LockoutTarget::Account(key) | LockoutTarget::Node(key) => key,
+        }
+    }
+}
+
+impl fmt::Display for LockoutTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockoutTarget::Account(name) => write!(f, "account {name}"),
+            LockoutTarget::Node(node) => write!(f, "node {node}"),
+        }
+    }
+}
+
+/// A lockout that has just started.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockout {
+    pub target: LockoutTarget,
+    pub until: DateTime<Utc>,
+    pub minutes: u64,
+    /// Failed attempts that led to it
+    pub attempts: u32,
+}
+
+fn record(map: &mut BTreeMap<String, LoginFailures>, key: &str, policy: &LockoutConfig, now: DateTime<Utc>) -> Option<(u64, DateTime<Utc>)> {
+    let entry = map.entry(key.to_string()).or_insert(LoginFailures { failures: 0, lockouts: 0, last_failure: now, locked_until: None });
+    if entry.expired(policy, now) {
+        *entry = LoginFailures { failures: 0, lockouts: 0, last_failure: now, locked_until: None };
+    }
+    entry.failures += 1;
+    entry.last_failure = now;
+    if entry.failures < policy.max_attempts.max(1) { return None; }
+    let doublings = entry.lockouts.min(32);
+    let minutes = policy.lockout_minutes.saturating_mul(1u64 << doublings).min(policy.max_lockout_minutes.max(policy.lockout_minutes));
+    let until = now + Duration::minutes(minutes as i64);
+    entry.failures = 0;
+    entry.lockouts += 1;
+    entry.locked_until = Some(until);
+    Some((minutes, until))
+}
+
+fn prune(map: &mut BTreeMap<String, LoginFailures>, policy: &LockoutConfig, now: DateTime<Utc>) {
+    map.retain(|_, e| !e.expired(policy, now));
+    if map.len() > MAX_TRACKED {
+        let mut by_age: Vec<(DateTime<Utc>, String)> = map.iter().map(|(k, e)| (e.last_failure, k.clone())).collect();
+        by_age.sort();
+        for (_, key) in by_age.into_iter().take(map.len() - MAX_TRACKED) {
+            map.remove(&key);
+        }
+    }
+}
+
+impl Storage {
+    /// When `username` or `node_id` is locked out at `now`, the time the later lockout ends.
+    /// Always `None` with the policy disabled.
+    pub async fn login_lockout(&self, username: Option<&str>, node_id: &str, policy: &LockoutConfig, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        if !policy.enabled { return Ok(None); }
+        let state = self.backend.load_login_failures()?;
+        let account = username.and_then(|u| state.accounts.get(u)).and_then(|e| e.locked_at(now));
+        let node = state.nodes.get(node_id).and_then(|e| e.locked_at(now));
+        Ok(account.max(node))
+    }
+
+    /// Count a failed login from `node_id`, against `username` too when the account exists.
+    /// Returns the lockouts this failure started.
+    pub async fn record_login_failure(&self, username: Option<&str>, node_id: &str, policy: &LockoutConfig, now: DateTime<Utc>) -> Result<Vec<Lockout>> {
+        if !policy.enabled { return Ok(Vec::new()); }
+        let mut state = self.backend.load_login_failures()?;
+        let mut started = Vec::new();
+        if let Some(user) = username {
+            if let Some((minutes, until)) = record(&mut state.accounts, user, policy, now) {
+                started.push(Lockout { target: LockoutTarget::Account(user.to_string()), until, minutes, attempts: policy.max_attempts });
+            }
+        }
+        if let Some((minutes, until)) = record(&mut state.nodes, node_id, policy, now) {
+            started.push(Lockout { target: LockoutTarget::Node(node_id.to_string()), until, minutes, attempts: policy.max_attempts });
+        }
+        prune(&mut state.accounts, policy, now);
+        prune(&mut state.nodes, policy, now);
+        self.backend.save_login_failures(&state)?;
+        Ok(started)
+    }
+
+    /// Forget the failures of `username` and `node_id` after a successful login.
+    pub async fn record_login_success(&self, username: &str, node_id: &str) -> Result<()> {
+        let mut state = self.backend.load_login_failures()?;
+        let account = state.accounts.remove(username).is_some();
+        let node = state.nodes.remove(node_id).is_some();
+        if account || node { self.backend.save_login_failures(&state)?; }
+        Ok(())
+    }
+
+    /// Lift the lockout of an account or node (`UNLOCKUSER`), clearing its failure history.
+    /// `target` may name an account (any case) or a node ID. Returns what was cleared;
+    /// empty if nothing was tracked for it.
+    pub async fn clear_lockout(&self, target: &str) -> Result<Vec<LockoutTarget>> {
+        let mut state = self.backend.load_login_failures()?;
+        let mut cleared = Vec::new();
+        let account = state.accounts.keys().find(|k| k.eq_ignore_ascii_case(target)).cloned();
+        if let Some(name) = account {
+            state.accounts.remove(&name);
+            cleared.push(LockoutTarget::Account(name));
+        }
+        if state.nodes.remove(target).is_some() {
+            cleared.push(LockoutTarget::Node(target.to_string()));
+        }
+        if !cleared.is_empty() { self.backend.save_login_failures(&state)?; }
+        Ok(cleared)
+    }
+
+    /// Accounts and nodes with a lockout in force at `now`, with its end.
+    pub async fn active_lockouts(&self, now: DateTime<Utc>) -> Result<Vec<(LockoutTarget, DateTime<Utc>)>> {
+        let state = self.backend.load_login_failures()?;
+        let accounts = state.accounts.into_iter().filter_map(|(k, e)| e.locked_at(now).map(|u| (LockoutTarget::Account(k), u)));
+        let nodes = state.nodes.into_iter().filter_map(|(k, e)| e.locked_at(now).map(|u| (LockoutTarget::Node(k), u)));
+        Ok(accounts.chain(nodes).collect())
+    }
+}
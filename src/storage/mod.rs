@@ -7,22 +7,50 @@
 //!
 //! - **Message Storage**: Persistent message boards with topic-based organization
 //! - **User Management**: Secure user account storage with Argon2id password hashing
+//! - **Private Mail**: Per-user mailboxes with read/unread flags
+//! - **Search**: Word-prefix index over posts and replies in every topic
+//! - **Retention**: Per-topic age and thread-count limits, with optional gzip archives
+//! - **Store-and-Forward**: Outbox of undelivered notices awaiting their node
+//! - **Login Lockout**: Persistent failed-login counts per account and node
+//! - **Password Reset**: Hashed one-time codes issued by staff, with an expiry
+//! - **Sanctions**: Bans, mutes and blocked nodes, each with a reason and optional expiry
 //! - **Audit Logging**: Comprehensive logging of administrative actions and deletions
+//! - **Pluggable Backends**: JSON files (default) or a single indexed SQLite database
+//! - **Encryption at Rest**: Optional AEAD sealing of every stored record
+//! - **Backups**: Consistent, versioned `.tar.gz` snapshots, validated on restore
+//! - **Export/Import**: Boards as JSON Lines, mbox or a static HTML site; JSONL import keeps ids and timestamps
 //! - **File Locking**: Safe concurrent access to data files
 //! - **Input Validation**: Comprehensive sanitization and validation of all stored data
 //!
 //! ## Architecture
 //!
-//! The storage system uses a file-based approach with JSON serialization:
+//! [`Storage`] enforces the BBS rules (validation, permissions, password hashing) and
+//! persists through a [`StorageBackend`]. The default JSON backend keeps one file per
+//! record:
 //!
 //! ```text
 //! data/
 //! ├── users/          ← User account data
 //! ├── messages/       ← Message topic storage
-//! ├── audit/          ← Administrative audit logs
-//! └── config/         ← Runtime configuration
+//! ├── mail/           ← Private mailboxes (one directory per recipient)
+//! ├── topics.json     ← Runtime topic configuration
+//! ├── outbox.json     ← Notices held for offline nodes
+//! ├── sanctions.json  ← Bans, mutes and blocked nodes
+//! ├── archive/        ← Expired threads (`*.jsonl.gz`, when archiving is on)
+//! └── *_audit.log     ← Administrative audit logs
 //! ```
 //!
+//! The SQLite backend (feature `sqlite`) stores the same records in `data/meshbbs.db`
+//! with indexes on topic, timestamp and author. `meshbbs migrate --from json --to sqlite`
+//! converts an existing data directory.
+//!
+//! With `[security.encryption]` configured, both backends seal each record with a key
+//! derived from the operator's passphrase or keyfile (see the `crypto` module);
+//! `meshbbs rekey` encrypts, re-keys or decrypts an existing tree in place.
+//!
+//! `meshbbs backup` archives the whole directory as of one instant while the server runs,
+//! and `meshbbs restore` validates an archive before swapping it in (see the `backup` module).
+//!
 //! ## Usage
 //!
 //! ```rust,no_run
@@ -72,6 +100,7 @@
 //! [storage]
 //! data_dir = "./data"
 //! max_message_size = 230
+//! backend = "json"   # or "sqlite"
 //! ```
 //!
 //! ## Error Handling
@@ -83,30 +112,58 @@
 //! - Concurrent access conflicts
 //! - Storage quota enforcement
 
+mod backend;
+mod backup;
+mod crypto;
+mod export;
+mod fsck;
+mod json;
+mod lockout;
+mod reset;
+mod retention;
+mod sanctions;
+mod search;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use backend::{migrate, open_backend, BackendKind, MigrationReport, StorageBackend};
+pub use backup::{backup, restore, rotate_backups, scheduled_backup, BackupManifest, RestoreReport, BACKUP_VERSION, MANIFEST_FILE};
+pub use crypto::{open_data_key, rekey, DataCipher, EncryptionHeader, KeySource, RekeyReport, HEADER_FILE};
+pub use export::{ExportFormat, ExportOptions, ExportReport, ImportReport, EXPORT_FORMAT, EXPORT_VERSION};
+pub use fsck::{fsck, upgrade_legacy_replies, FsckReport, Issue, Problem, LEGACY_REPLY_AUTHOR};
+pub use json::JsonBackend;
+pub use lockout::{Lockout, LockoutState, LockoutTarget, LoginFailures, LOCKOUT_ACTOR};
+pub use reset::{PasswordReset, RESET_CODE_TTL_MINUTES};
+pub use retention::{read_archive, RetentionReport, TopicRetention, RETENTION_ACTOR};
+pub use sanctions::{Sanction, SanctionKind, SanctionState, MAX_SANCTION_REASON};
+pub use search::SearchHit;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteBackend;
+
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::collections::{HashSet, HashMap};
-use std::io::ErrorKind;
 use tokio::fs;
 use uuid::Uuid;
 use crate::bbs::roles;
-use crate::validation::{validate_user_name, safe_filename, validate_topic_name, sanitize_message_content, secure_message_path, secure_topic_path, secure_json_parse, validate_file_size};
+use crate::validation::{validate_user_name, validate_topic_name, validate_message_id, sanitize_message_content};
 use password_hash::{PasswordHasher, PasswordVerifier};
 use argon2::{Argon2, Params, Algorithm, Version};
-use log::warn;
-use fs2::FileExt;
 
 /// Main storage interface
 pub struct Storage {
     data_dir: String,
+    backend: Box<dyn StorageBackend>,
     argon2: Argon2<'static>,
     locked_topics: HashSet<String>,
     #[allow(dead_code)]
     topic_levels: std::collections::HashMap<String, (u8,u8)>, // topic -> (read_level, post_level)
     max_message_bytes: usize,
     runtime_topics: RuntimeTopicsConfig, // Runtime-managed topic configurations
+    search: std::sync::Mutex<Option<search::SearchIndex>>, // built on first search
+    codec: crypto::RecordCodec, // seals retention archives like the backend's records
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -156,11 +213,52 @@ pub struct AdminAuditEntry {
     pub details: Option<String>, // additional context
 }
 
+/// A private user-to-user message, stored in the recipient's mailbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailMessage {
+    pub id: String,
+    pub from: String,
+    /// Mailbox owner
+    pub to: String,
+    pub content: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub read: bool,
+}
+
+/// Maximum messages held in one mailbox; further mail is refused until the owner deletes some
+pub const MAX_MAILBOX: usize = 100;
+
+/// A notice that could not be delivered to a node, held for store-and-forward
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HeldMessage {
+    pub id: String,
+    /// Destination node id (decimal, as used for sessions)
+    pub node_id: String,
+    pub content: String,
+    pub queued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Delivery attempts made so far (the original send counts as one)
+    #[serde(default)]
+    pub attempts: u32,
+}
+
+/// Upper bound on held messages across all nodes; the oldest are dropped beyond it
+pub const MAX_HELD_TOTAL: usize = 1000;
+
+/// Most nodes one user can trust
+pub const MAX_TRUSTED_NODES: usize = 8;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub username: String,
+    /// Home node: the first one the account was bound to; offline notices go here
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub node_id: Option<String>,
+    /// Every node the user has trusted, including the home node (older records list none;
+    /// their home node is still trusted)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_nodes: Vec<TrustedNode>,
     #[serde(
         rename = "access_level",
         default = "default_user_level",
@@ -169,6 +267,9 @@ pub struct User {
     pub user_level: u8,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub password_hash: Option<String>,
+    /// Outstanding one-time reset code from `RESETPW`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_reset: Option<PasswordReset>,
     pub first_login: DateTime<Utc>,
     pub last_login: DateTime<Utc>,
     pub total_messages: u32,
@@ -180,6 +281,35 @@ pub struct User {
 
 fn default_user_level() -> u8 { 1 }
 
+/// A node a user has marked as theirs with `TRUST`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedNode {
+    pub node_id: String,
+    /// Node name from the node cache at the time, if it was known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub added: DateTime<Utc>,
+}
+
+impl User {
+    /// True if `node_id` is the home node or one of the trusted nodes
+    pub fn trusts_node(&self, node_id: &str) -> bool {
+        self.node_id.as_deref() == Some(node_id) || self.trusted_nodes.iter().any(|n| n.node_id == node_id)
+    }
+
+    /// Trusted nodes, home node first; older records get their home node listed.
+    pub fn known_nodes(&self) -> Vec<TrustedNode> {
+        let mut nodes = self.trusted_nodes.clone();
+        if let Some(home) = &self.node_id {
+            match nodes.iter().position(|n| &n.node_id == home) {
+                Some(i) => { let node = nodes.remove(i); nodes.insert(0, node); }
+                None => nodes.insert(0, TrustedNode { node_id: home.clone(), label: None, added: self.first_login }),
+            }
+        }
+        nodes
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BbsStatistics {
     pub total_messages: u32,
@@ -201,6 +331,9 @@ pub struct RuntimeTopicConfig {
     /// Optional parent topic for hierarchical organization (subtopics)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parent: Option<String>,
+    /// Age and thread-count limits enforced by the retention task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retention: Option<TopicRetention>,
 }
 
 /// Collection of all runtime topic configurations
@@ -211,146 +344,95 @@ pub struct RuntimeTopicsConfig {
 }
 
 impl Storage {
-    /// Initialize storage with the given data directory
+    /// Initialize storage with the given data directory (JSON backend)
     pub async fn new(data_dir: &str) -> Result<Self> {
+        Self::open(data_dir, BackendKind::Json, None, None).await
+    }
+
+    /// Initialize storage with explicit Argon2 params (JSON backend)
+    pub async fn new_with_params(data_dir: &str, params: Option<Params>) -> Result<Self> {
+        Self::open(data_dir, BackendKind::Json, params, None).await
+    }
+
+    /// Initialize storage on the configured backend with optional Argon2 params, sealing
+    /// records with `cipher` (see [`open_data_key`]) when the data directory is encrypted
+    pub async fn open(data_dir: &str, kind: BackendKind, params: Option<Params>, cipher: Option<DataCipher>) -> Result<Self> {
         // Create data directory if it doesn't exist
         fs::create_dir_all(data_dir).await
             .map_err(|e| anyhow!("Failed to create data directory {}: {}", data_dir, e))?;
-        
-        // Create subdirectories
-        let messages_dir = Path::new(data_dir).join("messages");
-        let users_dir = Path::new(data_dir).join("users");
-        let files_dir = Path::new(data_dir).join("files");
-        
-        fs::create_dir_all(&messages_dir).await?;
-        fs::create_dir_all(&users_dir).await?;
-        fs::create_dir_all(&files_dir).await?;
-        
-        let locked = Self::load_locked_topics(data_dir).await?;
-        let runtime_topics = Self::load_runtime_topics(data_dir).await?;
+        fs::create_dir_all(Path::new(data_dir).join("files")).await?;
+        let backend = open_backend(data_dir, kind, cipher.clone())?;
+        let mut storage = Self::with_backend(data_dir, backend, params)?;
+        storage.codec = crypto::RecordCodec::new(cipher);
+        Ok(storage)
+    }
+
+    /// Wrap an already opened backend (retention archives are written unsealed; use
+    /// [`Storage::open`] for an encrypted data directory)
+    pub fn with_backend(data_dir: &str, backend: Box<dyn StorageBackend>, params: Option<Params>) -> Result<Self> {
+        let argon2 = if let Some(p) = params { Argon2::new(Algorithm::Argon2id, Version::V0x13, p) } else { Argon2::default() };
+        let locked = backend.load_locked_topics()?;
+        let runtime_topics = backend.load_topics()?;
         Ok(Storage {
             data_dir: data_dir.to_string(),
-            argon2: Argon2::default(),
+            backend,
+            argon2,
             locked_topics: locked,
             topic_levels: HashMap::new(),
             max_message_bytes: 230,
             runtime_topics,
+            search: Default::default(),
+            codec: Default::default(),
         })
     }
 
-    /// Initialize storage with explicit Argon2 params
-    pub async fn new_with_params(data_dir: &str, params: Option<Params>) -> Result<Self> {
-        // Ensure base and subdirectories just like `new`
-        fs::create_dir_all(data_dir).await?;
-        let messages_dir = Path::new(data_dir).join("messages");
-        let users_dir = Path::new(data_dir).join("users");
-        let files_dir = Path::new(data_dir).join("files");
-        fs::create_dir_all(&messages_dir).await?;
-        fs::create_dir_all(&users_dir).await?;
-        fs::create_dir_all(&files_dir).await?;
-        let argon2 = if let Some(p) = params { Argon2::new(Algorithm::Argon2id, Version::V0x13, p) } else { Argon2::default() };
-        let locked = Self::load_locked_topics(data_dir).await?;
-        let runtime_topics = Self::load_runtime_topics(data_dir).await?;
-        Ok(Storage { data_dir: data_dir.to_string(), argon2, locked_topics: locked, topic_levels: HashMap::new(), max_message_bytes: 230, runtime_topics })
-    }
-
     #[allow(dead_code)]
     pub fn set_topic_levels(&mut self, map: std::collections::HashMap<String,(u8,u8)>) { self.topic_levels = map; }
     pub fn get_topic_levels(&self, topic: &str) -> Option<(u8,u8)> { self.topic_levels.get(topic).copied() }
     #[allow(dead_code)]
     pub fn set_max_message_bytes(&mut self, max: usize) { self.max_message_bytes = max.min(230); }
 
-    async fn load_locked_topics(data_dir: &str) -> Result<HashSet<String>> {
-        let path = Path::new(data_dir).join("locked_topics.json");
-        match fs::read_to_string(&path).await {
-            Ok(data) => {
-                let v: Vec<String> = serde_json::from_str(&data).unwrap_or_default();
-                Ok(v.into_iter().collect())
-            }
-            Err(e) if e.kind() == ErrorKind::NotFound => Ok(HashSet::new()),
-            Err(e) => Err(anyhow!("Failed reading locked topics: {e}")),
-        }
+    /// Save runtime topic configurations
+    async fn save_runtime_topics(&self) -> Result<()> {
+        self.backend.save_topics(&self.runtime_topics)
     }
 
-    /// Load runtime topic configurations from topics.json
-    async fn load_runtime_topics(data_dir: &str) -> Result<RuntimeTopicsConfig> {
-        let path = Path::new(data_dir).join("topics.json");
-        match fs::read_to_string(&path).await {
-            Ok(data) => {
-                let config: RuntimeTopicsConfig = serde_json::from_str(&data)
-                    .map_err(|e| anyhow!("Failed to parse topics.json: {}", e))?;
-                Ok(config)
-            }
-            Err(e) if e.kind() == ErrorKind::NotFound => Ok(RuntimeTopicsConfig::default()),
-            Err(e) => Err(anyhow!("Failed reading topics.json: {}", e)),
-        }
+    async fn persist_locked_topics(&self) -> Result<()> {
+        self.backend.save_locked_topics(&self.locked_topics)
     }
 
-    /// Save runtime topic configurations to topics.json
-    async fn save_runtime_topics(&self) -> Result<()> {
-        let path = Path::new(&self.data_dir).join("topics.json");
-        let content = serde_json::to_string_pretty(&self.runtime_topics)
-            .map_err(|e| anyhow!("Failed to serialize topics: {}", e))?;
-        Self::write_file_locked(&path, &content).await
-    }
+    /// Return the base data directory path used by this storage instance
+    pub fn base_dir(&self) -> &str { &self.data_dir }
 
-    /// Helper function to write content to a file with exclusive locking
-    async fn write_file_locked(path: &Path, content: &str) -> Result<()> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        // Use synchronous I/O for file locking since fs2 doesn't support async
-        let mut file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(path)?;
-            
-        // Acquire exclusive lock
-        file.lock_exclusive()?;
-        
-        // Write content
-        file.write_all(content.as_bytes())?;
-        file.flush()?;
-        
-        // Lock is automatically released when file is dropped
-        Ok(())
-    }
+    /// The persistence backend (also used directly by the slot machine)
+    pub fn backend(&self) -> &dyn StorageBackend { self.backend.as_ref() }
 
-    /// Helper function to append content to a file with exclusive locking
-    async fn append_file_locked(path: &Path, content: &str) -> Result<()> {
-        use std::fs::OpenOptions;
-        use std::io::Write;
-        
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(path)?;
-            
-        // Acquire exclusive lock
-        file.lock_exclusive()?;
-        
-        // Write content
-        file.write_all(content.as_bytes())?;
-        file.flush()?;
-        
-        // Lock is automatically released when file is dropped
-        Ok(())
+    fn argon2_configured(&self) -> &Argon2<'static> { &self.argon2 }
+
+    /// Apply a change to the search index, if it has been built yet
+    fn reindex(&self, f: impl FnOnce(&mut search::SearchIndex)) {
+        if let Some(index) = self.search.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            f(index);
+        }
     }
 
-    async fn persist_locked_topics(&self) -> Result<()> {
-        let path = Path::new(&self.data_dir).join("locked_topics.json");
-        let mut list: Vec<String> = self.locked_topics.iter().cloned().collect();
-        list.sort();
-        let data = serde_json::to_string_pretty(&list)?;
-        Self::write_file_locked(&path, &data).await?;
-        Ok(())
+    /// Load an existing user or fail with "User not found"
+    fn require_user(&self, username: &str) -> Result<User> {
+        self.backend.get_user(username)?.ok_or_else(|| anyhow!("User not found"))
     }
 
-    /// Return the base data directory path used by this storage instance
-    pub fn base_dir(&self) -> &str { &self.data_dir }
+    /// Validate a (topic, message id) pair before it reaches the backend
+    fn message_ref(topic: &str, id: &str) -> Result<(String, String)> {
+        let topic = validate_topic_name(topic).map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
+        let id = validate_message_id(id).map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
+        Ok((topic, id))
+    }
 
-    fn argon2_configured(&self) -> &Argon2<'static> { &self.argon2 }
+    /// Load a message for modification or fail with "Message not found"
+    fn require_message(&self, topic: &str, id: &str) -> Result<Message> {
+        let (topic, id) = Self::message_ref(topic, id)?;
+        self.backend.get_message(&topic, &id)?.ok_or_else(|| anyhow!("Message not found"))
+    }
 
     /// Register a new user with password; fails if user exists.
     pub async fn register_user(&mut self, username: &str, password: &str, maybe_node: Option<&str>) -> Result<()> {
@@ -366,9 +448,6 @@ impl Storage {
             return Err(anyhow!("Username '{}' is already taken", validated_username)); 
         }
         
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_name = safe_filename(&validated_username);
-        let user_file = users_dir.join(format!("{}.json", safe_name));
         let now = Utc::now();
         let salt = password_hash::SaltString::generate(&mut rand::thread_rng());
         let hash = self.argon2_configured().hash_password(password.as_bytes(), &salt)
@@ -376,17 +455,17 @@ impl Storage {
         let user = User {
             username: validated_username,
             node_id: maybe_node.map(|s| s.to_string()),
+            trusted_nodes: maybe_node.map(|n| TrustedNode { node_id: n.to_string(), label: None, added: now }).into_iter().collect(),
             user_level: 1,
             password_hash: Some(hash.to_string()),
+            password_reset: None,
             first_login: now,
             last_login: now,
             total_messages: 0,
             welcome_shown_on_registration: false,
             welcome_shown_on_first_login: false,
         };
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
-        Ok(())
+        self.backend.put_user(&user)
     }
 
     /// Verify user password; returns (user, bool match)
@@ -404,37 +483,71 @@ impl Storage {
         Ok((None, false))
     }
 
-    /// Bind a user to a node id if not already bound. Returns updated user.
+    /// Bind a user to a node id if not already bound; the node becomes the home node and is
+    /// trusted. Returns updated user.
     pub async fn bind_user_node(&mut self, username: &str, node_id: &str) -> Result<User> {
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        if !user_file.exists() { return Err(anyhow!("User not found")); }
-        let content = fs::read_to_string(&user_file).await?;
-        let mut user: User = serde_json::from_str(&content)?;
-        if user.node_id.is_none() { user.node_id = Some(node_id.to_string()); }
+        let mut user = self.require_user(username)?;
+        if user.node_id.is_none() {
+            user.trusted_nodes = user.known_nodes();
+            if !user.trusts_node(node_id) {
+                user.trusted_nodes.insert(0, TrustedNode { node_id: node_id.to_string(), label: None, added: Utc::now() });
+            }
+            user.node_id = Some(node_id.to_string());
+        }
         user.last_login = Utc::now();
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
+        self.backend.put_user(&user)?;
+        Ok(user)
+    }
+
+    /// Trust `node_id` for `username` (`TRUST`), or refresh its label if already trusted.
+    /// The first trusted node becomes the home node. Returns updated user.
+    pub async fn trust_node(&mut self, username: &str, node_id: &str, label: Option<&str>) -> Result<User> {
+        let node_id = node_id.trim();
+        if node_id.is_empty() || node_id.len() > 32 || node_id.chars().any(|c| c.is_control() || c.is_whitespace()) {
+            return Err(anyhow!("Invalid node id"));
+        }
+        let label = label.map(|l| l.trim().chars().filter(|c| !c.is_control()).take(40).collect::<String>()).filter(|l| !l.is_empty());
+        let mut user = self.require_user(username)?;
+        user.trusted_nodes = user.known_nodes();
+        match user.trusted_nodes.iter_mut().find(|n| n.node_id == node_id) {
+            Some(node) => { if label.is_some() { node.label = label; } }
+            None => {
+                if user.trusted_nodes.len() >= MAX_TRUSTED_NODES {
+                    return Err(anyhow!("At most {} trusted nodes", MAX_TRUSTED_NODES));
+                }
+                user.trusted_nodes.push(TrustedNode { node_id: node_id.to_string(), label, added: Utc::now() });
+            }
+        }
+        if user.node_id.is_none() { user.node_id = Some(node_id.to_string()); }
+        self.backend.put_user(&user)?;
         Ok(user)
     }
 
+    /// Stop trusting `node_id` (`UNTRUST`). Removing the home node makes the next trusted
+    /// node the home node. Returns None if the node was not trusted.
+    pub async fn untrust_node(&mut self, username: &str, node_id: &str) -> Result<Option<User>> {
+        let mut user = self.require_user(username)?;
+        if !user.trusts_node(node_id) { return Ok(None); }
+        user.trusted_nodes = user.known_nodes();
+        user.trusted_nodes.retain(|n| n.node_id != node_id);
+        if user.node_id.as_deref() == Some(node_id) {
+            user.node_id = user.trusted_nodes.first().map(|n| n.node_id.clone());
+        }
+        self.backend.put_user(&user)?;
+        Ok(Some(user))
+    }
+
     /// Set password for an existing (possibly passwordless) user. Overwrites existing hash.
     pub async fn set_user_password(&mut self, username: &str, password: &str) -> Result<User> {
         if password.len() < 8 { return Err(anyhow!("Password too short (minimum 8 characters)")); }
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        if !user_file.exists() { return Err(anyhow!("User not found")); }
-        let content = fs::read_to_string(&user_file).await?;
-        let mut user: User = serde_json::from_str(&content)?;
+        let mut user = self.require_user(username)?;
         let salt = password_hash::SaltString::generate(&mut rand::thread_rng());
         let hash = self.argon2_configured().hash_password(password.as_bytes(), &salt)
             .map_err(|e| anyhow!("Password hash failure: {e}"))?;
         user.password_hash = Some(hash.to_string());
+        user.password_reset = None;
         user.last_login = Utc::now();
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
+        self.backend.put_user(&user)?;
         Ok(user)
     }
 
@@ -442,31 +555,20 @@ impl Storage {
     pub async fn update_user_password(&mut self, username: &str, new_password: &str) -> Result<()> {
         if new_password.len() < 8 { return Err(anyhow!("Password too short (min 8)")); }
         if new_password.len() > 128 { return Err(anyhow!("Password too long")); }
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        if !user_file.exists() { return Err(anyhow!("User not found")); }
-        let content = fs::read_to_string(&user_file).await?;
-        let mut user: User = serde_json::from_str(&content)?;
+        let mut user = self.require_user(username)?;
         let salt = password_hash::SaltString::generate(&mut rand::thread_rng());
         let hash = self.argon2_configured().hash_password(new_password.as_bytes(), &salt)
             .map_err(|e| anyhow!("Password hash failure: {e}"))?;
         user.password_hash = Some(hash.to_string());
+        user.password_reset = None;
         user.last_login = Utc::now(); // treat as activity
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
-        Ok(())
+        self.backend.put_user(&user)
     }
 
     /// Update a user's access level (e.g., promote/demote). Returns updated user.
     pub async fn update_user_level(&mut self, username: &str, new_level: u8, actor: &str) -> Result<User> {
         if new_level == 0 { return Err(anyhow!("Invalid level")); }
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        if !user_file.exists() { return Err(anyhow!("User not found")); }
-        let content = fs::read_to_string(&user_file).await?;
-        let mut user: User = serde_json::from_str(&content)?;
+        let mut user = self.require_user(username)?;
         // Prevent changing sysop level (level 10) via storage API to enforce immutability
         if user.user_level == 10 && user.username == username && new_level != 10 {
             return Err(anyhow!("Cannot modify sysop level"));
@@ -474,8 +576,7 @@ impl Storage {
         let old_level = user.user_level;
         user.user_level = new_level;
         user.last_login = Utc::now(); // treat promotion as activity
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
+        self.backend.put_user(&user)?;
         
         // Log the administrative action
         let action = if new_level > old_level { "PROMOTE" } else { "DEMOTE" };
@@ -485,6 +586,26 @@ impl Storage {
         Ok(user)
     }
 
+    /// Clear a user's home node and trusted nodes so the next login from any node binds again.
+    /// Returns updated user.
+    pub async fn unbind_user_node(&mut self, username: &str) -> Result<User> {
+        let mut user = self.require_user(username)?;
+        user.node_id = None;
+        user.trusted_nodes.clear();
+        self.backend.put_user(&user)?;
+        Ok(user)
+    }
+
+    /// Delete a user account and its mailbox; posts and replies keep their author name.
+    /// Returns false if the user does not exist.
+    pub async fn delete_user(&mut self, username: &str) -> Result<bool> {
+        if self.backend.get_user(username)?.is_none() { return Ok(false); }
+        for mail in self.backend.list_mail(username)? {
+            self.backend.delete_mail(username, &mail.id)?;
+        }
+        self.backend.delete_user(username)
+    }
+
     /// Store a new message
     pub async fn store_message(&mut self, topic: &str, author: &str, content: &str) -> Result<String> {
         // Validate and sanitize inputs
@@ -521,7 +642,7 @@ impl Storage {
 
         let message = Message {
             id: Uuid::new_v4().to_string(),
-            topic: validated_topic.clone(),
+            topic: validated_topic,
             author: author.to_string(),
             title,
             content: sanitized_content,
@@ -530,142 +651,50 @@ impl Storage {
             pinned: false,
         };
         
-        // Topic directory should already exist (created by create_topic)
-        let topic_dir = secure_topic_path(&self.data_dir, &validated_topic)
-            .map_err(|e| anyhow!("Path validation failed: {}", e))?;
-        
-        if !topic_dir.exists() {
-            return Err(anyhow!("Topic directory missing - topic may need to be recreated by sysop"));
-        }
-        
-        let message_file = secure_message_path(&self.data_dir, &validated_topic, &message.id)
-            .map_err(|e| anyhow!("Message path validation failed: {}", e))?;
-        
-        let json_content = serde_json::to_string_pretty(&message)?;
-        
-        Self::write_file_locked(&message_file, &json_content).await?;
+        self.backend.insert_message(&message)?;
+        self.reindex(|index| index.insert(&message));
         
         Ok(message.id)
     }
 
     /// Count messages whose timestamp is strictly greater than the supplied instant.
-    /// This performs a linear scan of all message JSON files. For typical small BBS
-    /// deployments this is acceptable; if performance becomes an issue we can
-    /// introduce a lightweight global index or per-topic cached high-water marks.
+    /// The JSON backend scans every message file; SQLite answers from its timestamp index.
     pub async fn count_messages_since(&self, since: DateTime<Utc>) -> Result<u32> {
-        let mut count: u32 = 0;
-        let messages_dir = Path::new(&self.data_dir).join("messages");
-        if !messages_dir.exists() { return Ok(0); }
-        let mut area_entries = fs::read_dir(&messages_dir).await?;
-        while let Some(area_entry) = area_entries.next_entry().await? {
-            if area_entry.file_type().await?.is_dir() {
-                // Validate topic name to prevent processing invalid directories
-                if let Some(area_name) = area_entry.file_name().to_str() {
-                    if validate_topic_name(area_name).is_err() {
-                        warn!("Skipping invalid area directory: {}", area_name);
-                        continue;
-                    }
-                } else {
-                    warn!("Skipping directory with invalid name: {:?}", area_entry.path());
-                    continue;
-                }
-                
-                let mut message_entries = fs::read_dir(area_entry.path()).await?;
-                while let Some(message_entry) = message_entries.next_entry().await? {
-                    if message_entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                        // Check file size before reading to prevent DoS
-                        if let Ok(metadata) = message_entry.metadata().await {
-                            if metadata.len() > 1_000_000 { // 1MB limit per message file
-                                warn!("Skipping oversized message file: {:?}", message_entry.path());
-                                continue;
-                            }
-                        }
-                        
-                        // Read then parse timestamp only; if parsing fails, skip file.
-                        if let Ok(content) = fs::read_to_string(message_entry.path()).await {
-                            if let Ok(msg) = serde_json::from_str::<Message>(&content) {
-                                if msg.timestamp > since { count += 1; }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        Ok(count)
+        self.backend.count_messages_since(None, since)
     }
 
     /// Count messages in a specific topic whose timestamp is strictly greater than `since`.
     pub async fn count_messages_since_in_topic(&self, topic: &str, since: DateTime<Utc>) -> Result<u32> {
-        let mut count: u32 = 0;
-        let topic_dir = Path::new(&self.data_dir).join("messages").join(safe_filename(topic));
-        if !topic_dir.exists() { return Ok(0); }
-        let mut message_entries = fs::read_dir(&topic_dir).await?;
-        while let Some(entry) = message_entries.next_entry().await? {
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Ok(metadata) = entry.metadata().await {
-                    if metadata.len() > 1_000_000 { continue; }
-                }
-                if let Ok(content) = fs::read_to_string(entry.path()).await {
-                    if let Ok(msg) = serde_json::from_str::<Message>(&content) {
-                        if msg.timestamp > since { count += 1; }
-                    }
-                }
-            }
-        }
-        Ok(count)
+        self.backend.count_messages_since(Some(topic), since)
     }
 
     /// Record a successful user login (updating last_login) and return updated user.
     pub async fn record_user_login(&self, username: &str) -> Result<User> {
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        if !user_file.exists() { return Err(anyhow!("User not found")); }
-        
-        // Check file size before reading
-        let metadata = fs::metadata(&user_file).await?;
-        validate_file_size(metadata.len(), 100_000) // 100KB limit for user files
-            .map_err(|e| anyhow!("User file too large: {:?}", e))?;
-        
-        let content = fs::read_to_string(&user_file).await?;
-        let mut user: User = secure_json_parse(&content, 100_000)
-            .map_err(|e| anyhow!("Failed to parse user file: {:?}", e))?;
-        
+        let mut user = self.require_user(username)?;
         user.last_login = Utc::now();
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
+        self.backend.put_user(&user)?;
         Ok(user)
     }
 
     /// Delete a message by topic and id
     pub async fn delete_message(&mut self, topic: &str, id: &str) -> Result<bool> {
         // Validate inputs to prevent path traversal
-        let message_file = secure_message_path(&self.data_dir, topic, id)
-            .map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
-        
-        if message_file.exists() { 
-            fs::remove_file(message_file).await?; 
-            return Ok(true); 
-        }
-        Ok(false)
+        let (topic, id) = Self::message_ref(topic, id)?;
+        let deleted = self.backend.delete_message(&topic, &id)?;
+        if deleted { self.reindex(|index| index.remove(&topic, &id)); }
+        Ok(deleted)
     }
 
     /// Append a deletion audit entry (caller ensures deletion occurred)
     pub async fn append_deletion_audit(&self, topic: &str, id: &str, actor: &str) -> Result<()> {
-        let path = Path::new(&self.data_dir).join("deletion_audit.log");
         let entry = DeletionAuditEntry { timestamp: Utc::now(), topic: topic.to_string(), id: id.to_string(), actor: actor.to_string() };
-        let line = serde_json::to_string(&entry)? + "\n";
-        Self::append_file_locked(&path, &line).await?;
-        Ok(())
+        self.backend.append_deletion_audit(&entry)
     }
 
     /// Fetch a page of deletion audit entries (newest first). page is 1-based.
     pub async fn get_deletion_audit_page(&self, page: usize, page_size: usize) -> Result<Vec<DeletionAuditEntry>> {
         if page == 0 { return Ok(vec![]); }
-        let path = Path::new(&self.data_dir).join("deletion_audit.log");
-        if !path.exists() { return Ok(vec![]); }
-        let content = fs::read_to_string(path).await?;
-        let mut entries: Vec<DeletionAuditEntry> = content.lines().filter_map(|l| serde_json::from_str(l).ok()).collect();
+        let mut entries = self.backend.deletion_audit()?;
         // Newest first: original order is append older->newer; reverse
         entries.reverse();
         let start = (page - 1) * page_size;
@@ -676,7 +705,6 @@ impl Storage {
 
     /// Log an administrative action to the audit trail
     pub async fn log_admin_action(&self, action: &str, target: Option<&str>, actor: &str, details: Option<&str>) -> Result<()> {
-        let path = Path::new(&self.data_dir).join("admin_audit.log");
         let entry = AdminAuditEntry {
             timestamp: Utc::now(),
             action: action.to_string(),
@@ -684,25 +712,12 @@ impl Storage {
             actor: actor.to_string(),
             details: details.map(|d| d.to_string()),
         };
-        let line = serde_json::to_string(&entry)? + "\n";
-        Self::append_file_locked(&path, &line).await?;
-        Ok(())
+        self.backend.append_admin_audit(&entry)
     }
 
     /// Fetch a page of admin audit entries (newest first). page is 1-based.
     pub async fn get_admin_audit_page(&self, page: usize, page_size: usize) -> Result<Vec<AdminAuditEntry>> {
-        let path = Path::new(&self.data_dir).join("admin_audit.log");
-        if !path.exists() { return Ok(vec![]); }
-        let content = fs::read_to_string(&path).await?;
-        let mut entries = Vec::new();
-        for line in content.lines() {
-            if !line.trim().is_empty() {
-                match serde_json::from_str::<AdminAuditEntry>(line) {
-                    Ok(entry) => entries.push(entry),
-                    Err(_) => continue, // Skip malformed lines
-                }
-            }
-        }
+        let mut entries = self.backend.admin_audit()?;
         
         // Sort by timestamp descending (newest first)
         entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
@@ -733,65 +748,21 @@ impl Storage {
     /// Get recent messages from a topic
     pub async fn get_messages(&self, topic: &str, limit: usize) -> Result<Vec<Message>> {
         // Validate topic name to prevent path traversal
-        let topic_dir = secure_topic_path(&self.data_dir, topic)
+        let topic = validate_topic_name(topic)
             .map_err(|e| anyhow!("Invalid topic name: {}", e))?;
-        
-        if !topic_dir.exists() {
-            return Ok(Vec::new());
-        }
-        
-        let mut messages = Vec::new();
-        let mut entries = fs::read_dir(&topic_dir).await?;
-        
-        while let Some(entry) = entries.next_entry().await? {
-            if entry.path().extension().and_then(|s| s.to_str()) == Some("json") {
-                // Check file size before reading to prevent DoS
-                let metadata = entry.metadata().await?;
-                if metadata.len() > 1_000_000 { // 1MB limit per message file
-                    warn!("Skipping oversized message file: {:?}", entry.path());
-                    continue;
-                }
-                
-                if let Ok(content) = fs::read_to_string(entry.path()).await {
-                    if let Ok(message) = serde_json::from_str::<Message>(&content) {
-                        // Validate the message ID matches the filename
-                        if let Some(filename) = entry.path().file_stem().and_then(|s| s.to_str()) {
-                            if message.id == filename {
-                                messages.push(message);
-                            } else {
-                                warn!("Message ID mismatch in file: {:?}", entry.path());
-                            }
-                        }
-                    } else {
-                        warn!("Failed to parse message file: {:?}", entry.path());
-                    }
-                } else {
-                    warn!("Failed to read message file: {:?}", entry.path());
-                }
-            }
-        }
-        
-        // Sort by timestamp, newest first
-        messages.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        messages.truncate(limit);
-        
-        Ok(messages)
+        // Sorted newest first by the backend
+        self.backend.recent_messages(&topic, limit)
     }
 
-    /// Append a reply to an existing message (stored inline in the message JSON).
-    pub async fn append_reply(&self, topic: &str, id: &str, author: &str, content: &str) -> Result<()> {
-        // Resolve and validate message path
-        let message_file = secure_message_path(&self.data_dir, topic, id)
-            .map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
-
-        if !message_file.exists() {
-            return Err(anyhow!("Message not found"));
-        }
+    /// Look up a single message; `None` if it does not exist
+    pub async fn get_message(&self, topic: &str, id: &str) -> Result<Option<Message>> {
+        let (topic, id) = Self::message_ref(topic, id)?;
+        self.backend.get_message(&topic, &id)
+    }
 
-        // Read and parse message
-        let raw = fs::read_to_string(&message_file).await?;
-        let mut msg: Message = secure_json_parse(&raw, 1_000_000)
-            .map_err(|e| anyhow!("Corrupt message file: {:?}", e))?;
+    /// Append a reply to an existing message (stored inline in the message record).
+    pub async fn append_reply(&self, topic: &str, id: &str, author: &str, content: &str) -> Result<()> {
+        let mut msg = self.require_message(topic, id)?;
 
         // Sanitize reply content and build compact reply string
         let sanitized = sanitize_message_content(content, self.max_message_bytes)
@@ -801,11 +772,30 @@ impl Storage {
         // Append and persist using structured reply (backward compatible via enum on read)
         let reply = Reply { author: author.to_string(), timestamp: Utc::now(), content: sanitized };
         msg.replies.push(ReplyEntry::Reply(reply));
-        let json_content = serde_json::to_string_pretty(&msg)?;
-        Self::write_file_locked(&message_file, &json_content).await?;
+        self.backend.update_message(&msg)?;
+        self.reindex(|index| index.insert(&msg));
         Ok(())
     }
 
+    /// Threads in any topic whose post, title or replies contain every word of `query`
+    /// (as word prefixes), newest activity first. Read permissions are left to the caller.
+    pub async fn search_messages(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let mut guard = self.search.lock().unwrap_or_else(|e| e.into_inner());
+        let index = match guard.as_mut() {
+            Some(index) => index,
+            None => {
+                let mut index = search::SearchIndex::default();
+                for topic in self.runtime_topics.topics.keys() {
+                    for msg in self.backend.recent_messages(topic, usize::MAX)? {
+                        index.insert(&msg);
+                    }
+                }
+                guard.insert(index)
+            }
+        };
+        Ok(index.search(query))
+    }
+
     /// List available message topics (now uses runtime configuration instead of directory scanning)
     pub async fn list_message_topics(&self) -> Result<Vec<String>> {
         // Use configured topics instead of scanning directories
@@ -826,9 +816,8 @@ impl Storage {
             return Err(anyhow!("Topic '{}' already exists", topic_id));
         }
 
-        // Create the topic directory
-        let topic_dir = Path::new(&self.data_dir).join("messages").join(topic_id);
-        fs::create_dir_all(&topic_dir).await?;
+        // Prepare message storage for the topic
+        self.backend.create_topic_area(topic_id)?;
 
         // Create runtime config
         let topic_config = RuntimeTopicConfig {
@@ -839,12 +828,13 @@ impl Storage {
             created_by: creator.to_string(),
             created_at: Utc::now(),
             parent: None,
+            retention: None,
         };
 
         // Add to runtime topics
         self.runtime_topics.topics.insert(topic_id.to_string(), topic_config);
 
-        // Persist to disk
+        // Persist
         self.save_runtime_topics().await?;
 
         Ok(())
@@ -866,7 +856,7 @@ impl Storage {
         // Update runtime topics
         self.runtime_topics.topics.insert(topic_id.to_string(), topic_config);
 
-        // Persist to disk
+        // Persist
         self.save_runtime_topics().await?;
 
         Ok(())
@@ -882,14 +872,11 @@ impl Storage {
         // Remove from runtime topics
         self.runtime_topics.topics.remove(topic_id);
 
-        // Remove the topic directory and all messages
-        let topic_dir = Path::new(&self.data_dir).join("messages").join(topic_id);
-        if topic_dir.exists() {
-            fs::remove_dir_all(&topic_dir).await
-                .map_err(|e| anyhow!("Failed to remove topic directory: {}", e))?;
-        }
+        // Remove all messages in the topic
+        self.backend.remove_topic_area(topic_id)?;
+        self.reindex(|index| index.remove_topic(topic_id));
 
-        // Persist to disk
+        // Persist
         self.save_runtime_topics().await?;
 
         Ok(())
@@ -914,135 +901,64 @@ impl Storage {
 
     /// Create or update a user
     pub async fn create_or_update_user(&mut self, username: &str, node_id: &str) -> Result<()> {
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let safe_filename = safe_filename(username);
-        let user_file = users_dir.join(format!("{}.json", safe_filename));
-        
         let now = Utc::now();
         
-        let mut user = if user_file.exists() {
-            let content = fs::read_to_string(&user_file).await?;
-            serde_json::from_str::<User>(&content)?
-        } else {
-            User {
+        let mut user = match self.backend.get_user(username)? {
+            Some(user) => user,
+            None => User {
                 username: username.to_string(),
                 node_id: Some(node_id.to_string()),
+                trusted_nodes: Vec::new(),
                 user_level: 1,
                 password_hash: None,
+                password_reset: None,
                 first_login: now,
                 last_login: now,
                 total_messages: 0,
                 welcome_shown_on_registration: false,
                 welcome_shown_on_first_login: false,
-            }
+            },
         };
         user.last_login = now;
         // Only overwrite node_id if not bound yet
         if user.node_id.is_none() { user.node_id = Some(node_id.to_string()); }
         
-        let json_content = serde_json::to_string_pretty(&user)?;
-        Self::write_file_locked(&user_file, &json_content).await?;
-        
-        Ok(())
+        self.backend.put_user(&user)
     }
 
     /// Get user information
     pub async fn get_user(&self, username: &str) -> Result<Option<User>> {
-        let safe_filename = safe_filename(username);
-        let user_file = Path::new(&self.data_dir).join("users").join(format!("{}.json", safe_filename));
-        
-        if !user_file.exists() {
-            return Ok(None);
-        }
-        
-        // Check file size before reading
-        let metadata = fs::metadata(&user_file).await?;
-        validate_file_size(metadata.len(), 100_000) // 100KB limit for user files
-            .map_err(|e| anyhow!("User file too large: {:?}", e))?;
-        
-        let content = fs::read_to_string(user_file).await?;
-        let user: User = secure_json_parse(&content, 100_000)
-            .map_err(|e| anyhow!("Failed to parse user file: {:?}", e))?;
-        
-        Ok(Some(user))
+        self.backend.get_user(username)
+    }
+
+    /// Write a user record as-is (no validation or password hashing), e.g. when seeding
+    /// the sysop account from the config file.
+    pub async fn save_user(&self, user: &User) -> Result<()> {
+        self.backend.put_user(user)
     }
 
     /// Get BBS statistics
     pub async fn get_statistics(&self) -> Result<BbsStatistics> {
-        let mut total_messages = 0;
-        let mut total_users = 0;
-        let mut moderator_count = 0;
-        let mut recent_registrations = 0;
-        
         let seven_days_ago = Utc::now() - chrono::Duration::days(7);
-        
-        // Count messages
-        let messages_dir = Path::new(&self.data_dir).join("messages");
-        if messages_dir.exists() {
-            let mut area_entries = fs::read_dir(&messages_dir).await?;
-            while let Some(area_entry) = area_entries.next_entry().await? {
-                if area_entry.file_type().await?.is_dir() {
-                    let mut message_entries = fs::read_dir(area_entry.path()).await?;
-                    while (message_entries.next_entry().await?).is_some() {
-                        total_messages += 1;
-                    }
-                }
-            }
-        }
+        let total_messages = self.backend.count_messages()?;
         
         // Count users and analyze roles/registrations
-        let users_dir = Path::new(&self.data_dir).join("users");
-        if users_dir.exists() {
-            let mut user_entries = fs::read_dir(&users_dir).await?;
-            while let Some(entry) = user_entries.next_entry().await? {
-                if entry.file_type().await?.is_file() && entry.path().extension().is_some_and(|ext| ext == "json") {
-                    total_users += 1;
-                    
-                    // Parse user file to get details
-                    if let Ok(content) = fs::read_to_string(entry.path()).await {
-                        if let Ok(user) = serde_json::from_str::<User>(&content) {
-                            if user.user_level >= roles::LEVEL_MODERATOR {
-                                moderator_count += 1;
-                            }
-                            if user.first_login >= seven_days_ago {
-                                recent_registrations += 1;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let users = self.backend.list_users()?;
+        let moderator_count = users.iter().filter(|u| u.user_level >= roles::LEVEL_MODERATOR).count() as u32;
+        let recent_registrations = users.iter().filter(|u| u.first_login >= seven_days_ago).count() as u32;
         
         Ok(BbsStatistics {
             total_messages,
-            total_users,
+            total_users: users.len() as u32,
             uptime_start: Utc::now(), // This would be stored persistently in a real implementation
             moderator_count,
             recent_registrations,
         })
     }
 
-    /// List all users with their basic information
+    /// List all users with their basic information (sorted by username)
     pub async fn list_all_users(&self) -> Result<Vec<User>> {
-        let users_dir = Path::new(&self.data_dir).join("users");
-        let mut users = Vec::new();
-        
-        if users_dir.exists() {
-            let mut user_entries = fs::read_dir(&users_dir).await?;
-            while let Some(entry) = user_entries.next_entry().await? {
-                if entry.file_type().await?.is_file() && entry.path().extension().is_some_and(|ext| ext == "json") {
-                    let content = fs::read_to_string(entry.path()).await?;
-                    match serde_json::from_str::<User>(&content) {
-                        Ok(user) => users.push(user),
-                        Err(e) => log::warn!("Failed to parse user file {:?}: {}", entry.path(), e),
-                    }
-                }
-            }
-        }
-        
-        // Sort by username for consistent output
-        users.sort_by(|a, b| a.username.cmp(&b.username));
-        Ok(users)
+        self.backend.list_users()
     }
 
     /// Get enhanced user information including post count in specific topics
@@ -1052,42 +968,11 @@ impl Storage {
 
     /// Count total posts by a specific user across all topics
     pub async fn count_user_posts(&self, username: &str) -> Result<u32> {
-        let messages_dir = Path::new(&self.data_dir).join("messages");
-        let mut post_count = 0;
-        
-        if messages_dir.exists() {
-            let mut area_entries = fs::read_dir(&messages_dir).await?;
-            while let Some(area_entry) = area_entries.next_entry().await? {
-                if area_entry.file_type().await?.is_dir() {
-                    let mut message_entries = fs::read_dir(area_entry.path()).await?;
-                    while let Some(message_entry) = message_entries.next_entry().await? {
-                        if message_entry.file_type().await?.is_file() && message_entry.path().extension().is_some_and(|ext| ext == "json") {
-                            // Check file size before reading
-                            if let Ok(metadata) = message_entry.metadata().await {
-                                if metadata.len() > 1_000_000 { // 1MB limit per message file
-                                    warn!("Skipping oversized message file: {:?}", message_entry.path());
-                                    continue;
-                                }
-                            }
-                            
-                            let content = fs::read_to_string(message_entry.path()).await?;
-                            if let Ok(msg) = secure_json_parse::<Message>(&content, 1_000_000) {
-                                if msg.author == username {
-                                    post_count += 1;
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        Ok(post_count)
+        self.backend.count_user_posts(username)
     }
 
     /// Mark that the welcome message has been shown to a user
     pub async fn mark_welcome_shown(&self, username: &str, registration_welcome: bool, first_login_welcome: bool) -> Result<()> {
-        let user_file = std::path::Path::new(&self.data_dir).join("users").join(format!("{}.json", username));
         if let Some(mut user) = self.get_user(username).await? {
             if registration_welcome {
                 user.welcome_shown_on_registration = true;
@@ -1095,8 +980,7 @@ impl Storage {
             if first_login_welcome {
                 user.welcome_shown_on_first_login = true;
             }
-            let json_content = serde_json::to_string_pretty(&user)?;
-            Self::write_file_locked(&user_file, &json_content).await?;
+            self.backend.put_user(&user)?;
         }
         Ok(())
     }
@@ -1115,9 +999,7 @@ impl Storage {
         if self.runtime_topics.topics.contains_key(topic_id) {
             return Err(anyhow!("Topic '{}' already exists", topic_id));
         }
-        // Create directory
-        let topic_dir = Path::new(&self.data_dir).join("messages").join(topic_id);
-        fs::create_dir_all(&topic_dir).await?;
+        self.backend.create_topic_area(topic_id)?;
         // Config
         let topic_config = RuntimeTopicConfig {
             name: name.to_string(),
@@ -1127,6 +1009,7 @@ impl Storage {
             created_by: creator.to_string(),
             created_at: Utc::now(),
             parent: Some(parent_id.to_string()),
+            retention: None,
         };
         self.runtime_topics.topics.insert(topic_id.to_string(), topic_config);
         self.save_runtime_topics().await?;
@@ -1147,32 +1030,135 @@ impl Storage {
 
     /// Set or clear the pinned flag on a message
     pub async fn set_message_pinned(&self, topic: &str, id: &str, pinned: bool) -> Result<()> {
-        let message_file = secure_message_path(&self.data_dir, topic, id)
-            .map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
-        if !message_file.exists() { return Err(anyhow!("Message not found")); }
-        let raw = fs::read_to_string(&message_file).await?;
-        let mut msg: Message = secure_json_parse(&raw, 1_000_000)
-            .map_err(|e| anyhow!("Corrupt message file: {:?}", e))?;
+        let mut msg = self.require_message(topic, id)?;
         msg.pinned = pinned;
-        let json_content = serde_json::to_string_pretty(&msg)?;
-        Self::write_file_locked(&message_file, &json_content).await?;
-        Ok(())
+        self.backend.update_message(&msg)
     }
 
     /// Update the title of a message (without rewriting content body). Pass None to clear.
     pub async fn set_message_title(&self, topic: &str, id: &str, title: Option<&str>) -> Result<()> {
-        let message_file = secure_message_path(&self.data_dir, topic, id)
-            .map_err(|e| anyhow!("Invalid path parameters: {}", e))?;
-        if !message_file.exists() { return Err(anyhow!("Message not found")); }
-        let raw = fs::read_to_string(&message_file).await?;
-        let mut msg: Message = secure_json_parse(&raw, 1_000_000)
-            .map_err(|e| anyhow!("Corrupt message file: {:?}", e))?;
+        let mut msg = self.require_message(topic, id)?;
         msg.title = title.map(|t| t.to_string());
-        let json_content = serde_json::to_string_pretty(&msg)?;
-        Self::write_file_locked(&message_file, &json_content).await?;
+        self.backend.update_message(&msg)?;
+        self.reindex(|index| index.insert(&msg));
+        Ok(())
+    }
+
+    /// Deliver a private message to `to`'s mailbox
+    pub async fn send_mail(&self, from: &str, to: &str, content: &str) -> Result<MailMessage> {
+        let to = validate_user_name(to).map_err(|e| anyhow!("Invalid username: {}", e))?;
+        let recipient = self.backend.get_user(&to)?.ok_or_else(|| anyhow!("No such user '{}'", to))?;
+        if recipient.username == from {
+            return Err(anyhow!("Cannot send mail to yourself"));
+        }
+        let content = sanitize_message_content(content, self.max_message_bytes)
+            .map_err(|e| anyhow!("Invalid message content: {}", e))?;
+        if content.trim().is_empty() {
+            return Err(anyhow!("Message is empty"));
+        }
+        if self.backend.list_mail(&recipient.username)?.len() >= MAX_MAILBOX {
+            return Err(anyhow!("{}'s mailbox is full", recipient.username));
+        }
+        let mail = MailMessage {
+            id: Uuid::new_v4().to_string(),
+            from: from.to_string(),
+            to: recipient.username,
+            content,
+            timestamp: Utc::now(),
+            read: false,
+        };
+        self.backend.insert_mail(&mail)?;
+        Ok(mail)
+    }
+
+    /// A user's mailbox, newest first
+    pub async fn list_mail(&self, username: &str) -> Result<Vec<MailMessage>> {
+        self.backend.list_mail(username)
+    }
+
+    /// Number of unread messages in a user's mailbox
+    pub async fn count_unread_mail(&self, username: &str) -> Result<u32> {
+        Ok(self.backend.list_mail(username)?.iter().filter(|m| !m.read).count() as u32)
+    }
+
+    /// Mark a mailbox message as read
+    pub async fn mark_mail_read(&self, username: &str, id: &str) -> Result<()> {
+        let id = validate_message_id(id).map_err(|e| anyhow!("Invalid mail id: {}", e))?;
+        let mut mail = self.backend.list_mail(username)?
+            .into_iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| anyhow!("Mail not found"))?;
+        if !mail.read {
+            mail.read = true;
+            self.backend.update_mail(&mail)?;
+        }
         Ok(())
     }
 
+    /// Delete a message from a user's mailbox; returns false if it did not exist
+    pub async fn delete_mail(&self, username: &str, id: &str) -> Result<bool> {
+        let id = validate_message_id(id).map_err(|e| anyhow!("Invalid mail id: {}", e))?;
+        self.backend.delete_mail(username, &id)
+    }
+
+    /// Hold `msg` until its node is heard again. Expired entries are purged, a node keeps
+    /// at most `max_per_node` messages and the outbox at most [`MAX_HELD_TOTAL`]; the oldest
+    /// go first. Returns how many held messages were dropped to make room.
+    pub async fn hold_message(&self, msg: HeldMessage, max_per_node: usize) -> Result<usize> {
+        let now = Utc::now();
+        let mut outbox = self.backend.load_outbox()?;
+        outbox.retain(|m| m.expires_at > now);
+        outbox.push(msg.clone());
+        outbox.sort_by_key(|m| m.queued_at);
+        let before = outbox.len();
+        let mut for_node = outbox.iter().filter(|m| m.node_id == msg.node_id).count();
+        outbox.retain(|m| {
+            if m.node_id == msg.node_id && for_node > max_per_node { for_node -= 1; false } else { true }
+        });
+        let excess = outbox.len().saturating_sub(MAX_HELD_TOTAL);
+        outbox.drain(..excess);
+        let dropped = before - outbox.len();
+        self.backend.save_outbox(&outbox)?;
+        Ok(dropped)
+    }
+
+    /// Remove and return the unexpired messages held for `node_id`, oldest first
+    pub async fn take_held_messages(&self, node_id: &str) -> Result<Vec<HeldMessage>> {
+        let now = Utc::now();
+        let outbox = self.backend.load_outbox()?;
+        if !outbox.iter().any(|m| m.node_id == node_id || m.expires_at <= now) {
+            return Ok(Vec::new());
+        }
+        let (taken, rest): (Vec<_>, Vec<_>) = outbox.into_iter()
+            .filter(|m| m.expires_at > now)
+            .partition(|m| m.node_id == node_id);
+        self.backend.save_outbox(&rest)?;
+        Ok(taken)
+    }
+
+    /// Everything currently held, oldest first
+    pub async fn list_held_messages(&self) -> Result<Vec<HeldMessage>> {
+        self.backend.load_outbox()
+    }
+
+    /// Record counts and bytes on disk, for the metrics endpoint.
+    pub async fn sizes(&self) -> Result<crate::metrics::StorageSizes> {
+        fn dir_bytes(path: &Path) -> u64 {
+            std::fs::read_dir(path).map(|entries| entries.flatten().map(|e| match e.metadata() {
+                Ok(m) if m.is_dir() => dir_bytes(&e.path()),
+                Ok(m) => m.len(),
+                Err(_) => 0,
+            }).sum()).unwrap_or(0)
+        }
+        Ok(crate::metrics::StorageSizes {
+            users: self.backend.list_users()?.len() as u64,
+            topics: self.runtime_topics.topics.len() as u64,
+            messages: self.backend.count_messages()? as u64,
+            held_messages: self.backend.load_outbox()?.len() as u64,
+            bytes: dir_bytes(Path::new(&self.data_dir)),
+        })
+    }
+
 }
 
 /// Serde helper to avoid serializing `pinned: false`
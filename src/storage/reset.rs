@@ -0,0 +1,77 @@
+//! One-time password reset codes.
+//!
+//! A moderator or sysop issues a code with `RESETPW <user>`; the user redeems it with
+//! `RESET <user> <code> <newpass>`. Only an Argon2 hash of the code is kept, on the
+//! [`User`](super::User) record, so it is encrypted and backed up with the account. A code
+//! works once and lapses after [`RESET_CODE_TTL_MINUTES`]; issuing a new one, or setting
+//! the password any other way, replaces it.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use password_hash::{PasswordHasher, PasswordVerifier};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::Storage;
+
+/// How long an issued code stays valid.
+pub const RESET_CODE_TTL_MINUTES: i64 = 60;
+
+/// Characters used in codes: no 0/O, 1/I/L, so they survive being read aloud.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const CODE_LEN: usize = 8;
+
+/// An outstanding reset code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordReset {
+    /// Argon2 hash of the code
+    pub code_hash: String,
+    pub expires_at: DateTime<Utc>,
+    /// Who issued it
+    pub issued_by: String,
+}
+
+/// Codes are matched case-insensitively, ignoring dashes and spaces.
+fn normalize(code: &str) -> String {
+    code.chars().filter(|c| *c != '-' && !c.is_whitespace()).collect::<String>().to_ascii_uppercase()
+}
+
+impl Storage {
+    /// Issue a fresh reset code for `username`, replacing any earlier one. Returns the code
+    /// in clear; it is not stored.
+    pub async fn issue_password_reset(&mut self, username: &str, issued_by: &str, now: DateTime<Utc>) -> Result<String> {
+        let mut user = self.require_user(username)?;
+        let mut rng = rand::thread_rng();
+        let code: String = (0..CODE_LEN).map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char).collect();
+        let salt = password_hash::SaltString::generate(&mut rng);
+        let hash = self.argon2_configured().hash_password(code.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Password hash failure: {e}"))?;
+        user.password_reset = Some(PasswordReset {
+            code_hash: hash.to_string(),
+            expires_at: now + Duration::minutes(RESET_CODE_TTL_MINUTES),
+            issued_by: issued_by.to_string(),
+        });
+        self.backend.put_user(&user)?;
+        Ok(code)
+    }
+
+    /// Redeem a reset code: on a match the password becomes `new_password` and the code is
+    /// used up. Returns `false` for an unknown user, a wrong code or an expired one (which
+    /// is discarded). An unacceptable new password is an error and leaves the code valid.
+    pub async fn redeem_password_reset(&mut self, username: &str, code: &str, new_password: &str, now: DateTime<Utc>) -> Result<bool> {
+        let Some(mut user) = self.backend.get_user(username)? else { return Ok(false) };
+        let Some(reset) = user.password_reset.clone() else { return Ok(false) };
+        if reset.expires_at <= now {
+            user.password_reset = None;
+            self.backend.put_user(&user)?;
+            return Ok(false);
+        }
+        let parsed = password_hash::PasswordHash::new(&reset.code_hash)
+            .map_err(|e| anyhow!("Corrupt reset code hash: {e}"))?;
+        if self.argon2_configured().verify_password(normalize(code).as_bytes(), &parsed).is_err() {
+            return Ok(false);
+        }
+        self.update_user_password(&user.username, new_password).await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,225 @@
+//! Per-topic message retention.
+//!
+//! A topic's [`TopicRetention`] (stored with the topic in `topics.json`) limits how old and
+//! how many threads it keeps. [`Storage::apply_retention`] removes the threads outside those
+//! limits through [`Storage::delete_message`], so the search index stays current, and records
+//! each removal in the deletion audit under the actor `retention`. With archiving on, the
+//! removed threads are first written to `data/archive/<topic>-<time>.jsonl.gz`, one message
+//! (with its replies) per line. In an encrypted data directory each line is sealed like any
+//! other record; [`read_archive`] opens them again, and `fsck`, backup and rekey cover the
+//! archives too.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::crypto::{DataCipher, RecordCodec};
+use super::json::MAX_MESSAGE_FILE;
+use super::{Message, ReplyEntry, Storage};
+use crate::validation::secure_json_parse;
+
+/// Actor recorded in the deletion audit for expired threads.
+pub const RETENTION_ACTOR: &str = "retention";
+
+/// Directory under the data directory that holds the archives.
+pub(crate) const ARCHIVE_DIR: &str = "archive";
+const ARCHIVE_SUFFIX: &str = ".jsonl.gz";
+/// Decompressed size an archive may not exceed when it is read back.
+const MAX_ARCHIVE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Retention limits of one topic; a topic without limits keeps everything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopicRetention {
+    /// Remove threads with no post or reply for this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Keep only this many threads, by most recent activity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_threads: Option<usize>,
+    /// Pinned threads never expire and do not count toward `max_threads`
+    #[serde(default = "default_keep_pinned")]
+    pub keep_pinned: bool,
+}
+
+fn default_keep_pinned() -> bool { true }
+
+impl Default for TopicRetention {
+    fn default() -> Self {
+        Self { max_age_days: None, max_threads: None, keep_pinned: true }
+    }
+}
+
+impl TopicRetention {
+    /// True when at least one limit is set
+    pub fn is_active(&self) -> bool {
+        self.max_age_days.is_some() || self.max_threads.is_some()
+    }
+
+    /// Compact form for listings, e.g. `30d 100 threads, pins kept`
+    pub fn describe(&self) -> String {
+        let mut limits = Vec::new();
+        if let Some(days) = self.max_age_days { limits.push(format!("{days}d")); }
+        if let Some(n) = self.max_threads { limits.push(format!("{n} threads")); }
+        if limits.is_empty() { return "keep all".to_string(); }
+        format!("{}, pins {}", limits.join(" "), if self.keep_pinned { "kept" } else { "expire" })
+    }
+
+    /// Ids of the threads in `messages` that fall outside the limits at `now`.
+    fn expired(&self, messages: &[Message], now: DateTime<Utc>) -> Vec<String> {
+        let mut candidates: Vec<&Message> = messages.iter().filter(|m| !(self.keep_pinned && m.pinned)).collect();
+        candidates.sort_by(|a, b| b.last_activity().cmp(&a.last_activity()).then_with(|| a.id.cmp(&b.id)));
+        let cutoff = self.max_age_days.map(|days| now - Duration::days(days.into()));
+        candidates
+            .into_iter()
+            .enumerate()
+            .filter(|(i, m)| self.max_threads.is_some_and(|max| *i >= max) || cutoff.is_some_and(|c| m.last_activity() < c))
+            .map(|(_, m)| m.id.clone())
+            .collect()
+    }
+}
+
+impl Message {
+    /// Time of the post or its latest reply
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        self.replies
+            .iter()
+            .filter_map(|r| match r { ReplyEntry::Reply(r) => Some(r.timestamp), ReplyEntry::Legacy(_) => None })
+            .fold(self.timestamp, DateTime::max)
+    }
+}
+
+/// Outcome of one retention pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Topic and number of threads removed, in topic order
+    pub removed: Vec<(String, usize)>,
+    /// Archives written before removal
+    pub archives: Vec<PathBuf>,
+}
+
+impl RetentionReport {
+    pub fn total(&self) -> usize {
+        self.removed.iter().map(|(_, n)| n).sum()
+    }
+
+    /// e.g. `5 thread(s): general 3, tech 2`
+    pub fn summary(&self) -> String {
+        let per_topic: Vec<String> = self.removed.iter().map(|(t, n)| format!("{t} {n}")).collect();
+        format!("{} thread(s): {}", self.total(), per_topic.join(", "))
+    }
+}
+
+impl Storage {
+    /// Set or clear (`None` or no limits) the retention limits of a topic
+    pub async fn set_topic_retention(&mut self, topic_id: &str, retention: Option<TopicRetention>) -> Result<()> {
+        let topic = self.runtime_topics.topics.get_mut(topic_id)
+            .ok_or_else(|| anyhow!("Topic '{}' not found", topic_id))?;
+        topic.retention = retention.filter(TopicRetention::is_active);
+        self.save_runtime_topics().await
+    }
+
+    /// Remove the threads outside each topic's retention limits as of `now`, archiving them
+    /// first when `archive` is set. A topic whose archive cannot be written is left untouched.
+    pub async fn apply_retention(&mut self, now: DateTime<Utc>, archive: bool) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        for topic in self.list_configured_topics() {
+            let Some(policy) = self.get_topic_config(&topic).and_then(|t| t.retention.clone()) else { continue };
+            if !policy.is_active() { continue; }
+            let messages = self.backend.recent_messages(&topic, usize::MAX)?;
+            let expired = policy.expired(&messages, now);
+            if expired.is_empty() { continue; }
+            if archive {
+                let threads: Vec<&Message> = messages.iter().filter(|m| expired.contains(&m.id)).collect();
+                let path = write_archive(Path::new(&self.data_dir), &topic, now, &threads, &self.codec)
+                    .map_err(|e| anyhow!("Failed to archive expired threads of '{}': {}", topic, e))?;
+                report.archives.push(path);
+            }
+            let mut removed = 0;
+            for id in &expired {
+                if self.delete_message(&topic, id).await? {
+                    self.append_deletion_audit(&topic, id, RETENTION_ACTOR).await?;
+                    removed += 1;
+                }
+            }
+            report.removed.push((topic, removed));
+        }
+        Ok(report)
+    }
+}
+
+/// Write `threads` as gzip-compressed JSON lines under `<data_dir>/archive/`, each line
+/// sealed through `codec`.
+fn write_archive(data_dir: &Path, topic: &str, now: DateTime<Utc>, threads: &[&Message], codec: &RecordCodec) -> Result<PathBuf> {
+    let dir = data_dir.join(ARCHIVE_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}-{}{}", topic, now.format("%Y%m%dT%H%M%SZ"), ARCHIVE_SUFFIX));
+    let lines: Vec<String> = threads.iter().map(|msg| Ok(codec.encode(serde_json::to_string(msg)?))).collect::<Result<_>>()?;
+    write_lines(&path, &lines)?;
+    Ok(path)
+}
+
+/// Messages in the archive at `path`, opened with `cipher` when the data directory is encrypted.
+pub fn read_archive(path: &Path, cipher: Option<DataCipher>) -> Result<Vec<Message>> {
+    decode_archive(path, &RecordCodec::new(cipher))
+}
+
+pub(crate) fn decode_archive(path: &Path, codec: &RecordCodec) -> Result<Vec<Message>> {
+    let mut messages = Vec::new();
+    for (i, line) in read_lines(path)?.into_iter().enumerate() {
+        let text = codec.decode(line).map_err(|e| anyhow!("line {}: {}", i + 1, e))?;
+        messages.push(secure_json_parse::<Message>(&text, MAX_MESSAGE_FILE as usize).map_err(|e| anyhow!("line {}: {}", i + 1, e))?);
+    }
+    Ok(messages)
+}
+
+/// Archive files under `data_dir`, oldest name first.
+pub(crate) fn archive_files(data_dir: &Path) -> Result<Vec<PathBuf>> {
+    let dir = data_dir.join(ARCHIVE_DIR);
+    let mut files = Vec::new();
+    if !dir.is_dir() { return Ok(files); }
+    for entry in std::fs::read_dir(&dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.file_name().to_string_lossy().ends_with(ARCHIVE_SUFFIX) {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Rewrite every archive line, as stored, through `f` (for `meshbbs rekey`); returns the
+/// number of lines rewritten.
+pub(crate) fn reseal_archives(data_dir: &Path, f: &mut dyn FnMut(&str) -> Result<String>) -> Result<usize> {
+    let mut count = 0;
+    for path in archive_files(data_dir)? {
+        let lines = read_lines(&path)?.iter().map(|l| f(l)).collect::<Result<Vec<_>>>()?;
+        write_lines(&path, &lines)?;
+        count += lines.len();
+    }
+    Ok(count)
+}
+
+fn read_lines(path: &Path) -> Result<Vec<String>> {
+    let mut text = String::new();
+    GzDecoder::new(std::fs::File::open(path)?).take(MAX_ARCHIVE_BYTES + 1).read_to_string(&mut text)?;
+    if text.len() as u64 > MAX_ARCHIVE_BYTES {
+        return Err(anyhow!("decompresses to over {MAX_ARCHIVE_BYTES} bytes"));
+    }
+    Ok(text.lines().filter(|l| !l.trim().is_empty()).map(str::to_string).collect())
+}
+
+fn write_lines(path: &Path, lines: &[String]) -> Result<()> {
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    for line in lines {
+        gz.write_all(line.as_bytes())?;
+        gz.write_all(b"\n")?;
+    }
+    // Written whole and renamed so a crash never leaves a truncated archive
+    let tmp = path.with_extension("gz.tmp");
+    std::fs::write(&tmp, gz.finish()?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
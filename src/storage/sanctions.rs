@@ -0,0 +1,157 @@
+//! Bans, mutes and blocked nodes.
+//!
+//! Staff restrict an account with `BAN` (no login) or `MUTE` (can read, can't post or
+//! reply), and a radio with `BLOCKNODE` (everything it sends is dropped). Each sanction
+//! carries a reason and an optional expiry. All of them live in one [`SanctionState`]
+//! record (`sanctions.json`, or a row of the SQLite `kv` table); a new sanction of the same
+//! kind replaces the old one, and expired ones are dropped on the next change.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::Storage;
+
+/// Longest reason kept, in characters.
+pub const MAX_SANCTION_REASON: usize = 80;
+
+/// What a sanction does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SanctionKind {
+    /// The account can't log in
+    Ban,
+    /// The account can read but not post or reply
+    Mute,
+    /// Text from the node is dropped
+    BlockNode,
+}
+
+impl SanctionKind {
+    /// The command that sets it
+    pub fn command(self) -> &'static str {
+        match self {
+            SanctionKind::Ban => "BAN",
+            SanctionKind::Mute => "MUTE",
+            SanctionKind::BlockNode => "BLOCKNODE",
+        }
+    }
+}
+
+impl fmt::Display for SanctionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SanctionKind::Ban => "ban",
+            SanctionKind::Mute => "mute",
+            SanctionKind::BlockNode => "block",
+        })
+    }
+}
+
+/// One ban, mute or node block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sanction {
+    pub reason: String,
+    pub issued_by: String,
+    pub issued_at: DateTime<Utc>,
+    /// `None` for a permanent sanction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Sanction {
+    /// True while the sanction is in force.
+    pub fn active_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|until| until > now)
+    }
+}
+
+/// Every sanction, by account name (bans, mutes) or node ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanctionState {
+    #[serde(default)]
+    pub bans: BTreeMap<String, Sanction>,
+    #[serde(default)]
+    pub mutes: BTreeMap<String, Sanction>,
+    #[serde(default)]
+    pub nodes: BTreeMap<String, Sanction>,
+}
+
+impl SanctionState {
+    fn map(&self, kind: SanctionKind) -> &BTreeMap<String, Sanction> {
+        match kind {
+            SanctionKind::Ban => &self.bans,
+            SanctionKind::Mute => &self.mutes,
+            SanctionKind::BlockNode => &self.nodes,
+        }
+    }
+
+    fn map_mut(&mut self, kind: SanctionKind) -> &mut BTreeMap<String, Sanction> {
+        match kind {
+            SanctionKind::Ban => &mut self.bans,
+            SanctionKind::Mute => &mut self.mutes,
+            SanctionKind::BlockNode => &mut self.nodes,
+        }
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        for map in [&mut self.bans, &mut self.mutes, &mut self.nodes] {
+            map.retain(|_, s| s.active_at(now));
+        }
+    }
+}
+
+impl Storage {
+    /// Put a sanction on `target`: an account for bans and mutes, a node ID for node blocks.
+    /// Replaces an earlier sanction of the same kind. Returns the key it is stored under
+    /// (the account's own spelling of its name) and the sanction as stored.
+    pub async fn add_sanction(&self, kind: SanctionKind, target: &str, mut sanction: Sanction) -> Result<(String, Sanction)> {
+        let key = match kind {
+            SanctionKind::Ban | SanctionKind::Mute => {
+                self.backend.get_user(target)?.ok_or_else(|| anyhow!("User not found"))?.username
+            }
+            SanctionKind::BlockNode => {
+                let node = target.trim();
+                if node.is_empty() || node.len() > 32 || node.chars().any(|c| c.is_control() || c.is_whitespace()) {
+                    return Err(anyhow!("Invalid node id"));
+                }
+                node.to_string()
+            }
+        };
+        sanction.reason = sanction.reason.trim().chars().filter(|c| !c.is_control()).take(MAX_SANCTION_REASON).collect();
+        let mut state = self.backend.load_sanctions()?;
+        state.prune(sanction.issued_at);
+        state.map_mut(kind).insert(key.clone(), sanction.clone());
+        self.backend.save_sanctions(&state)?;
+        Ok((key, sanction))
+    }
+
+    /// Lift the sanction of `kind` on `target` (an account in any case, or a node ID).
+    /// Returns the key it was stored under, or `None` if there was none.
+    pub async fn lift_sanction(&self, kind: SanctionKind, target: &str) -> Result<Option<String>> {
+        let mut state = self.backend.load_sanctions()?;
+        let map = state.map_mut(kind);
+        let Some(key) = map.keys().find(|k| k.eq_ignore_ascii_case(target)).cloned() else { return Ok(None) };
+        map.remove(&key);
+        self.backend.save_sanctions(&state)?;
+        Ok(Some(key))
+    }
+
+    /// The sanction of `kind` in force on `target` at `now`, if any. Accounts are looked up
+    /// by their stored name.
+    pub async fn sanction(&self, kind: SanctionKind, target: &str, now: DateTime<Utc>) -> Result<Option<Sanction>> {
+        let state = self.backend.load_sanctions()?;
+        Ok(state.map(kind).get(target).filter(|s| s.active_at(now)).cloned())
+    }
+
+    /// Every sanction in force at `now`: bans, then mutes, then node blocks.
+    pub async fn active_sanctions(&self, now: DateTime<Utc>) -> Result<Vec<(SanctionKind, String, Sanction)>> {
+        let state = self.backend.load_sanctions()?;
+        let mut active = Vec::new();
+        for kind in [SanctionKind::Ban, SanctionKind::Mute, SanctionKind::BlockNode] {
+            active.extend(state.map(kind).iter().filter(|(_, s)| s.active_at(now)).map(|(k, s)| (kind, k.clone(), s.clone())));
+        }
+        Ok(active)
+    }
+}
@@ -0,0 +1,106 @@
+//! In-memory inverted index over message posts and their replies (`SEARCH`).
+//!
+//! Text is split into lowercase words of two or more letters or digits. A query matches a
+//! thread when every query word is a prefix of some word in the post, its title or one of
+//! its replies, so `ant` finds "antenna" and "Antennas". The index is built from the
+//! backend on first use and then kept current by [`Storage`](super::Storage) as messages
+//! are stored, replied to, retitled and deleted.
+
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{Message, ReplyEntry};
+
+/// Shortest indexed word, in characters.
+const MIN_WORD_CHARS: usize = 2;
+
+/// A thread matching a search, newest activity first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub topic: String,
+    pub id: String,
+    pub title: String,
+    pub author: String,
+    /// Time of the post or its latest reply
+    pub last_activity: DateTime<Utc>,
+}
+
+type DocKey = (String, String); // (topic, message id)
+
+#[derive(Debug, Default)]
+pub(crate) struct SearchIndex {
+    words: BTreeMap<String, HashSet<DocKey>>,
+    docs: HashMap<DocKey, (SearchHit, HashSet<String>)>,
+}
+
+/// Lowercase words of `text` as indexed and queried.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_CHARS)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+impl SearchIndex {
+    /// Add or re-index a message.
+    pub fn insert(&mut self, msg: &Message) {
+        let key = (msg.topic.clone(), msg.id.clone());
+        self.remove(&msg.topic, &msg.id);
+        let mut words: HashSet<String> = tokenize(&msg.content).into_iter().collect();
+        words.extend(msg.title.as_deref().map(tokenize).unwrap_or_default());
+        for reply in &msg.replies {
+            match reply {
+                ReplyEntry::Reply(r) => words.extend(tokenize(&r.content)),
+                ReplyEntry::Legacy(s) => words.extend(tokenize(s)),
+            }
+        }
+        for word in &words {
+            self.words.entry(word.clone()).or_default().insert(key.clone());
+        }
+        let title = msg.title.clone().unwrap_or_else(|| msg.content.lines().next().unwrap_or("").to_string());
+        let hit = SearchHit { topic: msg.topic.clone(), id: msg.id.clone(), title, author: msg.author.clone(), last_activity: msg.last_activity() };
+        self.docs.insert(key, (hit, words));
+    }
+
+    pub fn remove(&mut self, topic: &str, id: &str) {
+        let key = (topic.to_string(), id.to_string());
+        let Some((_, words)) = self.docs.remove(&key) else { return };
+        for word in words {
+            if let Some(keys) = self.words.get_mut(&word) {
+                keys.remove(&key);
+                if keys.is_empty() { self.words.remove(&word); }
+            }
+        }
+    }
+
+    pub fn remove_topic(&mut self, topic: &str) {
+        let ids: Vec<String> = self.docs.keys().filter(|(t, _)| t == topic).map(|(_, id)| id.clone()).collect();
+        for id in ids {
+            self.remove(topic, &id);
+        }
+    }
+
+    /// Threads matching every word of `query`, newest activity first.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let terms = tokenize(query);
+        if terms.is_empty() { return Vec::new(); }
+        let mut matched: Option<HashSet<&DocKey>> = None;
+        for term in &terms {
+            let docs: HashSet<&DocKey> = self.words
+                .range(term.clone()..)
+                .take_while(|(word, _)| word.starts_with(term.as_str()))
+                .flat_map(|(_, keys)| keys.iter())
+                .collect();
+            matched = Some(match matched {
+                None => docs,
+                Some(prev) => prev.intersection(&docs).copied().collect(),
+            });
+        }
+        let mut hits: Vec<SearchHit> = matched.unwrap_or_default()
+            .into_iter()
+            .filter_map(|key| self.docs.get(key).map(|(hit, _)| hit.clone()))
+            .collect();
+        hits.sort_by(|a, b| b.last_activity.cmp(&a.last_activity).then_with(|| a.id.cmp(&b.id)));
+        hits
+    }
+}
@@ -0,0 +1,506 @@
+//! SQLite backend (feature `sqlite`).
+//!
+//! Everything lives in `<data_dir>/meshbbs.db`. Records are stored as the same JSON
+//! documents the file backend writes, next to the columns we query on: messages are
+//! indexed by `(topic, ts)`, `ts` and `author`, so listings, "new since" counts and
+//! per-user post counts are index lookups instead of directory scans.
+//!
+//! With at-rest encryption the `data` columns (and the jackpot value) hold sealed
+//! records; keys and the indexed columns stay in the clear.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::backend::{BackendKind, StorageBackend};
+use super::crypto::{DataCipher, RecordCodec};
+use super::{AdminAuditEntry, DeletionAuditEntry, HeldMessage, LockoutState, MailMessage, Message, RuntimeTopicConfig, RuntimeTopicsConfig, SanctionState, User};
+use crate::bbs::slotmachine::{GlobalJackpot, PlayersFile};
+
+/// Database file name inside the data directory.
+pub const DB_FILE: &str = "meshbbs.db";
+
+/// 1: initial schema; 2: mail
+const SCHEMA_VERSION: i32 = 2;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS users (
+    username TEXT PRIMARY KEY,
+    data     TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS topics (
+    id   TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    topic  TEXT NOT NULL,
+    id     TEXT NOT NULL,
+    author TEXT NOT NULL,
+    ts     INTEGER NOT NULL,
+    data   TEXT NOT NULL,
+    PRIMARY KEY (topic, id)
+);
+CREATE INDEX IF NOT EXISTS messages_topic_ts ON messages (topic, ts);
+CREATE INDEX IF NOT EXISTS messages_ts ON messages (ts);
+CREATE INDEX IF NOT EXISTS messages_author ON messages (author);
+CREATE TABLE IF NOT EXISTS mail (
+    recipient TEXT NOT NULL,
+    id        TEXT NOT NULL,
+    ts        INTEGER NOT NULL,
+    data      TEXT NOT NULL,
+    PRIMARY KEY (recipient, id)
+);
+CREATE INDEX IF NOT EXISTS mail_recipient_ts ON mail (recipient, ts);
+CREATE TABLE IF NOT EXISTS deletion_audit (
+    seq  INTEGER PRIMARY KEY AUTOINCREMENT,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS admin_audit (
+    seq  INTEGER PRIMARY KEY AUTOINCREMENT,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS locked_topics (
+    topic TEXT PRIMARY KEY
+);
+CREATE TABLE IF NOT EXISTS slot_players (
+    node_id TEXT PRIMARY KEY,
+    data    TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS kv (
+    key   TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+const JACKPOT_KEY: &str = "slot_jackpot";
+const OUTBOX_KEY: &str = "outbox";
+const LOGIN_FAILURES_KEY: &str = "login_failures";
+const SANCTIONS_KEY: &str = "sanctions";
+
+/// Record columns rewritten by `reseal`.
+const RECORD_COLUMNS: [(&str, &str); 8] = [
+    ("users", "data"),
+    ("topics", "data"),
+    ("messages", "data"),
+    ("mail", "data"),
+    ("deletion_audit", "data"),
+    ("admin_audit", "data"),
+    ("slot_players", "data"),
+    ("kv", "value"),
+];
+
+pub struct SqliteBackend {
+    conn: Mutex<Connection>,
+    codec: RecordCodec,
+}
+
+impl SqliteBackend {
+    /// Open (creating if needed) `<data_dir>/meshbbs.db`, sealing records with `cipher` if set.
+    pub fn open(data_dir: &str, cipher: Option<DataCipher>) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| anyhow!("Failed to create data directory {}: {}", data_dir, e))?;
+        let path = Path::new(data_dir).join(DB_FILE);
+        let conn = Connection::open(&path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+        Self::init(conn, cipher)
+    }
+
+    /// In-memory database (tests).
+    pub fn open_in_memory() -> Result<Self> { Self::init(Connection::open_in_memory()?, None) }
+
+    fn init(conn: Connection, cipher: Option<DataCipher>) -> Result<Self> {
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        // WAL keeps readers (e.g. `meshbbs status`) from blocking the running server
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        let version: i32 = conn.pragma_query_value(None, "user_version", |r| r.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(anyhow!("{} schema version {} is newer than this build supports ({})", DB_FILE, version, SCHEMA_VERSION));
+        }
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        Ok(SqliteBackend { conn: Mutex::new(conn), codec: RecordCodec::new(cipher) })
+    }
+
+    fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<String> {
+        Ok(self.codec.encode(serde_json::to_string(value)?))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, data: String) -> Result<T> {
+        let data = self.codec.decode(data)?;
+        serde_json::from_str(&data).map_err(|e| anyhow!("Corrupt record in {}: {}", DB_FILE, e))
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Connection) -> Result<T>) -> Result<T> {
+        let mut conn = self.conn.lock().map_err(|_| anyhow!("SQLite connection poisoned"))?;
+        f(&mut conn)
+    }
+
+    fn query_json<T: DeserializeOwned>(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<T>> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare_cached(sql)?;
+            let rows = stmt.query_map(params, |r| r.get::<_, String>(0))?;
+            rows.map(|row| self.decode(row?)).collect()
+        })
+    }
+
+    fn count(&self, sql: &str, params: impl rusqlite::Params) -> Result<u32> {
+        self.with(|conn| {
+            let n: i64 = conn.prepare_cached(sql)?.query_row(params, |r| r.get(0))?;
+            Ok(n as u32)
+        })
+    }
+
+    /// Write a transactionally consistent copy of the database to `path` (`VACUUM INTO`),
+    /// safe while the server is writing.
+    pub fn snapshot_into(&self, path: &Path) -> Result<()> {
+        self.with(|conn| {
+            conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+                .map_err(|e| anyhow!("Failed to snapshot {} to {}: {}", DB_FILE, path.display(), e))?;
+            Ok(())
+        })
+    }
+
+    /// Run `PRAGMA integrity_check` and decode every stored record; returns the record count.
+    pub fn verify(&self) -> Result<usize> {
+        let result: String = self.with(|conn| Ok(conn.query_row("PRAGMA integrity_check", [], |r| r.get(0))?))?;
+        if result != "ok" {
+            return Err(anyhow!("{} failed its integrity check: {}", DB_FILE, result));
+        }
+        Ok(self.query_json::<User>("SELECT data FROM users", [])?.len()
+            + self.query_json::<RuntimeTopicConfig>("SELECT data FROM topics", [])?.len()
+            + self.query_json::<Message>("SELECT data FROM messages", [])?.len()
+            + self.query_json::<MailMessage>("SELECT data FROM mail", [])?.len()
+            + self.query_json::<DeletionAuditEntry>("SELECT data FROM deletion_audit", [])?.len()
+            + self.query_json::<AdminAuditEntry>("SELECT data FROM admin_audit", [])?.len()
+            + self.query_json::<serde_json::Value>("SELECT data FROM slot_players", [])?.len()
+            + self.query_json::<serde_json::Value>("SELECT value FROM kv", [])?.len())
+    }
+}
+
+impl StorageBackend for SqliteBackend {
+    fn kind(&self) -> BackendKind { BackendKind::Sqlite }
+
+    fn get_user(&self, username: &str) -> Result<Option<User>> {
+        Ok(self.query_json("SELECT data FROM users WHERE username = ?1", [username])?.pop())
+    }
+
+    fn put_user(&self, user: &User) -> Result<()> {
+        let data = self.encode(user)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO users (username, data) VALUES (?1, ?2)
+                 ON CONFLICT(username) DO UPDATE SET data = excluded.data",
+                params![user.username, data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_users(&self) -> Result<Vec<User>> {
+        self.query_json("SELECT data FROM users ORDER BY username", [])
+    }
+
+    fn delete_user(&self, username: &str) -> Result<bool> {
+        self.with(|conn| Ok(conn.execute("DELETE FROM users WHERE username = ?1", [username])? > 0))
+    }
+
+    fn load_topics(&self) -> Result<RuntimeTopicsConfig> {
+        let rows: Vec<(String, String)> = self.with(|conn| {
+            let mut stmt = conn.prepare_cached("SELECT id, data FROM topics")?;
+            let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        })?;
+        let mut config = RuntimeTopicsConfig::default();
+        for (id, data) in rows {
+            config.topics.insert(id, self.decode(data)?);
+        }
+        Ok(config)
+    }
+
+    fn save_topics(&self, topics: &RuntimeTopicsConfig) -> Result<()> {
+        let rows = topics.topics.iter().map(|(id, cfg)| Ok((id, self.encode(cfg)?))).collect::<Result<Vec<_>>>()?;
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM topics", [])?;
+            for (id, data) in rows {
+                tx.execute("INSERT INTO topics (id, data) VALUES (?1, ?2)", params![id, data])?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn create_topic_area(&self, _topic: &str) -> Result<()> {
+        // Topics are rows; nothing to prepare
+        Ok(())
+    }
+
+    fn remove_topic_area(&self, topic: &str) -> Result<()> {
+        self.with(|conn| {
+            conn.execute("DELETE FROM messages WHERE topic = ?1", [topic])?;
+            Ok(())
+        })
+    }
+
+    fn insert_message(&self, message: &Message) -> Result<()> {
+        let data = self.encode(message)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO messages (topic, id, author, ts, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![message.topic, message.id, message.author, message.timestamp.timestamp_micros(), data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn get_message(&self, topic: &str, id: &str) -> Result<Option<Message>> {
+        let data: Option<String> = self.with(|conn| {
+            Ok(conn
+                .prepare_cached("SELECT data FROM messages WHERE topic = ?1 AND id = ?2")?
+                .query_row([topic, id], |r| r.get(0))
+                .optional()?)
+        })?;
+        data.map(|d| self.decode(d)).transpose()
+    }
+
+    fn update_message(&self, message: &Message) -> Result<()> {
+        let data = self.encode(message)?;
+        let changed = self.with(|conn| {
+            Ok(conn.execute(
+                "UPDATE messages SET author = ?3, ts = ?4, data = ?5 WHERE topic = ?1 AND id = ?2",
+                params![message.topic, message.id, message.author, message.timestamp.timestamp_micros(), data],
+            )?)
+        })?;
+        if changed == 0 { return Err(anyhow!("Message not found")); }
+        Ok(())
+    }
+
+    fn delete_message(&self, topic: &str, id: &str) -> Result<bool> {
+        self.with(|conn| Ok(conn.execute("DELETE FROM messages WHERE topic = ?1 AND id = ?2", [topic, id])? > 0))
+    }
+
+    fn recent_messages(&self, topic: &str, limit: usize) -> Result<Vec<Message>> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        self.query_json("SELECT data FROM messages WHERE topic = ?1 ORDER BY ts DESC LIMIT ?2", params![topic, limit])
+    }
+
+    fn count_messages(&self) -> Result<u32> {
+        self.count("SELECT COUNT(*) FROM messages", [])
+    }
+
+    fn count_messages_since(&self, topic: Option<&str>, since: DateTime<Utc>) -> Result<u32> {
+        let since = since.timestamp_micros();
+        match topic {
+            Some(t) => self.count("SELECT COUNT(*) FROM messages WHERE topic = ?1 AND ts > ?2", params![t, since]),
+            None => self.count("SELECT COUNT(*) FROM messages WHERE ts > ?1", [since]),
+        }
+    }
+
+    fn count_user_posts(&self, username: &str) -> Result<u32> {
+        self.count("SELECT COUNT(*) FROM messages WHERE author = ?1", [username])
+    }
+
+    fn insert_mail(&self, mail: &MailMessage) -> Result<()> {
+        let data = self.encode(mail)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO mail (recipient, id, ts, data) VALUES (?1, ?2, ?3, ?4)",
+                params![mail.to, mail.id, mail.timestamp.timestamp_micros(), data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn list_mail(&self, recipient: &str) -> Result<Vec<MailMessage>> {
+        self.query_json("SELECT data FROM mail WHERE recipient = ?1 ORDER BY ts DESC", [recipient])
+    }
+
+    fn update_mail(&self, mail: &MailMessage) -> Result<()> {
+        let data = self.encode(mail)?;
+        let changed = self.with(|conn| {
+            Ok(conn.execute("UPDATE mail SET data = ?3 WHERE recipient = ?1 AND id = ?2", params![mail.to, mail.id, data])?)
+        })?;
+        if changed == 0 { return Err(anyhow!("Mail not found")); }
+        Ok(())
+    }
+
+    fn delete_mail(&self, recipient: &str, id: &str) -> Result<bool> {
+        self.with(|conn| Ok(conn.execute("DELETE FROM mail WHERE recipient = ?1 AND id = ?2", [recipient, id])? > 0))
+    }
+
+    fn append_deletion_audit(&self, entry: &DeletionAuditEntry) -> Result<()> {
+        let data = self.encode(entry)?;
+        self.with(|conn| {
+            conn.execute("INSERT INTO deletion_audit (data) VALUES (?1)", [data])?;
+            Ok(())
+        })
+    }
+
+    fn deletion_audit(&self) -> Result<Vec<DeletionAuditEntry>> {
+        self.query_json("SELECT data FROM deletion_audit ORDER BY seq", [])
+    }
+
+    fn append_admin_audit(&self, entry: &AdminAuditEntry) -> Result<()> {
+        let data = self.encode(entry)?;
+        self.with(|conn| {
+            conn.execute("INSERT INTO admin_audit (data) VALUES (?1)", [data])?;
+            Ok(())
+        })
+    }
+
+    fn admin_audit(&self) -> Result<Vec<AdminAuditEntry>> {
+        self.query_json("SELECT data FROM admin_audit ORDER BY seq", [])
+    }
+
+    fn load_outbox(&self) -> Result<Vec<HeldMessage>> {
+        let data: Option<String> = self.with(|conn| {
+            Ok(conn.query_row("SELECT value FROM kv WHERE key = ?1", [OUTBOX_KEY], |r| r.get(0)).optional()?)
+        })?;
+        Ok(data.map(|d| self.decode(d)).transpose()?.unwrap_or_default())
+    }
+
+    fn save_outbox(&self, outbox: &[HeldMessage]) -> Result<()> {
+        let data = self.encode(outbox)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO kv (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![OUTBOX_KEY, data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn load_login_failures(&self) -> Result<LockoutState> {
+        let data: Option<String> = self.with(|conn| {
+            Ok(conn.query_row("SELECT value FROM kv WHERE key = ?1", [LOGIN_FAILURES_KEY], |r| r.get(0)).optional()?)
+        })?;
+        Ok(data.map(|d| self.decode(d)).transpose()?.unwrap_or_default())
+    }
+
+    fn save_login_failures(&self, state: &LockoutState) -> Result<()> {
+        let data = self.encode(state)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO kv (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![LOGIN_FAILURES_KEY, data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn load_sanctions(&self) -> Result<SanctionState> {
+        let data: Option<String> = self.with(|conn| {
+            Ok(conn.query_row("SELECT value FROM kv WHERE key = ?1", [SANCTIONS_KEY], |r| r.get(0)).optional()?)
+        })?;
+        Ok(data.map(|d| self.decode(d)).transpose()?.unwrap_or_default())
+    }
+
+    fn save_sanctions(&self, state: &SanctionState) -> Result<()> {
+        let data = self.encode(state)?;
+        self.with(|conn| {
+            conn.execute(
+                "INSERT INTO kv (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![SANCTIONS_KEY, data],
+            )?;
+            Ok(())
+        })
+    }
+
+    fn load_locked_topics(&self) -> Result<HashSet<String>> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare_cached("SELECT topic FROM locked_topics")?;
+            let rows = stmt.query_map([], |r| r.get(0))?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        })
+    }
+
+    fn save_locked_topics(&self, locked: &HashSet<String>) -> Result<()> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM locked_topics", [])?;
+            for topic in locked {
+                tx.execute("INSERT INTO locked_topics (topic) VALUES (?1)", [topic])?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn load_slot_players(&self) -> Result<PlayersFile> {
+        let rows: Vec<(String, String)> = self.with(|conn| {
+            let mut stmt = conn.prepare_cached("SELECT node_id, data FROM slot_players")?;
+            let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+            Ok(rows.collect::<rusqlite::Result<_>>()?)
+        })?;
+        let mut file = PlayersFile::default();
+        for (node_id, data) in rows {
+            file.players.insert(node_id, self.decode(data)?);
+        }
+        Ok(file)
+    }
+
+    fn save_slot_players(&self, players: &PlayersFile) -> Result<()> {
+        let rows = players.players.iter().map(|(id, p)| Ok((id, self.encode(p)?))).collect::<Result<Vec<_>>>()?;
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM slot_players", [])?;
+            for (node_id, data) in rows {
+                tx.execute("INSERT INTO slot_players (node_id, data) VALUES (?1, ?2)", params![node_id, data])?;
+            }
+            tx.commit()?;
+            Ok(())
+        })
+    }
+
+    fn load_jackpot(&self) -> Result<GlobalJackpot> {
+        let data: Option<String> = self.with(|conn| {
+            Ok(conn.query_row("SELECT value FROM kv WHERE key = ?1", [JACKPOT_KEY], |r| r.get(0)).optional()?)
+        })?;
+        Ok(data.and_then(|d| self.decode(d).ok()).unwrap_or_default())
+    }
+
+    fn update_jackpot(&self, update: &mut dyn FnMut(&mut GlobalJackpot)) -> Result<GlobalJackpot> {
+        self.with(|conn| {
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            let current: Option<String> = tx
+                .query_row("SELECT value FROM kv WHERE key = ?1", [JACKPOT_KEY], |r| r.get(0))
+                .optional()?;
+            let mut jackpot: GlobalJackpot = current.and_then(|d| self.decode(d).ok()).unwrap_or_default();
+            update(&mut jackpot);
+            tx.execute(
+                "INSERT INTO kv (key, value) VALUES (?1, ?2) ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![JACKPOT_KEY, self.encode(&jackpot)?],
+            )?;
+            tx.commit()?;
+            Ok(jackpot)
+        })
+    }
+
+    fn reseal(&self, f: &mut dyn FnMut(&str) -> Result<String>) -> Result<usize> {
+        self.with(|conn| {
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            let mut count = 0;
+            for (table, column) in RECORD_COLUMNS {
+                let rows: Vec<(i64, String)> = {
+                    let mut stmt = tx.prepare(&format!("SELECT rowid, {column} FROM {table}"))?;
+                    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?;
+                    rows.collect::<rusqlite::Result<_>>()?
+                };
+                for (rowid, data) in rows {
+                    let data = f(&data).map_err(|e| anyhow!("{} row {}: {}", table, rowid, e))?;
+                    tx.execute(&format!("UPDATE {table} SET {column} = ?1 WHERE rowid = ?2"), params![data, rowid])?;
+                    count += 1;
+                }
+            }
+            tx.commit()?;
+            // Don't leave the old records in free pages or the WAL
+            conn.execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")?;
+            Ok(count)
+        })
+    }
+}
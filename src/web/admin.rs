@@ -0,0 +1,249 @@
+//! Sysop admin dashboard and JSON API.
+//!
+//! Every route requires HTTP Basic auth as the configured sysop, checked against
+//! `bbs.sysop_password_hash`; without a hash the routes answer 503. Failed logins count
+//! toward the `[security.lockout]` policy per client address (`web:<ip>`, cleared with
+//! `UNLOCKUSER web:<ip>`); a locked-out address gets 429 without its password being
+//! checked. Requests are forwarded
+//! to the running server through an [`AdminHandle`] and recorded in the admin audit log
+//! under the sysop's name.
+//!
+//! Mutating routes take JSON bodies or use `PATCH`/`DELETE`, so a foreign web page cannot
+//! replay the browser's cached credentials with a plain form post.
+//!
+//! | Method | Path | Body / query |
+//! |--------|------|--------------|
+//! | GET | `/api/overview` | |
+//! | GET | `/api/users` | |
+//! | POST | `/api/users/{name}/level` | `{"level": 1\|5\|10}` |
+//! | POST | `/api/users/{name}/password` | `{"password": "..."}` |
+//! | GET, POST | `/api/topics` | POST: `{"id", "name", "description", "read_level", "post_level", "parent"}` |
+//! | PATCH, DELETE | `/api/topics/{id}` | PATCH: any of `name`, `description`, `read_level`, `post_level`, `retention` |
+//! | POST | `/api/topics/{id}/lock` | `{"locked": bool}` |
+//! | GET | `/api/topics/{id}/messages` | `?limit=50` |
+//! | DELETE | `/api/topics/{id}/messages/{msg}` | |
+//! | POST | `/api/topics/{id}/messages/{msg}/pin` | `{"pinned": bool}` |
+//! | GET | `/api/audit/deletions`, `/api/audit/admin` | `?page=1` |
+//! | GET | `/api/sessions` | |
+//! | POST | `/api/sessions/kick` | `{"username": "..."}` |
+//! | POST | `/api/broadcast` | `{"message": "..."}` |
+
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use axum::extract::{ConnectInfo, Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, patch, post};
+use axum::{Extension, Json, Router};
+use base64::Engine as _;
+use serde::Deserialize;
+use serde_json::json;
+use std::net::SocketAddr;
+
+use crate::bbs::admin::{AdminHandle, AdminRequest, NewTopic, NotFound, TopicChanges};
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+const REALM: &str = "Basic realm=\"meshbbs admin\", charset=\"UTF-8\"";
+
+/// What the admin routes need: the server channel and the sysop credentials.
+#[derive(Debug, Clone)]
+pub struct AdminAccess {
+    pub handle: AdminHandle,
+    pub sysop: String,
+    pub password_hash: Option<String>,
+}
+
+/// The authenticated sysop, attached to each request by [`require_sysop`].
+#[derive(Debug, Clone)]
+struct Actor(String);
+
+pub fn router(access: AdminAccess) -> Router {
+    Router::new()
+        .route("/", get(dashboard))
+        .route("/api/overview", get(overview))
+        .route("/api/users", get(list_users))
+        .route("/api/users/:name/level", post(set_level))
+        .route("/api/users/:name/password", post(reset_password))
+        .route("/api/topics", get(list_topics).post(create_topic))
+        .route("/api/topics/:id", patch(modify_topic).delete(delete_topic))
+        .route("/api/topics/:id/lock", post(lock_topic))
+        .route("/api/topics/:id/messages", get(list_messages))
+        .route("/api/topics/:id/messages/:msg", delete(delete_message))
+        .route("/api/topics/:id/messages/:msg/pin", post(pin_message))
+        .route("/api/audit/deletions", get(deletion_audit))
+        .route("/api/audit/admin", get(admin_audit))
+        .route("/api/sessions", get(list_sessions))
+        .route("/api/sessions/kick", post(kick))
+        .route("/api/broadcast", post(broadcast))
+        .route_layer(middleware::from_fn_with_state(access.clone(), require_sysop))
+        .with_state(access.handle)
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// `(user, password)` from an `Authorization: Basic` header.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let encoded = value.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let (user, pass) = String::from_utf8(decoded).ok()?.split_once(':').map(|(u, p)| (u.to_string(), p.to_string()))?;
+    Some((user, pass))
+}
+
+async fn require_sysop(State(access): State<AdminAccess>, mut req: Request, next: Next) -> Response {
+    let Some(hash) = access.password_hash.clone() else {
+        return error(StatusCode::SERVICE_UNAVAILABLE, "Admin UI disabled: set a sysop password with `meshbbs sysop-passwd`");
+    };
+    let credentials = basic_credentials(req.headers());
+    let source = match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("web:{}", addr.ip()),
+        None => "web:unknown".to_string(),
+    };
+    if credentials.is_some() {
+        let locked = match access.handle.call(&access.sysop, AdminRequest::LoginLockout { source: source.clone() }).await {
+            Ok(value) => !value["locked_until"].is_null(),
+            Err(e) => {
+                log::warn!("Web admin lockout check failed: {e:#}");
+                false
+            }
+        };
+        if locked {
+            return error(StatusCode::TOO_MANY_REQUESTS, "Too many failed logins; try again later");
+        }
+    }
+    let attempted = credentials.is_some();
+    let verified = match credentials {
+        Some((user, pass)) if user == access.sysop => {
+            // Argon2 is deliberately slow; keep it off the async workers
+            tokio::task::spawn_blocking(move || {
+                PasswordHash::new(&hash).map(|parsed| Argon2::default().verify_password(pass.as_bytes(), &parsed).is_ok()).unwrap_or(false)
+            }).await.unwrap_or(false)
+        }
+        Some((user, _)) => {
+            log::warn!(target: "security", "Web admin login refused for non-sysop user '{}'", crate::logutil::escape_log(&user));
+            false
+        }
+        None => false,
+    };
+    if attempted {
+        if !verified {
+            log::warn!(target: "security", "Web admin login failed from {}", source);
+        }
+        if let Err(e) = access.handle.call(&access.sysop, AdminRequest::LoginAttempt { source, ok: verified }).await {
+            log::warn!("Web admin login not recorded: {e:#}");
+        }
+    }
+    if !verified {
+        let mut resp = error(StatusCode::UNAUTHORIZED, "Authentication required");
+        resp.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static(REALM));
+        return resp;
+    }
+    req.extensions_mut().insert(Actor(access.sysop.clone()));
+    next.run(req).await
+}
+
+async fn call(handle: &AdminHandle, actor: &Actor, request: AdminRequest) -> Response {
+    match handle.call(&actor.0, request).await {
+        Ok(value) => Json(value).into_response(),
+        Err(e) if e.downcast_ref::<NotFound>().is_some() => error(StatusCode::NOT_FOUND, e.to_string()),
+        Err(e) => error(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+async fn dashboard() -> Html<&'static str> {
+    Html(DASHBOARD_HTML)
+}
+
+async fn overview(State(h): State<AdminHandle>, Extension(a): Extension<Actor>) -> Response {
+    call(&h, &a, AdminRequest::Overview).await
+}
+
+async fn list_users(State(h): State<AdminHandle>, Extension(a): Extension<Actor>) -> Response {
+    call(&h, &a, AdminRequest::ListUsers).await
+}
+
+#[derive(Deserialize)]
+struct LevelBody { level: u8 }
+
+async fn set_level(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(username): Path<String>, Json(body): Json<LevelBody>) -> Response {
+    call(&h, &a, AdminRequest::SetUserLevel { username, level: body.level }).await
+}
+
+#[derive(Deserialize)]
+struct PasswordBody { password: String }
+
+async fn reset_password(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(username): Path<String>, Json(body): Json<PasswordBody>) -> Response {
+    call(&h, &a, AdminRequest::ResetPassword { username, password: body.password }).await
+}
+
+async fn list_topics(State(h): State<AdminHandle>, Extension(a): Extension<Actor>) -> Response {
+    call(&h, &a, AdminRequest::ListTopics).await
+}
+
+async fn create_topic(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Json(body): Json<NewTopic>) -> Response {
+    call(&h, &a, AdminRequest::CreateTopic(body)).await
+}
+
+async fn modify_topic(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(id): Path<String>, Json(changes): Json<TopicChanges>) -> Response {
+    call(&h, &a, AdminRequest::ModifyTopic { id, changes }).await
+}
+
+async fn delete_topic(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(id): Path<String>) -> Response {
+    call(&h, &a, AdminRequest::DeleteTopic { id }).await
+}
+
+#[derive(Deserialize)]
+struct LockBody { locked: bool }
+
+async fn lock_topic(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(id): Path<String>, Json(body): Json<LockBody>) -> Response {
+    call(&h, &a, AdminRequest::SetTopicLocked { id, locked: body.locked }).await
+}
+
+#[derive(Deserialize)]
+struct LimitQuery { limit: Option<usize> }
+
+async fn list_messages(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path(topic): Path<String>, Query(q): Query<LimitQuery>) -> Response {
+    call(&h, &a, AdminRequest::ListMessages { topic, limit: q.limit.unwrap_or(50).min(500) }).await
+}
+
+async fn delete_message(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path((topic, id)): Path<(String, String)>) -> Response {
+    call(&h, &a, AdminRequest::DeleteMessage { topic, id }).await
+}
+
+#[derive(Deserialize)]
+struct PinBody { pinned: bool }
+
+async fn pin_message(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Path((topic, id)): Path<(String, String)>, Json(body): Json<PinBody>) -> Response {
+    call(&h, &a, AdminRequest::SetMessagePinned { topic, id, pinned: body.pinned }).await
+}
+
+#[derive(Deserialize)]
+struct PageQuery { page: Option<usize> }
+
+async fn deletion_audit(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Query(q): Query<PageQuery>) -> Response {
+    call(&h, &a, AdminRequest::DeletionAudit { page: q.page.unwrap_or(1) }).await
+}
+
+async fn admin_audit(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Query(q): Query<PageQuery>) -> Response {
+    call(&h, &a, AdminRequest::AdminAudit { page: q.page.unwrap_or(1) }).await
+}
+
+async fn list_sessions(State(h): State<AdminHandle>, Extension(a): Extension<Actor>) -> Response {
+    call(&h, &a, AdminRequest::ListSessions).await
+}
+
+#[derive(Deserialize)]
+struct KickBody { username: String }
+
+async fn kick(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Json(body): Json<KickBody>) -> Response {
+    call(&h, &a, AdminRequest::Kick { username: body.username }).await
+}
+
+#[derive(Deserialize)]
+struct BroadcastBody { message: String }
+
+async fn broadcast(State(h): State<AdminHandle>, Extension(a): Extension<Actor>, Json(body): Json<BroadcastBody>) -> Response {
+    call(&h, &a, AdminRequest::Broadcast { message: body.message }).await
+}
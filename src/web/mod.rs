@@ -0,0 +1,57 @@
+//! Embedded HTTP server (feature `web`).
+//!
+//! Enabled by `[web] enabled = true`; it listens on `bind` (default `127.0.0.1:9090`) and
+//! the BBS keeps running if the address cannot be bound.
+//!
+//! - `GET /metrics` — Prometheus text format from [`crate::metrics`], unauthenticated.
+//! - `/` and `/api/...` — the sysop admin dashboard and its JSON API (see [`admin`]),
+//!   behind HTTP Basic auth against `bbs.sysop_password_hash`.
+
+pub mod admin;
+
+use anyhow::{anyhow, Result};
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+
+use crate::config::WebConfig;
+pub use admin::AdminAccess;
+
+/// Content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// All routes; the admin UI and API are only mounted when `admin` is given.
+pub fn router(admin: Option<AdminAccess>) -> Router {
+    let router = Router::new().route("/metrics", get(metrics));
+    match admin {
+        Some(access) => router.merge(admin::router(access)),
+        None => router,
+    }
+}
+
+async fn metrics() -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], crate::metrics::render_prometheus())
+}
+
+/// Serve on an already bound listener until the task is dropped.
+pub async fn serve(listener: TcpListener, admin: Option<AdminAccess>) -> Result<()> {
+    // Peer addresses key the admin login lockout
+    axum::serve(listener, router(admin).into_make_service_with_connect_info::<SocketAddr>()).await?;
+    Ok(())
+}
+
+/// Bind `cfg.bind` and serve in the background; returns the bound address.
+pub async fn start(cfg: &WebConfig, admin: Option<AdminAccess>) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(&cfg.bind).await
+        .map_err(|e| anyhow!("Failed to bind web server to {}: {}", cfg.bind, e))?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move {
+        if let Err(e) = serve(listener, admin).await {
+            log::error!("Web server stopped: {e:?}");
+        }
+    });
+    Ok(addr)
+}
@@ -0,0 +1,104 @@
+//! `meshbbs user` / `meshbbs topic`: the offline admin requests act on storage through the
+//! same checks as the web admin and are audited under the `cli` actor.
+
+use meshbbs::bbs::admin::{AdminRequest, NewTopic, TopicChanges, CLI_ACTOR};
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::storage::{BackendKind, Storage};
+
+async fn server(backend: BackendKind) -> (BbsServer, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.storage.backend = backend;
+    (BbsServer::new(cfg).await.unwrap(), tmp)
+}
+
+async fn run(server: &mut BbsServer, request: AdminRequest) -> serde_json::Value {
+    server.handle_admin(CLI_ACTOR, request).await.unwrap()
+}
+
+async fn user_lifecycle(backend: BackendKind) {
+    let (mut server, _tmp) = server(backend).await;
+    let created = run(&mut server, AdminRequest::CreateUser {
+        username: "alice".into(),
+        password: "Password123".into(),
+        level: 5,
+        node_id: Some("1234".into()),
+    }).await;
+    assert_eq!((created["level"].as_u64(), created["node_id"].as_str(), created["has_password"].as_bool()), (Some(5), Some("1234"), Some(true)));
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::CreateUser { username: "alice".into(), password: "Password123".into(), level: 1, node_id: None }).await.is_err());
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::CreateUser { username: "bob".into(), password: "Password123".into(), level: 3, node_id: None }).await.is_err());
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::CreateUser { username: "sysop".into(), password: "Password123".into(), level: 10, node_id: None }).await.is_err());
+
+    run(&mut server, AdminRequest::SetUserLevel { username: "alice".into(), level: 1 }).await;
+    run(&mut server, AdminRequest::ResetPassword { username: "alice".into(), password: "Password456".into() }).await;
+    let unbound = run(&mut server, AdminRequest::UnbindNode { username: "alice".into() }).await;
+    assert_eq!(unbound["previous_node"], "1234");
+    let shown = run(&mut server, AdminRequest::ShowUser { username: "alice".into() }).await;
+    assert_eq!((shown["level"].as_u64(), shown["node_id"].is_null()), (Some(1), true));
+    assert!(server.get_user("alice").await.unwrap().unwrap().password_hash.is_some());
+
+    server.test_store_message("general", "alice", "Still here after the account is gone").await.unwrap();
+    run(&mut server, AdminRequest::DeleteUser { username: "alice".into() }).await;
+    assert!(server.get_user("alice").await.unwrap().is_none());
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::ShowUser { username: "alice".into() }).await.is_err());
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::DeleteUser { username: "sysop".into() }).await.is_err());
+    assert_eq!(run(&mut server, AdminRequest::ListUsers).await.as_array().unwrap().len(), 0);
+
+    let audit = run(&mut server, AdminRequest::AdminAudit { page: 1 }).await;
+    let mut entries: Vec<(String, String)> = audit.as_array().unwrap().iter()
+        .map(|e| (e["action"].as_str().unwrap().to_string(), e["actor"].as_str().unwrap().to_string()))
+        .collect();
+    entries.reverse();
+    let actions: Vec<&str> = entries.iter().map(|(a, _)| a.as_str()).collect();
+    assert_eq!(actions, ["CREATE_USER", "PROMOTE", "DEMOTE", "RESET_PASSWORD", "UNBIND_NODE", "DELETE_USER"]);
+    assert!(entries.iter().all(|(_, actor)| actor == CLI_ACTOR));
+}
+
+#[tokio::test]
+async fn users_are_managed_and_audited_json() {
+    user_lifecycle(BackendKind::Json).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn users_are_managed_and_audited_sqlite() {
+    user_lifecycle(BackendKind::Sqlite).await;
+}
+
+#[tokio::test]
+async fn topics_are_managed_and_audited() {
+    let (mut server, tmp) = server(BackendKind::Json).await;
+    let topic = |id: &str, parent: Option<&str>| NewTopic {
+        id: id.into(),
+        name: id.to_uppercase(),
+        description: String::new(),
+        read_level: 0,
+        post_level: 0,
+        parent: parent.map(Into::into),
+    };
+    run(&mut server, AdminRequest::CreateTopic(topic("radio", None))).await;
+    run(&mut server, AdminRequest::CreateTopic(topic("hf", Some("radio")))).await;
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::CreateTopic(topic("vhf", Some("nope")))).await.is_err());
+    let changes = TopicChanges { description: Some("Shortwave".into()), post_level: Some(5), ..Default::default() };
+    run(&mut server, AdminRequest::ModifyTopic { id: "hf".into(), changes }).await;
+    run(&mut server, AdminRequest::SetTopicLocked { id: "hf".into(), locked: true }).await;
+    assert!(server.handle_admin(CLI_ACTOR, AdminRequest::DeleteTopic { id: "radio".into() }).await.is_err(), "has a subtopic");
+
+    let topics = run(&mut server, AdminRequest::ListTopics).await;
+    let hf = topics.as_array().unwrap().iter().find(|t| t["id"] == "hf").unwrap();
+    assert_eq!((hf["parent"].as_str(), hf["description"].as_str(), hf["post_level"].as_u64(), hf["locked"].as_bool()), (Some("radio"), Some("Shortwave"), Some(5), Some(true)));
+
+    run(&mut server, AdminRequest::SetTopicLocked { id: "hf".into(), locked: false }).await;
+    run(&mut server, AdminRequest::DeleteTopic { id: "hf".into() }).await;
+    drop(server);
+
+    // Changes are persisted for the next run
+    let storage = Storage::new(&tmp.path().join("data").to_string_lossy()).await.unwrap();
+    assert!(storage.topic_exists("radio") && !storage.topic_exists("hf"));
+    let audit = storage.get_admin_audit_page(1, 20).await.unwrap();
+    let actions: Vec<&str> = audit.iter().rev().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, ["CREATE_TOPIC", "CREATE_TOPIC", "MODIFY_TOPIC", "LOCK_TOPIC", "UNLOCK_TOPIC", "DELETE_TOPIC"]);
+    assert!(audit.iter().all(|e| e.actor == CLI_ACTOR));
+}
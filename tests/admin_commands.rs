@@ -6,11 +6,13 @@ use std::collections::HashMap;
 async fn base_config() -> Config {
     Config {
         bbs: BbsConfig { name: "Test".into(), sysop: "sysop".into(), location: "loc".into(), description: "d".into(), max_users: 10, session_timeout: 10, welcome_message: "w".into(), sysop_password_hash: None },
-    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None },
-        storage: StorageConfig { data_dir: tempfile::tempdir().unwrap().path().join("data").to_str().unwrap().to_string(), max_message_size: 1024 },
+    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None, public_channels: None, store_forward_ttl_hours: None, store_forward_max_per_node: None },
+        storage: StorageConfig { data_dir: tempfile::tempdir().unwrap().path().join("data").to_str().unwrap().to_string(), max_message_size: 1024, backend: Default::default(), retention_interval_minutes: None, archive_expired: false },
         message_topics: HashMap::new(),
         logging: LoggingConfig { level: "error".into(), file: None, security_file: None },
         security: Default::default(),
+        web: None,
+        backup: None,
     }
 }
 
@@ -57,7 +59,7 @@ async fn admin_commands_moderator_access() {
     // Test ADMIN command
     server.route_test_text_direct(&alice_node, "ADMIN").await.unwrap();
     let response = server.test_messages().last().unwrap().1.clone();
-    assert!(response.contains("BBS Administration Dashboard"));
+    assert!(response.contains("ADMINISTRATIVE DASHBOARD"));
 }
 
 #[tokio::test]
@@ -93,20 +95,27 @@ async fn broadcast_and_kick_commands() {
     server.test_register("moderator", "password").await.unwrap();
     server.test_update_level("moderator", 5).await.unwrap(); // Make moderator a moderator
     
-    // Create session
+    // Create sessions
     let mut admin_session = meshbbs::bbs::session::Session::new("admin_session".into(), "admin_node".into());
     admin_session.login("moderator".into(), 5).await.unwrap();
     let admin_node = admin_session.node_id.clone();
     server.test_insert_session(admin_session);
+    server.test_register("troublemaker", "password789").await.unwrap();
+    let mut target_session = meshbbs::bbs::session::Session::new("target_session".into(), "target_node".into());
+    target_session.login("troublemaker".into(), 1).await.unwrap();
+    server.test_insert_session(target_session);
     
-    // Test BROADCAST command
+    // Test BROADCAST command: delivered to every logged-in session
     server.route_test_text_direct(&admin_node, "BROADCAST System maintenance in 5 minutes").await.unwrap();
+    assert!(server.test_messages().iter().any(|(to, m)| to == "target_node" && m.contains("SYSTEM MESSAGE from moderator: System maintenance in 5 minutes")));
     let response = server.test_messages().last().unwrap().1.clone();
-    assert!(response.contains("Broadcast sent:"));
-    assert!(response.contains("SYSTEM MAINTENANCE IN 5 MINUTES"));
+    assert!(response.contains("Broadcast sent to 2 users"), "broadcast reply: {}", response);
     
     // Test KICK command
     server.route_test_text_direct(&admin_node, "KICK troublemaker").await.unwrap();
     let response = server.test_messages().last().unwrap().1.clone();
-    assert!(response.contains("TROUBLEMAKER has been kicked"));
-}
\ No newline at end of file
+    assert!(response.contains("User troublemaker has been kicked"), "kick reply: {}", response);
+    server.route_test_text_direct(&admin_node, "KICK troublemaker").await.unwrap();
+    let response = server.test_messages().last().unwrap().1.clone();
+    assert!(response.contains("not logged in"));
+}
@@ -0,0 +1,113 @@
+//! `meshbbs export` / `import`: a JSON Lines export re-imports with authors, timestamps,
+//! titles, pins and replies intact (and is deduplicated by id), while mbox and HTML render
+//! the same threads for mail clients and the web.
+
+use chrono::{Duration, Utc};
+use meshbbs::storage::{ExportFormat, ExportOptions, ReplyEntry, Storage};
+
+async fn populate(storage: &mut Storage) -> (String, String) {
+    storage.create_topic("general", "General", "General talk", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("radio", "Radio", "Rigs & <antennas>", 0, 0, "sysop").await.unwrap();
+    storage.create_subtopic("antennas", "radio", "Antennas", "", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("staff", "Staff", "", 5, 0, "sysop").await.unwrap();
+    let old = storage.store_message("general", "alice", "Field day\nFrom the club: bring a chair").await.unwrap();
+    storage.append_reply("general", &old, "bob", "Count me in").await.unwrap();
+    storage.set_message_pinned("general", &old, true).await.unwrap();
+    let yagi = storage.store_message("antennas", "carol", "Yagi <3 elements>").await.unwrap();
+    storage.store_message("staff", "sysop", "Moderation notes").await.unwrap();
+    // Backdate the first thread so a re-stamped import would show
+    let backend = storage.backend();
+    let mut msg = backend.get_message("general", &old).unwrap().unwrap();
+    msg.timestamp -= Duration::days(30);
+    if let ReplyEntry::Reply(r) = &mut msg.replies[0] { r.timestamp -= Duration::days(29); }
+    msg.replies.push(ReplyEntry::Legacy("dave: see you there".into()));
+    backend.update_message(&msg).unwrap();
+    (old, yagi)
+}
+
+#[tokio::test]
+async fn jsonl_round_trips_and_reimport_is_deduplicated() {
+    let tmp = tempfile::tempdir().unwrap();
+    let src_dir = tmp.path().join("src").to_string_lossy().to_string();
+    let mut src = Storage::new(&src_dir).await.unwrap();
+    let (old, yagi) = populate(&mut src).await;
+    let file = tmp.path().join("board.jsonl");
+    let report = src.export(ExportFormat::Jsonl, &file, &ExportOptions { title: "Club BBS".into(), ..Default::default() }).await.unwrap();
+    assert_eq!((report.topics, report.messages, report.replies), (4, 3, 2));
+    let lines: Vec<serde_json::Value> = std::fs::read_to_string(&file).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!((lines[0]["type"].as_str(), lines[0]["bbs"].as_str()), (Some("export"), Some("Club BBS")));
+    let topic_order: Vec<&str> = lines.iter().filter(|l| l["type"] == "topic").map(|l| l["id"].as_str().unwrap()).collect();
+    assert_eq!(topic_order, ["general", "radio", "staff", "antennas"], "parents come first");
+
+    let dst_dir = tmp.path().join("dst").to_string_lossy().to_string();
+    let mut dst = Storage::new(&dst_dir).await.unwrap();
+    dst.create_topic("general", "Chat", "Kept as is", 1, 1, "sysop").await.unwrap();
+    let reader = std::io::BufReader::new(std::fs::File::open(&file).unwrap());
+    let report = dst.import_jsonl(reader).await.unwrap();
+    assert_eq!((report.topics_created, report.imported, report.duplicates), (3, 3, 0));
+    assert!(report.rejected.is_empty(), "{:?}", report.rejected);
+
+    let original = src.get_message("general", &old).await.unwrap().unwrap();
+    let copy = dst.get_message("general", &old).await.unwrap().expect("imported under its id");
+    assert_eq!(serde_json::to_value(&copy).unwrap(), serde_json::to_value(&original).unwrap());
+    assert!(copy.pinned && copy.title.as_deref() == Some("Field day") && copy.timestamp < Utc::now() - Duration::days(29));
+    assert_eq!(dst.get_topic_config("general").unwrap().name, "Chat");
+    assert_eq!(dst.get_topic_config("antennas").unwrap().parent.as_deref(), Some("radio"));
+    assert_eq!(dst.get_topic_config("staff").unwrap().read_level, 5);
+    assert_eq!(dst.get_message("antennas", &yagi).await.unwrap().unwrap().author, "carol");
+    assert_eq!(dst.search_messages("yagi").await.unwrap().len(), 1);
+
+    let reader = std::io::BufReader::new(std::fs::File::open(&file).unwrap());
+    let again = dst.import_jsonl(reader).await.unwrap();
+    assert_eq!((again.topics_created, again.imported, again.duplicates), (0, 0, 3));
+    assert_eq!(dst.get_messages("general", 10).await.unwrap().len(), 1);
+
+    // Bad lines are reported and skipped; the rest still imports
+    let id = "00000000-0000-4000-8000-000000000009";
+    let bad = [
+        "not json".to_string(),
+        format!(r#"{{"type":"message","id":"{id}","topic":"nowhere","author":"eve","content":"x","timestamp":"2026-01-01T00:00:00Z"}}"#),
+        format!(r#"{{"type":"message","id":"{id}","topic":"general","author":"../root","content":"x","timestamp":"2026-01-01T00:00:00Z"}}"#),
+        format!(r#"{{"type":"message","id":"{id}","topic":"general","author":"eve","content":"{}","timestamp":"2026-01-01T00:00:00Z"}}"#, "x".repeat(300)),
+        format!(r#"{{"type":"message","id":"{id}","topic":"general","author":"eve","content":"ok","timestamp":"2026-01-01T00:00:00Z"}}"#),
+    ]
+    .join("\n");
+    let report = dst.import_jsonl(bad.as_bytes()).await.unwrap();
+    let lines: Vec<usize> = report.rejected.iter().map(|(n, _)| *n).collect();
+    assert_eq!((lines, report.imported), (vec![1, 2, 3, 4], 1));
+    let future = r#"{"type":"export","format":"meshbbs-export","version":99,"exported_at":"2026-01-01T00:00:00Z"}"#;
+    assert!(dst.import_jsonl(future.as_bytes()).await.is_err());
+}
+
+#[tokio::test]
+async fn mbox_and_html_render_threads() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut storage = Storage::new(&data_dir).await.unwrap();
+    let (old, _) = populate(&mut storage).await;
+    let public = ExportOptions { max_read_level: Some(0), title: "Club BBS".into(), ..Default::default() };
+
+    let mbox = tmp.path().join("board.mbox");
+    let report = storage.export(ExportFormat::Mbox, &mbox, &public).await.unwrap();
+    assert_eq!((report.topics, report.messages), (3, 2), "staff is left out");
+    let text = std::fs::read_to_string(&mbox).unwrap();
+    assert_eq!(text.lines().filter(|l| l.starts_with("From ") && l.contains("@meshbbs.invalid")).count(), 4, "{text}");
+    assert!(text.contains("Subject: [general] Field day\n") && text.contains("X-Meshbbs-Pinned: yes"));
+    assert!(text.contains("\n>From the club: bring a chair\n"), "body From lines are quoted");
+    assert!(text.contains(&format!("In-Reply-To: <{old}@meshbbs.invalid>")));
+    assert!(text.contains("From: dave <dave@meshbbs.invalid>") && text.contains("Subject: Re: [general] Field day"));
+    assert!(!text.contains("Moderation notes"));
+
+    let site = tmp.path().join("site");
+    storage.export(ExportFormat::Html, &site, &public).await.unwrap();
+    let index = std::fs::read_to_string(site.join("index.html")).unwrap();
+    assert!(index.contains("<a href=\"general.html\">General</a>") && index.contains("Rigs &amp; &lt;antennas&gt;"));
+    assert!(!index.contains("staff.html") && !site.join("staff.html").exists());
+    let general = std::fs::read_to_string(site.join("general.html")).unwrap();
+    assert!(general.contains("📌 Field day") && general.contains("Count me in") && general.contains("see you there"));
+    let antennas = std::fs::read_to_string(site.join("antennas.html")).unwrap();
+    assert!(antennas.contains("Yagi &lt;3 elements&gt;") && !antennas.contains("<3 elements>"));
+
+    assert!(storage.export(ExportFormat::Jsonl, &tmp.path().join("x.jsonl"), &ExportOptions { topics: vec!["nope".into()], ..Default::default() }).await.is_err());
+    assert!("pdf".parse::<ExportFormat>().is_err());
+}
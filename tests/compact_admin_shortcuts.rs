@@ -16,7 +16,7 @@ async fn moderator_can_lock_unlock_with_k_in_threads() {
     server.test_register("mod", "Password123").await.unwrap();
     server.test_update_level("mod", 5).await.unwrap();
     let node = "n1";
-    server.route_test_text_direct(node, "LOGIN mod").await.unwrap();
+    server.route_test_text_direct(node, "LOGIN mod Password123").await.unwrap();
     // Enter default first topic (created via TOML merge) or create one
     // Ensure at least one topic exists
     if server.test_get_messages("general", 1).await.is_err() {
@@ -46,7 +46,7 @@ async fn sysop_grant_g_changes_user_level() {
     server.seed_sysop().await.unwrap();
     let node = "n1";
     // Sysop is auto-seeded on first DM if not present; login as sysop
-    server.route_test_text_direct(node, "LOGIN sysop").await.unwrap();
+    server.route_test_text_direct(node, "LOGIN sysop SecretP@ss1").await.unwrap();
     // Create a user and grant level
     server.test_register("bob", "Password123").await.unwrap();
     server.route_test_text_direct(node, "G @bob=5").await.unwrap();
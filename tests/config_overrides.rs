@@ -0,0 +1,114 @@
+//! Layered configuration: defaults < file < MESHBBS_* environment < --set.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{ConfigLoader, ValueSource};
+
+const FILE: &str = r#"
+[bbs]
+name = "Hilltop BBS"
+sysop = "sysop"
+max_users = 20
+sysop_password_hash = "$argon2id$v=19$secret"
+
+[meshtastic]
+port = "/dev/ttyACM0"
+"#;
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn later_layers_win_and_defaults_fill_gaps() {
+    let loader = ConfigLoader::new("config.toml")
+        .env(env(&[("MESHBBS_BBS__MAX_USERS", "30"), ("MESHBBS_STORAGE__DATA_DIR", "/srv/bbs"), ("HOME", "/root")]))
+        .sets(["bbs.max_users=40".to_string(), "meshtastic.dm_resend_backoff_seconds=[3, 6]".to_string()]);
+    let loaded = loader.load_str(FILE).unwrap();
+    let cfg = &loaded.config;
+
+    assert_eq!(cfg.bbs.max_users, 40);
+    assert_eq!(loaded.source_of("bbs.max_users"), ValueSource::Cli);
+    assert_eq!(cfg.storage.data_dir, "/srv/bbs");
+    assert_eq!(loaded.source_of("storage.data_dir"), ValueSource::Env("MESHBBS_STORAGE__DATA_DIR".into()));
+    assert_eq!(cfg.meshtastic.dm_resend_backoff_seconds, Some(vec![3, 6]));
+    assert_eq!((cfg.bbs.name.as_str(), cfg.meshtastic.port.as_str()), ("Hilltop BBS", "/dev/ttyACM0"));
+    assert_eq!(loaded.source_of("meshtastic.port"), ValueSource::File);
+    // Missing from the file: filled from defaults, but never the legacy topic table
+    assert_eq!((cfg.bbs.session_timeout, cfg.meshtastic.baud_rate), (10, 115200));
+    assert_eq!(loaded.source_of("bbs.session_timeout"), ValueSource::Default);
+    assert!(cfg.message_topics.is_empty());
+}
+
+#[test]
+fn optional_settings_missing_from_the_file_stay_unset() {
+    let file = format!("{FILE}\n[logging]\nlevel = \"info\"\n");
+    let loaded = ConfigLoader::new("config.toml").load_str(&file).unwrap();
+    let logging = &loaded.config.logging;
+    assert_eq!((logging.level.as_str(), logging.file.as_deref(), logging.security_file.as_deref()), ("info", None, None));
+    assert!(loaded.config.web.is_none() && loaded.config.backup.is_none());
+    let shown = loaded.render_effective().unwrap();
+    assert!(!shown.contains("logging.file") && !shown.contains("logging.security_file"), "{shown}");
+
+    // Still settable by override
+    let cfg = ConfigLoader::new("config.toml").sets(["logging.file=bbs.log".to_string()]).load_str(&file).unwrap().config;
+    assert_eq!(cfg.logging.file.as_deref(), Some("bbs.log"));
+}
+
+#[test]
+fn string_settings_take_the_raw_text_and_sections_can_be_created() {
+    let loader = ConfigLoader::new("config.toml")
+        .env(env(&[("MESHBBS_MESHTASTIC__NODE_ID", "1234"), ("MESHBBS_WEB__ENABLED", "true")]))
+        .sets(["bbs.name=73".to_string(), "meshtastic.public_channels=[{ index = 2, commands = [\"HELP\"] }]".to_string()]);
+    let cfg = loader.load_str(FILE).unwrap().config;
+    assert_eq!((cfg.meshtastic.node_id.as_str(), cfg.bbs.name.as_str()), ("1234", "73"));
+    let web = cfg.web.expect("web section created by the override");
+    assert!(web.enabled);
+    assert_eq!(web.bind, "127.0.0.1:9090");
+    assert_eq!(cfg.meshtastic.public_channels.unwrap()[0].index, 2);
+}
+
+#[test]
+fn bad_overrides_are_rejected() {
+    let load = |vars: &[(&str, &str)], sets: &[&str]| {
+        ConfigLoader::new("config.toml").env(env(vars)).sets(sets.iter().map(|s| s.to_string())).load_str(FILE)
+    };
+    let err = load(&[("MESHBBS_BBS__MAX_USER", "5")], &[]).unwrap_err().to_string();
+    assert!(err.contains("MESHBBS_BBS__MAX_USER") && err.contains("unknown setting 'bbs.max_user'"), "{err}");
+    assert!(load(&[], &["bbs.name"]).unwrap_err().to_string().contains("expected section.key=value"));
+    assert!(load(&[], &["bbs.max_users=lots"]).is_err());
+    assert!(load(&[], &["bbs.name.first=x"]).unwrap_err().to_string().contains("not a section"));
+    assert!(load(&[("MESHBBS___X", "1")], &[]).is_err());
+}
+
+#[test]
+fn effective_view_shows_sources_and_hides_secrets() {
+    let loaded = ConfigLoader::new("/etc/meshbbs.toml")
+        .env(env(&[("MESHBBS_SECURITY__ENCRYPTION__PASSPHRASE", "hunter2hunter2")]))
+        .sets(["bbs.max_users=40".to_string()])
+        .load_str(FILE)
+        .unwrap();
+    let shown = loaded.render_effective().unwrap();
+    assert!(shown.contains("bbs.max_users = 40  # --set\n"), "{shown}");
+    assert!(shown.contains("bbs.name = \"Hilltop BBS\"  # file /etc/meshbbs.toml\n"), "{shown}");
+    assert!(shown.contains("bbs.session_timeout = 10  # default\n"), "{shown}");
+    assert!(shown.contains("security.encryption.passphrase = \"<redacted>\"  # env MESHBBS_SECURITY__ENCRYPTION__PASSPHRASE\n"), "{shown}");
+    assert!(shown.contains("bbs.sysop_password_hash = \"<redacted>\""), "{shown}");
+    assert!(!shown.contains("hunter2") && !shown.contains("secret"), "{shown}");
+}
+
+#[tokio::test]
+async fn reload_keeps_startup_overrides() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("config.toml");
+    std::fs::write(&path, FILE).unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let loader = ConfigLoader::new(path.to_string_lossy()).env(env(&[("MESHBBS_STORAGE__DATA_DIR", &data_dir)]));
+    let mut server = BbsServer::new(loader.load().await.unwrap().config).await.unwrap();
+    server.set_config_loader(loader);
+
+    std::fs::write(&path, FILE.replace("max_users = 20", "max_users = 25")).unwrap();
+    let report = server.reload_config("SIGHUP").await.unwrap();
+    assert_eq!(report.applied, vec!["bbs.max_users"]);
+    assert!(report.restart_required.is_empty(), "{report:?}");
+    assert_eq!(server.config().storage.data_dir, data_dir);
+}
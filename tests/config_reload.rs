@@ -0,0 +1,91 @@
+//! Config hot reload: runtime-safe settings change without dropping sessions, restart-only
+//! settings are reported and kept, invalid files are rejected.
+
+use argon2::Argon2;
+use meshbbs::bbs::admin::AdminRequest;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use password_hash::{PasswordHasher, SaltString};
+
+const SYSOP_PASS: &str = "Sysop#Pass1";
+
+fn write_config(path: &std::path::Path, cfg: &Config) {
+    std::fs::write(path, toml::to_string_pretty(cfg).unwrap()).unwrap();
+}
+
+#[test]
+fn reload_plan_keeps_restart_only_settings() {
+    let current = Config::default();
+    let mut new = current.clone();
+    new.bbs.max_users = 5;
+    new.meshtastic.dm_to_dm_gap_ms = Some(900);
+    new.storage.data_dir = "/elsewhere".into();
+    new.meshtastic.port = "tcp://10.0.0.5".into();
+
+    let (next, report) = current.reload_plan(new).unwrap();
+    assert_eq!(report.applied, vec!["bbs.max_users", "meshtastic.dm_to_dm_gap_ms"]);
+    assert_eq!(report.restart_required, vec!["meshtastic.port", "storage.data_dir"]);
+    assert_eq!((next.bbs.max_users, next.meshtastic.dm_to_dm_gap_ms), (5, Some(900)));
+    assert_eq!((next.storage.data_dir.as_str(), next.meshtastic.port.as_str()), ("./data", "/dev/ttyUSB0"));
+    assert_eq!(current.changed_settings(&next).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn sysop_reload_applies_changes_and_keeps_sessions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("config.toml");
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(SYSOP_PASS.as_bytes(), &salt).unwrap().to_string());
+    write_config(&path, &cfg);
+
+    let mut server = BbsServer::new(cfg.clone()).await.unwrap();
+    server.set_config_path(path.to_string_lossy());
+    server.seed_sysop().await.unwrap();
+    server.test_register("bob", "Password456").await.unwrap();
+    server.route_test_text_direct("1001", &format!("LOGIN sysop {SYSOP_PASS}")).await.unwrap();
+    server.route_test_text_direct("2002", "LOGIN bob Password456").await.unwrap();
+
+    server.route_test_text_direct("2002", "RELOAD").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("Permission denied"));
+
+    cfg.bbs.max_users = 2;
+    cfg.bbs.session_timeout = 30;
+    cfg.meshtastic.min_send_gap_ms = Some(3000);
+    cfg.storage.data_dir = tmp.path().join("moved").to_string_lossy().to_string();
+    write_config(&path, &cfg);
+    server.route_test_text_direct("1001", "RELOAD").await.unwrap();
+    let reply = &server.test_messages().last().unwrap().1;
+    assert!(reply.contains("Config reloaded: 3 setting(s) applied; restart needed: storage.data_dir"), "{reply}");
+
+    assert_eq!((server.config().bbs.max_users, server.config().bbs.session_timeout), (2, 30));
+    assert_eq!(server.config().meshtastic.min_send_gap_ms, Some(3000));
+    assert!(server.config().storage.data_dir.ends_with("data"), "storage stays where it was opened");
+    assert_eq!(server.test_logged_in_count(), 2, "sessions survive the reload");
+
+    let audit = server.handle_admin("sysop", AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    assert_eq!((audit[0]["action"].as_str(), audit[0]["actor"].as_str()), (Some("RELOAD"), Some("sysop")));
+}
+
+#[tokio::test]
+async fn invalid_config_is_rejected_on_reload() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("config.toml");
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut server = BbsServer::new(cfg.clone()).await.unwrap();
+
+    assert!(server.reload_config("SIGHUP").await.is_err(), "no config file recorded");
+    server.set_config_path(path.to_string_lossy());
+
+    cfg.bbs.max_users = 0;
+    cfg.logging.level = "loud".into();
+    write_config(&path, &cfg);
+    let err = server.reload_config("SIGHUP").await.unwrap_err().to_string();
+    assert!(err.contains("bbs.max_users") && err.contains("logging.level"), "{err}");
+
+    std::fs::write(&path, "[bbs\nname = ").unwrap();
+    assert!(server.reload_config("SIGHUP").await.is_err());
+    assert_eq!(server.config().bbs.max_users, 100, "current settings are kept");
+}
@@ -0,0 +1,99 @@
+//! Semantic config validation: errors block startup and reload, warnings describe settings
+//! that are corrected at runtime.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Argon2Config, Config, EncryptionConfig, PublicChannelConfig, Severity};
+
+fn settings(cfg: &Config, severity: Severity) -> Vec<String> {
+    cfg.check().findings.into_iter().filter(|f| f.severity == severity).map(|f| f.setting).collect()
+}
+
+#[test]
+fn defaults_are_clean() {
+    let report = Config::default().check();
+    assert!(report.findings.is_empty(), "{report:?}");
+    assert_eq!(report.summary(), "0 error(s), 0 warning(s)");
+}
+
+#[test]
+fn runtime_corrections_are_warnings() {
+    let mut cfg = Config::default();
+    cfg.meshtastic.min_send_gap_ms = Some(500);
+    cfg.meshtastic.dm_resend_backoff_seconds = Some(vec![]);
+    cfg.meshtastic.post_dm_broadcast_gap_ms = Some(4000);
+    cfg.meshtastic.help_broadcast_delay_ms = Some(1000);
+
+    let report = cfg.check();
+    assert!(!report.has_errors(), "{report:?}");
+    assert!(cfg.validate().is_ok());
+    assert_eq!(
+        settings(&cfg, Severity::Warning),
+        ["meshtastic.min_send_gap_ms", "meshtastic.dm_resend_backoff_seconds", "meshtastic.help_broadcast_delay_ms"]
+    );
+    assert_eq!(
+        report.findings[0].to_string(),
+        "warning: meshtastic.min_send_gap_ms: 500ms is below the 2000ms minimum and is raised to it"
+    );
+}
+
+#[test]
+fn unusable_settings_are_errors() {
+    let mut cfg = Config::default();
+    cfg.storage.max_message_size = 400;
+    cfg.logging.level = "loud".into();
+    cfg.bbs.sysop_password_hash = Some("plaintext".into());
+    cfg.meshtastic.public_channels = Some(vec![
+        PublicChannelConfig { index: 1, commands: vec!["^slotmachine".into(), "?".into(), "DANCE".into()] },
+        PublicChannelConfig { index: 1, commands: vec![] },
+    ]);
+    let security = cfg.security.get_or_insert_with(Default::default);
+    security.argon2 = Some(Argon2Config { memory_kib: Some(1), time_cost: None, parallelism: None });
+    security.encryption = Some(EncryptionConfig { passphrase: Some("short".into()), keyfile: None });
+    security.lockout.max_attempts = 0;
+    security.lockout.max_lockout_minutes = 1;
+
+    assert_eq!(
+        settings(&cfg, Severity::Error),
+        [
+            "bbs.sysop_password_hash",
+            "meshtastic.public_channels[0].commands",
+            "meshtastic.public_channels[1].index",
+            "storage.max_message_size",
+            "logging.level",
+            "security.argon2",
+            "security.lockout.max_attempts",
+            "security.lockout.max_lockout_minutes",
+            "security.encryption.passphrase",
+        ]
+    );
+    let err = cfg.validate().unwrap_err().to_string();
+    assert!(err.starts_with("Invalid configuration: bbs.sysop_password_hash: "), "{err}");
+    assert!(err.contains("unknown public command 'DANCE'") && !err.contains("SLOTMACHINE"), "{err}");
+}
+
+#[tokio::test]
+async fn invalid_argon2_params_fail_startup() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.security = Some(meshbbs::config::SecurityConfig {
+        argon2: Some(Argon2Config { memory_kib: None, time_cost: Some(0), parallelism: None }),
+        encryption: None,
+        ..Default::default()
+    });
+    let err = BbsServer::new(cfg).await.err().expect("previously fell back to defaults silently").to_string();
+    assert!(err.contains("security.argon2"), "{err}");
+}
+
+#[cfg(not(feature = "sqlite"))]
+#[test]
+fn sqlite_backend_needs_the_feature() {
+    use meshbbs::storage::{self, BackendKind};
+    let mut cfg = Config::default();
+    cfg.storage.backend = BackendKind::Sqlite;
+    assert_eq!(settings(&cfg, Severity::Error), ["storage.backend"]);
+    // `meshbbs migrate --to sqlite` opens the destination the same way
+    let tmp = tempfile::tempdir().unwrap();
+    let err = storage::open_backend(&tmp.path().to_string_lossy(), BackendKind::Sqlite, None).err().unwrap();
+    assert!(err.to_string().contains("'sqlite' feature"), "{err}");
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "meshtastic-proto")]
+//! `meshbbs console`: input lines go through the direct-message path as a synthetic node and
+//! every outgoing packet is printed, optionally at radio pace.
+
+use meshbbs::bbs::console::{self, ConsoleOptions, DEFAULT_CONSOLE_NODE};
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use tokio::time::{Duration, Instant};
+
+async fn server(tmp: &tempfile::TempDir) -> BbsServer {
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.meshtastic.help_broadcast_delay_ms = Some(0);
+    cfg.meshtastic.post_dm_broadcast_gap_ms = Some(0);
+    BbsServer::new(cfg).await.unwrap()
+}
+
+async fn run_script(server: &mut BbsServer, opts: &ConsoleOptions, script: &str) -> String {
+    let mut out = Vec::new();
+    console::run(server, opts, script.as_bytes(), &mut out).await.unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[tokio::test]
+async fn console_runs_a_session_as_a_synthetic_node() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut server = server(&tmp).await;
+    let script = "REGISTER carol Password123\n\nHELP+\n^HELP\n";
+    let output = run_script(&mut server, &ConsoleOptions::default(), script).await;
+
+    assert!(output.contains("Registered as carol"), "{output}");
+    let user = server.get_user("carol").await.unwrap().unwrap();
+    assert_eq!(user.node_id.as_deref(), Some(DEFAULT_CONSOLE_NODE.to_string().as_str()));
+    // Each packet is printed as its own block and fits the radio payload limit
+    let packets: Vec<&str> = output.split("\n\n").filter(|p| !p.is_empty()).collect();
+    assert!(packets.len() > 3, "HELP+ is chunked: {output}");
+    assert!(packets.iter().all(|p| p.len() <= 230 + "[public ch 0] ".len()), "{output}");
+    assert!(packets.iter().any(|p| p.ends_with('>')), "replies carry the prompt: {output}");
+    assert!(packets.iter().any(|p| p.starts_with("[public ch 0] ") && p.contains("Public Commands")), "{output}");
+}
+
+#[tokio::test]
+async fn paced_console_spaces_packets_by_the_send_gap() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut server = server(&tmp).await;
+    let opts = ConsoleOptions { node_id: 0x1234, pace: true };
+    let start = Instant::now();
+    let output = run_script(&mut server, &opts, "REGISTER dave Password123\nHELP\n").await;
+
+    let packets: Vec<&str> = output.split("\n\n").filter(|p| !p.is_empty()).collect();
+    assert!(packets.len() >= 2, "{output}");
+    assert!(packets.iter().all(|p| p.starts_with("[+")), "paced packets show their offset: {output}");
+    // Two packets are at least one 2s minimum send gap apart
+    assert!(start.elapsed() >= Duration::from_secs(2), "{output}");
+}
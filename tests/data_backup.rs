@@ -0,0 +1,125 @@
+//! `meshbbs backup` / `restore`: archives taken while storage is open restore intact,
+//! corrupt or encrypted-without-key archives are refused before anything is swapped, and
+//! scheduled backups rotate.
+
+use chrono::{Duration, Utc};
+use meshbbs::storage::{self, BackendKind, Storage};
+use std::path::Path;
+
+async fn populate(storage: &mut Storage) -> String {
+    storage.create_topic("general", "General", "General talk", 0, 0, "sysop").await.unwrap();
+    storage.register_user("alice", "password123", Some("1234")).await.unwrap();
+    storage.register_user("bob", "password456", None).await.unwrap();
+    let id = storage.store_message("general", "alice", "Field day on Saturday").await.unwrap();
+    storage.append_reply("general", &id, "bob", "Count me in").await.unwrap();
+    storage.send_mail("alice", "bob", "Bring the yagi").await.unwrap();
+    storage.update_user_level("bob", 5, "sysop").await.unwrap();
+    id
+}
+
+#[tokio::test]
+async fn json_backup_restores_the_tree_as_of_the_snapshot() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut storage = Storage::new(&data_dir).await.unwrap();
+    let id = populate(&mut storage).await;
+    std::fs::write(Path::new(&data_dir).join("node_cache.json"), r#"{"nodes":{},"last_updated":"2026-01-01T00:00:00Z"}"#).unwrap();
+
+    // Taken with storage open, as the running server would have it
+    let archive = tmp.path().join("bbs.tar.gz");
+    let manifest = storage::backup(&data_dir, BackendKind::Json, &archive).unwrap();
+    assert_eq!((manifest.version, manifest.backend, manifest.encrypted), (storage::BACKUP_VERSION, BackendKind::Json, false));
+    let mut entries = tar::Archive::new(flate2::read::GzDecoder::new(std::fs::File::open(&archive).unwrap()));
+    let names: Vec<String> = entries.entries().unwrap().map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string()).collect();
+    assert_eq!(names[0], storage::MANIFEST_FILE);
+    assert_eq!(names.len(), manifest.files + 1);
+    assert!(names.iter().any(|n| n == "node_cache.json") && !names.iter().any(|n| n == ".lock"), "{names:?}");
+
+    // Changes after the snapshot are undone by the restore
+    storage.delete_message("general", &id).await.unwrap();
+    storage.register_user("mallory", "password789", None).await.unwrap();
+    drop(storage);
+    let report = storage::restore(&data_dir, &archive, None).unwrap();
+    assert!(report.records >= 7, "{report:?}");
+    let previous = report.previous.expect("old tree kept");
+    assert!(previous.join("users").exists());
+
+    let storage = Storage::new(&data_dir).await.unwrap();
+    let msg = storage.get_message("general", &id).await.unwrap().expect("message restored");
+    assert_eq!(msg.replies.len(), 1);
+    assert!(storage.get_user("mallory").await.unwrap().is_none());
+    assert!(storage.verify_user_password("alice", "password123").await.unwrap().1);
+    assert_eq!(storage.list_mail("bob").await.unwrap()[0].content, "Bring the yagi");
+    assert_eq!(storage.get_admin_audit_page(1, 10).await.unwrap()[0].action, "PROMOTE");
+}
+
+#[tokio::test]
+async fn corrupt_records_are_refused_before_the_swap() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut storage = Storage::new(&data_dir).await.unwrap();
+    let id = populate(&mut storage).await;
+    let message_file = Path::new(&data_dir).join("messages").join("general").join(format!("{id}.json"));
+    std::fs::write(&message_file, "{\"id\": \"half-writ").unwrap();
+    let archive = tmp.path().join("bad.tar.gz");
+    storage::backup(&data_dir, BackendKind::Json, &archive).unwrap();
+    std::fs::write(&message_file, "current").unwrap();
+
+    let err = storage::restore(&data_dir, &archive, None).unwrap_err().to_string();
+    assert!(err.contains(&format!("messages/general/{id}.json")), "{err}");
+    // Nothing swapped and no staging left behind
+    assert_eq!(std::fs::read_to_string(&message_file).unwrap(), "current");
+    let siblings: Vec<_> = std::fs::read_dir(tmp.path()).unwrap().flatten().map(|e| e.file_name()).collect();
+    assert_eq!(siblings.len(), 2, "{siblings:?}");
+
+    std::fs::write(tmp.path().join("junk.tar.gz"), b"not an archive").unwrap();
+    assert!(storage::restore(&data_dir, &tmp.path().join("junk.tar.gz"), None).is_err());
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn encrypted_sqlite_backup_needs_its_key() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let enc = meshbbs::config::EncryptionConfig { passphrase: Some("correct horse battery".into()), keyfile: None };
+    let cipher = storage::open_data_key(&data_dir, BackendKind::Sqlite, Some(&enc)).unwrap();
+    let mut storage = Storage::open(&data_dir, BackendKind::Sqlite, None, cipher.clone()).await.unwrap();
+    let id = populate(&mut storage).await;
+
+    let archive = tmp.path().join("enc.tar.gz");
+    let manifest = storage::backup(&data_dir, BackendKind::Sqlite, &archive).unwrap();
+    assert!(manifest.encrypted && manifest.backend == BackendKind::Sqlite);
+    let raw = std::fs::read(&archive).unwrap();
+    let mut plain = Vec::new();
+    std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(raw.as_slice()), &mut plain).unwrap();
+    assert!(!plain.windows(9).any(|w| w == b"Field day"), "records stay sealed in the archive");
+    storage.delete_message("general", &id).await.unwrap();
+    drop(storage);
+
+    let err = storage::restore(&data_dir, &archive, None).unwrap_err().to_string();
+    assert!(err.contains("encrypted"), "{err}");
+    let wrong = storage::KeySource::Passphrase("not the passphrase".into());
+    assert!(storage::restore(&data_dir, &archive, Some(&wrong)).is_err());
+
+    let key = storage::KeySource::from_config(&enc).unwrap();
+    storage::restore(&data_dir, &archive, Some(&key)).unwrap();
+    let cipher = storage::open_data_key(&data_dir, BackendKind::Sqlite, Some(&enc)).unwrap();
+    let storage = Storage::open(&data_dir, BackendKind::Sqlite, None, cipher).await.unwrap();
+    assert_eq!(storage.get_message("general", &id).await.unwrap().unwrap().content, "Field day on Saturday");
+}
+
+#[tokio::test]
+async fn scheduled_backups_keep_the_newest() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut storage = Storage::new(&data_dir).await.unwrap();
+    populate(&mut storage).await;
+    let dir = tmp.path().join("backups");
+    let start = Utc::now();
+    let paths: Vec<_> = (0..3)
+        .map(|day| storage::scheduled_backup(&data_dir, BackendKind::Json, &dir, 2, start + Duration::days(day)).unwrap())
+        .collect();
+    let mut left: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().map(|e| e.path()).collect();
+    left.sort();
+    assert_eq!(left, paths[1..]);
+}
@@ -0,0 +1,115 @@
+//! `meshbbs fsck`: every kind of damage is reported without touching anything, and
+//! `--repair` quarantines bad files and fixes topics, locks and legacy replies.
+
+use meshbbs::storage::{self, BackendKind, Problem, ReplyEntry, Storage};
+
+fn problems(report: &storage::FsckReport) -> Vec<(Problem, String)> {
+    let mut found: Vec<(Problem, String)> = report.issues.iter().map(|i| (i.problem, i.location.clone())).collect();
+    found.sort_by(|a, b| a.1.cmp(&b.1));
+    found
+}
+
+#[tokio::test]
+async fn json_tree_problems_are_found_and_repaired() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let root = tmp.path();
+    let mut storage = Storage::new(&data_dir).await.unwrap();
+    storage.create_topic("general", "General", "", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("radio", "Radio", "", 0, 0, "sysop").await.unwrap();
+    storage.create_subtopic("antennas", "radio", "Antennas", "", 0, 0, "sysop").await.unwrap();
+    storage.register_user("alice", "password123", None).await.unwrap();
+    let good = storage.store_message("general", "alice", "All fine here").await.unwrap();
+    let legacy = storage.store_message("general", "alice", "Old thread").await.unwrap();
+    drop(storage);
+
+    let general = root.join("messages/general");
+    let legacy_file = general.join(format!("{legacy}.json"));
+    let mut msg: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&legacy_file).unwrap()).unwrap();
+    msg["replies"] = serde_json::json!(["bob: nice one", "anonymous remark"]);
+    std::fs::write(&legacy_file, msg.to_string()).unwrap();
+    let misplaced = "00000000-0000-4000-8000-000000000001";
+    std::fs::copy(general.join(format!("{good}.json")), general.join(format!("{misplaced}.json"))).unwrap();
+    std::fs::write(general.join("00000000-0000-4000-8000-000000000002.json"), "{\"id\": ").unwrap();
+    std::fs::write(general.join("00000000-0000-4000-8000-000000000003.json"), vec![b' '; 1_100_000]).unwrap();
+    std::fs::create_dir_all(root.join("messages/oldtopic")).unwrap();
+    std::fs::write(root.join("messages/oldtopic/x.json"), "{}").unwrap();
+    let mut user: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(root.join("users/alice.json")).unwrap()).unwrap();
+    user["username"] = "root".into();
+    std::fs::write(root.join("users/root.json"), user.to_string()).unwrap();
+    let backend = storage::open_backend(&data_dir, BackendKind::Json, None).unwrap();
+    backend.save_locked_topics(&["ghost".to_string()].into_iter().collect()).unwrap();
+    let mut topics = backend.load_topics().unwrap();
+    topics.topics.remove("radio");
+    backend.save_topics(&topics).unwrap();
+
+    let report = storage::fsck(&data_dir, BackendKind::Json, None, false).unwrap();
+    let mut expected = vec![
+        (Problem::NameMismatch, format!("messages/general/{misplaced}.json")),
+        (Problem::Unparseable, "messages/general/00000000-0000-4000-8000-000000000002.json".to_string()),
+        (Problem::Oversized, "messages/general/00000000-0000-4000-8000-000000000003.json".to_string()),
+        (Problem::LegacyReplies, format!("messages/general/{legacy}.json")),
+        (Problem::OrphanTopicDir, "messages/oldtopic".to_string()),
+        (Problem::OrphanTopicDir, "messages/radio".to_string()),
+        (Problem::DanglingParent, "topic:antennas".to_string()),
+        (Problem::StaleLock, "topic:ghost".to_string()),
+        (Problem::InvalidUsername, "users/root.json".to_string()),
+    ];
+    expected.sort_by(|a, b| a.1.cmp(&b.1));
+    assert_eq!(problems(&report), expected, "{:#?}", report.issues);
+    assert_eq!(report.outstanding().count(), expected.len());
+    assert!(root.join("messages/oldtopic").exists() && report.quarantine.is_none(), "check only changes nothing");
+
+    let report = storage::fsck(&data_dir, BackendKind::Json, None, true).unwrap();
+    assert_eq!(report.outstanding().count(), 0, "{:#?}", report.issues);
+    let quarantine = report.quarantine.clone().unwrap();
+    assert!(quarantine.join("messages/oldtopic/x.json").exists());
+    assert!(quarantine.join(format!("messages/general/{misplaced}.json")).exists());
+    assert!(quarantine.join("users/root.json").exists() && !root.join("users/root.json").exists());
+
+    let again = storage::fsck(&data_dir, BackendKind::Json, None, false).unwrap();
+    assert!(again.issues.is_empty(), "{:#?}", again.issues);
+
+    let storage = Storage::new(&data_dir).await.unwrap();
+    assert_eq!(storage.get_topic_config("antennas").unwrap().parent, None);
+    assert!(!storage.is_topic_locked("ghost"));
+    let replies = storage.get_message("general", &legacy).await.unwrap().unwrap().replies;
+    let authors: Vec<(String, String)> = replies.iter().map(|r| match r {
+        ReplyEntry::Reply(r) => (r.author.clone(), r.content.clone()),
+        ReplyEntry::Legacy(s) => panic!("still legacy: {s}"),
+    }).collect();
+    assert_eq!(authors, vec![("bob".to_string(), "nice one".to_string()), (storage::LEGACY_REPLY_AUTHOR.to_string(), "anonymous remark".to_string())]);
+    assert_eq!(storage.get_messages("general", 10).await.unwrap().len(), 2);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_topics_locks_and_replies_are_repaired() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let mut storage = Storage::open(&data_dir, BackendKind::Sqlite, None, None).await.unwrap();
+    storage.create_topic("radio", "Radio", "", 0, 0, "sysop").await.unwrap();
+    storage.create_subtopic("antennas", "radio", "Antennas", "", 0, 0, "sysop").await.unwrap();
+    let id = storage.store_message("antennas", "alice", "Yagi").await.unwrap();
+    drop(storage);
+
+    let backend = storage::open_backend(&data_dir, BackendKind::Sqlite, None).unwrap();
+    let mut msg = backend.get_message("antennas", &id).unwrap().unwrap();
+    msg.replies.push(ReplyEntry::Legacy("carol: which coax?".into()));
+    backend.update_message(&msg).unwrap();
+    let mut topics = backend.load_topics().unwrap();
+    topics.topics.remove("radio");
+    backend.save_topics(&topics).unwrap();
+    backend.save_locked_topics(&["radio".to_string()].into_iter().collect()).unwrap();
+
+    let report = storage::fsck(&data_dir, BackendKind::Sqlite, None, true).unwrap();
+    let found: Vec<Problem> = report.issues.iter().map(|i| i.problem).collect();
+    assert_eq!(found, vec![Problem::DanglingParent, Problem::StaleLock, Problem::LegacyReplies]);
+    assert!(report.checked > 0 && report.outstanding().next().is_none());
+    assert!(storage::fsck(&data_dir, BackendKind::Sqlite, None, false).unwrap().issues.is_empty());
+    match &backend.get_message("antennas", &id).unwrap().unwrap().replies[0] {
+        ReplyEntry::Reply(r) => assert_eq!((r.author.as_str(), r.content.as_str()), ("carol", "which coax?")),
+        ReplyEntry::Legacy(s) => panic!("still legacy: {s}"),
+    }
+    assert!(!std::path::Path::new(&data_dir).join("quarantine").exists());
+}
@@ -12,7 +12,7 @@ async fn eightball_broadcasts_and_not_dm() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 4242u32;
-    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into() };
+    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into(), ..Default::default() };
     server.route_text_event(public_evt).await.expect("route public 8ball");
 
     // Inspect recorded outbound messages
@@ -30,8 +30,8 @@ async fn eightball_respects_cooldown() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 777u32;
-    let evt1 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into() };
-    let evt2 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into() };
+    let evt1 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into(), ..Default::default() };
+    let evt2 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^8BALL".into(), ..Default::default() };
     server.route_text_event(evt1).await.expect("first 8ball");
     server.route_text_event(evt2).await.expect("second 8ball (cooldown)");
 
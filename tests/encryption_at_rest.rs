@@ -0,0 +1,229 @@
+//! At-rest encryption: nothing readable reaches the data directory (retention archives
+//! included), the wrong key is rejected, and `rekey` moves a tree between plaintext and
+//! encrypted (and between keys) without losing anything.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, EncryptionConfig, SecurityConfig};
+use meshbbs::storage::{self, BackendKind, KeySource, Storage, TopicRetention};
+use std::io::Read;
+use std::path::Path;
+
+fn passphrase(p: &str) -> EncryptionConfig {
+    EncryptionConfig { passphrase: Some(p.to_string()), keyfile: None }
+}
+
+async fn open(data_dir: &str, kind: BackendKind, enc: Option<&EncryptionConfig>) -> anyhow::Result<Storage> {
+    let cipher = storage::open_data_key(data_dir, kind, enc)?;
+    Storage::open(data_dir, kind, None, cipher).await
+}
+
+async fn populate(storage: &mut Storage) {
+    storage.create_topic("general", "General", "General talk", 0, 0, "sysop").await.unwrap();
+    storage.register_user("alice", "password123", Some("1234")).await.unwrap();
+    let id = storage.store_message("general", "alice", "secret rendezvous at dawn").await.unwrap();
+    storage.append_reply("general", &id, "alice", "bring the antenna").await.unwrap();
+    storage.delete_message("general", &id).await.unwrap();
+    storage.append_deletion_audit("general", &id, "alice").await.unwrap();
+    storage.store_message("general", "alice", "secret rendezvous moved to noon").await.unwrap();
+    storage.update_user_level("alice", 5, "sysop").await.unwrap();
+}
+
+async fn assert_intact(storage: &Storage) {
+    let msgs = storage.get_messages("general", 10).await.unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].content, "secret rendezvous moved to noon");
+    assert!(storage.verify_user_password("alice", "password123").await.unwrap().1);
+    assert_eq!(storage.get_user("alice").await.unwrap().unwrap().node_id.as_deref(), Some("1234"));
+    assert_eq!(storage.get_deletion_audit_page(1, 10).await.unwrap().len(), 1);
+    assert_eq!(storage.get_admin_audit_page(1, 10).await.unwrap()[0].action, "PROMOTE");
+}
+
+/// True if any file under `dir` contains `needle`.
+fn tree_contains(dir: &Path, needle: &[u8]) -> bool {
+    std::fs::read_dir(dir).unwrap().flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            tree_contains(&path, needle)
+        } else {
+            std::fs::read(&path).unwrap().windows(needle.len()).any(|w| w == needle)
+        }
+    })
+}
+
+async fn fresh_encrypted_dir(kind: BackendKind) {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let enc = passphrase("correct horse battery");
+    let mut storage = open(&data_dir, kind, Some(&enc)).await.unwrap();
+    populate(&mut storage).await;
+    drop(storage);
+
+    assert!(tmp.path().join(storage::HEADER_FILE).exists());
+    for needle in ["rendezvous", "$argon2", "1234", "PROMOTE", "General talk"] {
+        assert!(!tree_contains(tmp.path(), needle.as_bytes()), "{kind}: '{needle}' found in plaintext");
+    }
+
+    let storage = open(&data_dir, kind, Some(&enc)).await.unwrap();
+    assert_intact(&storage).await;
+    drop(storage);
+
+    let err = open(&data_dir, kind, Some(&passphrase("wrong horse battery"))).await.err().unwrap();
+    assert!(err.to_string().contains("key mismatch"), "{err}");
+    let err = open(&data_dir, kind, None).await.err().unwrap();
+    assert!(err.to_string().contains("is encrypted"), "{err}");
+}
+
+#[tokio::test]
+async fn json_data_is_sealed() {
+    fresh_encrypted_dir(BackendKind::Json).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_data_is_sealed() {
+    fresh_encrypted_dir(BackendKind::Sqlite).await;
+}
+
+#[tokio::test]
+async fn rekey_encrypts_rotates_and_decrypts_in_place() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let kind = BackendKind::Json;
+    let mut storage = open(&data_dir, kind, None).await.unwrap();
+    populate(&mut storage).await;
+    drop(storage);
+    assert!(tree_contains(tmp.path(), b"rendezvous"));
+
+    // Configuring a key on an existing plaintext tree is refused until rekey runs
+    let first = passphrase("first passphrase");
+    let err = open(&data_dir, kind, Some(&first)).await.err().unwrap();
+    assert!(err.to_string().contains("meshbbs rekey"), "{err}");
+
+    let first_key = KeySource::from_config(&first).unwrap();
+    let report = storage::rekey(&data_dir, kind, None, Some(&first_key)).unwrap();
+    assert!(report.encrypted);
+    // topics.json, one user, one message, one line in each audit log
+    assert_eq!(report.records, 5);
+    assert!(!tree_contains(tmp.path(), b"rendezvous"));
+    assert_intact(&open(&data_dir, kind, Some(&first)).await.unwrap()).await;
+
+    // Rotate to a keyfile
+    let keyfile = tmp.path().with_extension("key");
+    std::fs::write(&keyfile, b"0123456789abcdef0123456789abcdef").unwrap();
+    let second = EncryptionConfig { passphrase: None, keyfile: Some(keyfile.to_string_lossy().to_string()) };
+    let second_key = KeySource::from_config(&second).unwrap();
+    assert!(storage::rekey(&data_dir, kind, None, Some(&second_key)).is_err(), "current key is required");
+    storage::rekey(&data_dir, kind, Some(&first_key), Some(&second_key)).unwrap();
+    assert!(open(&data_dir, kind, Some(&first)).await.is_err());
+    assert_intact(&open(&data_dir, kind, Some(&second)).await.unwrap()).await;
+
+    // And back to plaintext
+    let report = storage::rekey(&data_dir, kind, Some(&second_key), None).unwrap();
+    assert!(!report.encrypted);
+    assert!(!tmp.path().join(storage::HEADER_FILE).exists());
+    assert_intact(&open(&data_dir, kind, None).await.unwrap()).await;
+    std::fs::remove_file(keyfile).unwrap();
+}
+
+#[tokio::test]
+async fn interrupted_rekey_only_resumes_with_the_same_key() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let kind = BackendKind::Json;
+    let mut storage = open(&data_dir, kind, None).await.unwrap();
+    populate(&mut storage).await;
+    drop(storage);
+    let user_file = tmp.path().join("users").join("alice.json");
+    let plain_user = std::fs::read(&user_file).unwrap();
+
+    // Stage the state a run killed partway leaves: the pending header, no header yet, and
+    // records sealed under the new key next to one still in plaintext
+    let first = passphrase("first passphrase");
+    let first_key = KeySource::from_config(&first).unwrap();
+    storage::rekey(&data_dir, kind, None, Some(&first_key)).unwrap();
+    let header = tmp.path().join(storage::HEADER_FILE);
+    let pending = tmp.path().join(format!("{}.pending", storage::HEADER_FILE));
+    std::fs::rename(&header, &pending).unwrap();
+    std::fs::write(&user_file, plain_user).unwrap();
+    let staged = std::fs::read(&pending).unwrap();
+
+    let other = KeySource::Passphrase("some other passphrase".into());
+    let err = storage::rekey(&data_dir, kind, None, Some(&other)).unwrap_err().to_string();
+    assert!(err.contains("run it again with that key"), "{err}");
+    let err = storage::rekey(&data_dir, kind, None, None).unwrap_err().to_string();
+    assert!(err.contains("run it again with that key"), "{err}");
+    assert_eq!(std::fs::read(&pending).unwrap(), staged, "staged header must not be replaced");
+
+    storage::rekey(&data_dir, kind, None, Some(&first_key)).unwrap();
+    assert!(!pending.exists());
+    assert!(!tree_contains(tmp.path(), b"rendezvous"));
+    assert_intact(&open(&data_dir, kind, Some(&first)).await.unwrap()).await;
+}
+
+/// Decompressed text of a retention archive.
+fn gunzip(path: &Path) -> String {
+    let mut text = String::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(path).unwrap()).read_to_string(&mut text).unwrap();
+    text
+}
+
+#[tokio::test]
+async fn retention_archives_are_sealed() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let kind = BackendKind::Json;
+    let enc = passphrase("archive passphrase");
+    let mut storage = open(&data_dir, kind, Some(&enc)).await.unwrap();
+    populate(&mut storage).await;
+    storage.set_topic_retention("general", Some(TopicRetention { max_age_days: Some(1), ..Default::default() })).await.unwrap();
+    let report = storage.apply_retention(chrono::Utc::now() + chrono::Duration::days(2), true).await.unwrap();
+    drop(storage);
+    let archive = &report.archives[0];
+
+    let text = gunzip(archive);
+    assert!(!text.contains("rendezvous"), "archive holds plaintext: {text}");
+    assert!(text.lines().all(|l| l.starts_with("enc1:")));
+    let cipher = storage::open_data_key(&data_dir, kind, Some(&enc)).unwrap();
+    let archived = storage::read_archive(archive, cipher.clone()).unwrap();
+    assert_eq!(archived[0].content, "secret rendezvous moved to noon");
+    assert!(storage::read_archive(archive, None).is_err());
+
+    // fsck and restore unseal it; one that cannot be read is reported
+    assert!(storage::fsck(&data_dir, kind, cipher.clone(), false).unwrap().issues.is_empty());
+    let key = KeySource::from_config(&enc).unwrap();
+    let backup = tmp.path().join("backup.tar.gz");
+    storage::backup(&data_dir, kind, &backup).unwrap();
+    storage::restore(&data_dir, &backup, Some(&key)).unwrap();
+    let broken = archive.with_file_name("general-broken.jsonl.gz");
+    std::fs::write(&broken, b"not gzip").unwrap();
+    let issues = storage::fsck(&data_dir, kind, cipher, false).unwrap().issues;
+    assert_eq!(issues.len(), 1, "{issues:?}");
+    assert_eq!((issues[0].problem, issues[0].location.as_str()), (storage::Problem::Unparseable, "archive/general-broken.jsonl.gz"));
+    std::fs::remove_file(&broken).unwrap();
+
+    // Decrypting the tree opens the archive again
+    storage::rekey(&data_dir, kind, Some(&key), None).unwrap();
+    assert!(gunzip(archive).contains("secret rendezvous moved to noon"));
+    assert_eq!(storage::read_archive(archive, None).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn server_starts_on_encrypted_dir() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.security = Some(SecurityConfig { argon2: None, encryption: Some(passphrase("server passphrase")), ..Default::default() });
+
+    let mut server = BbsServer::new(cfg.clone()).await.unwrap();
+    server.test_register("carol", "Password123").await.unwrap();
+    server.route_test_text_direct("4321", "LOGIN carol Password123").await.unwrap();
+    drop(server);
+    assert!(!tree_contains(&tmp.path().join("data"), b"4321"));
+
+    let server = BbsServer::new(cfg.clone()).await.unwrap();
+    assert_eq!(server.get_user("carol").await.unwrap().unwrap().node_id.as_deref(), Some("4321"));
+    drop(server);
+
+    cfg.security = None;
+    assert!(BbsServer::new(cfg).await.is_err());
+}
@@ -10,7 +10,7 @@ async fn first_help_no_banner() {
     let mut server = BbsServer::new(cfg).await.unwrap();
 
     let node: u32 = 0x12345678;
-    let help_event = TextEvent { source: node, dest: None, is_direct: true, channel: None, content: "HELP".into() };
+    let help_event = TextEvent { source: node, dest: None, is_direct: true, channel: None, content: "HELP".into(), ..Default::default() };
     server.route_text_event(help_event).await.unwrap();
 
     // Collect messages for this node
@@ -24,11 +24,12 @@ async fn compact_flow_topics_threads_read_compose_reply() {
         .await
         .expect("create topic");
 
+    server.test_register("alice", "password123").await.unwrap();
     let node = "n1";
 
     // Login
     server
-        .route_test_text_direct(node, "LOGIN alice")
+        .route_test_text_direct(node, "LOGIN alice password123")
         .await
         .expect("login");
     let m = last_for_node(server.test_messages(), node).expect("welcome msg");
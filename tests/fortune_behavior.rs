@@ -12,7 +12,7 @@ async fn fortune_broadcasts_and_not_dm() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 4242u32;
-    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into() };
+    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into(), ..Default::default() };
     server.route_text_event(public_evt).await.expect("route public fortune");
 
     // Inspect recorded outbound messages
@@ -30,8 +30,8 @@ async fn fortune_respects_cooldown() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 1234567890u32;
-    let evt1 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into() };
-    let evt2 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into() };
+    let evt1 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into(), ..Default::default() };
+    let evt2 = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^FORTUNE".into(), ..Default::default() };
 
     server.route_text_event(evt1).await.expect("first fortune");
     server.route_text_event(evt2).await.expect("second fortune"); // Second call should be rate limited
@@ -14,10 +14,10 @@ async fn help_after_login() {
 
     // Use a unique username each run to avoid collision with existing test data
     let uname = format!("tuh_{}", &uuid::Uuid::new_v4().simple().to_string()[..12]);
-    let dm_register = TextEvent { source: 77, dest: Some(1), is_direct: true, channel: None, content: format!("REGISTER {} testpass1", uname) };
+    let dm_register = TextEvent { source: 77, dest: Some(1), is_direct: true, channel: None, content: format!("REGISTER {} testpass1", uname), ..Default::default() };
     server.route_text_event(dm_register).await.expect("register");
     // Issue HELP
-    let dm_help = TextEvent { source: 77, dest: Some(1), is_direct: true, channel: None, content: "HELP".into() };
+    let dm_help = TextEvent { source: 77, dest: Some(1), is_direct: true, channel: None, content: "HELP".into(), ..Default::default() };
     server.route_text_event(dm_help).await.expect("help");
 
     // Find last message containing Commands:
@@ -35,7 +35,7 @@ async fn help_broadcast_is_delayed() {
         });
 
         // Craft a HELP public TextEvent from node id 1234
-    let ev = TextEvent { source: 1234, dest: None, is_direct: false, channel: Some(0), content: "^HELP".to_string() };
+    let ev = TextEvent { source: 1234, dest: None, is_direct: false, channel: Some(0), content: "^HELP".to_string(), ..Default::default() };
         server.route_text_event(ev).await.expect("route help");
 
         // Allow a brief time for immediate DM queue (writer queue entry)
@@ -11,7 +11,7 @@ async fn help_broadcasts_public_commands() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 4242u32;
-    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^HELP".into() };
+    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^HELP".into(), ..Default::default() };
     server.route_text_event(public_evt).await.expect("route public help");
 
     // Inspect recorded outbound messages
@@ -55,7 +55,7 @@ async fn help_dm_and_broadcast_content() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 1337u32;
-    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^help".into() };
+    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^help".into(), ..Default::default() };
     server.route_text_event(public_evt).await.expect("route public help lowercase");
 
     let msgs = server.test_messages();
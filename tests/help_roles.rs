@@ -39,4 +39,5 @@ async fn help_moderator_and_sysop() {
     let sys_help = meshbbs::bbs::commands::CommandProcessor::new().process(&mut sys_session, "?", &mut storage, &cfg).await.unwrap();
     assert!(sys_help.contains("ADM:"), "sysop help missing ADM section");
     assert!(sys_help.contains("PROMOTE"), "sysop help should list PROMOTE");
+    assert!(sys_help.len() <= 230 && sys_help.ends_with("OTHER: MAIL | WHERE | U | Q\n"), "sysop help must fit one frame unclipped: {sys_help}");
 }
@@ -29,19 +29,19 @@ async fn public_login_then_dm_session_inline_commands() {
 
     // Simulate a public LOGIN (would normally arrive via TextEvent)
     use meshbbs::meshtastic::TextEvent; // re-export not present, path adjust if needed
-    let public_event = TextEvent { source: 123, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into() };
+    let public_event = TextEvent { source: 123, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into(), ..Default::default() };
     server.route_text_event(public_event).await.expect("public login");
 
     // Now simulate DM message to trigger session creation and finalize login
-    let dm_event = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "READ".into() };
+    let dm_event = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "READ".into(), ..Default::default() };
     server.route_text_event(dm_event).await.expect("dm read");
 
     // Post a message inline
-    let dm_post = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "POST Hello world from inline".into() };
+    let dm_post = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "POST Hello world from inline".into(), ..Default::default() };
     server.route_text_event(dm_post).await.expect("dm post");
 
     // Read again to confirm (basic success path; deeper assertions would require exposing responses)
-    let dm_read2 = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "READ".into() };
+    let dm_read2 = TextEvent { source: 123, dest: Some(999), is_direct: true, channel: None, content: "READ".into(), ..Default::default() };
     server.route_text_event(dm_read2).await.expect("dm read2");
 
     // At this stage we at least validated no panics and state transitions executed.
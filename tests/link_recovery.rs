@@ -0,0 +1,120 @@
+#![cfg(feature = "meshtastic-proto")]
+//! Radio link supervision against the simulated radio: device loss, reconnect with
+//! backoff, config handshake re-run and no loss of queued outbound traffic.
+
+use std::time::Duration;
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::meshtastic::link::{LinkState, LinkStatus, LinkSupervisor};
+use meshbbs::meshtastic::sim::{self, SimConfig};
+use meshbbs::meshtastic::{
+    create_reader_writer_system_with_transport, ControlMessage, MessagePriority, OutgoingKind, OutgoingMessage,
+    WriterTuning,
+};
+
+const WAIT: Duration = Duration::from_secs(20);
+
+async fn wait_for_state(data_dir: &str, state: LinkState) -> LinkStatus {
+    let deadline = tokio::time::Instant::now() + WAIT;
+    loop {
+        if let Some(status) = LinkStatus::load(data_dir) {
+            if status.state == state { return status; }
+        }
+        assert!(tokio::time::Instant::now() < deadline, "link never reached {:?}", state);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn held_messages_are_sent_after_reconnect() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let (_, mut sim) = sim::start(SimConfig::with_nodes(1));
+    let mut opener = sim.opener();
+    let transport = std::sync::Arc::new(std::sync::Mutex::new(opener().await.unwrap()));
+    let (reader, writer, _text_rx, outgoing_tx, reader_ctrl, writer_ctrl) =
+        create_reader_writer_system_with_transport(transport.clone(), WriterTuning::default()).await.unwrap();
+    let status = std::sync::Arc::new(std::sync::Mutex::new(LinkStatus::new("sim", LinkState::Connected)));
+    let supervisor = LinkSupervisor::new(transport, opener, status.clone(), reader_ctrl.clone(), writer_ctrl.clone(), Some(data_dir.clone()));
+    tokio::spawn(reader.run());
+    tokio::spawn(writer.run());
+    tokio::spawn(supervisor.run());
+    sim.wait_configured(WAIT).await.unwrap();
+
+    sim.unplug();
+    let down = wait_for_state(&data_dir, LinkState::Reconnecting).await;
+    assert!(down.last_error.unwrap().contains("unplugged"));
+
+    // Queued while the device is gone: must survive the outage
+    outgoing_tx.send(OutgoingMessage {
+        to_node: None,
+        channel: 0,
+        content: "still here".into(),
+        priority: MessagePriority::Normal,
+        kind: OutgoingKind::Normal,
+        request_ack: false,
+        durable: false,
+    }).unwrap();
+    assert!(sim.recv(Duration::from_millis(1500)).await.is_none());
+
+    sim.plug();
+    let delivered = sim.expect(None, "still here", WAIT).await.unwrap();
+    assert_eq!(delivered.from, sim.our_node());
+    let up = status.lock().unwrap().clone();
+    assert_eq!(up.state, LinkState::Connected);
+    assert_eq!(up.reconnects, 1);
+    assert_eq!(sim.stats().handshakes, 2, "config handshake re-run after reconnect");
+
+    let _ = reader_ctrl.send(ControlMessage::Shutdown);
+    let _ = writer_ctrl.send(ControlMessage::Shutdown);
+}
+
+#[tokio::test]
+async fn server_starts_without_device_and_recovers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let data_dir = cfg.storage.data_dir.clone();
+    let (_, mut sim) = sim::start(SimConfig::with_nodes(1));
+    sim.unplug();
+
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.connect_supervised("sim://radio", sim.opener()).await.expect("missing device is not fatal");
+    let status = server.link_status().unwrap();
+    assert_eq!(status.state, LinkState::Reconnecting);
+    assert_eq!(status.target, "sim://radio");
+
+    // Sysop dashboard shows the link
+    server.test_register("mod", "Password123").await.unwrap();
+    server.test_update_level("mod", 5).await.unwrap();
+    server.route_test_text_direct("1234", "LOGIN mod Password123").await.unwrap();
+    server.route_test_text_direct("1234", "ADMIN").await.unwrap();
+    let dashboard = server.test_messages().iter().rev().find(|(to, _)| to == "1234").unwrap().1.clone();
+    assert!(dashboard.contains("Radio Link: DOWN, reconnecting to sim://radio"), "{}", dashboard);
+
+    let drive = async {
+        sim.plug();
+        sim.wait_configured(WAIT).await?;
+        let up = wait_for_state(&data_dir, LinkState::Connected).await;
+        assert_eq!(up.reconnects, 1);
+
+        let node = sim.node(1).unwrap();
+        sim.send_dm(node, "REGISTER erin password123")?;
+        sim.expect(Some(node), "Registered as erin", WAIT).await?;
+
+        // Lost again mid-session: the session carries on after the second reconnect
+        sim.unplug();
+        wait_for_state(&data_dir, LinkState::Reconnecting).await;
+        sim.plug();
+        sim.wait_handshakes(2, WAIT).await?;
+        sim.send_dm(node, "M")?;
+        sim.expect(Some(node), "Topics", WAIT).await?;
+        anyhow::Ok(())
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = drive => res.unwrap(),
+    }
+    assert_eq!(server.link_status().unwrap().reconnects, 2);
+}
@@ -0,0 +1,127 @@
+//! Failed-login lockout: per-account and per-node counts survive a restart, lockouts double
+//! up to the cap, sysops are told and can lift them with UNLOCKUSER.
+
+use argon2::Argon2;
+use chrono::{Duration, Utc};
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, LockoutConfig, SecurityConfig};
+use meshbbs::storage::{BackendKind, LockoutTarget, Storage};
+use password_hash::{PasswordHasher, SaltString};
+
+async fn escalation(backend: BackendKind) {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().to_string_lossy().to_string();
+    let storage = Storage::open(&dir, backend, None, None).await.unwrap();
+    let policy = LockoutConfig { max_attempts: 3, lockout_minutes: 5, max_lockout_minutes: 15, ..Default::default() };
+    let mut now = Utc::now();
+
+    for _ in 0..2 {
+        assert!(storage.record_login_failure(Some("alice"), "1234", &policy, now).await.unwrap().is_empty());
+    }
+    let started = storage.record_login_failure(Some("alice"), "1234", &policy, now).await.unwrap();
+    let targets: Vec<(LockoutTarget, u64)> = started.iter().map(|l| (l.target.clone(), l.minutes)).collect();
+    assert_eq!(targets, [(LockoutTarget::Account("alice".into()), 5), (LockoutTarget::Node("1234".into()), 5)]);
+    assert_eq!(storage.login_lockout(Some("alice"), "9999", &policy, now).await.unwrap(), Some(now + Duration::minutes(5)));
+    assert!(storage.login_lockout(Some("bob"), "9999", &policy, now).await.unwrap().is_none());
+    drop(storage);
+
+    // Counts persist; each further lockout doubles, up to the cap
+    let storage = Storage::open(&dir, backend, None, None).await.unwrap();
+    assert!(storage.login_lockout(None, "1234", &policy, now).await.unwrap().is_some());
+    for expected in [10, 15, 15] {
+        now += Duration::minutes(20);
+        assert!(storage.login_lockout(Some("alice"), "1234", &policy, now).await.unwrap().is_none());
+        let mut started = Vec::new();
+        for _ in 0..3 {
+            started = storage.record_login_failure(Some("alice"), "5678", &policy, now).await.unwrap();
+        }
+        assert_eq!(started[0].minutes, expected);
+        assert_eq!(started[1].minutes, 5, "a new node starts at the base time");
+        storage.clear_lockout("5678").await.unwrap();
+    }
+
+    // A quiet hour after the lockout ends starts the account over
+    now += Duration::minutes(15 + 60);
+    for _ in 0..3 {
+        storage.record_login_failure(Some("alice"), "4321", &policy, now).await.unwrap();
+    }
+    assert_eq!(storage.login_lockout(Some("alice"), "0", &policy, now).await.unwrap(), Some(now + Duration::minutes(5)));
+
+    let cleared = storage.clear_lockout("ALICE").await.unwrap();
+    assert_eq!(cleared, [LockoutTarget::Account("alice".into())]);
+    assert!(storage.login_lockout(Some("alice"), "0", &policy, now).await.unwrap().is_none());
+    assert!(storage.clear_lockout("alice").await.unwrap().is_empty());
+
+    let off = LockoutConfig { enabled: false, ..policy };
+    assert!(storage.login_lockout(None, "4321", &off, now).await.unwrap().is_none());
+    assert!(storage.record_login_failure(None, "4321", &off, now).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn lockouts_escalate_and_persist_json() {
+    escalation(BackendKind::Json).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn lockouts_escalate_and_persist_sqlite() {
+    escalation(BackendKind::Sqlite).await;
+}
+
+#[tokio::test]
+async fn brute_force_over_dm_is_locked_out_and_sysop_unlocks() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    cfg.security = Some(SecurityConfig { lockout: LockoutConfig { max_attempts: 3, ..Default::default() }, ..Default::default() });
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    server.test_register("alice", "Password123").await.unwrap();
+    server.route_test_text_direct("1001", "LOGIN sysop Sysop#Pass1").await.unwrap();
+    let reply_to = |server: &BbsServer, node: &str| {
+        server.test_messages().iter().rev().find(|(to, _)| to == node).map(|(_, m)| m.clone()).unwrap()
+    };
+
+    for _ in 0..2 {
+        server.route_test_text_direct("2002", "LOGIN alice wrong-guess").await.unwrap();
+        assert!(reply_to(&server, "2002").starts_with("Invalid password.\n"));
+    }
+    server.route_test_text_direct("2002", "LOGIN alice wrong-guess").await.unwrap();
+    assert!(reply_to(&server, "2002").starts_with("Invalid password. Locked for 5 min.\n"));
+    let notice = reply_to(&server, "1001");
+    assert!(notice.starts_with("[BBS] Failed logins: account alice, node 2002 locked 5 min"), "{notice}");
+
+    // The right password no longer helps, from this node or another
+    server.route_test_text_direct("2002", "LOGIN alice Password123").await.unwrap();
+    assert!(reply_to(&server, "2002").starts_with("Too many failed logins. Try again in 5 min.\n"));
+    server.route_test_text_direct("3003", "LOGIN alice Password123").await.unwrap();
+    assert!(reply_to(&server, "3003").starts_with("Too many failed logins"));
+
+    server.route_test_text_direct("2002", "UNLOCKUSER alice").await.unwrap();
+    assert!(reply_to(&server, "2002").starts_with("Permission denied.\n"));
+    server.route_test_text_direct("1001", "UNLOCKUSER").await.unwrap();
+    assert!(reply_to(&server, "1001").starts_with("Locked:\naccount alice 5m\nnode 2002 5m\n"));
+    server.route_test_text_direct("1001", "UNLOCKUSER alice").await.unwrap();
+    assert!(reply_to(&server, "1001").starts_with("Unlocked account alice.\n"));
+    server.route_test_text_direct("3003", "LOGIN alice Password123").await.unwrap();
+    assert!(reply_to(&server, "3003").starts_with("Welcome, alice"));
+    server.route_test_text_direct("1001", "UNLOCKUSER 2002").await.unwrap();
+    assert!(reply_to(&server, "1001").starts_with("Unlocked node 2002.\n"));
+
+    // Guessing user names counts against the node
+    for name in ["bob", "carol", "dave"] {
+        server.route_test_text_direct("4004", &format!("LOGIN {name} x")).await.unwrap();
+    }
+    server.route_test_text_direct("4004", "LOGIN alice Password123").await.unwrap();
+    assert!(reply_to(&server, "4004").starts_with("Too many failed logins"));
+
+    let audit = server.handle_admin("test", meshbbs::bbs::admin::AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    let mut actions: Vec<(String, String)> = audit.as_array().unwrap().iter()
+        .map(|e| (e["action"].as_str().unwrap().to_string(), e["target"].as_str().unwrap_or_default().to_string()))
+        .collect();
+    actions.reverse();
+    let expected = [("LOCKOUT", "alice"), ("LOCKOUT", "2002"), ("UNLOCKUSER", "alice"), ("UNLOCKUSER", "2002"), ("LOCKOUT", "4004")];
+    assert_eq!(actions, expected.map(|(a, t)| (a.to_string(), t.to_string())));
+}
@@ -0,0 +1,87 @@
+//! SEARCH: word-prefix index across topics, kept current on post/reply/delete, filtered by
+//! read level and paged within one frame.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, MessageTopicConfig};
+use meshbbs::storage::Storage;
+
+fn titles(hits: &[meshbbs::storage::SearchHit]) -> Vec<&str> {
+    hits.iter().map(|h| h.title.as_str()).collect()
+}
+
+#[tokio::test]
+async fn index_follows_posts_replies_and_deletes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().to_string_lossy().to_string();
+    let mut storage = Storage::new(&dir).await.unwrap();
+    storage.create_topic("general", "General", "", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("tech", "Tech", "", 0, 0, "sysop").await.unwrap();
+    let yagi = storage.store_message("tech", "alice", "Yagi build\n\nA 3-element ANTENNA for 915MHz").await.unwrap();
+    storage.store_message("general", "bob", "Meetup Saturday\n\nBring food").await.unwrap();
+
+    // Built from the backend on first use
+    let reopened = Storage::new(&dir).await.unwrap();
+    assert_eq!(titles(&reopened.search_messages("antenna").await.unwrap()), ["Yagi build"]);
+
+    // Prefix and case-insensitive; every word must match
+    assert_eq!(storage.search_messages("ANT 915").await.unwrap().len(), 1);
+    assert!(storage.search_messages("antenna saturday").await.unwrap().is_empty());
+    assert!(storage.search_messages("a").await.unwrap().is_empty(), "single letters are not indexed");
+
+    // Replies are searchable and make the thread the most recent hit
+    let dipole = storage.store_message("general", "carol", "Dipole question\n\nWhich antenna is best?").await.unwrap();
+    assert_eq!(titles(&storage.search_messages("antenna").await.unwrap()), ["Dipole question", "Yagi build"]);
+    storage.append_reply("tech", &yagi, "bob", "Great antenna, what coax?").await.unwrap();
+    let hits = storage.search_messages("coax").await.unwrap();
+    assert_eq!((hits[0].topic.as_str(), hits[0].id.as_str()), ("tech", yagi.as_str()));
+    assert_eq!(titles(&storage.search_messages("antenna").await.unwrap()), ["Yagi build", "Dipole question"]);
+
+    storage.set_message_title("general", &dipole, Some("Best dipole?")).await.unwrap();
+    assert_eq!(titles(&storage.search_messages("best").await.unwrap()), ["Best dipole?"]);
+    assert!(storage.delete_message("general", &dipole).await.unwrap());
+    assert_eq!(titles(&storage.search_messages("antenna").await.unwrap()), ["Yagi build"]);
+    storage.delete_topic("tech").await.unwrap();
+    assert!(storage.search_messages("antenna").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn search_respects_read_levels_pages_and_opens_threads() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.message_topics.insert("mods".into(), MessageTopicConfig { name: "Mods".into(), description: "m".into(), read_level: 5, post_level: 5 });
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_register("moderator", "Password123").await.unwrap();
+    server.test_update_level("moderator", 5).await.unwrap();
+    for i in 1..=6 {
+        let body = format!("Antenna report number {i} with a rather long descriptive title\n\nsignal was fine");
+        server.test_store_message("technical", "alice", &body).await.unwrap();
+    }
+    server.test_store_message("mods", "moderator", "Antenna vandalism at the tower site").await.unwrap();
+
+    server.route_test_text_direct("1001", "LOGIN alice Password123").await.unwrap();
+    server.route_test_text_direct("1001", "SEARCH antenna").await.unwrap();
+    let page1 = server.test_messages().last().unwrap().1.clone();
+    assert!(page1.starts_with("[BBS] antenna: 6 found p1/2\n"), "mods topic is hidden from level 1: {page1}");
+    assert!(page1.contains("L more") && page1.ends_with("alice@search>"), "{page1}");
+    assert!(page1.len() <= 230, "{} bytes: {page1}", page1.len());
+    server.route_test_text_direct("1001", "L").await.unwrap();
+    let page2 = server.test_messages().last().unwrap().1.clone();
+    assert!(page2.starts_with("[BBS] antenna: 6 found p2/2\n1 technical: Antenna report number 1"), "{page2}");
+
+    // Jump into the thread, then B returns to the results
+    server.route_test_text_direct("1001", "1").await.unwrap();
+    let read = server.test_messages().last().unwrap().1.clone();
+    assert!(read.contains("signal was fine") && read.contains("alice@technical>"), "{read}");
+    server.route_test_text_direct("1001", "B").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("p2/2"));
+    server.route_test_text_direct("1001", "B").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("Main Menu"));
+
+    server.route_test_text_direct("2002", "LOGIN moderator Password123").await.unwrap();
+    server.route_test_text_direct("2002", "SEARCH vandal tower").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("1 mods: Antenna vandalism"));
+    server.route_test_text_direct("2002", "SEARCH nothing-here").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("No posts match nothing-here"));
+}
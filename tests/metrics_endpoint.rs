@@ -0,0 +1,68 @@
+#![cfg(all(feature = "web", feature = "meshtastic-proto"))]
+//! `/metrics` serves reliable-send counters, the ACK latency histogram, scheduler gauges,
+//! session counts, public command counts and storage sizes in the Prometheus text format.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, WebConfig};
+use meshbbs::meshtastic::TextEvent;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+/// Value of an exported sample, e.g. `meshbbs_active_sessions` or `x_bucket{le="+Inf"}`.
+fn sample(body: &str, name: &str) -> f64 {
+    body.lines()
+        .find_map(|l| l.strip_prefix(name).and_then(|rest| rest.strip_prefix(' ')))
+        .unwrap_or_else(|| panic!("{name} missing from:\n{body}"))
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn metrics_endpoint_exports_runtime_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_store_message("general", "alice", "hello mesh").await.unwrap();
+    server.route_test_text_direct("1001", "LOGIN alice Password123").await.unwrap();
+    server.route_test_text_direct("2002", "HELP").await.unwrap();
+    for text in ["^HELP", "^8BALL", "^8BALL"] {
+        let ev = TextEvent { source: 3003, dest: None, is_direct: false, channel: Some(0), content: text.into(), ..Default::default() };
+        server.route_text_event(ev).await.unwrap();
+    }
+    meshbbs::metrics::observe_ack_latency(std::time::Instant::now());
+    server.publish_metrics().await.unwrap();
+
+    let addr = meshbbs::web::start(&WebConfig { enabled: true, bind: "127.0.0.1:0".into() }, None).await.unwrap();
+    let response = get(addr, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("text/plain; version=0.0.4"), "{response}");
+    let body = response.split("\r\n\r\n").nth(1).unwrap();
+
+    assert_eq!(sample(body, "meshbbs_active_sessions"), 2.0);
+    assert_eq!(sample(body, "meshbbs_logged_in_users"), 1.0);
+    assert_eq!(sample(body, "meshbbs_public_commands_total{command=\"8BALL\"}"), 2.0);
+    assert_eq!(sample(body, "meshbbs_public_commands_total{command=\"HELP\"}"), 1.0);
+    assert_eq!(sample(body, "meshbbs_public_commands_total{command=\"SLOT\"}"), 0.0);
+    assert_eq!(sample(body, "meshbbs_storage_users"), 1.0);
+    assert_eq!(sample(body, "meshbbs_storage_messages"), 1.0);
+    assert!(sample(body, "meshbbs_storage_bytes") > 0.0);
+    assert_eq!(sample(body, "meshbbs_ack_latency_seconds_bucket{le=\"0.25\"}"), 1.0);
+    assert_eq!(sample(body, "meshbbs_ack_latency_seconds_bucket{le=\"+Inf\"}"), 1.0);
+    assert_eq!(sample(body, "meshbbs_ack_latency_seconds_count"), 1.0);
+    for name in ["meshbbs_reliable_sent_total", "meshbbs_reliable_retries_total", "meshbbs_scheduler_queue_depth", "meshbbs_scheduler_dropped_total", "meshbbs_scheduler_escalations_total"] {
+        sample(body, name);
+        assert!(body.contains(&format!("# TYPE {name} ")), "{name} lacks a TYPE line");
+    }
+
+    assert!(get(addr, "/nope").await.starts_with("HTTP/1.1 404"));
+}
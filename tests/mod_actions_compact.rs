@@ -17,7 +17,7 @@ async fn moderator_pin_rename_delete_in_compact_flow() {
     server.test_register("mod", "Password123").await.unwrap();
     server.test_update_level("mod", 5).await.unwrap();
     let node = "n1";
-    server.route_test_text_direct(node, "LOGIN mod").await.unwrap();
+    server.route_test_text_direct(node, "LOGIN mod Password123").await.unwrap();
 
     // Enter topics and topic
     server.route_test_text_direct(node, "M").await.unwrap();
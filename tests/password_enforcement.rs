@@ -8,11 +8,13 @@ async fn passwordless_user_prompt_and_set() {
     let data_dir = tmp.path().to_str().unwrap().to_string();
     let _cfg = Config {
         bbs: BbsConfig { name: "Test".into(), sysop: "sysop".into(), location: "loc".into(), description: "d".into(), max_users: 10, session_timeout: 10, welcome_message: "Welcome".into(), sysop_password_hash: None },
-    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 0, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None },
-        storage: StorageConfig { data_dir: data_dir.clone(), max_message_size: 230 },
+    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 0, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None, public_channels: None, store_forward_ttl_hours: None, store_forward_max_per_node: None },
+        storage: StorageConfig { data_dir: data_dir.clone(), max_message_size: 230, backend: Default::default(), retention_interval_minutes: None, archive_expired: false },
         message_topics: Default::default(),
         logging: LoggingConfig { level: "error".into(), file: None, security_file: None },
         security: None,
+        web: None,
+        backup: None,
     };
     // Server instance not required for this test; we manipulate user file directly.
     // Use test helper to create passwordless legacy user via storage public method not exposed; mimic by writing file through create_or_update_user equivalent path: call internal method via public test_register? Not possible without password.
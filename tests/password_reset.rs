@@ -0,0 +1,93 @@
+//! Password reset codes: staff issue one with RESETPW, the user redeems it once with RESET
+//! before it expires, and both steps are audited.
+
+use argon2::Argon2;
+use chrono::{Duration, Utc};
+use meshbbs::bbs::admin::AdminRequest;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::storage::{Storage, RESET_CODE_TTL_MINUTES};
+use password_hash::{PasswordHasher, SaltString};
+
+#[tokio::test]
+async fn reset_codes_are_single_use_and_expire() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut storage = Storage::new(&tmp.path().to_string_lossy()).await.unwrap();
+    storage.register_user("alice", "Password123", None).await.unwrap();
+    let now = Utc::now();
+
+    let code = storage.issue_password_reset("alice", "sysop", now).await.unwrap();
+    let alice = storage.get_user("alice").await.unwrap().unwrap();
+    let pending = alice.password_reset.expect("code is pending");
+    assert!(!pending.code_hash.contains(&code), "only a hash is stored");
+    assert_eq!((pending.issued_by.as_str(), pending.expires_at), ("sysop", now + Duration::minutes(RESET_CODE_TTL_MINUTES)));
+
+    assert!(!storage.redeem_password_reset("alice", "WRONG234", "NewPassword1", now).await.unwrap());
+    assert!(storage.redeem_password_reset("alice", &code, "short", now).await.is_err());
+    assert!(storage.redeem_password_reset("alice", &code.to_lowercase(), "NewPassword1", now).await.unwrap());
+    assert!(storage.verify_user_password("alice", "NewPassword1").await.unwrap().1);
+    assert!(!storage.redeem_password_reset("alice", &code, "Another1234", now).await.unwrap(), "codes work once");
+
+    // An expired code is discarded; setting the password any other way drops a pending one
+    let code = storage.issue_password_reset("alice", "sysop", now).await.unwrap();
+    let later = now + Duration::minutes(RESET_CODE_TTL_MINUTES);
+    assert!(!storage.redeem_password_reset("alice", &code, "Another1234", later).await.unwrap());
+    assert!(storage.get_user("alice").await.unwrap().unwrap().password_reset.is_none());
+    storage.issue_password_reset("alice", "sysop", now).await.unwrap();
+    storage.update_user_password("alice", "Another1234").await.unwrap();
+    assert!(storage.get_user("alice").await.unwrap().unwrap().password_reset.is_none());
+    assert!(!storage.redeem_password_reset("nobody", &code, "Another1234", now).await.unwrap());
+}
+
+/// Everything the BBS sends back to `node` for `text`, chunks joined
+async fn send(server: &mut BbsServer, node: &str, text: &str) -> String {
+    let before = server.test_messages().len();
+    server.route_test_text_direct(node, text).await.unwrap();
+    server.test_messages()[before..].iter().filter(|(to, _)| to == node).map(|(_, m)| m.as_str()).collect()
+}
+
+#[tokio::test]
+async fn staff_issue_codes_and_users_redeem_them_over_dm() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    for name in ["alice", "mod1", "mod2"] {
+        server.test_register(name, "Password123").await.unwrap();
+    }
+    send(&mut server, "1001", "LOGIN sysop Sysop#Pass1").await;
+    send(&mut server, "1001", "PROMOTE mod1").await;
+    send(&mut server, "1001", "PROMOTE mod2").await;
+    send(&mut server, "2002", "LOGIN alice Password123").await;
+    assert!(send(&mut server, "2002", "RESETPW mod1").await.starts_with("Permission denied."));
+    send(&mut server, "3003", "LOGIN mod1 Password123").await;
+    assert!(send(&mut server, "3003", "RESETPW mod2").await.starts_with("Permission denied."), "not a peer's");
+    assert!(send(&mut server, "3003", "RESETPW sysop").await.starts_with("Sysop password managed externally"));
+
+    let reply = send(&mut server, "3003", "RESETPW alice").await;
+    assert!(reply.starts_with("Reset code for alice: "), "{reply}");
+    let code = reply["Reset code for alice: ".len()..].split_whitespace().next().unwrap().to_string();
+
+    // The user redeems it from any node without logging in
+    assert!(send(&mut server, "4004", "RESET alice").await.starts_with("Usage: RESET <user> <code> <newpass>"));
+    assert!(send(&mut server, "4004", "RESET alice ZZZZZZZZ NewPassword1").await.starts_with("Invalid or expired code."));
+    assert!(send(&mut server, "4004", &format!("RESET alice {code} short")).await.starts_with("New password too short"));
+    assert!(send(&mut server, "4004", &format!("RESET alice {code} NewPassword1")).await.starts_with("Password changed. LOGIN alice"));
+    assert!(send(&mut server, "4004", &format!("RESET alice {code} Another1234")).await.starts_with("Invalid or expired code."));
+    assert!(send(&mut server, "4004", "LOGIN alice NewPassword1").await.starts_with("Welcome, alice"));
+
+    // The sysop can reset anyone but the sysop account
+    assert!(send(&mut server, "1001", "RESETPW mod2").await.starts_with("Reset code for mod2: "));
+
+    let audit = server.handle_admin("test", AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    let mut actions: Vec<(String, String, String)> = audit.as_array().unwrap().iter()
+        .filter(|e| e["action"].as_str().unwrap().starts_with("RESETPW"))
+        .map(|e| [&e["action"], &e["target"], &e["actor"]].map(|v| v.as_str().unwrap_or_default().to_string()).into())
+        .collect();
+    actions.reverse();
+    let expected = [("RESETPW_ISSUED", "alice", "mod1"), ("RESETPW_USED", "alice", "alice"), ("RESETPW_ISSUED", "mod2", "sysop")];
+    assert_eq!(actions, expected.map(|(a, t, by)| (a.to_string(), t.to_string(), by.to_string())));
+}
@@ -9,11 +9,13 @@ async fn config_with_areas(dir: &str) -> Config {
     areas.insert("ann".into(), MessageTopicConfig { name: "Ann".into(), description: "a".into(), read_level: 0, post_level: 10 });
     Config {
         bbs: BbsConfig { name: "Test".into(), sysop: "sysop".into(), location: "loc".into(), description: "d".into(), max_users: 10, session_timeout: 10, welcome_message: "w".into(), sysop_password_hash: None },
-    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None },
-        storage: StorageConfig { data_dir: dir.to_string(), max_message_size: 1024 },
+    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None, public_channels: None, store_forward_ttl_hours: None, store_forward_max_per_node: None },
+        storage: StorageConfig { data_dir: dir.to_string(), max_message_size: 1024, backend: Default::default(), retention_interval_minutes: None, archive_expired: false },
         message_topics: areas,
         logging: LoggingConfig { level: "error".into(), file: None, security_file: None },
         security: Default::default(),
+        web: None,
+        backup: None,
     }
 }
 
@@ -0,0 +1,157 @@
+//! PKI direct messages: node keys are remembered in the node cache, DMs to a known key ask
+//! the radio for PKI, password commands can be refused over channel-key DMs, and a node
+//! announcing a different key is a security event that leaves DMs on the old key until a
+//! sysop accepts the new one.
+#![cfg(feature = "meshtastic-proto")]
+
+use argon2::Argon2;
+use meshbbs::bbs::admin::AdminRequest;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::meshtastic::{DeliveryEvent, KeyUpdate, NodeCache, TextEvent};
+use password_hash::{PasswordHasher, SaltString};
+use rand::Rng;
+
+#[test]
+fn node_cache_keeps_one_key_per_node() {
+    let mut cache = NodeCache::new();
+    cache.update_node(42, "Base".into(), "BASE".into());
+    assert_eq!(cache.record_public_key(42, &[]), KeyUpdate::Unchanged, "no key, no change");
+    assert_eq!(cache.record_public_key(42, &[1; 32]), KeyUpdate::New);
+    assert_eq!(cache.record_public_key(42, &[1; 32]), KeyUpdate::Unchanged);
+    let previous = cache.nodes[&42].public_key.clone().unwrap();
+    assert_eq!(cache.record_public_key(42, &[2; 32]), KeyUpdate::Changed { previous });
+    assert_eq!(cache.record_public_key(42, &[2; 32]), KeyUpdate::Unchanged, "reported once");
+    assert_eq!(cache.public_key(42), Some(vec![1; 32]), "old key stays pinned");
+    assert_eq!(cache.accept_public_key(42), Some(vec![2; 32]));
+    assert_eq!(cache.public_key(42), Some(vec![2; 32]));
+    assert_eq!(cache.accept_public_key(42), None, "nothing pending");
+    // A key can arrive before the node's names
+    assert_eq!(cache.record_public_key(7, &[3; 32]), KeyUpdate::New);
+    assert_eq!(cache.public_keys().len(), 2);
+
+    // Older cache files without keys still load
+    let legacy = r#"{"nodes":{"42":{"node_id":42,"long_name":"Base","short_name":"BASE",
+        "last_seen":"2025-01-01T00:00:00Z","first_seen":"2025-01-01T00:00:00Z"}},"last_updated":"2025-01-01T00:00:00Z"}"#;
+    let cache: NodeCache = serde_json::from_str(legacy).unwrap();
+    assert_eq!(cache.public_key(42), None);
+}
+
+fn config(tmp: &tempfile::TempDir) -> Config {
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    cfg.security.as_mut().unwrap().pki.require_for_passwords = true;
+    cfg
+}
+
+fn dm(source: u32, content: &str, pki_encrypted: bool) -> TextEvent {
+    TextEvent { source, dest: Some(1), is_direct: true, content: content.into(), pki_encrypted, ..Default::default() }
+}
+
+async fn audit_actions(server: &mut BbsServer, action: &str) -> Vec<(String, String)> {
+    let audit = server.handle_admin("test", AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    audit.as_array().unwrap().iter()
+        .filter(|e| e["action"] == action)
+        .map(|e| (e["target"].as_str().unwrap_or_default().to_string(), e["actor"].as_str().unwrap_or_default().to_string()))
+        .collect()
+}
+
+/// Everything the BBS sends back for `ev`, chunks joined
+async fn reply(server: &mut BbsServer, ev: TextEvent) -> String {
+    let before = server.test_messages().len();
+    server.route_text_event(ev).await.unwrap();
+    server.test_messages()[before..].iter().map(|(_, m)| m.as_str()).collect()
+}
+
+#[tokio::test]
+async fn password_commands_need_pki_when_required() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut server = BbsServer::new(config(&tmp)).await.unwrap();
+    server.seed_sysop().await.unwrap();
+
+    assert!(reply(&mut server, dm(1001, "REGISTER alice Password123", false)).await.starts_with("Passwords need an encrypted DM"));
+    assert!(server.get_user("alice").await.unwrap().is_none());
+    assert!(!reply(&mut server, dm(1001, "HELP", false)).await.starts_with("Passwords need"), "other commands still work");
+
+    reply(&mut server, dm(1001, "REGISTER alice Password123", true)).await;
+    assert!(server.get_user("alice").await.unwrap().is_some());
+    reply(&mut server, dm(1001, "LOGOUT", false)).await;
+    assert!(reply(&mut server, dm(1001, "login alice Password123", false)).await.starts_with("Passwords need an encrypted DM"));
+    drop(server);
+
+    // Off by default
+    let mut server = BbsServer::new(Config { security: None, ..config(&tmp) }).await.unwrap();
+    assert!(reply(&mut server, dm(1001, "LOGIN alice Password123", false)).await.starts_with("Welcome, alice"));
+}
+
+#[tokio::test]
+async fn key_change_is_audited_and_sysops_are_told() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut server = BbsServer::new(config(&tmp)).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    server.route_text_event(dm(1001, "LOGIN sysop Sysop#Pass1", true)).await.unwrap();
+
+    let event = DeliveryEvent::KeyChanged { node: 2002, previous: "b2xk".into(), current: "bmV3".into() };
+    server.handle_delivery_event(event).await.unwrap();
+    let notice = &server.test_messages().last().unwrap();
+    assert_eq!(notice.0, "1001");
+    assert!(notice.1.starts_with("[BBS] Node 2002 changed its PKI key"), "{notice:?}");
+    assert_eq!(audit_actions(&mut server, "PKI_KEY_CHANGED").await, [("2002".to_string(), "pki".to_string())]);
+}
+
+#[tokio::test]
+async fn simulated_radio_pki_end_to_end() {
+    use meshbbs::meshtastic::sim::{self, SimConfig, SimNode};
+    use std::time::Duration;
+    const WAIT: Duration = Duration::from_secs(20);
+
+    let tmp = tempfile::tempdir().unwrap();
+    let mut server = BbsServer::new(config(&tmp)).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    // Node ids unseen by the shared node cache, so every key below is new to it
+    let base = 0x5200_0000 + (rand::thread_rng().gen::<u16>() as u32) * 4;
+    let nodes = (1..=3).map(|i| SimNode { id: base + i, long_name: format!("PKI Node {i}"), short_name: format!("PK{i}") }).collect();
+    let (transport, mut sim) = sim::start(SimConfig { nodes, ..SimConfig::with_nodes(0) });
+    server.connect_transport(transport).await.unwrap();
+    let key = || rand::thread_rng().gen::<[u8; 32]>();
+    let (alice_key, sysop_key, new_key) = (key(), key(), key());
+    let (alice, bob, sysop) = (base + 1, base + 2, base + 3);
+
+    let drive = async {
+        sim.wait_configured(WAIT).await?;
+        sim.announce_key(alice, &alice_key)?;
+        sim.announce_key(sysop, &sysop_key)?;
+
+        sim.send_dm(bob, "REGISTER bob password456")?;
+        let reply = sim.expect(Some(bob), "Passwords need an encrypted DM", WAIT).await?;
+        assert!(!reply.pki_encrypted, "no key known for bob");
+
+        sim.send_pki_dm(alice, &alice_key, "REGISTER alice password123")?;
+        let reply = sim.expect(Some(alice), "Registered as alice", WAIT).await?;
+        assert!(reply.pki_encrypted, "replies to a known key ask for PKI");
+
+        sim.send_pki_dm(sysop, &sysop_key, "LOGIN sysop Sysop#Pass1")?;
+        sim.expect(Some(sysop), "Welcome, sysop", WAIT).await?;
+        sim.announce_key(alice, &new_key)?;
+        sim.expect(Some(sysop), &format!("[BBS] Node {alice} changed its PKI key"), WAIT).await?;
+
+        // Not re-keyed until a sysop accepts the change
+        sim.send_dm(alice, "WHERE")?;
+        let reply = sim.expect(Some(alice), "", WAIT).await?;
+        assert_eq!(reply.public_key, alice_key, "DMs stay pinned to the known key");
+        sim.send_pki_dm(sysop, &sysop_key, &format!("ACCEPTKEY {alice}"))?;
+        sim.expect(Some(sysop), &format!("DMs to node {alice} now use its new key"), WAIT).await?;
+        sim.send_dm(alice, "WHERE")?;
+        let reply = sim.expect(Some(alice), "", WAIT).await?;
+        assert_eq!(reply.public_key, new_key);
+        anyhow::Ok(())
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = drive => res.unwrap(),
+    }
+    assert_eq!(audit_actions(&mut server, "PKI_KEY_CHANGED").await, [(alice.to_string(), "pki".to_string())]);
+    assert_eq!(audit_actions(&mut server, "PKI_KEY_ACCEPTED").await, [(alice.to_string(), "sysop".to_string())]);
+}
@@ -0,0 +1,110 @@
+//! Private mail: SEND delivers to a per-user mailbox, the recipient sees an unread count
+//! at login, and MAIL lists, reads and deletes messages.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::storage::{BackendKind, Storage};
+
+async fn setup() -> (BbsServer, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_register("bob", "Password456").await.unwrap();
+    (server, tmp)
+}
+
+/// Everything sent to `node` since the previous call for that node.
+fn replies(server: &BbsServer, node: &str, seen: &mut usize) -> String {
+    let msgs = server.test_messages();
+    let out = msgs[*seen..].iter().filter(|(to, _)| to == node).map(|(_, m)| m.as_str()).collect::<Vec<_>>().join("");
+    *seen = msgs.len();
+    out
+}
+
+#[tokio::test]
+async fn send_login_summary_read_and_delete() {
+    let (mut server, _tmp) = setup().await;
+    let mut seen = 0;
+    server.route_test_text_direct("1001", "LOGIN alice Password123").await.unwrap();
+    server.route_test_text_direct("1001", "SEND @bob meet at the repeater").await.unwrap();
+    assert!(replies(&server, "1001", &mut seen).contains("Mail sent to bob."));
+    server.route_test_text_direct("1001", "SEND bob").await.unwrap();
+    assert!(replies(&server, "1001", &mut seen).contains("Mail to bob"));
+    server.route_test_text_direct("1001", "bring coax").await.unwrap();
+    assert!(replies(&server, "1001", &mut seen).contains("Mail sent to bob."));
+    server.route_test_text_direct("1001", "SEND @nobody hi").await.unwrap();
+    assert!(replies(&server, "1001", &mut seen).contains("No such user"));
+
+    server.route_test_text_direct("2002", "LOGIN bob Password456").await.unwrap();
+    assert!(replies(&server, "2002", &mut seen).contains("You have 2 unread mail."));
+
+    server.route_test_text_direct("2002", "MAIL").await.unwrap();
+    let inbox = replies(&server, "2002", &mut seen);
+    assert!(inbox.contains("Mail: 2 msgs, 2 unread"), "{inbox}");
+    assert!(inbox.contains("1* alice") && inbox.contains("bring coax"), "{inbox}");
+
+    server.route_test_text_direct("2002", "2").await.unwrap();
+    let read = replies(&server, "2002", &mut seen);
+    assert!(read.contains("Mail from alice") && read.contains("meet at the repeater"), "{read}");
+    server.route_test_text_direct("2002", "B").await.unwrap();
+    assert!(replies(&server, "2002", &mut seen).contains("2 msgs, 1 unread"));
+
+    // Replying from the read view notifies alice, who is online
+    server.route_test_text_direct("2002", "1").await.unwrap();
+    server.route_test_text_direct("2002", "Y").await.unwrap();
+    server.route_test_text_direct("2002", "see you there").await.unwrap();
+    assert!(replies(&server, "1001", &mut seen).contains("New mail from bob"));
+
+    server.route_test_text_direct("2002", "DEL 1").await.unwrap();
+    assert!(replies(&server, "2002", &mut seen).contains("Mail: 1 msgs, 0 unread"));
+}
+
+#[tokio::test]
+async fn mail_requires_login() {
+    let (mut server, _tmp) = setup().await;
+    server.route_test_text_direct("1001", "SEND @bob hi").await.unwrap();
+    let msgs = server.test_messages();
+    assert!(msgs.iter().any(|(_, m)| m.contains("Log in to use mail")), "{msgs:?}");
+}
+
+async fn mailbox_rules(kind: BackendKind) {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let mut storage = Storage::open(&data_dir, kind, None, None).await.unwrap();
+    storage.register_user("alice", "password123", None).await.unwrap();
+    storage.register_user("bob", "password456", None).await.unwrap();
+
+    assert!(storage.send_mail("alice", "alice", "me").await.is_err());
+    assert!(storage.send_mail("alice", "bob", "   ").await.is_err());
+    let first = storage.send_mail("alice", "bob", "first").await.unwrap();
+    assert_eq!(first.to, "bob");
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    storage.send_mail("alice", "bob", "second").await.unwrap();
+
+    let mail = storage.list_mail("bob").await.unwrap();
+    assert_eq!(mail.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["second", "first"], "{kind}");
+    assert_eq!(storage.count_unread_mail("bob").await.unwrap(), 2);
+    storage.mark_mail_read("bob", &first.id).await.unwrap();
+    assert!(storage.mark_mail_read("alice", &first.id).await.is_err(), "mailboxes are per user");
+    drop(storage);
+
+    let storage = Storage::open(&data_dir, kind, None, None).await.unwrap();
+    assert_eq!(storage.count_unread_mail("bob").await.unwrap(), 1);
+    assert!(storage.delete_mail("bob", &first.id).await.unwrap());
+    assert!(!storage.delete_mail("bob", &first.id).await.unwrap());
+    assert_eq!(storage.list_mail("bob").await.unwrap().len(), 1);
+    assert!(storage.list_mail("alice").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn json_mailbox() {
+    mailbox_rules(BackendKind::Json).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sqlite_mailbox() {
+    mailbox_rules(BackendKind::Sqlite).await;
+}
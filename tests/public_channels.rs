@@ -0,0 +1,90 @@
+#![cfg(feature = "meshtastic-proto")]
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, PublicChannelConfig};
+use meshbbs::meshtastic::{OutgoingMessage, TextEvent};
+use tokio::sync::mpsc;
+
+fn public(source: u32, channel: u32, content: &str) -> TextEvent {
+    TextEvent { source, dest: None, is_direct: false, channel: Some(channel), content: content.into(), ..Default::default() }
+}
+
+async fn server_with(cfg: Config) -> (BbsServer, mpsc::UnboundedReceiver<OutgoingMessage>, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = cfg;
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let mut server = BbsServer::new(cfg).await.expect("server");
+    let (tx, rx) = mpsc::unbounded_channel();
+    server.test_set_outgoing(tx);
+    (server, rx, tmp)
+}
+
+#[tokio::test]
+async fn ignores_public_commands_off_configured_channel() {
+    let mut cfg = Config::default();
+    cfg.meshtastic.channel = 2;
+    let (mut server, mut rx, _tmp) = server_with(cfg).await;
+
+    server.route_text_event(public(1001, 0, "^8BALL")).await.unwrap();
+    assert!(rx.try_recv().is_err(), "primary channel must be ignored when BBS runs on channel 2");
+
+    server.route_text_event(public(1001, 2, "^8BALL")).await.unwrap();
+    let out = rx.try_recv().expect("8BALL reply");
+    assert!(out.to_node.is_none());
+    assert_eq!(out.channel, 2, "broadcast reply goes out on the request channel");
+}
+
+#[tokio::test]
+async fn replies_on_request_channel_with_per_channel_rules() {
+    let mut cfg = Config::default();
+    cfg.meshtastic.channel = 1;
+    cfg.meshtastic.public_channels = Some(vec![
+        PublicChannelConfig { index: 1, commands: vec![] },
+        PublicChannelConfig { index: 3, commands: vec!["^login".into(), "FORTUNE".into()] },
+    ]);
+    let (mut server, mut rx, _tmp) = server_with(cfg).await;
+
+    // 8BALL is not enabled on channel 3
+    server.route_text_event(public(2002, 3, "^8BALL")).await.unwrap();
+    assert!(rx.try_recv().is_err());
+
+    // FORTUNE is enabled on channel 3 and answered there
+    server.route_text_event(public(2002, 3, "^FORTUNE")).await.unwrap();
+    let out = rx.try_recv().expect("fortune reply");
+    assert_eq!(out.channel, 3);
+
+    // LOGIN pending notice is a DM, also sent on the request channel
+    server.route_text_event(public(2003, 3, "^LOGIN alice")).await.unwrap();
+    let dm = rx.try_recv().expect("login DM");
+    assert_eq!(dm.to_node, Some(2003));
+    assert_eq!(dm.channel, 3);
+
+    // Channel 1 allows everything; channel 0 is not listened on at all
+    server.route_text_event(public(2004, 1, "^8BALL")).await.unwrap();
+    assert_eq!(rx.try_recv().expect("8ball").channel, 1);
+    server.route_text_event(public(2005, 0, "^8BALL")).await.unwrap();
+    assert!(rx.try_recv().is_err());
+
+    // Unsolicited broadcasts use the configured primary channel
+    server.send_broadcast("notice").await.unwrap();
+    assert_eq!(rx.try_recv().expect("notice").channel, 1);
+}
+
+#[tokio::test]
+async fn reply_channel_is_forgotten_after_kick() {
+    let mut cfg = Config::default();
+    cfg.meshtastic.channel = 1;
+    cfg.meshtastic.public_channels = Some(vec![PublicChannelConfig { index: 3, commands: vec![] }]);
+    let (mut server, mut rx, _tmp) = server_with(cfg).await;
+    server.test_register("alice", "Password123").await.unwrap();
+
+    let login = TextEvent { source: 2003, dest: Some(1), is_direct: true, channel: Some(3), content: "LOGIN alice Password123".into(), ..Default::default() };
+    server.route_text_event(login).await.unwrap();
+    assert_eq!(rx.try_recv().expect("welcome").channel, 3);
+
+    // The disconnect notice still goes out on the request channel, later DMs on the primary
+    assert!(server.force_logout_user("alice").await.unwrap());
+    assert_eq!(rx.try_recv().expect("kick notice").channel, 3);
+    while rx.try_recv().is_ok() {}
+    server.send_message("2003", "hello").await.unwrap();
+    assert_eq!(rx.try_recv().expect("dm").channel, 1);
+}
@@ -21,6 +21,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: true,
         channel: None,
         content: "REGISTER alice secretpass123".into(),
+        ..Default::default()
     };
     server.route_text_event(register_event).await.expect("user registration");
 
@@ -31,6 +32,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: true,
         channel: None,
         content: "LOGOUT".into(),
+        ..Default::default()
     };
     server.route_text_event(logout_event).await.expect("logout");
 
@@ -41,6 +43,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: false,
         channel: None,
         content: "^LOGIN alice".into(),
+        ..Default::default()
     };
     server.route_text_event(public_event).await.expect("public login");
 
@@ -51,6 +54,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: true,
         channel: None,
         content: "HI".into(),
+        ..Default::default()
     };
     server.route_text_event(dm_event).await.expect("dm hi");
 
@@ -61,6 +65,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: true,
         channel: None,
         content: "READ".into(),
+        ..Default::default()
     };
     server.route_text_event(read_event).await.expect("read attempt");
 
@@ -77,6 +82,7 @@ async fn public_login_with_password_requires_auth() {
         is_direct: true,
         channel: None,
         content: "LOGIN alice secretpass123".into(),
+        ..Default::default()
     };
     server.route_text_event(login_with_pass).await.expect("login with password");
 
@@ -105,6 +111,7 @@ async fn public_login_without_password_auto_succeeds() {
         is_direct: false,
         channel: None,
         content: "^LOGIN bob".into(),
+        ..Default::default()
     };
     server.route_text_event(public_event).await.expect("public login");
 
@@ -115,6 +122,7 @@ async fn public_login_without_password_auto_succeeds() {
         is_direct: true,
         channel: None,
         content: "HI".into(),
+        ..Default::default()
     };
     server.route_text_event(dm_event).await.expect("dm hi");
 
@@ -143,6 +151,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: true,
         channel: None,
         content: "REGISTER charlie mypassword".into(),
+        ..Default::default()
     };
     server.route_text_event(register_event).await.expect("user registration");
 
@@ -153,6 +162,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: true,
         channel: None,
         content: "LOGOUT".into(),
+        ..Default::default()
     };
     server.route_text_event(logout_event).await.expect("logout");
 
@@ -163,6 +173,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: false,
         channel: None,
         content: "^LOGIN charlie".into(),
+        ..Default::default()
     };
     server.route_text_event(public_event).await.expect("public login");
 
@@ -173,6 +184,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: true,
         channel: None,
         content: "HI".into(),
+        ..Default::default()
     };
     server.route_text_event(dm_event).await.expect("dm hi");
 
@@ -183,6 +195,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: true,
         channel: None,
         content: "LOGIN charlie wrongpassword".into(),
+        ..Default::default()
     };
     server.route_text_event(wrong_pass).await.expect("wrong password attempt");
 
@@ -199,6 +212,7 @@ async fn public_login_wrong_password_rejected() {
         is_direct: true,
         channel: None,
         content: "LOGIN charlie mypassword".into(),
+        ..Default::default()
     };
     server.route_text_event(correct_pass).await.expect("correct password");
 
@@ -16,7 +16,7 @@ async fn compact_registration_message_fits() {
     let mut server = BbsServer::new(cfg).await.unwrap();
     let node: u32 = 0xABCDEF01;
     let cmd = format!("REGISTER {} passw0rd8", long_user);
-    let ev = TextEvent { source: node, dest: None, is_direct: true, channel: None, content: cmd };
+    let ev = TextEvent { source: node, dest: None, is_direct: true, channel: None, content: cmd, ..Default::default() };
     server.route_text_event(ev).await.unwrap();
 
     let key = node.to_string();
@@ -19,6 +19,7 @@ async fn register_no_invalid_command_echo() {
         is_direct: true,
         channel: None,
         content: "REGISTER bob password123".into(),
+        ..Default::default()
     };
     server.route_text_event(register_event).await.expect("register event");
 
@@ -0,0 +1,136 @@
+//! Bans, mutes and node blocks: staff set them with BAN, MUTE and BLOCKNODE, each with an
+//! expiry and reason, they persist through storage, and every change is audited.
+#![cfg(feature = "meshtastic-proto")]
+
+use argon2::Argon2;
+use chrono::{Duration, Utc};
+use meshbbs::bbs::admin::AdminRequest;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::meshtastic::TextEvent;
+use meshbbs::storage::{BackendKind, Sanction, SanctionKind, Storage};
+use password_hash::{PasswordHasher, SaltString};
+
+async fn persistence(backend: BackendKind) {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().to_string_lossy().to_string();
+    let mut storage = Storage::open(&dir, backend, None, None).await.unwrap();
+    storage.register_user("alice", "Password123", None).await.unwrap();
+    let now = Utc::now();
+    let sanction = |hours: Option<i64>| Sanction {
+        reason: "  spam\u{7}  ".into(),
+        issued_by: "sysop".into(),
+        issued_at: now,
+        expires_at: hours.map(|h| now + Duration::hours(h)),
+    };
+
+    let (key, stored) = storage.add_sanction(SanctionKind::Ban, "alice", sanction(Some(2))).await.unwrap();
+    assert_eq!((key.as_str(), stored.reason.as_str()), ("alice", "spam"));
+    assert!(storage.add_sanction(SanctionKind::Mute, "nobody", sanction(None)).await.is_err());
+    assert!(storage.add_sanction(SanctionKind::BlockNode, "bad id", sanction(None)).await.is_err());
+    storage.add_sanction(SanctionKind::BlockNode, "1234", sanction(None)).await.unwrap();
+    drop(storage);
+
+    let storage = Storage::open(&dir, backend, None, None).await.unwrap();
+    assert!(storage.sanction(SanctionKind::Ban, "alice", now).await.unwrap().is_some());
+    assert!(storage.sanction(SanctionKind::Ban, "alice", now + Duration::hours(2)).await.unwrap().is_none(), "expired");
+    assert!(storage.sanction(SanctionKind::Mute, "alice", now).await.unwrap().is_none());
+    let later = now + Duration::days(365);
+    let active: Vec<(SanctionKind, String)> = storage.active_sanctions(later).await.unwrap().into_iter().map(|(k, t, _)| (k, t)).collect();
+    assert_eq!(active, [(SanctionKind::BlockNode, "1234".to_string())]);
+
+    assert_eq!(storage.lift_sanction(SanctionKind::Ban, "ALICE").await.unwrap().as_deref(), Some("alice"));
+    assert!(storage.lift_sanction(SanctionKind::Ban, "alice").await.unwrap().is_none());
+    assert!(storage.sanction(SanctionKind::Ban, "alice", now).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn sanctions_expire_and_persist_json() {
+    persistence(BackendKind::Json).await;
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn sanctions_expire_and_persist_sqlite() {
+    persistence(BackendKind::Sqlite).await;
+}
+
+/// Everything the BBS sends back to `node` for `text`, chunks joined
+async fn send(server: &mut BbsServer, node: u32, text: &str) -> String {
+    let before = server.test_messages().len();
+    let ev = TextEvent { source: node, dest: Some(1), is_direct: true, content: text.into(), ..Default::default() };
+    server.route_text_event(ev).await.unwrap();
+    let node = node.to_string();
+    server.test_messages()[before..].iter().filter(|(to, _)| *to == node).map(|(_, m)| m.as_str()).collect()
+}
+
+#[tokio::test]
+async fn staff_ban_mute_and_block_over_dm() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    server.test_create_topic("general", "General", "General chat", 0, 0, "sysop").await.ok();
+    for name in ["alice", "bob", "mod1"] {
+        server.test_register(name, "Password123").await.unwrap();
+    }
+    send(&mut server, 1001, "LOGIN sysop Sysop#Pass1").await;
+    send(&mut server, 1001, "PROMOTE mod1").await;
+    send(&mut server, 2002, "LOGIN alice Password123").await;
+    send(&mut server, 3003, "LOGIN bob Password123").await;
+    send(&mut server, 4004, "LOGIN mod1 Password123").await;
+
+    assert!(send(&mut server, 2002, "BAN bob 1h").await.starts_with("Permission denied."));
+    assert!(send(&mut server, 4004, "BAN bob").await.starts_with("Usage: BAN <user> <30m|12h|7d|PERM|OFF> [reason]"));
+    assert!(send(&mut server, 4004, "BAN bob soon").await.starts_with("Usage: BAN"));
+    assert!(send(&mut server, 4004, "BAN sysop 1h").await.starts_with("Cannot modify sysop."));
+
+    // A ban ends the session and keeps the user out, whatever the node
+    assert!(send(&mut server, 4004, "BAN alice 2h flooding the board").await.starts_with("Banned alice for 2h."));
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m.starts_with("You have been disconnected")));
+    assert!(send(&mut server, 2002, "LOGIN alice Password123").await.starts_with("Account banned for 2h: flooding the board"));
+    assert!(send(&mut server, 5005, "LOGIN alice Password123").await.starts_with("Account banned"));
+
+    // A muted user still reads but can't post
+    assert!(send(&mut server, 4004, "MUTE bob PERM").await.starts_with("Muted bob permanently."));
+    assert!(send(&mut server, 3003, "POST general hello").await.starts_with("You are muted."));
+    assert!(send(&mut server, 3003, "READ general").await.starts_with("Messages in general"));
+
+    // A blocked node is ignored, public or direct
+    assert!(send(&mut server, 4004, "BLOCKNODE 4004 1d").await.starts_with("Cannot block your own node."));
+    assert!(send(&mut server, 4004, "BLOCKNODE 0x1A0A 1d beacon spam").await.starts_with("Blocked node 6666 for 1d."));
+    assert_eq!(send(&mut server, 6666, "HELP").await, "");
+    let before = server.test_messages().len();
+    let public = TextEvent { source: 6666, content: "^HELP".into(), ..Default::default() };
+    server.route_text_event(public).await.unwrap();
+    assert_eq!(server.test_messages().len(), before);
+    assert!(send(&mut server, 4004, "BLOCKNODE 6666 OFF").await.starts_with("Lifted block on 6666."));
+    assert_ne!(send(&mut server, 6666, "HELP").await, "", "unblocked right away");
+    send(&mut server, 4004, "BLOCKNODE 6666 1d beacon spam").await;
+    assert_eq!(send(&mut server, 6666, "HELP").await, "");
+
+    let bans = send(&mut server, 4004, "BANS").await;
+    assert!(bans.starts_with("Sanctions:\nban alice 2h flooding the board\nmute bob perm\nblock 6666 1d beacon spam\n"), "{bans}");
+    assert!(send(&mut server, 4004, "BAN alice OFF").await.starts_with("Lifted ban on alice."));
+    assert!(send(&mut server, 4004, "BAN alice OFF").await.starts_with("No ban on alice."));
+    assert!(send(&mut server, 2002, "LOGIN alice Password123").await.starts_with("Welcome, alice"));
+
+    let audit = server.handle_admin("test", AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    let mut actions: Vec<(String, String, String)> = audit.as_array().unwrap().iter()
+        .filter(|e| e["actor"] == "mod1")
+        .map(|e| [&e["action"], &e["target"], &e["details"]].map(|v| v.as_str().unwrap_or_default().to_string()).into())
+        .collect();
+    actions.reverse();
+    let expected = [
+        ("BAN", "alice", "for 2h: flooding the board"),
+        ("MUTE", "bob", "permanently"),
+        ("BLOCKNODE", "6666", "for 1d: beacon spam"),
+        ("UNBLOCKNODE", "6666", ""),
+        ("BLOCKNODE", "6666", "for 1d: beacon spam"),
+        ("UNBAN", "alice", ""),
+    ];
+    assert_eq!(actions, expected.map(|(a, t, d)| (a.to_string(), t.to_string(), d.to_string())));
+}
@@ -14,15 +14,15 @@ async fn max_users_enforced() {
     let mut server = BbsServer::new(cfg).await.expect("server");
 
     // First user login flow
-    let public1 = TextEvent { source: 100, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into() };
+    let public1 = TextEvent { source: 100, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into(), ..Default::default() };
     server.route_text_event(public1).await.expect("public1");
-    let dm1 = TextEvent { source: 100, dest: Some(1), is_direct: true, channel: None, content: "LOGIN alice".into() };
+    let dm1 = TextEvent { source: 100, dest: Some(1), is_direct: true, channel: None, content: "LOGIN alice".into(), ..Default::default() };
     server.route_text_event(dm1).await.expect("dm1");
 
     // Second user attempts login
-    let public2 = TextEvent { source: 200, dest: None, is_direct: false, channel: None, content: "^LOGIN bob".into() };
+    let public2 = TextEvent { source: 200, dest: None, is_direct: false, channel: None, content: "^LOGIN bob".into(), ..Default::default() };
     server.route_text_event(public2).await.expect("public2");
-    let dm2 = TextEvent { source: 200, dest: Some(1), is_direct: true, channel: None, content: "LOGIN bob".into() };
+    let dm2 = TextEvent { source: 200, dest: Some(1), is_direct: true, channel: None, content: "LOGIN bob".into(), ..Default::default() };
     // Should not panic; actual rejection message isn't captured (no outbound capture hook)
     server.route_text_event(dm2).await.expect("dm2");
     // We assert internal count still 1
@@ -39,9 +39,9 @@ async fn idle_logout_triggers() {
     cfg.storage.data_dir = crate::common::fixture_root().to_string_lossy().to_string();
     let mut server = BbsServer::new(cfg).await.expect("server");
 
-    let public = TextEvent { source: 300, dest: None, is_direct: false, channel: None, content: "^LOGIN carol".into() };
+    let public = TextEvent { source: 300, dest: None, is_direct: false, channel: None, content: "^LOGIN carol".into(), ..Default::default() };
     server.route_text_event(public).await.expect("public");
-    let dm = TextEvent { source: 300, dest: Some(2), is_direct: true, channel: None, content: "LOGIN carol".into() };
+    let dm = TextEvent { source: 300, dest: Some(2), is_direct: true, channel: None, content: "LOGIN carol".into(), ..Default::default() };
     server.route_text_event(dm).await.expect("dm");
     assert_eq!(server.test_logged_in_count(), 1);
 
@@ -0,0 +1,105 @@
+#![cfg(feature = "meshtastic-proto")]
+//! End-to-end sessions through the real reader, writer and scheduler against the
+//! simulated radio (no hardware). Outgoing text is paced at >= 2s per packet, so
+//! these tests keep the number of round trips small.
+
+use std::time::Duration;
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::meshtastic::sim::{self, SimConfig, SimHandle};
+use meshbbs::protobuf::meshtastic_generated::routing::Error as RoutingError;
+
+const WAIT: Duration = Duration::from_secs(20);
+
+async fn setup(sim_cfg: SimConfig) -> (BbsServer, SimHandle, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.meshtastic.dm_resend_backoff_seconds = Some(vec![1, 1, 1]);
+    let (transport, handle) = sim::start(sim_cfg);
+    let mut server = BbsServer::new(cfg).await.expect("server");
+    server.connect_transport(transport).await.expect("connect sim transport");
+    (server, handle, tmp)
+}
+
+#[tokio::test]
+async fn register_browse_and_public_command() {
+    let (mut server, mut sim, _tmp) = setup(SimConfig::with_nodes(2)).await;
+    let drive = async {
+        sim.wait_configured(WAIT).await?;
+        let alice = sim.node(1).unwrap();
+        let bob = sim.node(2).unwrap();
+
+        sim.send_dm(alice, "REGISTER alice password123")?;
+        let reply = sim.expect(Some(alice), "Registered as alice", WAIT).await?;
+        assert_eq!(reply.from, sim.our_node());
+
+        sim.send_dm(alice, "M")?;
+        sim.expect(Some(alice), "Topics", WAIT).await?;
+
+        sim.send_public(bob, 0, "^8BALL")?;
+        let bcast = sim.expect(None, "", WAIT).await?;
+        assert_eq!(bcast.channel, 0);
+        anyhow::Ok(())
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = drive => res.unwrap(),
+    }
+    let stats = sim.stats();
+    assert!(stats.acks >= 2, "both DMs acknowledged: {:?}", stats);
+    assert_eq!(stats.routing_errors, 0);
+}
+
+#[tokio::test]
+async fn routing_errors_and_loss_are_retried() {
+    let (mut server, mut sim, _tmp) = setup(SimConfig { seed: Some(7), ..SimConfig::with_nodes(1) }).await;
+    let drive = async {
+        sim.wait_configured(WAIT).await?;
+        let node = sim.node(1).unwrap();
+
+        // Transient routing error: the writer keeps the packet pending and resends it
+        sim.fail_next(RoutingError::RateLimitExceeded);
+        sim.send_dm(node, "REGISTER carol password123")?;
+        sim.expect(Some(node), "Registered as carol", WAIT).await?;
+
+        // Lost on the air: no delivery and no ACK until a retry gets through
+        sim.set_loss_rate(1.0);
+        sim.send_dm(node, "WHO")?;
+        assert!(sim.recv(Duration::from_millis(2500)).await.is_none());
+        sim.set_loss_rate(0.0);
+        sim.expect(Some(node), "Permission denied", WAIT).await?;
+        anyhow::Ok(())
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = drive => res.unwrap(),
+    }
+    let stats = sim.stats();
+    assert_eq!(stats.routing_errors, 1, "{:?}", stats);
+    assert!(stats.dropped >= 1, "{:?}", stats);
+    assert_eq!(stats.delivered, 2, "retries are not delivered twice: {:?}", stats);
+}
+
+#[tokio::test]
+async fn scripted_session_writes_transcript() {
+    let (mut server, mut sim, _tmp) = setup(SimConfig::with_nodes(1)).await;
+    let script = "\
+        # login flow\n\
+        dm 1 REGISTER dave password123\n\
+        expect 1 Registered as dave\n\
+        dm 1 LOGOUT\n\
+        expect 1 logged out\n";
+    let mut transcript = Vec::new();
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = sim::run_script(&mut sim, script, &mut transcript) => res.unwrap(),
+    }
+    let transcript = String::from_utf8(transcript).unwrap();
+    assert!(transcript.contains(">> [1] REGISTER dave password123"), "{}", transcript);
+    assert!(transcript.contains("<< [1] User dave logged out."), "{}", transcript);
+
+    let err = sim::run_script(&mut sim, "bogus 1", &mut Vec::new()).await.unwrap_err();
+    assert!(err.to_string().contains("line 1"), "{}", err);
+}
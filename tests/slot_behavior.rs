@@ -11,7 +11,7 @@ async fn slot_broadcasts_and_not_dm() {
 
     use meshbbs::meshtastic::TextEvent;
     let node_id = 5150u32;
-    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^SLOT".into() };
+    let public_evt = TextEvent { source: node_id, dest: None, is_direct: false, channel: None, content: "^SLOT".into(), ..Default::default() };
     server.route_text_event(public_evt).await.expect("route public slot");
 
     // Inspect recorded outbound messages
@@ -0,0 +1,144 @@
+#![cfg(feature = "sqlite")]
+//! The JSON and SQLite storage backends behave the same through `Storage`, and
+//! `migrate` carries a JSON data directory over to SQLite.
+
+use chrono::{Duration, Utc};
+use meshbbs::bbs::slotmachine;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::storage::{self, BackendKind, HeldMessage, Storage};
+
+async fn populate(storage: &mut Storage) -> Vec<String> {
+    storage.create_topic("general", "General", "General talk", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("tech", "Tech", "Hardware", 0, 0, "sysop").await.unwrap();
+    storage.register_user("alice", "password123", Some("1234")).await.unwrap();
+    storage.register_user("bob", "password456", None).await.unwrap();
+    let mut ids = Vec::new();
+    for (topic, author, body) in [("general", "alice", "first"), ("general", "bob", "second"), ("tech", "alice", "radio"), ("general", "alice", "third")] {
+        ids.push(storage.store_message(topic, author, body).await.unwrap());
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    ids
+}
+
+async fn exercise(kind: BackendKind) {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let start = Utc::now() - Duration::seconds(1);
+    let mut storage = Storage::open(&data_dir, kind, None, None).await.unwrap();
+    let ids = populate(&mut storage).await;
+
+    let general = storage.get_messages("general", 10).await.unwrap();
+    assert_eq!(general.iter().map(|m| m.content.as_str()).collect::<Vec<_>>(), ["third", "second", "first"], "{kind}");
+    assert_eq!(storage.get_messages("general", 2).await.unwrap().len(), 2);
+    assert_eq!(storage.count_messages_since(start).await.unwrap(), 4);
+    assert_eq!(storage.count_messages_since(general[1].timestamp).await.unwrap(), 2);
+    assert_eq!(storage.count_messages_since_in_topic("tech", start).await.unwrap(), 1);
+    assert_eq!(storage.count_user_posts("alice").await.unwrap(), 3);
+
+    storage.append_reply("general", &ids[0], "bob", "nice").await.unwrap();
+    storage.set_message_pinned("general", &ids[0], true).await.unwrap();
+    storage.set_message_title("general", &ids[0], Some("Hello")).await.unwrap();
+    let first = storage.get_messages("general", 10).await.unwrap().pop().unwrap();
+    assert_eq!((first.replies.len(), first.pinned, first.title.as_deref()), (1, true, Some("Hello")));
+    assert!(storage.append_reply("general", "not-a-uuid", "bob", "x").await.is_err());
+
+    assert!(storage.delete_message("general", &ids[1]).await.unwrap());
+    assert!(!storage.delete_message("general", &ids[1]).await.unwrap());
+    storage.append_deletion_audit("general", &ids[1], "sysop").await.unwrap();
+    storage.update_user_level("bob", 5, "sysop").await.unwrap();
+    assert_eq!(storage.get_deletion_audit_page(1, 10).await.unwrap()[0].id, ids[1]);
+    assert_eq!(storage.get_admin_audit_page(1, 10).await.unwrap()[0].action, "PROMOTE");
+
+    let stats = storage.get_statistics().await.unwrap();
+    assert_eq!((stats.total_messages, stats.total_users, stats.moderator_count), (3, 2, 1));
+
+    storage.lock_topic_persist("tech").await.unwrap();
+    let (outcome, _) = slotmachine::perform_spin(storage.backend(), "5150");
+    assert_ne!(outcome.r1, "⛔");
+    drop(storage);
+
+    // Everything survives a reopen
+    let mut storage = Storage::open(&data_dir, kind, None, None).await.unwrap();
+    assert!(storage.is_topic_locked("tech"));
+    assert!(storage.store_message("tech", "alice", "nope").await.is_err());
+    assert_eq!(storage.list_configured_topics(), ["general", "tech"]);
+    assert!(storage.verify_user_password("alice", "password123").await.unwrap().1);
+    assert_eq!(slotmachine::get_player_summary(storage.backend(), "5150").unwrap().total_spins, 1);
+
+    storage.delete_topic("general").await.unwrap();
+    assert_eq!(storage.count_user_posts("alice").await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn json_backend_behaviour() {
+    exercise(BackendKind::Json).await;
+}
+
+#[tokio::test]
+async fn sqlite_backend_behaviour() {
+    exercise(BackendKind::Sqlite).await;
+}
+
+#[tokio::test]
+async fn migrate_json_to_sqlite() {
+    let tmp = tempfile::tempdir().unwrap();
+    let data_dir = tmp.path().to_string_lossy().to_string();
+    let mut json = Storage::open(&data_dir, BackendKind::Json, None, None).await.unwrap();
+    let ids = populate(&mut json).await;
+    json.append_reply("general", &ids[0], "bob", "reply").await.unwrap();
+    json.lock_topic_persist("tech").await.unwrap();
+    json.append_deletion_audit("general", &ids[1], "sysop").await.unwrap();
+    json.send_mail("alice", "bob", "private").await.unwrap();
+    let held = HeldMessage {
+        id: "held-1".to_string(),
+        node_id: "1234".to_string(),
+        content: "notice".to_string(),
+        queued_at: Utc::now(),
+        expires_at: Utc::now() + Duration::hours(1),
+        attempts: 1,
+    };
+    json.hold_message(held.clone(), 10).await.unwrap();
+    slotmachine::perform_spin(json.backend(), "5150");
+    let jackpot = slotmachine::get_jackpot_summary(json.backend()).amount;
+
+    let source = storage::open_backend(&data_dir, BackendKind::Json, None).unwrap();
+    let dest = storage::open_backend(&data_dir, BackendKind::Sqlite, None).unwrap();
+    let report = storage::migrate(source.as_ref(), dest.as_ref()).unwrap();
+    assert_eq!((report.users, report.topics, report.messages, report.mail, report.audit_entries, report.held_messages, report.slot_players), (2, 2, 4, 1, 1, 1, 1));
+    // A second run must not duplicate anything
+    assert!(storage::migrate(source.as_ref(), dest.as_ref()).is_err());
+    drop(dest);
+
+    let sqlite = Storage::open(&data_dir, BackendKind::Sqlite, None, None).await.unwrap();
+    for topic in ["general", "tech"] {
+        let a = json.get_messages(topic, 100).await.unwrap();
+        let b = sqlite.get_messages(topic, 100).await.unwrap();
+        assert_eq!(serde_json::to_value(&a).unwrap(), serde_json::to_value(&b).unwrap());
+    }
+    assert!(sqlite.is_topic_locked("tech"));
+    assert!(sqlite.verify_user_password("bob", "password456").await.unwrap().1);
+    assert_eq!(sqlite.get_user("alice").await.unwrap().unwrap().node_id.as_deref(), Some("1234"));
+    assert_eq!(sqlite.get_deletion_audit_page(1, 10).await.unwrap().len(), 1);
+    assert_eq!(sqlite.list_mail("bob").await.unwrap()[0].content, "private");
+    assert_eq!(sqlite.list_held_messages().await.unwrap(), [held]);
+    assert_eq!(slotmachine::get_jackpot_summary(sqlite.backend()).amount, jackpot);
+    assert_eq!(slotmachine::get_player_summary(sqlite.backend(), "5150").unwrap().total_spins, 1);
+}
+
+#[tokio::test]
+async fn server_runs_on_sqlite_backend() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    cfg.storage.backend = BackendKind::Sqlite;
+    let data_dir = cfg.storage.data_dir.clone();
+
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.test_register("carol", "Password123").await.unwrap();
+    server.route_test_text_direct("4321", "LOGIN carol Password123").await.unwrap();
+    assert_eq!(server.get_user("carol").await.unwrap().unwrap().node_id.as_deref(), Some("4321"));
+
+    assert!(std::path::Path::new(&data_dir).join("meshbbs.db").exists());
+    assert!(!std::path::Path::new(&data_dir).join("users").join("carol.json").exists());
+}
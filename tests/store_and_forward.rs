@@ -0,0 +1,132 @@
+//! Store-and-forward: durable notices the radio gives up on are held in the data
+//! directory and sent again when their node is next heard, within expiry and per-node caps.
+
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::meshtastic::DeliveryEvent;
+use meshbbs::storage::Storage;
+
+async fn setup(tweak: impl FnOnce(&mut Config)) -> (BbsServer, Config, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    tweak(&mut cfg);
+    let server = BbsServer::new(cfg.clone()).await.unwrap();
+    (server, cfg, tmp)
+}
+
+fn undelivered(to: u32, content: &str) -> DeliveryEvent {
+    DeliveryEvent::Undelivered { to, channel: 0, content: content.to_string() }
+}
+
+async fn held(cfg: &Config) -> Vec<(String, String, u32)> {
+    let storage = Storage::new(&cfg.storage.data_dir).await.unwrap();
+    storage.list_held_messages().await.unwrap().into_iter().map(|m| (m.node_id, m.content, m.attempts)).collect()
+}
+
+#[tokio::test]
+async fn mail_notice_reaches_offline_user_when_node_is_heard() {
+    let (mut server, cfg, _tmp) = setup(|_| {}).await;
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_register("bob", "Password456").await.unwrap();
+    // Bind bob to node 2002, then leave
+    server.route_test_text_direct("2002", "LOGIN bob Password456").await.unwrap();
+    server.route_test_text_direct("2002", "LOGOUT").await.unwrap();
+
+    server.route_test_text_direct("1001", "LOGIN alice Password123").await.unwrap();
+    server.route_test_text_direct("1001", "SEND @bob are you there?").await.unwrap();
+    let notice = "📬 New mail from alice. MAIL to read.";
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m == notice), "{:?}", server.test_messages());
+
+    // The radio gives up on it: held until bob's node shows up again
+    server.handle_delivery_event(undelivered(2002, notice)).await.unwrap();
+    assert_eq!(held(&cfg).await, [("2002".to_string(), notice.to_string(), 1)]);
+    server.handle_delivery_event(DeliveryEvent::NodeHeard(3003)).await.unwrap();
+    assert_eq!(held(&cfg).await.len(), 1, "other nodes don't release it");
+
+    let before = server.test_messages().len();
+    server.handle_delivery_event(DeliveryEvent::NodeHeard(2002)).await.unwrap();
+    assert!(held(&cfg).await.is_empty());
+    assert_eq!(server.test_messages()[before..], [("2002".to_string(), notice.to_string())]);
+
+    // Failing again puts it back with its attempt count
+    server.handle_delivery_event(undelivered(2002, notice)).await.unwrap();
+    assert_eq!(held(&cfg).await, [("2002".to_string(), notice.to_string(), 2)]);
+}
+
+#[tokio::test]
+async fn per_node_cap_keeps_newest() {
+    let (mut server, cfg, _tmp) = setup(|c| c.meshtastic.store_forward_max_per_node = Some(2)).await;
+    for text in ["one", "two", "three"] {
+        server.handle_delivery_event(undelivered(2002, text)).await.unwrap();
+    }
+    server.handle_delivery_event(undelivered(3003, "other")).await.unwrap();
+    let contents: Vec<String> = held(&cfg).await.into_iter().map(|(_, c, _)| c).collect();
+    assert_eq!(contents, ["two", "three", "other"]);
+}
+
+#[tokio::test]
+async fn expired_messages_are_not_held() {
+    let (mut server, cfg, _tmp) = setup(|c| c.meshtastic.store_forward_ttl_hours = Some(0)).await;
+    server.handle_delivery_event(undelivered(2002, "too late")).await.unwrap();
+    assert!(held(&cfg).await.is_empty());
+}
+
+#[tokio::test]
+async fn reply_and_moderator_notices_go_to_the_author() {
+    let (mut server, _cfg, _tmp) = setup(|_| {}).await;
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_register("bob", "Password456").await.unwrap();
+    server.route_test_text_direct("2002", "LOGIN bob Password456").await.unwrap();
+    server.route_test_text_direct("2002", "LOGOUT").await.unwrap();
+    let id = server.test_store_message("general", "bob", "antenna for sale").await.unwrap();
+
+    server.route_test_text_direct("1001", "LOGIN alice Password123").await.unwrap();
+    for step in ["M", "2", "1", "Y", "still available?"] {
+        server.route_test_text_direct("1001", step).await.unwrap();
+    }
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m.contains("alice replied to your post in general")), "{:?}", server.test_messages());
+
+    server.moderator_delete_message("general", &id, "sysop").await.unwrap();
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m.contains("removed by a moderator")));
+}
+
+#[cfg(feature = "meshtastic-proto")]
+#[tokio::test]
+async fn simulated_node_out_of_range_gets_notice_on_position_report() {
+    use meshbbs::meshtastic::sim::{self, SimConfig};
+    use meshbbs::protobuf::meshtastic_generated::routing::Error as RoutingError;
+    use std::time::Duration;
+    const WAIT: Duration = Duration::from_secs(20);
+
+    let (mut server, cfg, _tmp) = setup(|c| c.meshtastic.dm_resend_backoff_seconds = Some(vec![1, 1, 1])).await;
+    let (transport, mut sim) = sim::start(SimConfig::with_nodes(2));
+    server.connect_transport(transport).await.unwrap();
+    let drive = async {
+        sim.wait_configured(WAIT).await?;
+        let alice = sim.node(1).unwrap();
+        let bob = sim.node(2).unwrap();
+        sim.send_dm(bob, "REGISTER bob password456")?;
+        sim.expect(Some(bob), "Registered as bob", WAIT).await?;
+        sim.send_dm(bob, "LOGOUT")?;
+        sim.expect(Some(bob), "logged out", WAIT).await?;
+        sim.send_dm(alice, "REGISTER alice password123")?;
+        sim.expect(Some(alice), "Registered as alice", WAIT).await?;
+
+        // Bob's node is out of range when the notice goes out
+        sim.fail_next(RoutingError::NoRoute);
+        sim.send_dm(alice, "SEND @bob ping")?;
+        sim.expect(Some(alice), "Mail sent to bob", WAIT).await?;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        sim.send_position(bob)?;
+        sim.expect(Some(bob), "New mail from alice", WAIT).await?;
+        anyhow::Ok(())
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        res = drive => res.unwrap(),
+    }
+    assert!(held(&cfg).await.is_empty());
+    assert_eq!(sim.stats().routing_errors, 1);
+}
@@ -16,8 +16,9 @@ async fn list_navigate_and_post_in_subtopic() {
     server.test_create_topic("community", "Community", "Root", 0, 0, "sysop").await.unwrap();
     server.test_create_subtopic("community_news", "community", "News", "Subtopic", 0, 0, "sysop").await.unwrap();
 
+    server.test_register("alice", "password123").await.unwrap();
     let node = "n1";
-    server.route_test_text_direct(node, "LOGIN alice").await.unwrap();
+    server.route_test_text_direct(node, "LOGIN alice password123").await.unwrap();
     let _ = last_for_node(server.test_messages(), node).unwrap();
 
     // M -> Topics should show community with subtopic marker
@@ -16,11 +16,13 @@ fn sysop_user_seeded_with_hash() {
         let hash = Argon2::default().hash_password("SecretP@ss1".as_bytes(), &salt).unwrap().to_string();
         let cfg = Config {
             bbs: BbsConfig { name: "Test".into(), sysop: "sysop".into(), location: "loc".into(), description: "d".into(), max_users: 10, session_timeout: 10, welcome_message: "w".into(), sysop_password_hash: Some(hash.clone()) },
-            meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None },
-            storage: StorageConfig { data_dir: datadir.to_str().unwrap().to_string(), max_message_size: 1024 },
+            meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None, public_channels: None, store_forward_ttl_hours: None, store_forward_max_per_node: None },
+            storage: StorageConfig { data_dir: datadir.to_str().unwrap().to_string(), max_message_size: 1024, backend: Default::default(), retention_interval_minutes: None, archive_expired: false },
             message_topics: HashMap::new(),
             logging: LoggingConfig { level: "info".into(), file: None, security_file: None },
             security: Default::default(),
+            web: None,
+            backup: None,
         };
         let mut server = BbsServer::new(cfg).await.unwrap();
         server.seed_sysop().await.unwrap();
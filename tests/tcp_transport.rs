@@ -0,0 +1,113 @@
+#![cfg(feature = "meshtastic-proto")]
+//! Exercise the reader/writer pair over the TCP stream transport against a local
+//! listener that plays the part of a network-attached Meshtastic node.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use meshbbs::meshtastic::{
+    create_reader_writer_system, MessagePriority, OutgoingKind, OutgoingMessage, TransportAddr, WriterTuning,
+};
+use meshbbs::protobuf::meshtastic_generated as proto;
+use prost::Message;
+
+fn write_frame(stream: &mut TcpStream, msg: &proto::FromRadio) {
+    let payload = msg.encode_to_vec();
+    let hdr = [0x94, 0xC3, (payload.len() >> 8) as u8, (payload.len() & 0xFF) as u8];
+    stream.write_all(&hdr).unwrap();
+    stream.write_all(&payload).unwrap();
+    stream.flush().unwrap();
+}
+
+fn read_frame(stream: &mut TcpStream) -> proto::ToRadio {
+    let mut hdr = [0u8; 4];
+    stream.read_exact(&mut hdr).unwrap();
+    assert_eq!(&hdr[..2], &[0x94, 0xC3], "stream API header");
+    let len = ((hdr[2] as usize) << 8) | hdr[3] as usize;
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).unwrap();
+    proto::ToRadio::decode(&payload[..]).unwrap()
+}
+
+#[test]
+fn parses_tcp_addresses() {
+    assert_eq!(TransportAddr::parse("tcp://10.0.0.5:4403").unwrap(), TransportAddr::Tcp("10.0.0.5:4403".into()));
+    assert_eq!(TransportAddr::parse("tcp://meshnode.local").unwrap(), TransportAddr::Tcp("meshnode.local:4403".into()));
+    assert_eq!(TransportAddr::parse("TCP://[::1]").unwrap(), TransportAddr::Tcp("[::1]:4403".into()));
+    assert_eq!(TransportAddr::parse("/dev/ttyUSB0").unwrap(), TransportAddr::Serial("/dev/ttyUSB0".into()));
+    assert!(TransportAddr::parse("tcp://").is_err());
+}
+
+#[tokio::test]
+async fn reader_writer_over_tcp() {
+    use proto::from_radio::PayloadVariant as FR;
+    use proto::mesh_packet::PayloadVariant as MP;
+    use proto::to_radio::PayloadVariant as TR;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let our_node: u32 = 0x0BB5_0001;
+    let remote: u32 = 0x1234_5678;
+
+    // Fake radio: expect want_config, announce node id, deliver a DM, then capture our reply.
+    let radio = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+        let first = read_frame(&mut stream);
+        assert!(matches!(first.payload_variant, Some(TR::WantConfigId(_))), "first frame should be want_config");
+
+        write_frame(&mut stream, &proto::FromRadio {
+            payload_variant: Some(FR::MyInfo(proto::MyNodeInfo { my_node_num: our_node, ..Default::default() })),
+            ..Default::default()
+        });
+        let pkt = proto::MeshPacket {
+            from: remote,
+            to: our_node,
+            channel: 0,
+            payload_variant: Some(MP::Decoded(proto::Data {
+                portnum: proto::PortNum::TextMessageApp as i32,
+                payload: b"HELLO".to_vec().into(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        write_frame(&mut stream, &proto::FromRadio { payload_variant: Some(FR::Packet(pkt)), ..Default::default() });
+
+        // Skip heartbeats until the text packet arrives
+        loop {
+            if let Some(TR::Packet(p)) = read_frame(&mut stream).payload_variant {
+                let Some(MP::Decoded(d)) = p.payload_variant else { panic!("expected decoded payload") };
+                return (p.to, p.from, String::from_utf8(d.payload.to_vec()).unwrap());
+            }
+        }
+    });
+
+    let (reader, writer, mut text_rx, outgoing_tx, reader_ctrl, writer_ctrl) =
+        create_reader_writer_system(&format!("tcp://{}", addr), 0, WriterTuning::default()).await.expect("tcp connect");
+    tokio::spawn(reader.run());
+    tokio::spawn(writer.run());
+
+    let ev = tokio::time::timeout(Duration::from_secs(5), text_rx.recv()).await.expect("text event").unwrap();
+    assert_eq!(ev.source, remote);
+    assert!(ev.is_direct);
+    assert_eq!(ev.content, "HELLO");
+
+    outgoing_tx.send(OutgoingMessage {
+        to_node: Some(remote),
+        channel: 0,
+        content: "WORLD".into(),
+        priority: MessagePriority::High,
+        kind: OutgoingKind::Normal,
+        request_ack: false,
+        durable: false,
+    }).unwrap();
+
+    let (to, from, text) = tokio::task::spawn_blocking(move || radio.join().unwrap()).await.unwrap();
+    assert_eq!(to, remote);
+    assert_eq!(from, our_node);
+    assert_eq!(text, "WORLD");
+
+    let _ = reader_ctrl.send(meshbbs::meshtastic::ControlMessage::Shutdown);
+    let _ = writer_ctrl.send(meshbbs::meshtastic::ControlMessage::Shutdown);
+}
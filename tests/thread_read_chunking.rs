@@ -35,11 +35,12 @@ async fn thread_read_is_chunked_and_prompt_on_last() {
         .await
         .expect("store long message");
 
+    server.test_register("alice", "password123").await.unwrap();
     let node = "n1";
 
     // Act: login and navigate to read the thread (M -> 1 -> 1)
     server
-        .route_test_text_direct(node, "LOGIN alice")
+        .route_test_text_direct(node, "LOGIN alice password123")
         .await
         .expect("login");
     server
@@ -0,0 +1,95 @@
+//! Per-topic retention: age and thread-count limits, pinned threads, gzip archives, the
+//! deletion log and the sysop controls.
+
+use std::io::Read;
+
+use argon2::Argon2;
+use chrono::{Duration, Utc};
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::Config;
+use meshbbs::storage::{Message, Storage, TopicRetention};
+use password_hash::{PasswordHasher, SaltString};
+
+#[tokio::test]
+async fn limits_expire_threads_and_archive_them() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().to_string_lossy().to_string();
+    let mut storage = Storage::new(&dir).await.unwrap();
+    storage.create_topic("general", "General", "", 0, 0, "sysop").await.unwrap();
+    storage.create_topic("tech", "Tech", "", 0, 0, "sysop").await.unwrap();
+    let first = storage.store_message("general", "alice", "First thread").await.unwrap();
+    let second = storage.store_message("general", "bob", "Second thread about antennas").await.unwrap();
+    let third = storage.store_message("general", "carol", "Third thread").await.unwrap();
+    let rules = storage.store_message("general", "sysop", "House rules").await.unwrap();
+    storage.set_message_pinned("general", &rules, true).await.unwrap();
+    storage.store_message("tech", "alice", "No policy here").await.unwrap();
+    // A reply makes the oldest thread the most active one
+    storage.append_reply("general", &first, "bob", "bump").await.unwrap();
+
+    let keep_two = TopicRetention { max_threads: Some(2), ..Default::default() };
+    storage.set_topic_retention("general", Some(keep_two.clone())).await.unwrap();
+    let report = storage.apply_retention(Utc::now(), true).await.unwrap();
+    assert_eq!(report.removed, vec![("general".to_string(), 1)]);
+    assert!(storage.get_message("general", &second).await.unwrap().is_none());
+    assert!(storage.search_messages("antennas").await.unwrap().is_empty());
+    let audit = storage.get_deletion_audit_page(1, 10).await.unwrap();
+    assert_eq!((audit[0].id.as_str(), audit[0].actor.as_str()), (second.as_str(), "retention"));
+
+    assert_eq!(report.archives.len(), 1);
+    let mut lines = String::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(&report.archives[0]).unwrap()).read_to_string(&mut lines).unwrap();
+    let archived: Vec<Message> = lines.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(archived.len(), 1);
+    assert_eq!((archived[0].id.as_str(), archived[0].content.as_str()), (second.as_str(), "Second thread about antennas"));
+
+    // Limits persist with the topic
+    let reopened = Storage::new(&dir).await.unwrap();
+    assert_eq!(reopened.get_topic_config("general").unwrap().retention, Some(keep_two));
+
+    // Age limit: everything idle for over a week goes, except the pinned thread
+    let week = TopicRetention { max_age_days: Some(7), ..Default::default() };
+    storage.set_topic_retention("general", Some(week)).await.unwrap();
+    assert_eq!(storage.apply_retention(Utc::now(), false).await.unwrap().total(), 0);
+    let report = storage.apply_retention(Utc::now() + Duration::days(8), false).await.unwrap();
+    assert_eq!((report.total(), report.archives.len()), (2, 0));
+    let left: Vec<String> = storage.get_messages("general", 10).await.unwrap().into_iter().map(|m| m.id).collect();
+    assert_eq!(left, vec![rules.clone()]);
+    assert!(storage.get_message("general", &third).await.unwrap().is_none());
+    assert_eq!(storage.get_messages("tech", 10).await.unwrap().len(), 1);
+
+    storage.set_topic_retention("general", Some(TopicRetention { max_age_days: Some(7), max_threads: None, keep_pinned: false })).await.unwrap();
+    assert_eq!(storage.apply_retention(Utc::now() + Duration::days(8), false).await.unwrap().total(), 1);
+
+    // No limits is the same as no policy
+    storage.set_topic_retention("general", Some(TopicRetention::default())).await.unwrap();
+    assert_eq!(storage.get_topic_config("general").unwrap().retention, None);
+}
+
+#[tokio::test]
+async fn sysop_sets_limits_and_is_told_about_removals() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    for i in 1..=3 {
+        server.test_store_message("general", "sysop", &format!("Post {i}")).await.unwrap();
+    }
+
+    server.route_test_text_direct("1001", "LOGIN sysop Sysop#Pass1").await.unwrap();
+    server.route_test_text_direct("1001", "MODIFYTOPIC general maxthreads=1 maxage=30").await.unwrap();
+    assert!(server.test_messages().last().unwrap().1.contains("Topic 'general' modified"));
+
+    let report = server.run_retention().await.unwrap();
+    assert_eq!(report.total(), 2);
+    assert_eq!(server.test_get_messages("general", 10).await.unwrap()[0].content, "Post 3");
+    let (to, notice) = server.test_messages().last().unwrap();
+    assert_eq!((to.as_str(), notice.as_str()), ("1001", "[BBS] Retention removed 2 thread(s): general 2\n"));
+
+    // Clearing both limits stops further removals
+    server.route_test_text_direct("1001", "MODIFYTOPIC general maxthreads=off maxage=0").await.unwrap();
+    server.test_store_message("general", "sysop", "Post 4").await.unwrap();
+    assert_eq!(server.run_retention().await.unwrap().total(), 0);
+}
@@ -0,0 +1,92 @@
+//! Trusted nodes: a user can trust several nodes (NODES/TRUST/UNTRUST), older single-node
+//! records keep working, and with `security.trusted_node_login` a trusted node may log in
+//! without the password while any other node still needs it.
+
+use argon2::Argon2;
+use chrono::Utc;
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, SecurityConfig};
+use meshbbs::storage::{Storage, User, MAX_TRUSTED_NODES};
+use password_hash::{PasswordHasher, SaltString};
+
+#[tokio::test]
+async fn trusted_node_list_is_kept_per_user() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut storage = Storage::new(&tmp.path().to_string_lossy()).await.unwrap();
+    storage.register_user("alice", "Password123", Some("1234")).await.unwrap();
+    let alice = storage.trust_node("alice", "5678", Some("Car radio")).await.unwrap();
+    let nodes: Vec<(String, Option<String>)> = alice.known_nodes().into_iter().map(|n| (n.node_id, n.label)).collect();
+    assert_eq!(nodes, [("1234".to_string(), None), ("5678".to_string(), Some("Car radio".to_string()))]);
+    assert!(alice.trusts_node("5678") && !alice.trusts_node("9999"));
+    assert!(storage.trust_node("alice", "bad id", None).await.is_err());
+    for i in 2..MAX_TRUSTED_NODES {
+        storage.trust_node("alice", &format!("{}", 7000 + i), None).await.unwrap();
+    }
+    assert!(storage.trust_node("alice", "9999", None).await.is_err(), "list is capped");
+
+    // Removing the home node hands that role to the next trusted node
+    let alice = storage.untrust_node("alice", "1234").await.unwrap().unwrap();
+    assert_eq!((alice.node_id.as_deref(), alice.trusted_nodes.len()), (Some("5678"), MAX_TRUSTED_NODES - 1));
+    assert!(storage.untrust_node("alice", "1234").await.unwrap().is_none());
+    let alice = storage.unbind_user_node("alice").await.unwrap();
+    assert!(alice.node_id.is_none() && alice.known_nodes().is_empty());
+
+    // A record from before trusted nodes still trusts its bound node
+    let legacy: User = serde_json::from_value(serde_json::json!({
+        "username": "bob", "node_id": "4321", "access_level": 1,
+        "first_login": Utc::now(), "last_login": Utc::now(), "total_messages": 0
+    })).unwrap();
+    storage.save_user(&legacy).await.unwrap();
+    let bob = storage.trust_node("bob", "8765", None).await.unwrap();
+    let ids: Vec<String> = bob.known_nodes().into_iter().map(|n| n.node_id).collect();
+    assert_eq!(ids, ["4321", "8765"]);
+}
+
+/// Everything the BBS sends back to `node` for `text`, chunks joined
+async fn send(server: &mut BbsServer, node: &str, text: &str) -> String {
+    let before = server.test_messages().len();
+    server.route_test_text_direct(node, text).await.unwrap();
+    server.test_messages()[before..].iter().filter(|(to, _)| to == node).map(|(_, m)| m.as_str()).collect()
+}
+
+#[tokio::test]
+async fn trusted_nodes_skip_the_password_only_when_enabled() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(b"Sysop#Pass1", &salt).unwrap().to_string());
+    cfg.security = Some(SecurityConfig { trusted_node_login: true, ..Default::default() });
+    let mut server = BbsServer::new(cfg.clone()).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    send(&mut server, "1001", "REGISTER alice Password123").await;
+    send(&mut server, "1001", "LOGOUT").await;
+    assert!(send(&mut server, "1001", "LOGIN alice").await.starts_with("Welcome, alice"), "the registering node is trusted");
+
+    assert!(send(&mut server, "2002", "LOGIN alice").await.starts_with("Password required"));
+    send(&mut server, "2002", "LOGIN alice Password123").await;
+    assert!(send(&mut server, "2002", "TRUST").await.starts_with("Trusted this node (2002)."));
+    assert!(send(&mut server, "2002", "TRUST").await.starts_with("This node is already trusted."));
+    assert!(send(&mut server, "2002", "NODES").await.starts_with("Trusted nodes:\n1. 1001 [home]\n2. 2002 [this]\n"));
+    send(&mut server, "2002", "LOGOUT").await;
+    assert!(send(&mut server, "2002", "LOGIN alice").await.starts_with("Welcome, alice"));
+
+    assert!(send(&mut server, "2002", "UNTRUST 1").await.starts_with("Node 1001 is no longer trusted."));
+    assert!(send(&mut server, "2002", "UNTRUST 5").await.starts_with("Usage: UNTRUST <n>"));
+    let alice = server.get_user("alice").await.unwrap().unwrap();
+    assert_eq!((alice.node_id.as_deref(), alice.trusts_node("1001")), (Some("2002"), false));
+    send(&mut server, "1001", "LOGOUT").await;
+    assert!(send(&mut server, "1001", "LOGIN alice").await.starts_with("Password required"));
+
+    // The sysop always needs the password
+    send(&mut server, "3003", "LOGIN sysop Sysop#Pass1").await;
+    send(&mut server, "3003", "TRUST").await;
+    send(&mut server, "3003", "LOGOUT").await;
+    assert!(send(&mut server, "3003", "LOGIN sysop").await.starts_with("Password required"));
+    drop(server);
+
+    // Off by default: a trusted node still needs the password
+    cfg.security = None;
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    assert!(send(&mut server, "2002", "LOGIN alice").await.starts_with("Password required"));
+}
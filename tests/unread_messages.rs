@@ -11,8 +11,8 @@ async fn unread_message_count_on_login() {
     std::fs::create_dir_all(&data_dir).unwrap();
     let cfg = Config {
         bbs: BbsConfig { name: "Test".into(), sysop: "sysop".into(), location: "Loc".into(), description: "Desc".into(), max_users: 10, session_timeout: 5, welcome_message: "Welcome".into(), sysop_password_hash: None },
-    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None },
-        storage: StorageConfig { data_dir: data_dir.to_string_lossy().to_string(), max_message_size: 230 },
+    meshtastic: MeshtasticConfig { port: "".into(), baud_rate: 115200, node_id: "".into(), channel: 0, min_send_gap_ms: None, dm_resend_backoff_seconds: None, post_dm_broadcast_gap_ms: None, dm_to_dm_gap_ms: None, help_broadcast_delay_ms: None, scheduler_max_queue: None, scheduler_aging_threshold_ms: None, scheduler_stats_interval_ms: None, public_channels: None, store_forward_ttl_hours: None, store_forward_max_per_node: None },
+        storage: StorageConfig { data_dir: data_dir.to_string_lossy().to_string(), max_message_size: 230, backend: Default::default(), retention_interval_minutes: None, archive_expired: false },
         message_topics: {
             let mut m = HashMap::new();
             m.insert("general".into(), MessageTopicConfig { name: "General".into(), description: "Gen".into(), read_level: 0, post_level: 0 });
@@ -20,6 +20,8 @@ async fn unread_message_count_on_login() {
         },
         logging: LoggingConfig { level: "info".into(), file: None, security_file: None },
         security: None,
+        web: None,
+        backup: None,
     };
     let cfg_clone = cfg.clone();
     let mut server = BbsServer::new(cfg_clone).await.unwrap();
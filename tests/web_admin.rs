@@ -0,0 +1,206 @@
+#![cfg(feature = "web")]
+//! Web admin: the dashboard and JSON API require the sysop's password, act on the running
+//! server (sessions, storage) and land in the admin audit log. Failed logins lock out the
+//! client address.
+
+use argon2::Argon2;
+use base64::Engine as _;
+use meshbbs::bbs::admin::{AdminRequest, NewTopic};
+use meshbbs::bbs::BbsServer;
+use meshbbs::config::{Config, WebConfig};
+use meshbbs::web::{self, AdminAccess};
+use password_hash::{PasswordHasher, SaltString};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const SYSOP_PASS: &str = "Sysop#Pass1";
+
+async fn setup(with_hash: bool) -> (BbsServer, tempfile::TempDir) {
+    setup_with(with_hash, |_| {}).await
+}
+
+async fn setup_with(with_hash: bool, tweak: impl FnOnce(&mut Config)) -> (BbsServer, tempfile::TempDir) {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut cfg = Config::default();
+    cfg.storage.data_dir = tmp.path().join("data").to_string_lossy().to_string();
+    tweak(&mut cfg);
+    if with_hash {
+        let salt = SaltString::generate(&mut rand::thread_rng());
+        cfg.bbs.sysop_password_hash = Some(Argon2::default().hash_password(SYSOP_PASS.as_bytes(), &salt).unwrap().to_string());
+    }
+    let mut server = BbsServer::new(cfg).await.unwrap();
+    server.seed_sysop().await.unwrap();
+    (server, tmp)
+}
+
+async fn start_web(server: &mut BbsServer) -> SocketAddr {
+    let access = AdminAccess {
+        handle: server.admin_handle(),
+        sysop: "sysop".into(),
+        password_hash: server.get_user("sysop").await.unwrap().and_then(|u| u.password_hash),
+    };
+    web::start(&WebConfig { enabled: true, bind: "127.0.0.1:0".into() }, Some(access)).await.unwrap()
+}
+
+fn basic(user: &str, pass: &str) -> String {
+    format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(format!("{user}:{pass}")))
+}
+
+/// Minimal HTTP/1.1 client: returns the status code and the body (JSON, or a string for HTML).
+async fn request(addr: SocketAddr, method: &str, path: &str, auth: Option<&str>, body: Option<Value>) -> (u16, Value) {
+    let mut head = format!("{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n");
+    if let Some(auth) = auth {
+        head.push_str(&format!("Authorization: {auth}\r\n"));
+    }
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    if !body.is_empty() {
+        head.push_str(&format!("Content-Type: application/json\r\nContent-Length: {}\r\n", body.len()));
+    }
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(format!("{head}\r\n{body}").as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response[9..12].parse().unwrap();
+    let payload = response.split_once("\r\n\r\n").map(|(_, b)| b).unwrap_or("");
+    (status, serde_json::from_str(payload).unwrap_or_else(|_| Value::String(payload.to_string())))
+}
+
+#[tokio::test]
+async fn admin_api_requires_sysop_and_manages_the_bbs() {
+    let (mut server, _tmp) = setup(true).await;
+    server.test_register("alice", "Password123").await.unwrap();
+    server.test_register("bob", "Password456").await.unwrap();
+    let post_id = server.test_store_message("general", "bob", "buy my spam").await.unwrap();
+    server.route_test_text_direct("2002", "LOGIN bob Password456").await.unwrap();
+    let addr = start_web(&mut server).await;
+    let auth = basic("sysop", SYSOP_PASS);
+    let auth = Some(auth.as_str());
+
+    let drive = async {
+        let (status, _) = request(addr, "GET", "/api/overview", None, None).await;
+        assert_eq!(status, 401);
+        let (status, _) = request(addr, "GET", "/api/overview", Some(&basic("sysop", "wrong-password")), None).await;
+        assert_eq!(status, 401);
+        let (status, _) = request(addr, "GET", "/api/overview", Some(&basic("alice", "Password123")), None).await;
+        assert_eq!(status, 401, "only the sysop may log in");
+        assert_eq!(request(addr, "GET", "/metrics", None, None).await.0, 200, "metrics stay open");
+
+        let (status, page) = request(addr, "GET", "/", auth, None).await;
+        assert_eq!(status, 200);
+        assert!(page.as_str().unwrap().contains("meshbbs admin"));
+        let (_, overview) = request(addr, "GET", "/api/overview", auth, None).await;
+        assert_eq!((overview["total_users"].as_u64(), overview["logged_in_users"].as_u64()), (Some(3), Some(1)));
+
+        // Users
+        let (status, users) = request(addr, "GET", "/api/users", auth, None).await;
+        assert_eq!(status, 200);
+        assert!(users.as_array().unwrap().iter().all(|u| u.get("password_hash").is_none()));
+        assert_eq!(request(addr, "POST", "/api/users/alice/level", auth, Some(json!({"level": 5}))).await.0, 200);
+        let (status, err) = request(addr, "POST", "/api/users/sysop/level", auth, Some(json!({"level": 1}))).await;
+        assert_eq!(status, 400, "{err}");
+        assert_eq!(request(addr, "POST", "/api/users/nobody/level", auth, Some(json!({"level": 5}))).await.0, 404);
+        assert_eq!(request(addr, "POST", "/api/users/alice/password", auth, Some(json!({"password": "NewPassword9"}))).await.0, 200);
+
+        // Topics and subtopics
+        let topic = json!({"id": "radio", "name": "Radio", "description": "Antennas", "post_level": 5});
+        assert_eq!(request(addr, "POST", "/api/topics", auth, Some(topic)).await.0, 200);
+        let sub = json!({"id": "radio_hf", "name": "HF", "parent": "radio"});
+        assert_eq!(request(addr, "POST", "/api/topics", auth, Some(sub)).await.0, 200);
+        assert_eq!(request(addr, "PATCH", "/api/topics/radio", auth, Some(json!({"description": "Antennas and rigs"}))).await.0, 200);
+        assert_eq!(request(addr, "POST", "/api/topics/radio/lock", auth, Some(json!({"locked": true}))).await.0, 200);
+        assert_eq!(request(addr, "DELETE", "/api/topics/radio", auth, None).await.0, 400, "has a subtopic");
+        let (_, topics) = request(addr, "GET", "/api/topics", auth, None).await;
+        let radio = topics.as_array().unwrap().iter().find(|t| t["id"] == "radio").unwrap().clone();
+        assert_eq!((radio["description"].as_str(), radio["locked"].as_bool(), radio["post_level"].as_u64()), (Some("Antennas and rigs"), Some(true), Some(5)));
+        assert!(topics.as_array().unwrap().iter().any(|t| t["id"] == "radio_hf" && t["parent"] == "radio"));
+        assert_eq!(request(addr, "DELETE", "/api/topics/radio_hf", auth, None).await.0, 200);
+        assert_eq!(request(addr, "DELETE", "/api/topics/radio_hf", auth, None).await.0, 404);
+
+        // Moderation
+        let (_, msgs) = request(addr, "GET", "/api/topics/general/messages", auth, None).await;
+        assert_eq!(msgs[0]["content"], "buy my spam");
+        let pin = format!("/api/topics/general/messages/{post_id}/pin");
+        assert_eq!(request(addr, "POST", &pin, auth, Some(json!({"pinned": true}))).await.0, 200);
+        let del = format!("/api/topics/general/messages/{post_id}");
+        assert_eq!(request(addr, "DELETE", &del, auth, None).await.0, 200);
+        assert_eq!(request(addr, "DELETE", &del, auth, None).await.0, 404);
+        let (_, deletions) = request(addr, "GET", "/api/audit/deletions", auth, None).await;
+        assert_eq!((deletions[0]["id"].as_str(), deletions[0]["actor"].as_str()), (Some(post_id.as_str()), Some("sysop")));
+
+        // Sessions, broadcast, kick
+        let (_, sessions) = request(addr, "GET", "/api/sessions", auth, None).await;
+        assert_eq!(sessions[0]["username"], "bob");
+        let (_, sent) = request(addr, "POST", "/api/broadcast", auth, Some(json!({"message": "net at 8pm"}))).await;
+        assert_eq!(sent["recipients"], 1);
+        assert_eq!(request(addr, "POST", "/api/sessions/kick", auth, Some(json!({"username": "bob"}))).await.0, 200);
+        assert_eq!(request(addr, "POST", "/api/sessions/kick", auth, Some(json!({"username": "bob"}))).await.0, 404);
+
+        let (_, audit) = request(addr, "GET", "/api/audit/admin", auth, None).await;
+        let actions: Vec<&str> = audit.as_array().unwrap().iter().map(|e| e["action"].as_str().unwrap()).collect();
+        for action in ["PROMOTE", "RESET_PASSWORD", "CREATE_TOPIC", "MODIFY_TOPIC", "DELETE_TOPIC", "BROADCAST", "KICK"] {
+            assert!(actions.contains(&action), "{action} missing from {actions:?}");
+        }
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        _ = drive => {}
+    }
+
+    assert_eq!(server.get_user("alice").await.unwrap().unwrap().user_level, 5);
+    server.route_test_text_direct("3003", "LOGIN alice NewPassword9").await.unwrap();
+    assert_eq!(server.test_logged_in_count(), 1, "bob was kicked, alice logs in with the new password");
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m.contains("net at 8pm")));
+    assert!(server.test_messages().iter().any(|(to, m)| to == "2002" && m.contains("disconnected by an administrator")));
+}
+
+#[tokio::test]
+async fn admin_api_is_disabled_without_sysop_password() {
+    let (mut server, _tmp) = setup(false).await;
+    let addr = start_web(&mut server).await;
+    let (status, body) = request(addr, "GET", "/api/users", Some(&basic("sysop", "anything")), None).await;
+    assert_eq!(status, 503, "{body}");
+}
+
+#[tokio::test]
+async fn failed_web_logins_lock_out_the_address() {
+    let (mut server, _tmp) = setup_with(true, |cfg| cfg.security.get_or_insert_with(Default::default).lockout.max_attempts = 2).await;
+    let addr = start_web(&mut server).await;
+    let good = basic("sysop", SYSOP_PASS);
+    let bad = basic("sysop", "guess");
+
+    let drive = async {
+        assert_eq!(request(addr, "GET", "/api/overview", Some(&good), None).await.0, 200);
+        assert_eq!(request(addr, "GET", "/api/overview", Some(&bad), None).await.0, 401);
+        assert_eq!(request(addr, "GET", "/api/overview", Some(&bad), None).await.0, 401);
+        // Locked: even the right password is not checked
+        let (status, body) = request(addr, "GET", "/api/overview", Some(&good), None).await;
+        assert_eq!(status, 429, "{body}");
+        assert_eq!(request(addr, "GET", "/metrics", None, None).await.0, 200, "metrics stay open");
+    };
+    tokio::select! {
+        res = server.run() => panic!("server exited early: {:?}", res),
+        _ = drive => {}
+    }
+
+    let audit = server.handle_admin("test", AdminRequest::AdminAudit { page: 1 }).await.unwrap();
+    assert!(audit.as_array().unwrap().iter().any(|e| e["action"] == "LOCKOUT" && e["target"] == "web:127.0.0.1"), "{audit}");
+    server.route_test_text_direct("1001", "LOGIN sysop Sysop#Pass1").await.unwrap();
+    server.route_test_text_direct("1001", "UNLOCKUSER web:127.0.0.1").await.unwrap();
+    assert!(server.test_messages().iter().any(|(_, m)| m.starts_with("Unlocked node web:127.0.0.1")), "{:?}", server.test_messages());
+}
+
+#[tokio::test]
+async fn admin_requests_enforce_rules() {
+    let (mut server, _tmp) = setup(true).await;
+    server.test_register("alice", "Password123").await.unwrap();
+    let err = server.handle_admin("sysop", AdminRequest::SetUserLevel { username: "alice".into(), level: 7 }).await.unwrap_err();
+    assert!(err.to_string().contains("Level must be"), "{err}");
+    let err = server.handle_admin("sysop", AdminRequest::ResetPassword { username: "alice".into(), password: "short".into() }).await.unwrap_err();
+    assert!(err.to_string().contains("too short"), "{err}");
+    assert!(server.handle_admin("sysop", AdminRequest::ResetPassword { username: "sysop".into(), password: "Password999".into() }).await.is_err());
+    let topic = NewTopic { id: "general".into(), name: "Dup".into(), description: String::new(), read_level: 0, post_level: 0, parent: None };
+    assert!(server.handle_admin("sysop", AdminRequest::CreateTopic(topic)).await.is_err(), "duplicate topic");
+    assert!(server.handle_admin("sysop", AdminRequest::Broadcast { message: "  ".into() }).await.is_err());
+}
@@ -15,9 +15,9 @@ async fn welcome_message_sent_on_login() {
     let mut server = BbsServer::new(cfg).await.expect("server");
 
     // Simulate public login then DM to finalize
-    let public = TextEvent { source: 42, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into() };
+    let public = TextEvent { source: 42, dest: None, is_direct: false, channel: None, content: "^LOGIN alice".into(), ..Default::default() };
     server.route_text_event(public).await.expect("public");
-    let dm_login = TextEvent { source: 42, dest: Some(99), is_direct: true, channel: None, content: "LOGIN alice".into() };
+    let dm_login = TextEvent { source: 42, dest: Some(99), is_direct: true, channel: None, content: "LOGIN alice".into(), ..Default::default() };
     server.route_text_event(dm_login).await.expect("dm login");
 
     // New behavior: pending public login finalization should NOT send the full banner, only the welcome + unread summary.
@@ -18,7 +18,8 @@ async fn welcome_messages_on_registration_and_first_login() {
         dest: None, 
         is_direct: true, 
         channel: None, 
-        content: "REGISTER welcometest password123".into() 
+        content: "REGISTER welcometest password123".into(),
+        ..Default::default() 
     };
     server.route_text_event(register_event).await.unwrap();
 
@@ -46,7 +47,8 @@ async fn welcome_messages_on_registration_and_first_login() {
         dest: None, 
         is_direct: true,
         channel: None,
-        content: "LOGOUT".into()
+        content: "LOGOUT".into(),
+        ..Default::default()
     };
     server.route_text_event(logout_event).await.unwrap();
 
@@ -61,7 +63,8 @@ async fn welcome_messages_on_registration_and_first_login() {
         dest: None,
         is_direct: true, 
         channel: None,
-        content: "LOGIN welcometest password123".into()
+        content: "LOGIN welcometest password123".into(),
+        ..Default::default()
     };
     server.route_text_event(login_event).await.unwrap();
 
@@ -88,7 +91,8 @@ async fn welcome_messages_on_registration_and_first_login() {
         dest: None,
         is_direct: true,
         channel: None, 
-        content: "LOGOUT".into()
+        content: "LOGOUT".into(),
+        ..Default::default()
     };
     server.route_text_event(logout_event2).await.unwrap();
 
@@ -97,7 +101,8 @@ async fn welcome_messages_on_registration_and_first_login() {
         dest: None,
         is_direct: true,
         channel: None,
-        content: "LOGIN welcometest password123".into()
+        content: "LOGIN welcometest password123".into(),
+        ..Default::default()
     };
     server.route_text_event(login_event2).await.unwrap();
 